@@ -0,0 +1,37 @@
+//! Timings of the operations on cluster lists, with 1M clusters.
+//!
+//! Run with `cargo bench --bench clusters`.
+use std::hint::black_box;
+use std::time::Instant;
+
+use std_dev::{ClusterList, Fraction, OwnedClusterList};
+
+const CLUSTERS: usize = 1_000_000;
+
+fn time<T>(label: &str, f: impl FnOnce() -> T) {
+    let now = Instant::now();
+    black_box(f());
+    println!("{label}: {:.2?}", now.elapsed());
+}
+
+fn main() {
+    // distinct values in a shuffled order, with counts from 1 to 5
+    let list: Vec<_> = (0..CLUSTERS)
+        .map(|i| (((i * 7919) % CLUSTERS) as f64, i % 5 + 1))
+        .collect();
+    let clusters = ClusterList::new(&list);
+    let half = clusters.len() / 2;
+
+    time("split_start", || clusters.split_start(half));
+    time("split_end", || clusters.split_end(half));
+
+    let mut owned = OwnedClusterList::new(list.clone());
+    time("median", || std_dev::percentiles_cluster(&mut owned));
+    let targets: Vec<_> = (1..100).map(|p| Fraction::new(p, 100)).collect();
+    let mut owned = OwnedClusterList::new(list.clone());
+    time("99 percentiles", || {
+        std_dev::percentiles_cluster_list(&mut owned, &targets)
+    });
+    let mut owned = OwnedClusterList::new(list);
+    time("optimize_values", || owned.optimize_values());
+}
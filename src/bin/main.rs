@@ -1,36 +1,112 @@
 use clap::{Arg, ArgAction, ValueHint};
 use std::env;
 use std::fmt::{Debug, Display};
+use std::io::IsTerminal;
 #[cfg(feature = "regression")]
 use std::io::Write;
-use std::io::{stdin, BufRead, IsTerminal};
+use std::io::{stdin, BufRead};
 use std::process::exit;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 use std::time::Instant;
+#[cfg(feature = "regression")]
+use std_dev::regression::bayesian::BayesianLinear;
+#[cfg(feature = "regression")]
+use std_dev::regression::trimmed::Trimmed;
 use std_dev::regression::{
     BinarySearchOptions, CosecantEstimator, CosineEstimator, CotangentEstimator,
     ExponentialEstimator, GradientDescentParallelOptions, GradientDescentSimultaneousOptions,
     LogisticEstimator, PowerEstimator, SecantEstimator, SineEstimator, TangentEstimator,
 };
 #[cfg(feature = "regression")]
-use std_dev::regression::{Determination, LinearEstimator, PolynomialEstimator, Predictive};
+use std_dev::regression::{
+    DegreeCriterion, Determination, LinearEstimator, PolynomialEstimator, Predictive,
+};
+use std_dev::units::{Notation, Prefixes, ValueFormat};
 
 pub use std_dev;
 
-fn parse<T: FromStr>(s: &str) -> Option<T> {
-    if let Ok(v) = s.parse() {
-        Some(v)
-    } else {
-        eprintln!("Failed to parse value {s:?}");
-        None
+mod pipeline;
+#[cfg(feature = "plot")]
+mod plot;
+
+/// Set by `--decimal-comma`: commas are decimal separators, and values are separated by
+/// semicolons and/or whitespace.
+static DECIMAL_COMMA: AtomicBool = AtomicBool::new(false);
+/// Set by `--delimiter`. If it's not set, values are separated by commas (semicolons with
+/// `--decimal-comma`) and/or whitespace.
+static DELIMITER: OnceLock<Delimiter> = OnceLock::new();
+/// Set by `--quiet` and `--porcelain`: no prompts, sparklines, warnings, or notes.
+static QUIET: AtomicBool = AtomicBool::new(false);
+/// Set by `--seed`.
+static SEED: OnceLock<u64> = OnceLock::new();
+
+/// A random number generator, seeded by `--seed` if it's given.
+#[cfg(any(feature = "percentile-rand", feature = "dp"))]
+fn rng() -> rand::rngs::StdRng {
+    use rand::SeedableRng;
+    match SEED.get() {
+        Some(seed) => rand::rngs::StdRng::seed_from_u64(*seed),
+        None => rand::rngs::StdRng::from_rng(&mut rand::rng()),
+    }
+}
+/// [`std_dev::percentiles_cluster`], with the pivots picked by [`rng`].
+fn percentiles(values: &mut std_dev::OwnedClusterList) -> std_dev::PercentilesOutput {
+    #[cfg(feature = "percentile-rand")]
+    {
+        std_dev::percentiles_cluster_with_rng(values, &mut rng())
+    }
+    #[cfg(not(feature = "percentile-rand"))]
+    {
+        std_dev::percentiles_cluster(values)
+    }
+}
+
+/// Parses `s`, after removing separators of digit groups (see
+/// [`std_dev::input::normalize_number`]).
+///
+/// Values with prefixes or units of time (e.g. `1.5k` and `250ms`) are also accepted, see
+/// [`std_dev::units::parse_value`].
+fn parse<T: FromStr>(s: &str) -> Option<T>
+where
+    T::Err: Display,
+{
+    let decimal_comma = DECIMAL_COMMA.load(Ordering::Relaxed);
+    let s = std_dev::input::normalize_number(s, decimal_comma);
+    match s.parse() {
+        Ok(v) => Some(v),
+        Err(err) => {
+            if let Some(v) = std_dev::units::parse_value(&s)
+                .ok()
+                .and_then(|v| v.to_string().parse().ok())
+            {
+                Some(v)
+            } else {
+                eprintln!("Failed to parse value {s:?}: {err}");
+                None
+            }
+        }
+    }
+}
+/// A decimal number given with `--decimal`, which can also be in scientific notation (see
+/// [`std_dev::decimal::parse`]).
+#[cfg(feature = "decimal")]
+#[derive(Debug, Clone, Copy)]
+struct DecimalValue(std_dev::decimal::Decimal);
+#[cfg(feature = "decimal")]
+impl FromStr for DecimalValue {
+    type Err = rust_decimal::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        std_dev::decimal::parse(s).map(Self)
     }
 }
 #[derive(Debug)]
-enum InputValue {
-    Count(Vec<std_dev::Cluster>),
-    List(Vec<Vec<f64>>),
+enum InputValue<T = f64> {
+    Count(Vec<(T, usize)>),
+    List(Vec<Vec<T>>),
 }
-impl InputValue {
+impl<T> InputValue<T> {
     fn is_empty(&self) -> bool {
         match self {
             Self::Count(count) => count.is_empty(),
@@ -39,95 +115,490 @@ impl InputValue {
     }
 }
 
-fn input(
+/// The separator of values, given to `--delimiter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delimiter {
+    /// Any whitespace, except separators of digit groups.
+    Whitespace,
+    Char(char),
+}
+impl Delimiter {
+    fn matches(self, c: char) -> bool {
+        match self {
+            Self::Whitespace => c.is_whitespace() && !std_dev::input::is_digit_group_separator(c),
+            Self::Char(delimiter) => c == delimiter,
+        }
+    }
+}
+impl FromStr for Delimiter {
+    type Err = String;
+    /// Parses `comma`, `tab` (or `\t`), `semicolon`, `whitespace` (or `space`), or any single
+    /// character.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "comma" => Self::Char(','),
+            "tab" | "\\t" => Self::Char('\t'),
+            "semicolon" => Self::Char(';'),
+            "whitespace" | "space" => Self::Whitespace,
+            _ => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Self::Char(c),
+                    _ => {
+                        return Err(format!(
+                            "expected comma, tab, semicolon, whitespace, or a single character, \
+                            got {s:?}"
+                        ))
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// The tokens of `line`, separated by the `--delimiter`, else by commas (semicolons with
+/// `--decimal-comma`) and/or whitespace. Separators of digit groups, such as the no-break space
+/// in `1 000`, don't separate tokens. With `--decimal-comma`, neither do plain spaces between
+/// groups of three digits (see [`std_dev::input::is_digit_group_space`]).
+fn tokens(line: &str) -> impl Iterator<Item = &str> {
+    let delimiter = DELIMITER.get().copied();
+    let decimal_comma = DECIMAL_COMMA.load(Ordering::Relaxed);
+    let separator = if decimal_comma { ';' } else { ',' };
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    for (i, c) in line.char_indices() {
+        let separates = match delimiter {
+            Some(delimiter) => delimiter.matches(c),
+            None => {
+                c == separator
+                    || (Delimiter::Whitespace.matches(c)
+                        && !(decimal_comma && std_dev::input::is_digit_group_space(line, i)))
+            }
+        };
+        if separates {
+            tokens.push(&line[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    tokens.push(&line[start..]);
+    tokens
+        .into_iter()
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// The values of a line of multiline input.
+///
+/// `<value>x<count>` is the same as the two values `<value> <count>`, e.g. the value and its count,
+/// or in regression, the outcome and the weight of the point.
+fn parse_row<T: FromStr>(line: &str) -> Vec<T>
+where
+    T::Err: Display,
+{
+    tokens(line)
+        .flat_map(|segment| match segment.split_once('x') {
+            Some((value, count)) => [Some(value), Some(count)],
+            None => [Some(segment), None],
+        })
+        .flatten()
+        .filter_map(|segment| parse(segment.trim()))
+        .collect()
+}
+/// The values of single line input, which can use the `<value>x<count>` notation.
+fn parse_counted<T: FromStr>(s: &str) -> Vec<(T, usize)>
+where
+    T::Err: Display,
+{
+    tokens(s)
+        .filter_map(|s| {
+            Some(if let Some((v, count)) = s.split_once('x') {
+                let count = parse(count)?;
+                (parse(v)?, count)
+            } else {
+                (parse(s)?, 1)
+            })
+        })
+        .collect()
+}
+
+/// Reads the datasets of the input `files` (`-` is stdin).
+///
+/// When `multiline`, the datasets of each file are separated by empty lines, like the input of
+/// the prompt. Else, all the values of a file are one dataset.
+///
+/// Each dataset is labelled with its file if there are several.
+fn read_files<T: FromStr>(files: &[String], multiline: bool) -> Vec<(Option<&str>, InputValue<T>)>
+where
+    T::Err: Display,
+{
+    use pipeline::Source;
+
+    let mut inputs = Vec::new();
+    for file in files {
+        let mut source = pipeline::Lines::open(file, multiline).unwrap_or_else(|err| {
+            eprintln!("Failed to read {file}: {err}");
+            exit(1);
+        });
+        let label = (files.len() > 1).then_some(file.as_str());
+        let len = inputs.len();
+        while let Some(input) = source.next_dataset() {
+            match input {
+                Ok(input) => inputs.push((label, input)),
+                Err(err) => {
+                    eprintln!("Failed to read {file}: {err}");
+                    exit(1);
+                }
+            }
+        }
+        if inputs.len() == len {
+            eprintln!("No values in {file}.");
+        }
+    }
+    inputs
+}
+
+/// Reads the values of the `f64le` input `files` (`-` is stdin), see [`std_dev::input::F64Le`].
+///
+/// Each file is one dataset, labelled with the file if there are several.
+fn read_binary_files(files: &[String]) -> Vec<(Option<&str>, InputValue)> {
+    use pipeline::Source;
+
+    let mut inputs = Vec::new();
+    for file in files {
+        let reader: Box<dyn std::io::Read> = if file == "-" {
+            Box::new(stdin().lock())
+        } else {
+            match std::fs::File::open(file) {
+                Ok(f) => Box::new(std::io::BufReader::new(f)),
+                Err(err) => {
+                    eprintln!("Failed to read {file}: {err}");
+                    exit(1);
+                }
+            }
+        };
+        match pipeline::Binary::new(reader).next_dataset() {
+            Some(Ok(input)) => inputs.push(((files.len() > 1).then_some(file.as_str()), input)),
+            Some(Err(err)) => {
+                eprintln!("Failed to read {file}: {err}");
+                exit(1);
+            }
+            None => eprintln!("No values in {file}."),
+        }
+    }
+    inputs
+}
+
+/// Tells how many outliers `--drop-outliers` removed.
+fn report_dropped(count: usize) {
+    if QUIET.load(Ordering::Relaxed) {
+        return;
+    }
+    match count {
+        0 => {}
+        1 => eprintln!("Dropped 1 outlier."),
+        _ => eprintln!("Dropped {count} outliers."),
+    }
+}
+
+/// Reads all of `file` (`-` is stdin), or exits if it can't be read.
+fn read_file(file: &str) -> String {
+    let content = if file == "-" {
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut stdin().lock(), &mut content).map(|_| content)
+    } else {
+        std::fs::read_to_string(file)
+    };
+    content.unwrap_or_else(|err| {
+        eprintln!("Failed to read {file}: {err}");
+        exit(1);
+    })
+}
+
+/// The columns given to `--x-column`, `--y-column`, and `--count-column`.
+#[derive(Debug, Clone, PartialEq)]
+struct CsvColumns {
+    x: Option<ColumnRef>,
+    y: Option<ColumnRef>,
+    count: Option<ColumnRef>,
+}
+impl CsvColumns {
+    fn from_matches(matches: &clap::ArgMatches) -> Self {
+        let column = |id| matches.get_one::<ColumnRef>(id).cloned();
+        Self {
+            x: column("x_column"),
+            y: column("y_column"),
+            count: column("count_column"),
+        }
+    }
+}
+/// Reads the CSV `files` (`-` is stdin), each being one dataset.
+///
+/// When `points`, each record is a row of the x and y columns, or of all fields if neither is
+/// selected. Else, the values are those of the x column (or all fields), counted by the count
+/// column.
+///
+/// The first record is the header if any column is referenced by name or it isn't numeric.
+///
+/// Each dataset is labelled with its file if there are several.
+fn read_csv<'a, T: FromStr>(
+    files: &'a [String],
+    columns: &CsvColumns,
+    points: bool,
+) -> Vec<(Option<&'a str>, InputValue<T>)>
+where
+    T::Err: Display,
+{
+    let mut inputs = Vec::new();
+    for file in files {
+        let content = read_file(file);
+        let mut records = std_dev::input::csv_records(&content).into_iter().peekable();
+        let named = [&columns.x, &columns.y, &columns.count]
+            .iter()
+            .any(|c| matches!(c, Some(ColumnRef::Name(_))));
+        let has_header = named
+            || records
+                .peek()
+                .is_some_and(|first| first.iter().any(|field| field.trim().parse::<T>().is_err()));
+        let header = if has_header { records.next() } else { None };
+        let header: Option<Vec<&str>> = header
+            .as_ref()
+            .map(|header| header.iter().map(String::as_str).collect());
+        let resolve = |column: &ColumnRef| {
+            column.resolve(header.as_deref()).unwrap_or_else(|| {
+                eprintln!("Column {column} isn't in the header of {file}.");
+                exit(1);
+            })
+        };
+        let (x, y, count) = if points && (columns.x.is_some() || columns.y.is_some()) {
+            (
+                Some(resolve(columns.x.as_ref().unwrap_or(&ColumnRef::Index(0)))),
+                Some(resolve(columns.y.as_ref().unwrap_or(&ColumnRef::Index(1)))),
+                None,
+            )
+        } else {
+            (
+                columns.x.as_ref().map(resolve),
+                None,
+                columns.count.as_ref().map(resolve),
+            )
+        };
+        let selected: Vec<usize> = x.into_iter().chain(y).collect();
+
+        let mut rows = Vec::new();
+        let mut counted = Vec::new();
+        for (idx, record) in records.enumerate() {
+            let fields: Option<Vec<&str>> = if selected.is_empty() {
+                Some(record.iter().map(|field| field.trim()).collect())
+            } else {
+                selected
+                    .iter()
+                    .map(|&column| record.get(column).map(|field| field.trim()))
+                    .collect()
+            };
+            let count = match count {
+                Some(column) => record.get(column).map(|field| parse(field.trim())),
+                None => Some(Some(1)),
+            };
+            let (Some(fields), Some(count)) = (fields, count) else {
+                let record = idx + 1 + usize::from(has_header);
+                eprintln!("Record {record} of {file} doesn't have the selected columns.");
+                continue;
+            };
+            let Some(values) = fields.into_iter().map(parse).collect::<Option<Vec<T>>>() else {
+                continue;
+            };
+            if points {
+                rows.push(values);
+            } else if let Some(count) = count {
+                counted.extend(values.into_iter().map(|v| (v, count)));
+            }
+        }
+        let input = if points {
+            InputValue::List(rows)
+        } else {
+            InputValue::Count(counted)
+        };
+        if input.is_empty() {
+            eprintln!("No values in {file}.");
+        } else {
+            inputs.push(((files.len() > 1).then_some(file.as_str()), input));
+        }
+    }
+    inputs
+}
+
+/// The values of `input`, where the second value of each line of multiline input is the count of
+/// the first.
+///
+/// Returns [`None`] if a line doesn't have one or two values.
+fn clusters(input: InputValue) -> Option<std_dev::OwnedClusterList> {
+    match input {
+        InputValue::Count(count) => Some(std_dev::OwnedClusterList::new(count)),
+        InputValue::List(list) => {
+            let mut count = Vec::with_capacity(list.len());
+            for item in list {
+                if item.len() != 1 && item.len() != 2 {
+                    eprintln!(
+                        "Expected one or two values per line. \
+                        Use `summary --by-column` to summarize more columns."
+                    );
+                    return None;
+                }
+                let first = item[0];
+                let second = item.get(1).map_or(1, |f| f.round() as usize);
+                count.push((first, second))
+            }
+            Some(std_dev::OwnedClusterList::new(count))
+        }
+    }
+}
+
+/// State kept between interactive prompts.
+struct Prompt {
+    /// When the last prompt was printed.
+    #[cfg(feature = "pretty")]
+    last: Instant,
+    /// The text of the last dataset, to run it again with a `:<command>`.
+    dataset: String,
+    /// A `:<command>` entered instead of a dataset.
+    command: Option<String>,
+    /// Line editing and history, if stdin is a terminal.
+    editor: Option<rustyline::DefaultEditor>,
+}
+/// A line read at the prompt.
+enum Line {
+    Read(String),
+    /// Ctrl-C was pressed.
+    Cancelled,
+    /// The end of input (Ctrl-D) was reached.
+    End,
+}
+impl Prompt {
+    fn new() -> Self {
+        let editor = if stdin().is_terminal() {
+            rustyline::DefaultEditor::new().ok()
+        } else {
+            None
+        };
+        Self {
+            #[cfg(feature = "pretty")]
+            last: Instant::now(),
+            dataset: String::new(),
+            command: None,
+            editor,
+        }
+    }
+    /// Reads a line after printing `prompt`. Lines read with the editor are added to its history.
+    fn read_line(&mut self, prompt: &str) -> Line {
+        let Some(editor) = &mut self.editor else {
+            let mut line = String::new();
+            return match stdin().lock().read_line(&mut line) {
+                Ok(0) => Line::End,
+                Ok(_) => Line::Read(line),
+                Err(err) => {
+                    eprintln!("Failed to read stdin: {err}");
+                    exit(1);
+                }
+            };
+        };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    let _ = editor.add_history_entry(line.as_str());
+                }
+                Line::Read(line)
+            }
+            Err(rustyline::error::ReadlineError::Interrupted) => Line::Cancelled,
+            Err(rustyline::error::ReadlineError::Eof) => Line::End,
+            Err(err) => {
+                eprintln!("Failed to read the prompt: {err}");
+                exit(1);
+            }
+        }
+    }
+}
+
+/// Reads a dataset at the prompt. Exits on an empty line or at the end of input.
+///
+/// Returns [`None`] if the dataset was invalid, cancelled with Ctrl-C, or if a `:<command>` was
+/// entered, which is put in [`Prompt::command`].
+fn input<T: FromStr>(
     _is_tty: bool,
     debug_performance: bool,
     multiline: bool,
-    _last_prompt: &mut Instant,
-) -> Option<InputValue> {
+    prompt: &mut Prompt,
+) -> Option<InputValue<T>>
+where
+    T::Err: Display,
+{
+    #[allow(unused_mut)]
+    let mut prompt_text = "";
     #[cfg(feature = "pretty")]
     {
         if _is_tty {
-            use std::io::stdout;
-
-            if multiline {
-                print!("multiline > ");
-            } else {
-                print!("> ")
-            }
-            stdout().lock().flush().unwrap();
+            prompt_text = if multiline { "multiline > " } else { "> " };
         }
-        *_last_prompt = Instant::now();
     }
-    let mut s = String::new();
+    let first = match prompt.read_line(prompt_text) {
+        Line::Read(line) => line,
+        Line::Cancelled => return None,
+        Line::End => exit(0),
+    };
+    #[cfg(feature = "pretty")]
+    {
+        prompt.last = Instant::now();
+    }
+    let now = Instant::now();
 
-    let mut now = Instant::now();
+    if first.trim().is_empty() {
+        exit(0);
+    }
+    if let Some(command) = first.trim().strip_prefix(':') {
+        prompt.command = Some(command.to_owned());
+        return None;
+    }
+    let mut s = first;
 
     let values = if multiline {
         let mut values = Vec::with_capacity(8);
-        let stdin = stdin();
-        let stdin = stdin.lock().lines();
-        let mut lines = 0_usize;
-        for line in stdin {
-            if lines == 0 {
-                now = Instant::now();
-            }
-            lines += 1;
-            let line = line.unwrap();
-            if line.trim().is_empty() {
-                break;
-            }
-            let mut current = Vec::with_capacity(2);
-            for segment in line.split(',').flat_map(|s| s.split_whitespace()) {
-                let f = parse(segment.trim());
-                if let Some(f) = f {
-                    current.push(f)
+        let mut line = s.clone();
+        loop {
+            values.push(parse_row(&line));
+            #[allow(unused_mut)]
+            let mut next_prompt = String::new();
+            #[cfg(feature = "pretty")]
+            {
+                if _is_tty && prompt.last.elapsed().as_millis() > 10 {
+                    next_prompt = format!("{} > ", values.len() + 1);
                 }
             }
-            values.push(current);
+            line = match prompt.read_line(&next_prompt) {
+                Line::Read(line) => line,
+                Line::Cancelled => return None,
+                Line::End => break,
+            };
             #[cfg(feature = "pretty")]
             {
-                if _is_tty && _last_prompt.elapsed().as_millis() > 10 {
-                    use std::io::stdout;
-
-                    let next = values.len() + 1;
-                    print!("{next} > ");
-                    stdout().lock().flush().unwrap();
-                }
-                *_last_prompt = Instant::now();
+                prompt.last = Instant::now();
             }
-        }
-        if lines <= 1 {
-            exit(0);
+            if line.trim().is_empty() {
+                break;
+            }
+            if !s.ends_with('\n') {
+                s.push('\n');
+            }
+            s.push_str(&line);
         }
         InputValue::List(values)
     } else {
-        stdin().lock().read_line(&mut s).unwrap();
-        now = Instant::now();
-
-        if s.trim().is_empty() {
-            exit(0);
-        }
-
-        let values: Vec<_> = s
-            .split(',')
-            .flat_map(|s| s.split_whitespace())
-            .filter_map(|s| {
-                Some(if let Some((v, count)) = s.split_once('x') {
-                    let count = parse(count)?;
-                    (parse(v)?, count)
-                } else {
-                    (parse(s)?, 1)
-                })
-            })
-            .collect();
-        InputValue::Count(values)
+        InputValue::Count(parse_counted(&s))
     };
 
     if values.is_empty() {
         eprintln!("Only invalid input. Try again.");
         return None;
     }
+    prompt.dataset = s;
 
     if debug_performance {
         println!("Parsing/reading input took {}µs", now.elapsed().as_micros());
@@ -135,6 +606,76 @@ fn input(
     Some(values)
 }
 
+/// Parses a `:<command>` entered at the prompt, e.g. `:regression -o 2`, and the last `dataset`
+/// again for it.
+fn rerun(
+    app: &clap::Command,
+    command: &str,
+    dataset: &str,
+) -> Option<(clap::ArgMatches, InputValue)> {
+    if dataset.is_empty() {
+        eprintln!("There's no dataset to run `:{command}` on yet.");
+        return None;
+    }
+    let args = std::iter::once("std-dev").chain(command.split_whitespace());
+    let matches = match app.clone().try_get_matches_from(args) {
+        Ok(matches) => matches,
+        Err(err) => {
+            let _ = err.print();
+            return None;
+        }
+    };
+    let from_command_line = |matches: &clap::ArgMatches, id: &str| {
+        matches.ids().any(|arg| arg == id)
+            && matches.value_source(id) == Some(clap::parser::ValueSource::CommandLine)
+    };
+    let reads_input = [
+        "follow",
+        "stream",
+        "group_by",
+        "pivot",
+        "decimal",
+        "csv",
+        "input_format",
+    ]
+    .iter()
+    .any(|id| from_command_line(&matches, id))
+        || matches.subcommand().is_some_and(|(_, config)| {
+            from_command_line(config, "files") || from_command_line(config, "stream")
+        });
+    if reads_input
+        || !matches!(
+            matches.subcommand_name(),
+            None | Some("regression" | "correlation" | "histogram" | "bench")
+        )
+    {
+        eprintln!("`:{command}` can't be run on the last dataset.");
+        return None;
+    }
+    let multiline = matches.get_flag("multiline")
+        || matches.get_flag("buckets")
+        || matches!(
+            matches.subcommand_name(),
+            Some("regression" | "correlation" | "bench")
+        );
+    let input = if multiline {
+        InputValue::List(
+            dataset
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(parse_row)
+                .collect(),
+        )
+    } else {
+        InputValue::Count(parse_counted(dataset))
+    };
+    if input.is_empty() {
+        eprintln!("The last dataset has no values for `:{command}`.");
+        return None;
+    }
+    Some((matches, input))
+}
+
 #[cfg(feature = "regression")]
 fn print_regression(
     regression: &(impl std_dev::regression::Predictive + Display),
@@ -157,48 +698,2528 @@ fn print_regression(
     }
 }
 
-fn main() {
-    let mut app = clap::command!();
+/// Prints a scatter plot of the points (`o`) with `model` drawn over them (`*`), between the
+/// smallest and largest values.
+#[cfg(feature = "regression")]
+fn print_scatter(
+    model: &impl std_dev::regression::Predictive,
+    x: &[f64],
+    y: &[f64],
+    precision: Option<usize>,
+) {
+    const WIDTH: usize = 64;
+    const HEIGHT: usize = 20;
 
-    app = app
-        .about(
-            "Statistics calculation tool.\n\
-            A common pattern is to cat files and pipe the data.",
+    let bounds = |values: &[f64]| {
+        values
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+                (min.min(*v), max.max(*v))
+            })
+    };
+    let (x_min, x_max) = bounds(x);
+    let (y_min, y_max) = bounds(y);
+    let x_range = if x_max > x_min { x_max - x_min } else { 1. };
+    let y_range = if y_max > y_min { y_max - y_min } else { 1. };
+    // the row of `y`, or `None` if it's outside the plot
+    let row = |y: f64| {
+        let row = ((y_max - y) / y_range * (HEIGHT - 1) as f64).round();
+        (0. ..=(HEIGHT - 1) as f64)
+            .contains(&row)
+            .then_some(row as usize)
+    };
+    let column = |x: f64| ((x - x_min) / x_range * (WIDTH - 1) as f64).round() as usize;
+
+    let mut grid = vec![[' '; WIDTH]; HEIGHT];
+    let curve = (0..WIDTH).map(|col| {
+        let x = x_min + x_range * col as f64 / (WIDTH - 1) as f64;
+        row(model.predict_outcome(x))
+    });
+    for (col, row) in curve.enumerate() {
+        if let Some(row) = row {
+            grid[row][col] = '*';
+        }
+    }
+    for (x, y) in x.iter().zip(y) {
+        if let Some(row) = row(*y) {
+            grid[row][column(*x)] = 'o';
+        }
+    }
+
+    let format = |v: f64| {
+        if let Some(p) = precision {
+            format!("{v:.p$}")
+        } else {
+            format!("{v}")
+        }
+    };
+    let (top, bottom) = (format(y_max), format(y_min));
+    let margin = top.len().max(bottom.len());
+    for (idx, cells) in grid.iter().enumerate() {
+        let label = match idx {
+            0 => top.as_str(),
+            _ if idx == HEIGHT - 1 => bottom.as_str(),
+            _ => "",
+        };
+        let cells: String = cells.iter().collect();
+        println!("{label:>margin$} |{cells}");
+    }
+    println!("{:>margin$} +{}", "", "-".repeat(WIDTH));
+    let (left, right) = (format(x_min), format(x_max));
+    let gap = (WIDTH + 1).saturating_sub(left.len() + right.len()).max(1);
+    println!("{:>margin$} {left}{:gap$}{right}", "", "");
+}
+
+#[cfg(feature = "regression")]
+fn print_axes_fit(fit: &std_dev::regression::derived::AxesFit, precision: Option<usize>) {
+    use std_dev::regression::derived::Axes;
+    let (x, y) = match fit.axes {
+        Axes::LogLog => ("lg(x)", "lg(y)"),
+        Axes::SemiLogX => ("lg(x)", "y"),
+        Axes::SemiLogY => ("x", "lg(y)"),
+    };
+    let p = precision.unwrap_or(5);
+    println!(
+        "Transformed line: {y} = {:.3$} * {x} + {:.3$}, Determination ({y} against {x}): {:.4$}",
+        fit.line.k,
+        fit.line.m,
+        fit.determination,
+        p,
+        precision.unwrap_or(4),
+    );
+    println!("Equivalent equation: {fit:.p$}");
+}
+
+/// Fits a polynomial to the points in the file at `path`, reading it twice instead of keeping
+/// the points in memory.
+#[cfg(feature = "regression")]
+fn regression_stream(path: &str, config: &clap::ArgMatches, matches: &clap::ArgMatches) {
+    use std::io::BufRead;
+    use std_dev::online::TwoPassPolynomial;
+
+    let points = || {
+        let file = std::fs::File::open(path).unwrap_or_else(|err| {
+            eprintln!("Failed to open {path:?}: {err}");
+            exit(1);
+        });
+        std::io::BufReader::new(file)
+            .lines()
+            .enumerate()
+            .filter_map(|(idx, line)| {
+                let line = line.unwrap_or_else(|err| {
+                    eprintln!("Failed to read {path:?}: {err}");
+                    exit(1);
+                });
+                let parsed = std_dev::input::parse_rows(&line);
+                if let Some(err) = parsed.errors.first() {
+                    eprintln!(
+                        "{path}: line {}, column {}: {}",
+                        idx + 1,
+                        err.column,
+                        err.reason
+                    );
+                    exit(1);
+                }
+                let row = parsed.values.into_iter().next()?;
+                if row.len() != 2 {
+                    eprintln!("{path}: line {}: expected 2 values per line.", idx + 1);
+                    exit(1);
+                }
+                Some((row[0], row[1]))
+            })
+    };
+    let degree = config.get_one::<usize>("degree").copied().unwrap_or(1);
+    let Some(regression) = TwoPassPolynomial::fit(points, degree) else {
+        eprintln!("{path:?} contains no points.");
+        exit(1);
+    };
+    let Some(coefficients) = regression.coefficients() else {
+        eprintln!("Degree of polynomial is too large; add more datapoints.");
+        exit(1);
+    };
+    let determination = regression.determination();
+    if let Some(p) = matches.get_one::<usize>("precision").copied() {
+        println!("Determination: {determination:.p$}, Predicted equation: {coefficients:.p$}");
+    } else {
+        println!("Determination: {determination:.4}, Predicted equation: {coefficients}");
+    }
+}
+
+/// Fits a line per group of `rows`, where the group is the value in `column` and the other two
+/// values are the predictor and outcome.
+#[cfg(feature = "regression")]
+fn regression_grouped(
+    rows: &[Vec<f64>],
+    column: usize,
+    estimator: &impl LinearEstimator,
+    precision: Option<usize>,
+) {
+    // in order of first appearance
+    let mut groups: Vec<(f64, Vec<f64>, Vec<f64>)> = Vec::new();
+    for row in rows {
+        let mut row = row.clone();
+        let group = row.remove(column);
+        let index = match groups
+            .iter()
+            .position(|(g, _, _)| std_dev::F64OrdHash(*g) == std_dev::F64OrdHash(group))
+        {
+            Some(index) => index,
+            None => {
+                groups.push((group, Vec::new(), Vec::new()));
+                groups.len() - 1
+            }
+        };
+        groups[index].1.push(row[0]);
+        groups[index].2.push(row[1]);
+    }
+    if let Some((group, _, _)) = groups.iter().find(|(_, x, _)| x.len() < 3) {
+        eprintln!("Group {group} has fewer than 3 points.");
+        return;
+    }
+
+    let p = precision.unwrap_or(4);
+    println!(
+        "{:>10} {:>6} {:>12} {:>12} {:>8}",
+        "group", "n", "slope", "intercept", "R²"
+    );
+    for (group, x, y) in &groups {
+        let line = estimator.model_linear(x, y);
+        println!(
+            "{group:>10} {:>6} {:>12.p$} {:>12.p$} {:>8.4}",
+            x.len(),
+            line.k,
+            line.m,
+            line.determination_slice(x, y),
+        );
+    }
+    if groups.len() < 2 {
+        return;
+    }
+    let pairs: Vec<(&[f64], &[f64])> = groups
+        .iter()
+        .map(|(_, x, y)| (x.as_slice(), y.as_slice()))
+        .collect();
+    let comparison = std_dev::regression::grouped::compare_slopes(&pairs);
+    println!(
+        "Common slope: {:.p$}, Slopes differ: F = {:.p$}, p = {:.4}",
+        comparison.common_slope, comparison.test.statistic, comparison.test.p_value,
+    );
+}
+
+#[cfg(feature = "regression")]
+fn write_diagnostics(plots: &std_dev::regression::diagnostics::DiagnosticPlots, dir: &str) {
+    let dir = std::path::Path::new(dir);
+    if let Err(err) = std::fs::create_dir_all(dir) {
+        eprintln!("Failed to create directory {dir:?}: {err}");
+        exit(1);
+    }
+    let files = [
+        (
+            "residuals_vs_fitted.csv",
+            "fitted,residual",
+            &plots.residuals_vs_fitted,
+        ),
+        (
+            "normal_qq.csv",
+            "theoretical_quantile,standardized_residual",
+            &plots.normal_qq,
+        ),
+        (
+            "scale_location.csv",
+            "fitted,sqrt_abs_standardized_residual",
+            &plots.scale_location,
+        ),
+        (
+            "residuals_vs_leverage.csv",
+            "leverage,standardized_residual",
+            &plots.residuals_vs_leverage,
+        ),
+    ];
+    for (name, header, points) in files {
+        let mut content = format!("{header}\n");
+        for (x, y) in points {
+            content.push_str(&format!("{x},{y}\n"));
+        }
+        let path = dir.join(name);
+        if let Err(err) = std::fs::write(&path, content) {
+            eprintln!("Failed to write {path:?}: {err}");
+            exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "regression")]
+fn print_posterior(
+    posterior: &std_dev::regression::bayesian::LinearPosterior,
+    x: &[f64],
+    credibility: f64,
+    precision: Option<usize>,
+) {
+    let p = precision.unwrap_or(4);
+    let (k_low, k_high) = posterior.slope_interval(credibility);
+    let (m_low, m_high) = posterior.intercept_interval(credibility);
+    let x_mean: f64 = std_dev::mean(x);
+    let (y_low, y_high) = posterior.predictive_interval(x_mean, credibility);
+    println!(
+        "{:.0}% credible intervals: slope [{k_low:.p$}, {k_high:.p$}], \
+        y intercept [{m_low:.p$}, {m_high:.p$}], \
+        predicted outcome at x = {x_mean:.p$} [{y_low:.p$}, {y_high:.p$}]",
+        credibility * 100.,
+    );
+}
+
+/// The default `--ransac-threshold`: 3 times the [MAD](std_dev::robust::mad) of the residuals of
+/// a least squares fit of `degree`.
+#[cfg(feature = "regression")]
+fn ransac_threshold(x: &[f64], y: &[f64], degree: usize) -> f64 {
+    let fit = std_dev::regression::OlsEstimator.model_polynomial(x, y, degree);
+    let residuals: Vec<f64> = x
+        .iter()
+        .zip(y)
+        .map(|(x, y)| y - fit.predict_outcome(*x))
+        .collect();
+    3. * std_dev::robust::mad(&residuals)
+}
+
+/// The estimators of the `bench` subcommand.
+#[cfg(feature = "regression")]
+const BENCH_ESTIMATORS: [&str; 8] = [
+    "ols",
+    "theil-sen",
+    "siegel",
+    "huber",
+    "ransac",
+    "spiral",
+    "binary",
+    "descent",
+];
+
+/// Fits `x` and `y` with each of the `estimators` (see [`BENCH_ESTIMATORS`]) and prints the
+/// equations, their R², and the median time of `runs` fits side by side.
+#[cfg(feature = "regression")]
+fn bench(
+    x: &[f64],
+    y: &[f64],
+    degree: usize,
+    estimators: &[&str],
+    runs: usize,
+    precision: Option<usize>,
+) {
+    use std_dev::regression::{spiral, DynModel, OlsEstimator};
+
+    let mut rows = Vec::new();
+    for &estimator in estimators {
+        let (name, fit): (&str, Box<dyn Fn() -> DynModel>) = match (estimator, degree) {
+            ("ols", 1) => ("OLS", Box::new(|| OlsEstimator.model_linear(x, y).boxed())),
+            ("ols", _) => (
+                "OLS",
+                Box::new(|| OlsEstimator.model_polynomial(x, y, degree).boxed()),
+            ),
+            ("theil-sen", 1) => (
+                "Theil-Sen",
+                Box::new(|| {
+                    std_dev::regression::LinearTheilSen
+                        .model_linear(x, y)
+                        .boxed()
+                }),
+            ),
+            ("theil-sen", _) => (
+                "Theil-Sen",
+                Box::new(|| {
+                    std_dev::regression::PolynomialTheilSen
+                        .model_polynomial(x, y, degree)
+                        .boxed()
+                }),
+            ),
+            ("siegel", 1) => (
+                "Siegel",
+                Box::new(|| std_dev::regression::LinearSiegel.model_linear(x, y).boxed()),
+            ),
+            ("huber", 1) => (
+                "Huber",
+                Box::new(|| {
+                    std_dev::regression::HuberEstimator::default()
+                        .model_linear(x, y)
+                        .boxed()
+                }),
+            ),
+            ("ransac", _) => {
+                let ransac = std_dev::regression::ransac::Ransac::new(
+                    OlsEstimator,
+                    ransac_threshold(x, y, degree),
+                );
+                (
+                    "RANSAC",
+                    Box::new(move || ransac.model_polynomial(x, y, degree).boxed()),
+                )
+            }
+            ("spiral", 1) => (
+                "Spiral",
+                Box::new(|| spiral::Options::default().model_linear(x, y).boxed()),
+            ),
+            ("spiral", 2) => (
+                "Spiral",
+                Box::new(|| {
+                    spiral::Options::default()
+                        .model_polynomial(x, y, degree)
+                        .boxed()
+                }),
+            ),
+            ("binary", _) => (
+                "Binary search",
+                Box::new(|| {
+                    BinarySearchOptions::default()
+                        .model_polynomial(x, y, degree)
+                        .boxed()
+                }),
+            ),
+            ("descent", _) => (
+                "Gradient descent",
+                Box::new(|| {
+                    GradientDescentParallelOptions::default()
+                        .model_polynomial(x, y, degree)
+                        .boxed()
+                }),
+            ),
+            ("spiral", _) => {
+                eprintln!(
+                    "The spiral estimator only fits polynomials of degree 1 & 2; skipping it."
+                );
+                continue;
+            }
+            (estimator, _) => {
+                eprintln!("The {estimator} estimator only fits lines; skipping it.");
+                continue;
+            }
+        };
+
+        let mut times = Vec::with_capacity(runs);
+        let mut model = None;
+        with_progress(&format!("Fitting {name}"), || {
+            for _ in 0..runs {
+                let now = Instant::now();
+                model = Some(fit());
+                times.push(now.elapsed());
+            }
+        });
+        let model = model.expect("runs is at least 1");
+        times.sort_unstable();
+        let determination = model.determination(x.iter().copied(), y.iter().copied(), x.len());
+        rows.push(match precision {
+            Some(p) => vec![
+                name.to_owned(),
+                format!("{model:.p$}"),
+                format!("{determination:.p$}"),
+                format!("{:.2?}", times[runs / 2]),
+            ],
+            None => vec![
+                name.to_owned(),
+                model.to_string(),
+                format!("{determination:.4}"),
+                format!("{:.2?}", times[runs / 2]),
+            ],
+        });
+    }
+    let time = if runs > 1 { "Median time" } else { "Time" };
+    print_table(&["Estimator", "Equation", "R²", time], &rows);
+}
+
+/// Prints the confidence interval of each coefficient of `fit`, from the highest degree.
+#[cfg(feature = "regression")]
+fn print_coefficient_intervals(
+    fit: &std_dev::regression::WithUncertainty<std_dev::regression::PolynomialCoefficients>,
+    confidence: f64,
+    precision: Option<usize>,
+) {
+    let p = precision.unwrap_or(4);
+    let intervals: Vec<String> = (0..fit.covariance.len())
+        .rev()
+        .map(|index| {
+            let (low, high) = fit.coefficient_interval(index, confidence);
+            let term = match index {
+                0 => "constant".to_owned(),
+                1 => "x".to_owned(),
+                _ => format!("x^{index}"),
+            };
+            format!("{term} [{low:.p$}, {high:.p$}]")
+        })
+        .collect();
+    println!(
+        "{}% confidence intervals of the coefficients: {}",
+        confidence * 100.,
+        intervals.join(", "),
+    );
+}
+
+/// Prints the outcome `model` predicts at each of the `predictors`, and if an `interval` is given,
+/// the prediction interval of the fit with that confidence level.
+#[cfg(feature = "regression")]
+fn print_predictions(
+    model: &impl std_dev::regression::Predictive,
+    predictors: &[f64],
+    interval: Option<(
+        &std_dev::regression::WithUncertainty<std_dev::regression::PolynomialCoefficients>,
+        f64,
+    )>,
+    precision: Option<usize>,
+) {
+    let format = |v: f64| {
+        if let Some(p) = precision {
+            format!("{v:.p$}")
+        } else {
+            format!("{v}")
+        }
+    };
+    for x in predictors {
+        let y = model.predict_outcome(*x);
+        if let Some((fit, level)) = interval {
+            let (low, high) = fit.predict_interval(*x, level);
+            println!(
+                "f({}) = {} ({}% prediction interval: [{}, {}])",
+                format(*x),
+                format(y),
+                level * 100.,
+                format(low),
+                format(high),
+            );
+        } else {
+            println!("f({}) = {}", format(*x), format(y));
+        }
+    }
+}
+
+#[cfg(feature = "regression")]
+fn print_runners_up(
+    score: f64,
+    ranked: &[std_dev::regression::RankedModel],
+    precision: Option<usize>,
+) {
+    let p = precision.unwrap_or(4);
+    println!("Score: {score:.p$}");
+    for candidate in ranked {
+        if let Some(precision) = precision {
+            println!(
+                "Runner-up: Score: {:.p$}, Determination: {:.p$}, Predicted equation: {:.precision$}",
+                candidate.score, candidate.determination, candidate.model,
+            );
+        } else {
+            println!(
+                "Runner-up: Score: {:.p$}, Determination: {:.p$}, Predicted equation: {}",
+                candidate.score, candidate.determination, candidate.model,
+            );
+        }
+    }
+}
+
+#[cfg(feature = "decimal")]
+fn print_decimal(input: InputValue<DecimalValue>, precision: Option<usize>) {
+    use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+    use std_dev::decimal::{self, Decimal};
+
+    let clusters = match input {
+        InputValue::Count(count) => count,
+        InputValue::List(list) => {
+            let mut count = Vec::with_capacity(list.len());
+            for item in list {
+                if item.len() != 1 && item.len() != 2 {
+                    eprintln!("Expected one or two values per line.");
+                    return;
+                }
+                let second = item
+                    .get(1)
+                    .map_or(Some(1), |d| d.0.round().to_usize())
+                    .unwrap_or(0);
+                count.push((item[0], second));
+            }
+            count
+        }
+    };
+    let mut values: Vec<Decimal> = clusters
+        .into_iter()
+        .flat_map(|(value, count)| std::iter::repeat(value.0).take(count))
+        .collect();
+    if values.is_empty() {
+        eprintln!("Only invalid input. Try again.");
+        return;
+    }
+
+    let round = |d: Decimal| precision.map_or(d.normalize(), |p| d.round_dp(p as u32));
+    let Some(sum) = decimal::sum(&values) else {
+        eprintln!(
+            "The sum is larger than decimals can represent ({}). Try again without --decimal.",
+            Decimal::MAX
+        );
+        return;
+    };
+    let mean = decimal::mean(&values).expect("the sum didn't overflow");
+    let std_dev = match decimal::standard_deviation(&values) {
+        Some(std_dev) => std_dev.standard_deviation,
+        None => {
+            if !QUIET.load(Ordering::Relaxed) {
+                eprintln!(
+                    "The squared deviations are larger than decimals can represent, \
+                    so the standard deviation is approximated with floats."
+                );
+            }
+            let floats: Vec<f64> = values.iter().filter_map(Decimal::to_f64).collect();
+            let std_dev = std_dev::standard_deviation(&floats).standard_deviation;
+            // at most half the range of the values, which are decimals
+            Decimal::from_f64(std_dev).unwrap_or(Decimal::MAX)
+        }
+    };
+    let percentiles = decimal::percentiles(&mut values);
+    println!(
+        "Sum: {}, standard deviation: {}, mean: {}, median: {}{}{}",
+        round(sum),
+        round(std_dev),
+        round(mean),
+        round(percentiles.median),
+        percentiles.lower_quadrille.map_or("".into(), |quadrille| {
+            format!(", lower quadrille: {}", round(quadrille))
+        }),
+        percentiles.higher_quadrille.map_or("".into(), |quadrille| {
+            format!(", upper quadrille: {}", round(quadrille))
+        }),
+    );
+}
+
+#[cfg(feature = "dp")]
+fn print_dp(
+    values: &mut std_dev::OwnedClusterList,
+    epsilon: f64,
+    range: std::ops::RangeInclusive<f64>,
+    precision: Option<usize>,
+) {
+    use std_dev::{dp, Fraction};
+
+    // count, mean, median, and the two quadrilles
+    let epsilon_each = epsilon / 5.;
+    let mut rng = rng();
+    let count = dp::count(values.borrow().len(), epsilon_each, &mut rng);
+    let mean = dp::mean_cluster(&values.borrow(), range.clone(), epsilon_each, &mut rng);
+    let mut quantile =
+        |fraction| dp::quantile_cluster(values, fraction, range.clone(), epsilon_each, &mut rng);
+    let median = quantile(Fraction::HALF);
+    let lower = quantile(Fraction::ONE_QUARTER);
+    let upper = quantile(Fraction::THREE_QUARTERS);
+
+    let p = precision.unwrap_or(4);
+    println!(
+        "Count: {:.p$}, mean: {:.p$}, median: {:.p$}, \
+        lower quadrille: {:.p$}, upper quadrille: {:.p$}",
+        count.value, mean.value, median.value, lower.value, upper.value,
+    );
+    println!(
+        "Differentially private with ε = {epsilon} (ε = {epsilon_each} each). \
+        Noise scale: count {:.p$}, mean {:.p$}, quantiles {:.p$} ranks",
+        count.noise_scale, mean.noise_scale, median.noise_scale,
+    );
+}
+
+/// Reads the numbers in the file at `path`, printing an error and exiting if it can't be read.
+///
+/// If `column` is [`Some`], only the values of that column (starting at 0) are returned.
+fn read_values(path: &str, column: Option<usize>) -> Vec<f64> {
+    let content = std::fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {path:?}: {err}");
+        exit(1)
+    });
+    parse_values(&content, column)
+}
+/// Parses all the values of `content`, or only those in `column` (starting at 0).
+/// Invalid values (e.g. headers) are ignored.
+fn parse_values(content: &str, column: Option<usize>) -> Vec<f64> {
+    std_dev::input::parse_values(content, column).values
+}
+
+/// Runs the stages of the `pipe` subcommand, printing an error and exiting on failure.
+fn pipe(config: &clap::ArgMatches, matches: &clap::ArgMatches) {
+    let stages: Vec<pipeline::Stage> = config
+        .get_many::<String>("then")
+        .expect("stages are required")
+        .map(|stage| stage.parse().expect("we've validated the stages"))
+        .collect();
+    let path = config.get_one::<String>("file");
+    let binary = matches
+        .get_one::<String>("input_format")
+        .map(String::as_str)
+        == Some("f64le");
+    let path = path.map_or("-", String::as_str);
+    let mut source: Box<dyn pipeline::Source> = if binary {
+        let reader: Box<dyn std::io::Read> = if path == "-" {
+            Box::new(stdin().lock())
+        } else {
+            match std::fs::File::open(path) {
+                Ok(file) => Box::new(std::io::BufReader::new(file)),
+                Err(err) => {
+                    eprintln!("Failed to read {path}: {err}");
+                    exit(1);
+                }
+            }
+        };
+        Box::new(pipeline::Binary::new(reader))
+    } else if matches.get_flag("csv") {
+        let files = [path.to_owned()];
+        let rows = read_csv(&files, &CsvColumns::from_matches(matches), true)
+            .into_iter()
+            .flat_map(|(_, input)| match input {
+                InputValue::List(rows) => rows,
+                InputValue::Count(_) => unreachable!("we read the CSV as rows"),
+            })
+            .collect();
+        Box::new(pipeline::Rows(rows))
+    } else {
+        match pipeline::Lines::open(path, true) {
+            Ok(text) => Box::new(text),
+            Err(err) => {
+                eprintln!("Failed to read {path}: {err}");
+                exit(1);
+            }
+        }
+    };
+    let reports = pipeline::run(&mut *source, &stages).unwrap_or_else(|err| {
+        eprintln!("Failed to read input: {err}");
+        exit(1)
+    });
+    let mut sink: Box<dyn pipeline::Sink> = match config
+        .get_one::<String>("sink")
+        .expect("we've provided a default value")
+        .as_str()
+    {
+        "json" => Box::new(pipeline::Json),
+        "csv" => Box::new(pipeline::Csv),
+        _ => Box::new(pipeline::Text(value_format(matches, None))),
+    };
+    if let Err(err) = sink.write(&reports) {
+        eprintln!("Failed to write output: {err}");
+        exit(1);
+    }
+}
+
+/// Prints `rows` with each column aligned.
+fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let header = header.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+/// Prints a row of statistics per column of `content`, named by the first line if it isn't
+/// numeric.
+fn summary_by_column(content: &str, matches: &clap::ArgMatches) {
+    let mut lines = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .peekable();
+    let header = lines
+        .next_if(|line| {
+            split_line(line)
+                .iter()
+                .any(|token| std_dev::units::parse_value(token).is_err())
+        })
+        .map(split_line);
+    let mut columns: Vec<Vec<(f64, usize)>> = Vec::new();
+    for line in lines {
+        for (idx, token) in split_line(line).into_iter().enumerate() {
+            if columns.len() <= idx {
+                columns.resize_with(idx + 1, Vec::new);
+            }
+            if let Ok(value) = std_dev::units::parse_value(token) {
+                columns[idx].push((value, 1));
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (idx, values) in columns.into_iter().enumerate() {
+        let format = value_format(matches, Some(idx));
+        let f = |v: f64| format.format(v);
+        let name = header
+            .as_ref()
+            .and_then(|header| header.get(idx))
+            .map_or_else(|| (idx + 1).to_string(), |name| name.to_string());
+        if values.is_empty() {
+            rows.push(vec![name, "0".into()]);
+            continue;
+        }
+        let (min, max) = values
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (v, _)| {
+                (min.min(*v), max.max(*v))
+            });
+        let mut values = std_dev::OwnedClusterList::new(values);
+        let std_dev = std_dev::standard_deviation_cluster(&values.borrow());
+        let percentiles = percentiles(&mut values);
+        let quartile = |q: Option<f64>| q.map_or("-".into(), f);
+        rows.push(vec![
+            name,
+            values.borrow().len().to_string(),
+            f(std_dev.mean),
+            f(std_dev.standard_deviation),
+            f(min),
+            quartile(percentiles.lower_quadrille),
+            f(percentiles.median),
+            quartile(percentiles.higher_quadrille),
+            f(max),
+        ]);
+    }
+    print_table(
+        &[
+            "column",
+            "count",
+            "mean",
+            "standard deviation",
+            "min",
+            "lower quartile",
+            "median",
+            "upper quartile",
+            "max",
+        ],
+        &rows,
+    );
+}
+
+fn summary(config: &clap::ArgMatches, matches: &clap::ArgMatches) {
+    use std_dev::online::{Accumulator, Sketch};
+
+    if config.get_flag("by_column") {
+        let stdin = ["-".to_owned()];
+        let files: Vec<&String> = config
+            .get_many::<String>("files")
+            .map_or_else(|| stdin.iter().collect(), Iterator::collect);
+        for file in &files {
+            if files.len() > 1 {
+                println!("{file}:");
+            }
+            summary_by_column(&read_file(file), matches);
+        }
+        return;
+    }
+
+    let column = config.get_one::<usize>("column").map(|c| c - 1);
+    let format = value_format(matches, column);
+    let f = |v: f64| format.format(v);
+    let row = |name: &str, sketch: &Sketch| {
+        let stats = sketch.stats;
+        let std_dev = stats.standard_deviation();
+        // sketches of the first version have no digest
+        let quantile = |q| sketch.digest.quantile(q).map_or("-".into(), f);
+        vec![
+            name.to_owned(),
+            stats.len().to_string(),
+            f(std_dev.mean),
+            f(std_dev.standard_deviation),
+            f(stats.min()),
+            f(stats.max()),
+            quantile(0.5),
+            quantile(0.95),
+        ]
+    };
+
+    let mut combined = Sketch::new();
+    let mut rows = Vec::new();
+    for path in config.get_many::<String>("files").into_iter().flatten() {
+        let sketch = if config.get_flag("merge_sketches") {
+            read_sketches([path])
+        } else {
+            let mut sketch = Sketch::new();
+            sketch.extend(read_values(path, column));
+            sketch
+        };
+        combined.merge(&sketch);
+        rows.push(row(path, &sketch));
+    }
+    rows.push(row("combined", &combined));
+    print_table(
+        &[
+            "file",
+            "count",
+            "mean",
+            "standard deviation",
+            "min",
+            "max",
+            "~median",
+            "~p95",
+        ],
+        &rows,
+    );
+
+    if let Some(path) = config.get_one::<String>("save_sketch") {
+        write_sketch(&combined, path);
+    }
+}
+
+/// Reads and merges the sketches at `paths`, printing an error and exiting on failure.
+fn read_sketches<'a>(paths: impl IntoIterator<Item = &'a String>) -> std_dev::online::Sketch {
+    use std_dev::online::{Accumulator, Sketch};
+
+    let mut combined = Sketch::new();
+    for path in paths {
+        let content = std::fs::read_to_string(path).unwrap_or_else(|err| {
+            eprintln!("Failed to read {path:?}: {err}");
+            exit(1)
+        });
+        let sketch: Sketch = content.parse().unwrap_or_else(|err| {
+            eprintln!("Failed to parse {path:?}: {err}");
+            exit(1)
+        });
+        combined.merge(&sketch);
+    }
+    combined
+}
+fn write_sketch(sketch: &std_dev::online::Sketch, path: &str) {
+    if let Err(err) = std::fs::write(path, sketch.to_string()) {
+        eprintln!("Failed to write sketch to {path:?}: {err}");
+        exit(1);
+    }
+}
+/// The `sketch` subcommand: build, merge, and query sketches.
+fn sketch(config: &clap::ArgMatches, matches: &clap::ArgMatches) {
+    use std_dev::online::{Sketch, TDigest};
+
+    match config.subcommand() {
+        Some(("build", config)) => {
+            let column = config.get_one::<usize>("column").map(|c| c - 1);
+            let compression = *config
+                .get_one::<f64>("compression")
+                .expect("we provided a default value");
+            let mut sketch = Sketch {
+                digest: TDigest::new(compression),
+                ..Default::default()
+            };
+            if let Some(files) = config.get_many::<String>("files") {
+                for path in files {
+                    sketch.extend(read_values(path, column));
+                }
+            } else {
+                // read line by line, so unbounded streams use constant memory
+                for line in stdin().lock().lines().map_while(Result::ok) {
+                    sketch.extend(parse_values(&line, column));
+                }
+            }
+            let out = config.get_one::<String>("out").expect("out is required");
+            write_sketch(&sketch, out);
+        }
+        Some(("merge", config)) => {
+            let sketch = read_sketches(config.get_many::<String>("files").into_iter().flatten());
+            let out = config.get_one::<String>("out").expect("out is required");
+            write_sketch(&sketch, out);
+        }
+        Some(("query", config)) => {
+            let sketch = read_sketches(config.get_many::<String>("files").into_iter().flatten());
+            let format = value_format(matches, None);
+            if sketch.digest.is_empty() {
+                eprintln!("The sketches contain no quantiles.");
+                exit(1);
+            }
+            for q in config
+                .get_many::<f64>("quantile")
+                .expect("we provided a default value")
+            {
+                let value = sketch
+                    .digest
+                    .quantile(*q)
+                    .expect("we checked that the digest isn't empty");
+                println!("{q}: {}", format.format(value));
+            }
+        }
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+fn compare(config: &clap::ArgMatches, precision: Option<usize>) {
+    use std_dev::summary::Summary;
+
+    let p = precision.unwrap_or(4);
+    let column = config.get_one::<usize>("column").map(|c| c - 1);
+    let (before, after) = if let Some(files) = config.get_many::<String>("files") {
+        let files: Vec<&String> = files.collect();
+        (read_values(files[0], column), read_values(files[1], column))
+    } else {
+        let mut content = String::new();
+        if let Err(err) = std::io::Read::read_to_string(&mut stdin().lock(), &mut content) {
+            eprintln!("Failed to read input: {err}");
+            exit(1);
+        }
+        content
+            .lines()
+            .filter_map(|line| {
+                let mut values = tokens(line).filter_map(|s| s.parse::<f64>().ok());
+                Some((values.next()?, values.next()?))
+            })
+            .unzip()
+    };
+    if before.is_empty() || after.is_empty() {
+        eprintln!("No values to compare.");
+        exit(1);
+    }
+
+    if config.get_flag("paired") {
+        if before.len() != after.len() {
+            eprintln!(
+                "Paired series must have the same number of values, got {} and {}.",
+                before.len(),
+                after.len()
+            );
+            exit(1);
+        }
+        let comparison = std_dev::tests::paired(&before, &after);
+        let differences = &comparison.differences;
+        println!(
+            "Differences (after - before) of {} pairs: mean {:.p$}, standard deviation {:.p$}, \
+            median {:.p$}",
+            differences.count,
+            differences.mean,
+            differences.standard_deviation,
+            differences
+                .median
+                .expect("summary was created from all values"),
+        );
+        for (q, v) in &differences.quantiles {
+            println!("  p{}: {v:.p$}", q * 100.);
+        }
+        println!(
+            "Sign test: {} positive differences, p-value {:.4}",
+            comparison.sign_test.statistic, comparison.sign_test.p_value
+        );
+        println!(
+            "Wilcoxon signed-rank test: W+ = {}, p-value {:.4}",
+            comparison.wilcoxon.statistic, comparison.wilcoxon.p_value
+        );
+        return;
+    }
+
+    let diff = Summary::new(&before).diff(&Summary::new(&after));
+    let print_delta = |name: &str, delta: std_dev::summary::Delta| {
+        println!(
+            "{name}: {:.p$} -> {:.p$} ({:+.p$}, {:+.2}%)",
+            delta.before,
+            delta.after,
+            delta.absolute,
+            delta.relative * 100.,
+        );
+    };
+    print_delta("Mean", diff.mean);
+    if let Some(median) = diff.median {
+        print_delta("Median", median);
+    }
+    print_delta("Standard deviation", diff.standard_deviation);
+    if let Some(significance) = diff.mean_significance {
+        println!(
+            "Welch's t-test of the means: t = {:.p$}, p-value {:.4}",
+            significance.t, significance.p_value
+        );
+    }
+    let mann_whitney = std_dev::tests::mann_whitney_u(&before, &after);
+    println!(
+        "Mann-Whitney U test: U = {}, p-value {:.4}",
+        mann_whitney.statistic, mann_whitney.p_value
+    );
+}
+
+/// A statistic compared by the `gate` subcommand.
+#[cfg(feature = "gate")]
+#[derive(Debug, Clone, Copy)]
+enum Metric {
+    Mean,
+    Median,
+    Min,
+    Max,
+    StandardDeviation,
+    Quantile(f64),
+}
+#[cfg(feature = "gate")]
+impl FromStr for Metric {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "mean" => Self::Mean,
+            "median" | "p50" => Self::Median,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "standard-deviation" | "std-dev" => Self::StandardDeviation,
+            _ => {
+                let percentile = s
+                    .strip_prefix('p')
+                    .and_then(|p| p.parse::<f64>().ok())
+                    .filter(|p| *p > 0. && *p < 100.)
+                    .ok_or(
+                        "metric needs to be mean, median, min, max, \
+                        standard-deviation, or a percentile like p95",
+                    )?;
+                Self::Quantile(percentile / 100.)
+            }
+        })
+    }
+}
+#[cfg(feature = "gate")]
+impl Metric {
+    fn delta(self, diff: &std_dev::summary::SummaryDiff) -> Option<std_dev::summary::Delta> {
+        match self {
+            Self::Mean => Some(diff.mean),
+            Self::Median => diff.median,
+            Self::Min => Some(diff.min),
+            Self::Max => Some(diff.max),
+            Self::StandardDeviation => Some(diff.standard_deviation),
+            Self::Quantile(q) => diff.quantile(q),
+        }
+    }
+}
+#[cfg(feature = "gate")]
+impl Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mean => write!(f, "mean"),
+            Self::Median => write!(f, "median"),
+            Self::Min => write!(f, "min"),
+            Self::Max => write!(f, "max"),
+            Self::StandardDeviation => write!(f, "standard deviation"),
+            Self::Quantile(q) => write!(f, "p{}", q * 100.),
+        }
+    }
+}
+/// The largest allowed regression, either relative to the baseline or absolute.
+#[cfg(feature = "gate")]
+#[derive(Debug, Clone, Copy)]
+enum Threshold {
+    Relative(f64),
+    Absolute(f64),
+}
+#[cfg(feature = "gate")]
+impl FromStr for Threshold {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = "max-regression needs to be a non-negative number, optionally with a % suffix";
+        if let Some(percent) = s.strip_suffix('%') {
+            let v: f64 = percent.trim().parse().map_err(|_| err)?;
+            (v >= 0.).then_some(Self::Relative(v / 100.)).ok_or(err)
+        } else {
+            let v: f64 = s.trim().parse().map_err(|_| err)?;
+            (v >= 0.).then_some(Self::Absolute(v)).ok_or(err)
+        }
+    }
+}
+
+/// Compares fresh input to a saved baseline and exits with 1 if the metric has regressed.
+#[cfg(feature = "gate")]
+fn gate(config: &clap::ArgMatches, precision: Option<usize>) -> ! {
+    use std_dev::summary::{Summary, DEFAULT_QUANTILES};
+
+    let p = precision.unwrap_or(4);
+    let column = config.get_one::<usize>("column").map(|c| c - 1);
+    let metric = *config
+        .get_one::<Metric>("metric")
+        .expect("we provided a default value");
+    let baseline_path = config
+        .get_one::<String>("baseline")
+        .expect("baseline is required");
+
+    let mut values = Vec::new();
+    if let Some(files) = config.get_many::<String>("files") {
+        for path in files {
+            values.extend(read_values(path, column));
+        }
+    } else {
+        let mut content = String::new();
+        if let Err(err) = std::io::Read::read_to_string(&mut stdin().lock(), &mut content) {
+            eprintln!("Failed to read input: {err}");
+            exit(1);
+        }
+        values = parse_values(&content, column);
+    }
+    if values.is_empty() {
+        eprintln!("No values to compare.");
+        exit(1);
+    }
+    let mut quantiles = DEFAULT_QUANTILES.to_vec();
+    if let Metric::Quantile(q) = metric {
+        if !quantiles.iter().any(|v| (v - q).abs() < 1e-9) {
+            quantiles.push(q);
+        }
+    }
+    let current = Summary::with_quantiles(&values, &quantiles);
+
+    if config.get_flag("save") {
+        let json = serde_json::to_string_pretty(&current).expect("summaries are serializable");
+        if let Err(err) = std::fs::write(baseline_path, json) {
+            eprintln!("Failed to write baseline to {baseline_path:?}: {err}");
+            exit(1);
+        }
+        println!("Saved baseline to {baseline_path:?}.");
+        exit(0);
+    }
+
+    let baseline: Summary = std::fs::read_to_string(baseline_path)
+        .map_err(|err| err.to_string())
+        .and_then(|s| serde_json::from_str(&s).map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to read baseline {baseline_path:?}: {err}");
+            exit(1)
+        });
+    let diff = baseline.diff(&current);
+    let Some(delta) = metric.delta(&diff) else {
+        eprintln!("The baseline doesn't contain the {metric}.");
+        exit(1)
+    };
+
+    let sign = if config.get_flag("higher_is_better") {
+        -1.
+    } else {
+        1.
+    };
+    let threshold = *config
+        .get_one::<Threshold>("max_regression")
+        .expect("we provided a default value");
+    let exceeds = match threshold {
+        Threshold::Relative(max) => sign * delta.relative > max,
+        Threshold::Absolute(max) => sign * delta.absolute > max,
+    };
+    let significant = config
+        .get_one::<f64>("significance")
+        .map_or(true, |alpha| diff.is_significant(*alpha));
+
+    print!(
+        "{metric}: {:.p$} -> {:.p$} ({:+.p$}, {:+.2}%)",
+        delta.before,
+        delta.after,
+        delta.absolute,
+        delta.relative * 100.,
+    );
+    if let Some(significance) = diff.mean_significance {
+        print!(
+            ", p-value of the change of the mean: {:.4}",
+            significance.p_value
+        );
+    }
+    println!();
+    if exceeds && significant {
+        println!("Regression: the {metric} changed more than allowed.");
+        exit(1);
+    } else if exceeds {
+        println!("OK: the {metric} changed more than allowed, but the change isn't significant.");
+    } else {
+        println!("OK");
+    }
+    exit(0)
+}
+
+/// Continuously reads lines from stdin and prints the statistics of the changed columns, or of
+/// the changed groups with `--group-by`.
+///
+/// The statistics and a [t-digest](std_dev::online::TDigest) of the quantiles are kept per column
+/// or group. While lines have two columns, a line is also fitted to them, and printed from the
+/// second line.
+fn follow(matches: &clap::ArgMatches, debug_performance: bool) -> ! {
+    use std_dev::online::{Accumulator, Cache, Sketch};
+
+    #[cfg(feature = "regression")]
+    let p = matches.get_one::<usize>("precision").copied();
+    #[cfg(feature = "regression")]
+    let mut regression = std_dev::online::IncrementalLinear::new();
+    #[cfg(feature = "regression")]
+    let print_fit = |fit: &std_dev::online::IncrementalLinear, prefix: &str, elapsed: &str| {
+        let coefficients = fit.coefficients();
+        let determination = fit.determination();
+        if let Some(p) = p {
+            println!(
+                "{prefix}Determination: {determination:.p$}, \
+                Predicted equation: {coefficients:.p$}{elapsed}",
+            );
+        } else {
+            println!(
+                "{prefix}Determination: {determination:.4}, \
+                Predicted equation: {coefficients}{elapsed}",
+            );
+        }
+    };
+    let print_sketch = |name: &str, sketch: &Sketch, format: ValueFormat| {
+        let f = |v: f64| format.format(v);
+        let q = |quantile: f64| sketch.digest.quantile(quantile).map_or("-".into(), f);
+        let stats = &sketch.stats;
+        let std_dev = stats.standard_deviation();
+        println!(
+            "{name}: count: {}, standard deviation: {}, mean: {}, ~median: {}, ~q1: {}, ~q3: {}, \
+            min: {}, max: {}",
+            stats.len(),
+            f(std_dev.standard_deviation),
+            f(std_dev.mean),
+            q(0.5),
+            q(0.25),
+            q(0.75),
+            f(stats.min()),
+            f(stats.max()),
+        );
+    };
+    let print_elapsed = |elapsed: u128| {
+        if debug_performance {
+            println!("Update took {elapsed}µs");
+        } else {
+            println!("(updated in {elapsed}µs)");
+        }
+    };
+
+    let mut lines = stdin().lock().lines().map_while(Result::ok);
+    if let Some(key) = matches.get_one::<ColumnRef>("group_by") {
+        let value = matches.get_one::<ColumnRef>("value_column");
+        let (key, value) = group_columns(key, value, &mut lines);
+        let mut groups: Cache<String, Sketch> = Cache::new();
+        for line in lines {
+            let now = Instant::now();
+            let tokens = split_line(&line);
+            let (Some(k), Some(v)) = (tokens.get(key), tokens.get(value)) else {
+                continue;
+            };
+            let Some(v) = parse(v) else { continue };
+            groups.push(k.to_string(), v);
+            let changed = groups.take_changed();
+            let elapsed = now.elapsed().as_micros();
+            for group in changed {
+                let sketch = groups.get(&group).expect("changed groups exist");
+                print_sketch(&group, sketch, value_format(matches, Some(value)));
+            }
+            print_elapsed(elapsed);
+        }
+        exit(0)
+    }
+
+    let mut columns: Cache<usize, Sketch> = Cache::new();
+    for line in lines {
+        let now = Instant::now();
+        let values: Vec<f64> = tokens(&line).filter_map(parse).collect();
+        if values.is_empty() {
+            continue;
+        }
+        match matches.subcommand() {
+            #[cfg(feature = "regression")]
+            Some(("regression", _)) => {
+                if values.len() != 2 {
+                    eprintln!("Expected 2 values per line.");
+                    continue;
+                }
+                regression.push((values[0], values[1]));
+                let elapsed = now.elapsed().as_micros();
+                print_fit(&regression, "", &format!(" (updated in {elapsed}µs)"));
+            }
+            _ => {
+                #[cfg(feature = "regression")]
+                let fitted = if values.len() == 2 {
+                    regression.push((values[0], values[1]));
+                    true
+                } else {
+                    false
+                };
+                for (column, value) in values.into_iter().enumerate() {
+                    columns.push(column, value);
+                }
+                let changed = columns.take_changed();
+                let elapsed = now.elapsed().as_micros();
+                for column in changed {
+                    let sketch = columns.get(&column).expect("changed columns exist");
+                    let name = format!("Column {}", column + 1);
+                    print_sketch(&name, sketch, value_format(matches, Some(column)));
+                }
+                #[cfg(feature = "regression")]
+                if fitted && regression.len() >= 2 {
+                    print_fit(&regression, "Column 2 by column 1: ", "");
+                }
+                print_elapsed(elapsed);
+            }
+        }
+    }
+    exit(0)
+}
+
+/// Reads values from stdin, and prints the running count, mean, standard deviation, and estimated
+/// p95 every `--every` lines with values, or for the first such line after `--interval` seconds.
+///
+/// Text in the lines is ignored, so e.g. the lines of a log can be piped directly.
+fn stream(matches: &clap::ArgMatches) -> ! {
+    use std_dev::online::{Accumulator, RunningStats};
+    use std_dev::percentile::P2Estimator;
+
+    let every = matches.get_one::<usize>("every").copied().unwrap_or(1);
+    let interval = matches
+        .get_one::<f64>("interval")
+        .map(|secs| std::time::Duration::from_secs_f64(*secs));
+    let format = value_format(matches, None);
+    let f = |v: f64| format.format(v);
+    let print = |stats: &RunningStats, p95: &P2Estimator| {
+        let std_dev = stats.standard_deviation();
+        println!(
+            "count: {}, mean: {}, standard deviation: {}, ~p95: {}",
+            stats.len(),
+            f(std_dev.mean),
+            f(std_dev.standard_deviation),
+            p95.estimate().map_or("-".into(), f),
+        );
+    };
+
+    let mut stats = RunningStats::new();
+    let mut p95 = P2Estimator::new(0.95);
+    let mut lines = 0_usize;
+    let mut last_print = Instant::now();
+    let mut printed = true;
+    for line in stdin().lock().lines().map_while(Result::ok) {
+        let len = stats.len();
+        let decimal_comma = DECIMAL_COMMA.load(Ordering::Relaxed);
+        for value in tokens(&line).filter_map(|token| {
+            std_dev::units::parse_value(&std_dev::input::normalize_number(token, decimal_comma))
+                .ok()
+        }) {
+            stats.push(value);
+            p95.push(value);
+        }
+        if stats.len() == len {
+            continue;
+        }
+        lines += 1;
+        printed = false;
+        let due = match interval {
+            Some(interval) => last_print.elapsed() >= interval,
+            None => lines % every == 0,
+        };
+        if due {
+            print(&stats, &p95);
+            last_print = Instant::now();
+            printed = true;
+        }
+    }
+    if !printed {
+        print(&stats, &p95);
+    }
+    exit(0)
+}
+
+/// The prefixes given to `--units`: the default, and those of specific columns.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Units {
+    default: Prefixes,
+    /// `(column, prefixes)`, with columns starting at 0.
+    columns: Vec<(usize, Prefixes)>,
+}
+impl Units {
+    fn column(&self, column: Option<usize>) -> Prefixes {
+        column
+            .and_then(|column| self.columns.iter().find(|(c, _)| *c == column))
+            .map_or(self.default, |(_, prefixes)| *prefixes)
+    }
+}
+impl FromStr for Units {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERROR: &str = "expected `plain`, `si`, or `binary`, optionally prefixed by a column \
+            (starting at 1), e.g. `si,3=binary`";
+        let mut units = Self::default();
+        for part in s.split(',') {
+            match part.split_once('=') {
+                Some((column, prefixes)) => {
+                    let column = column
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|c| *c > 0)
+                        .ok_or(ERROR)?;
+                    units
+                        .columns
+                        .push((column - 1, prefixes.parse().map_err(|_| ERROR)?));
+                }
+                None => units.default = part.parse().map_err(|_| ERROR)?,
+            }
+        }
+        Ok(units)
+    }
+}
+/// How to print the values of `column` (starting at 0), according to `--units`,
+/// `--significant-digits`, `--precision`, and `--format`.
+fn value_format(matches: &clap::ArgMatches, column: Option<usize>) -> ValueFormat {
+    ValueFormat {
+        prefixes: matches
+            .get_one::<Units>("units")
+            .map_or(Prefixes::None, |units| units.column(column)),
+        significant_digits: matches.get_one::<usize>("significant_digits").copied(),
+        decimals: matches.get_one::<usize>("precision").copied(),
+        notation: matches
+            .get_one::<Notation>("format")
+            .copied()
+            .unwrap_or_default(),
+    }
+}
+
+/// A column, referenced by either the name in the header or the index (starting at 0).
+#[derive(Debug, Clone, PartialEq)]
+enum ColumnRef {
+    Name(String),
+    Index(usize),
+}
+impl ColumnRef {
+    /// Parses a name, or an index starting at 1.
+    fn parse(s: &str) -> Self {
+        match s.parse::<usize>() {
+            Ok(idx) if idx > 0 => Self::Index(idx - 1),
+            _ => Self::Name(s.to_owned()),
+        }
+    }
+    fn resolve(&self, header: Option<&[&str]>) -> Option<usize> {
+        match self {
+            Self::Index(idx) => Some(*idx),
+            Self::Name(name) => header?.iter().position(|h| h.trim() == name),
+        }
+    }
+}
+impl Display for ColumnRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{name:?}"),
+            Self::Index(idx) => write!(f, "{}", idx + 1),
+        }
+    }
+}
+/// The columns given to `--pivot`.
+#[derive(Debug, Clone, PartialEq)]
+struct Pivot {
+    key: ColumnRef,
+    value: ColumnRef,
+}
+impl FromStr for Pivot {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mut key, mut value) = (None, None);
+        for part in s.split(',') {
+            match part.split_once('=') {
+                Some(("key", column)) => key = Some(ColumnRef::parse(column.trim())),
+                Some(("value", column)) => value = Some(ColumnRef::parse(column.trim())),
+                _ => return Err("expected `key=<column>,value=<column>`"),
+            }
+        }
+        Ok(Self {
+            key: key.ok_or("pivot requires `key=<column>`")?,
+            value: value.ok_or("pivot requires `value=<column>`")?,
+        })
+    }
+}
+/// Splits a line into its columns, on the `--delimiter` if it's given. Else, on commas
+/// (semicolons with `--decimal-comma`) if there are any, else on whitespace.
+fn split_line(line: &str) -> Vec<&str> {
+    match DELIMITER.get() {
+        Some(Delimiter::Char(delimiter)) => {
+            return line.split(*delimiter).map(str::trim).collect();
+        }
+        Some(Delimiter::Whitespace) => return tokens(line).collect(),
+        None => {}
+    }
+    let separator = if DECIMAL_COMMA.load(Ordering::Relaxed) {
+        ';'
+    } else {
+        ','
+    };
+    if line.contains(separator) {
+        line.split(separator).map(str::trim).collect()
+    } else {
+        tokens(line).collect()
+    }
+}
+/// Reads long-format data from stdin (until an empty line or EOF) and groups the values by key,
+/// in the order the keys first appear.
+///
+/// If any column is referenced by name, the first line is the header.
+fn read_pivot(pivot: &Pivot) -> Vec<(String, Vec<std_dev::Cluster>)> {
+    read_groups(&pivot.key, Some(&pivot.value))
+}
+/// Reads rows from stdin (until an empty line or EOF) and groups the values of the `value` column
+/// by the `key` column, in the order the keys first appear.
+///
+/// Without a `value` column, the first column other than the key is used.
+/// If any column is referenced by name, the first line is the header.
+fn read_groups(key: &ColumnRef, value: Option<&ColumnRef>) -> Vec<(String, Vec<std_dev::Cluster>)> {
+    let mut lines = stdin().lock().lines().map_while(Result::ok);
+    let (key, value) = group_columns(key, value, &mut lines);
+
+    let mut groups: Vec<(String, Vec<std_dev::Cluster>)> = Vec::new();
+    let mut indices = std::collections::HashMap::new();
+    for line in lines {
+        if line.trim().is_empty() {
+            break;
+        }
+        let tokens = split_line(&line);
+        let (Some(k), Some(v)) = (tokens.get(key), tokens.get(value)) else {
+            eprintln!("Line {line:?} doesn't have the key and value columns.");
+            continue;
+        };
+        let Some(v) = parse(v) else { continue };
+        let idx = *indices.entry(k.to_string()).or_insert_with(|| {
+            groups.push((k.to_string(), Vec::new()));
+            groups.len() - 1
+        });
+        groups[idx].1.push((v, 1));
+    }
+    groups
+}
+
+/// The indices of the `key` and `value` columns. If any is referenced by name, the first of
+/// `lines` is read as the header.
+///
+/// Without a `value` column, the first column other than the key is used.
+/// Exits if a column isn't in the header.
+fn group_columns(
+    key: &ColumnRef,
+    value: Option<&ColumnRef>,
+    lines: &mut impl Iterator<Item = String>,
+) -> (usize, usize) {
+    let has_header = matches!(key, ColumnRef::Name(_)) || matches!(value, Some(ColumnRef::Name(_)));
+    let header_line = if has_header {
+        lines.next().unwrap_or_default()
+    } else {
+        String::new()
+    };
+    let header = has_header.then(|| split_line(&header_line));
+    let key = key.resolve(header.as_deref());
+    let value = match value {
+        Some(value) => value.resolve(header.as_deref()),
+        None => key.map(|key| usize::from(key == 0)),
+    };
+    let (Some(key), Some(value)) = (key, value) else {
+        eprintln!("The columns weren't found in the header {header_line:?}.");
+        exit(1);
+    };
+    (key, value)
+}
+
+/// Prints a table of the count, mean, standard deviation, min, median, and max of each group.
+fn print_groups(groups: Vec<(String, Vec<std_dev::Cluster>)>, matches: &clap::ArgMatches) {
+    use pipeline::Sink;
+
+    let reports: Vec<pipeline::Report> = groups
+        .into_iter()
+        .filter(|(_, values)| !values.is_empty())
+        .map(|(key, values)| {
+            let (min, max) = values
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (v, _)| {
+                    (min.min(*v), max.max(*v))
+                });
+            let mut values = std_dev::OwnedClusterList::new(values);
+            let std_dev = std_dev::standard_deviation_cluster(&values.borrow());
+            let median = percentiles(&mut values).median;
+            let values = [
+                ("count", values.borrow().len() as f64),
+                ("mean", std_dev.mean),
+                ("standard deviation", std_dev.standard_deviation),
+                ("min", min),
+                ("median", median),
+                ("max", max),
+            ];
+            pipeline::Report {
+                group: Some(key),
+                values: values
+                    .into_iter()
+                    .map(|(name, value)| (name.to_owned(), value))
+                    .collect(),
+            }
+        })
+        .collect();
+    let mut table = pipeline::Table {
+        key: "group",
+        format: value_format(matches, None),
+    };
+    if let Err(err) = table.write(&reports) {
+        eprintln!("Failed to write output: {err}");
+        exit(1);
+    }
+}
+
+/// Returns `n + 1` pairs of the fraction and the value at that fraction, for every `1/n`.
+///
+/// `values` must be sorted.
+fn quantile_table(values: &std_dev::OwnedClusterList, n: usize) -> Vec<(f64, f64)> {
+    use std_dev::percentile::{MeanValue, OrderedListIndex};
+
+    let len = values.borrow().len();
+    // the index of the last item of each cluster
+    let mut ends = Vec::with_capacity(values.len());
+    let mut end = 0;
+    for (_, count) in values.iter() {
+        end += count;
+        ends.push(end - 1);
+    }
+    let value_at = |idx: usize| values[ends.partition_point(|end| *end < idx)].0;
+    (0..=n)
+        .map(|i| {
+            let fraction = std_dev::Fraction::new(i as u64, n as u64);
+            let value = match fraction.index(len) {
+                MeanValue::Single(idx) => value_at(idx),
+                MeanValue::Mean(a, b) => (value_at(a) + value_at(b)) / 2.,
+            };
+            (i as f64 / n as f64, value)
+        })
+        .collect()
+}
+/// Formats the output of [`quantile_table`] as `csv` or `json`.
+fn format_quantile_table(table: &[(f64, f64)], format: &str) -> String {
+    let mut out = String::new();
+    if format == "json" {
+        out.push('[');
+        for (idx, (quantile, value)) in table.iter().enumerate() {
+            if idx > 0 {
+                out.push(',');
+            }
+            // JSON doesn't support NaN or infinities
+            let value = if value.is_finite() {
+                value.to_string()
+            } else {
+                "null".to_owned()
+            };
+            out.push_str(&format!("{{\"quantile\":{quantile},\"value\":{value}}}"));
+        }
+        out.push_str("]\n");
+    } else {
+        out.push_str("quantile,value\n");
+        for (quantile, value) in table {
+            out.push_str(&format!("{quantile},{value}\n"));
+        }
+    }
+    out
+}
+
+/// Prints the approximate statistics of the cumulative `le,count` buckets in `list`.
+fn print_buckets(list: Vec<Vec<f64>>, precision: Option<usize>) {
+    use std_dev::buckets::BucketHistogram;
+
+    let mut buckets = Vec::with_capacity(list.len());
+    for item in list {
+        if item.len() != 2 || item[1] < 0. {
+            eprintln!("Expected an upper bound and a cumulative count per line.");
+            return;
+        }
+        buckets.push((item[0], item[1].round() as u64));
+    }
+    let histogram = match BucketHistogram::from_cumulative(buckets) {
+        Ok(h) => h,
+        Err(err) => {
+            eprintln!("Invalid buckets: {err}.");
+            return;
+        }
+    };
+    let p = precision.unwrap_or(4);
+    let format = |a: Option<std_dev::Approximation>| match a {
+        Some(a) if a.is_exact() => format!("{:.p$}", a.value),
+        Some(a) => format!(
+            "{:.p$} [{:.p$}, {:.p$}]",
+            a.value, a.lower_bound, a.upper_bound
+        ),
+        None => "-".to_owned(),
+    };
+    let percentiles = histogram.percentiles();
+    println!(
+        "Count: {}, mean: {:.p$}, median: {}, lower quadrille: {}, upper quadrille: {}, \
+        90th percentile: {}, 99th percentile: {} (approximated from buckets, bounds in brackets)",
+        histogram.len(),
+        histogram.mean(),
+        format(Some(percentiles.median)),
+        format(percentiles.lower_quadrille),
+        format(percentiles.higher_quadrille),
+        format(Some(histogram.quantile_bounded(0.9))),
+        format(Some(histogram.quantile_bounded(0.99))),
+    );
+}
+
+/// Runs `f`, and if it takes more than a second, shows a spinner with the `label` and the elapsed
+/// time on stderr until it's done, so long computations (e.g. Theil-Sen of many points) don't look
+/// hung. Only shown when stderr is a terminal, and not with `--quiet`.
+#[cfg(feature = "pretty")]
+fn with_progress<T>(label: &str, f: impl FnOnce() -> T) -> T {
+    use std::time::Duration;
+
+    if !std::io::stderr().is_terminal() || QUIET.load(Ordering::Relaxed) {
+        return f();
+    }
+    let done = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        let spinner = scope.spawn(|| {
+            let start = Instant::now();
+            let mut shown = false;
+            for frame in ['|', '/', '-', '\\'].into_iter().cycle() {
+                std::thread::park_timeout(Duration::from_millis(100));
+                if done.load(Ordering::Relaxed) {
+                    break;
+                }
+                let elapsed = start.elapsed();
+                if elapsed >= Duration::from_secs(1) {
+                    eprint!("\r{frame} {label}... {}s", elapsed.as_secs());
+                    shown = true;
+                }
+            }
+            if shown {
+                // clear the line
+                eprint!("\r\x1b[2K");
+            }
+        });
+        let value = f();
+        done.store(true, Ordering::Relaxed);
+        spinner.thread().unpark();
+        value
+    })
+}
+#[cfg(not(feature = "pretty"))]
+fn with_progress<T>(_label: &str, f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+/// Prints the standard deviation, mean, percentiles, and mode of `values`.
+/// A one-line histogram of `values`, between the smallest and largest value.
+#[cfg(feature = "pretty")]
+fn sparkline(values: &std_dev::ClusterList, precision: Option<usize>) -> String {
+    use std_dev::histogram::{Bins, Histogram};
+
+    const WIDTH: usize = 32;
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let histogram = Histogram::from_clusters(values, Bins::Count(WIDTH));
+    let max = histogram.counts().iter().copied().max().unwrap_or(0).max(1);
+    let bars: String = histogram
+        .counts()
+        .iter()
+        .map(|count| {
+            if *count == 0 {
+                ' '
+            } else {
+                // non-empty bins get at least the lowest bar
+                BARS[((count * BARS.len() - 1) / max).min(BARS.len() - 1)]
+            }
+        })
+        .collect();
+    let edges = histogram.edges();
+    let (low, high) = (edges[0], edges[edges.len() - 1]);
+    if let Some(p) = precision {
+        format!("{low:.p$} {bars} {high:.p$}")
+    } else {
+        format!("{low} {bars} {high}")
+    }
+}
+
+/// Prints a bar per bin of the histogram of `values`, with the count and percentage of the values
+/// in it.
+fn print_histogram(
+    values: &std_dev::ClusterList,
+    bins: std_dev::histogram::Bins,
+    matches: &clap::ArgMatches,
+) {
+    use std_dev::histogram::Histogram;
+
+    const WIDTH: usize = 40;
+
+    let mut format = value_format(matches, None);
+    if format.decimals.is_none() && format.significant_digits.is_none() {
+        format.significant_digits = Some(4);
+    }
+    let histogram = Histogram::from_clusters(values, bins);
+    let max = histogram.counts().iter().copied().max().unwrap_or(0).max(1);
+    let total = histogram.total().max(1);
+    let labels: Vec<String> = histogram
+        .iter()
+        .enumerate()
+        .map(|(idx, (low, high, _))| {
+            // only the last bin includes its upper edge
+            let close = if idx + 1 == histogram.len() { ']' } else { ')' };
+            format!("[{}, {}{close}", format.format(low), format.format(high))
+        })
+        .collect();
+    let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let count_width = max.to_string().len();
+    for (label, count) in labels.iter().zip(histogram.counts()) {
+        // non-empty bins get at least one character
+        let len = if *count == 0 {
+            0
+        } else {
+            (count * WIDTH / max).max(1)
+        };
+        println!(
+            "{label:<label_width$} {:<WIDTH$} {count:>count_width$} ({:.1}%)",
+            "#".repeat(len),
+            *count as f64 / total as f64 * 100.,
+        );
+    }
+}
+
+/// Prints the statistics named in `--stats`, looked up in the global
+/// [`Registry`](std_dev::statistic::Registry).
+fn print_selected_statistics<'a>(
+    values: &std_dev::OwnedClusterList,
+    names: impl Iterator<Item = &'a String>,
+    matches: &clap::ArgMatches,
+) {
+    let registry = std_dev::statistic::Registry::global()
+        .read()
+        .expect("the registry is never poisoned");
+    let mut results = Vec::new();
+    for name in names {
+        let Some(statistic) = registry.get(name.trim()) else {
+            eprintln!(
+                "Unknown statistic {name:?}. Available: {}.",
+                registry.names().collect::<Vec<_>>().join(", ")
+            );
+            exit(1);
+        };
+        results.push((
+            statistic.name().to_owned(),
+            statistic.compute(&values.borrow()),
+        ));
+    }
+    let format = matches
+        .get_one::<String>("stats_format")
+        .expect("we've provided a default value");
+    if format == "json" {
+        let fields: Vec<String> = results
+            .iter()
+            .map(|(name, value)| {
+                // JSON doesn't support NaN or infinities
+                let value = if value.is_finite() {
+                    value.to_string()
+                } else {
+                    "null".to_owned()
+                };
+                format!("{name:?}:{value}")
+            })
+            .collect();
+        println!("{{{}}}", fields.join(","));
+    } else {
+        let format = value_format(matches, None);
+        let fields: Vec<String> = results
+            .iter()
+            .map(|(name, value)| format!("{name}: {}", format.format(*value)))
+            .collect();
+        println!("{}", fields.join(", "));
+    }
+}
+fn print_statistics(
+    mut values: std_dev::OwnedClusterList,
+    matches: &clap::ArgMatches,
+    debug_performance: bool,
+) {
+    let now = Instant::now();
+
+    values = values.borrow().optimize_values();
+
+    if debug_performance {
+        println!("Optimizing input took {}µs", now.elapsed().as_micros());
+    }
+
+    #[cfg(feature = "dp")]
+    if let Some(epsilon) = matches.get_one::<f64>("dp_epsilon").copied() {
+        let (low, high) = *matches
+            .get_one::<(f64, f64)>("dp_range")
+            .expect("dp-epsilon requires dp-range");
+        let p = matches.get_one::<usize>("precision").copied();
+        print_dp(&mut values, epsilon, low..=high, p);
+        return;
+    }
+
+    if let Some(names) = matches.get_many::<String>("stats") {
+        print_selected_statistics(&values, names, matches);
+        return;
+    }
+
+    let now = Instant::now();
+
+    let mean = std_dev::standard_deviation_cluster(&values.borrow());
+
+    if debug_performance {
+        println!(
+            "Standard deviation & mean took {}µs",
+            now.elapsed().as_micros()
+        );
+    }
+    let now = Instant::now();
+
+    // Sort of clusters required.
+    values.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let median = percentiles(&mut values);
+
+    if debug_performance {
+        println!("Median & quadrilles took {}µs", now.elapsed().as_micros());
+    }
+
+    let confidence = matches.get_one::<f64>("confidence").copied();
+    let mean_interval = confidence.and_then(|confidence| {
+        std_dev::distributions::mean_interval(
+            mean.mean,
+            mean.standard_deviation,
+            values.borrow().len(),
+            confidence,
+        )
+    });
+
+    if matches.get_flag("porcelain") {
+        let (q1, q3) = match (median.lower_quadrille, median.higher_quadrille) {
+            (Some(q1), Some(q3)) => (q1, q3),
+            // also print the quadrilles of few values, so the keys are always the same
+            _ => {
+                let quadrilles = std_dev::percentiles_cluster_list(
+                    &mut values,
+                    &[
+                        std_dev::Fraction::ONE_QUARTER,
+                        std_dev::Fraction::THREE_QUARTERS,
+                    ],
+                );
+                (quadrilles[0], quadrilles[1])
+            }
+        };
+        let mut report = vec![
+            ("std_dev", mean.standard_deviation),
+            ("mean", mean.mean),
+            ("median", median.median),
+            ("q1", q1),
+            ("q3", q3),
+            ("n", values.borrow().len() as f64),
+            ("min", values.borrow().min().unwrap_or(f64::NAN)),
+            ("max", values.borrow().max().unwrap_or(f64::NAN)),
+        ];
+        if let Some((low, high)) = mean_interval {
+            report.extend([("mean_low", low), ("mean_high", high)]);
+        }
+        let report = pipeline::Report {
+            group: None,
+            values: report
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value))
+                .collect(),
+        };
+        if let Err(err) = pipeline::Sink::write(&mut pipeline::Porcelain, &[report]) {
+            eprintln!("Failed to write output: {err}");
+            exit(1);
+        }
+        return;
+    }
+
+    if let Some(n) = matches.get_one::<usize>("export_quantiles").copied() {
+        // the percentile selection above reorders the values
+        values.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let table = quantile_table(&values, n);
+        let format = matches
+            .get_one::<String>("export_format")
+            .expect("we've provided a default value");
+        let out = format_quantile_table(&table, format);
+        if let Some(path) = matches.get_one::<String>("export_out") {
+            if let Err(err) = std::fs::write(path, out) {
+                eprintln!("Failed to write quantiles to {path:?}: {err}");
+                exit(1);
+            }
+        } else {
+            print!("{out}");
+            return;
+        }
+    }
+
+    let format = value_format(matches, None);
+    let f = |v: f64| format.format(v);
+
+    let mode = if let Some(width) = matches.get_one::<f64>("mode_bin_width") {
+        std_dev::mode_cluster_binned(&values.borrow(), *width)
+    } else {
+        std_dev::mode_cluster(&values.borrow())
+    };
+    // if no value occurs more than once, all values are modes
+    let mode = if mode.count > 1 {
+        let modes = mode.modes.iter().map(|mode| f(*mode)).collect::<Vec<_>>();
+        if mode.is_multimodal() {
+            format!(", modes: {}", modes.join(", "))
+        } else {
+            format!(", mode: {}", modes[0])
+        }
+    } else {
+        String::new()
+    };
+
+    #[cfg(feature = "pretty")]
+    if std::io::stdout().is_terminal() && !QUIET.load(Ordering::Relaxed) {
+        let p = matches.get_one::<usize>("precision").copied();
+        println!("{}", sparkline(&values.borrow(), p));
+    }
+
+    let extremes = match (values.borrow().min(), values.borrow().max()) {
+        (Some(min), Some(max)) => format!(", min: {}, max: {}", f(min), f(max)),
+        _ => String::new(),
+    };
+
+    println!(
+        "Standard deviation: {}, mean: {}, median: {}{}{}{}{}",
+        f(mean.standard_deviation),
+        f(mean.mean),
+        f(median.median),
+        median
+            .lower_quadrille
+            .map_or("".into(), |quadrille| format!(
+                ", lower quadrille: {}",
+                f(quadrille)
+            )),
+        median
+            .higher_quadrille
+            .map_or("".into(), |quadrille| format!(
+                ", upper quadrille: {}",
+                f(quadrille)
+            )),
+        extremes,
+        mode,
+    );
+    if let (Some(confidence), Some((low, high))) = (confidence, mean_interval) {
+        println!(
+            "{}% confidence interval of the mean: [{}, {}]",
+            confidence * 100.,
+            f(low),
+            f(high),
+        );
+    }
+    if let Some(normality) = std_dev::tests::anderson_darling_cluster(&values.borrow()) {
+        if normality.is_significant(0.001) && !QUIET.load(Ordering::Relaxed) {
+            eprintln!(
+                "Warning: the values are unlikely to be normally distributed \
+                (Anderson-Darling p-value {:.1e}), so the standard deviation and mean might not \
+                describe them well. Consider the median and quadrilles.",
+                normality.p_value
+            );
+        }
+    }
+}
+
+fn main() {
+    let mut app = clap::command!();
+
+    let input_files = Arg::new("files")
+        .help(
+            "Read the input from these files (`-` is stdin) instead of the prompt. \
+            Each file is one dataset, or with multiline input, \
+            the datasets of a file are separated by empty lines.",
+        )
+        .num_args(1..)
+        .value_hint(ValueHint::FilePath);
+
+    app = app
+        .about(
+            "Statistics calculation tool.\n\
+            A common pattern is to cat files and pipe the data.",
+        )
+        .arg(input_files.clone())
+        .arg(
+            Arg::new("debug-performance")
+                .action(ArgAction::SetTrue)
+                .long("debug-performance")
+                .help(
+                    "Print performance information. \
+                    Can also be enabled by setting the \
+                    DEBUG_PERFORMANCE environment variable.",
+                ),
+        )
+        .arg(
+            Arg::new("multiline")
+                .short('m')
+                .action(ArgAction::SetTrue)
+                .long("multiline")
+                .help(
+                    "Accept multiple lines as one input. \
+            Two consecutive newlines is treated as the series separator. \
+            When not doing regression analysis the second 'column' \
+            is the count of the first. Acts more like CSV.",
+                ),
+        )
+        .arg(
+            Arg::new("csv")
+                .long("csv")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Read the input as CSV, with quoted fields and an optional header row. \
+                    All the input (or each file) is one dataset. \
+                    Select the columns with --x-column, --y-column, and --count-column.",
+                )
+                .conflicts_with_all(["follow", "pivot"]),
+        )
+        .arg(
+            Arg::new("x_column")
+                .long("x-column")
+                .help(
+                    "The CSV column of the predictors in regression and correlation, \
+                    or of the values otherwise. \
+                    Referenced by the name in the header or the index, starting at 1.",
+                )
+                .num_args(1)
+                .requires("csv")
+                .value_parser(|v: &str| Ok::<_, std::convert::Infallible>(ColumnRef::parse(v)))
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("y_column")
+                .long("y-column")
+                .help(
+                    "The CSV column of the outcomes in regression and correlation. \
+                    Referenced by the name in the header or the index, starting at 1.",
+                )
+                .num_args(1)
+                .requires("csv")
+                .value_parser(|v: &str| Ok::<_, std::convert::Infallible>(ColumnRef::parse(v)))
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("count_column")
+                .long("count-column")
+                .help(
+                    "The CSV column of the count of each value. \
+                    Referenced by the name in the header or the index, starting at 1.",
+                )
+                .num_args(1)
+                .requires("csv")
+                .value_parser(|v: &str| Ok::<_, std::convert::Infallible>(ColumnRef::parse(v)))
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("precision")
+                .short('n')
+                .long("precision")
+                .help(
+                    "Sets the precision of the output. When this isn't set, \
+                    Rust decides how many digits to print. \
+                    The determination will be 4 decimal places long. \
+                    When this is set, all numbers are rounded.",
+                )
+                .num_args(1)
+                .value_parser(clap::value_parser!(usize))
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("input_format")
+                .long("input-format")
+                .help(
+                    "Format of the input. `f64le` is a raw stream of little-endian 64-bit floats, \
+                    which is much faster to read than text for large datasets. \
+                    It has one dataset per file (or stdin).",
+                )
+                .num_args(1)
+                .value_parser(["text", "f64le"])
+                .default_value("text")
+                .conflicts_with_all(["csv", "multiline", "follow", "stream"]),
+        )
+        .arg(
+            Arg::new("confidence")
+                .long("confidence")
+                .help(
+                    "Also print confidence intervals at this level (e.g. 0.95): \
+                    of the mean, or in regression, of the coefficients of lines and polynomials \
+                    fitted using least squares, and of the outcomes at --predict-at.",
+                )
+                .num_args(1)
+                .value_parser(|v: &str| {
+                    parse::<f64>(v)
+                        .filter(|v| *v > 0. && *v < 1.)
+                        .ok_or("the confidence needs to be in the range (0, 1)")
+                })
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("porcelain")
+                .long("porcelain")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Print the statistics for scripts: one `<key><TAB><value>` per line, \
+                    with the keys std_dev, mean, median, q1, q3, n, min, and max, in that order. \
+                    With --confidence, mean_low and mean_high follow. \
+                    With several files, each is preceded by its `file<TAB><path>`. \
+                    Implies --quiet.",
+                )
+                .conflicts_with_all(["stats", "export_quantiles"]),
+        )
+        .arg(
+            Arg::new("quiet")
+                .long("quiet")
+                .short('q')
+                .action(ArgAction::SetTrue)
+                .help("Don't print prompts, sparklines, warnings, or other notes."),
+        )
+        .arg(
+            Arg::new("drop_outliers")
+                .long("drop-outliers")
+                .help(
+                    "Remove outliers before the statistics or regression: \
+                    `iqr` (outside 1.5 interquartile ranges of the quartiles), \
+                    `zscore` (3 standard deviations from the mean), \
+                    or `mad` (3.5 median absolute deviations from the median). \
+                    Set the threshold with e.g. `iqr:3`. \
+                    In regression, points with outlying outcomes are removed.",
+                )
+                .num_args(1)
+                .value_parser(|v: &str| v.parse::<std_dev::outliers::Filter>())
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .help(
+                    "Seed the random number generator, to get the same output and timings \
+                    every run. Applies to percentiles and `--dp`.",
+                )
+                .num_args(1)
+                .value_parser(clap::value_parser!(u64))
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("delimiter")
+                .long("delimiter")
+                .help(
+                    "Separator of values and columns: comma, tab, semicolon, whitespace, \
+                    or any single character. \
+                    By default, values are separated by commas and/or whitespace.",
+                )
+                .num_args(1)
+                .value_parser(|v: &str| v.parse::<Delimiter>())
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("decimal_comma")
+                .long("decimal-comma")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Read numbers with a decimal comma, e.g. `3,14`, `1.000,5`, and `1 000,5`. \
+                    Values are then separated by semicolons and/or whitespace, \
+                    except a single space followed by exactly three digits, \
+                    which separates digit groups. \
+                    Underscores and no-break or thin spaces between digits \
+                    are always ignored.",
+                ),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help(
+                    "How to write the numbers of the statistics: `plain` (the default, \
+                    the shortest representation unless --precision is set), \
+                    `fixed` (--precision decimals, or 6), \
+                    or `scientific` (e.g. 1.23e4, with --precision decimals). \
+                    Regression equations are rounded to --precision.",
+                )
+                .num_args(1)
+                .value_parser(|v: &str| v.parse::<Notation>())
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("units")
+                .long("units")
+                .help(
+                    "Print values with prefixes: `plain` (the default), `si` (e.g. 12.4k), \
+                    or `binary` (powers of 1024, e.g. 1.5Gi, for bytes). \
+                    Set the prefixes of specific columns using e.g. `si,3=binary`. \
+                    Used by the statistics, --follow, and the summary subcommand.",
+                )
+                .num_args(1)
+                .value_parser(|v: &str| v.parse::<Units>())
+                .value_hint(ValueHint::Other),
         )
         .arg(
-            Arg::new("debug-performance")
+            Arg::new("significant_digits")
+                .long("significant-digits")
+                .help(
+                    "Round values to this count of significant digits. \
+                    Takes precedence over --precision.",
+                )
+                .num_args(1)
+                .value_parser(|v: &str| {
+                    parse::<usize>(v)
+                        .filter(|v| *v > 0)
+                        .ok_or("significant-digits needs to be a positive integer")
+                })
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("follow")
+                .short('f')
+                .long("follow")
                 .action(ArgAction::SetTrue)
-                .long("debug-performance")
+                .conflicts_with("multiline")
                 .help(
-                    "Print performance information. \
-                    Can also be enabled by setting the \
-                    DEBUG_PERFORMANCE environment variable.",
+                    "Continuously read lines and print the updated statistics \
+                    of each changed column (or group, with --group-by) after every line, \
+                    along with how long the update took. Only running statistics \
+                    (count, standard deviation, mean, min, max) and a t-digest of the \
+                    approximate quartiles are kept, so memory use is bounded. \
+                    While lines have two values, a line is fitted to them using OLS. \
+                    With the regression subcommand, only the line is printed \
+                    and other model options are ignored.",
                 ),
         )
         .arg(
-            Arg::new("multiline")
-                .short('m')
+            Arg::new("stream")
+                .long("stream")
                 .action(ArgAction::SetTrue)
-                .long("multiline")
+                .conflicts_with_all(["follow", "multiline", "pivot", "group_by", "csv"])
                 .help(
-                    "Accept multiple lines as one input. \
-            Two consecutive newlines is treated as the series separator. \
-            When not doing regression analysis the second 'column' \
-            is the count of the first. Acts more like CSV.",
+                    "Treat each line as new observations, and print the running count, mean, \
+                    standard deviation, and estimated p95, e.g. for `tail -f access.log`. \
+                    Text in the lines is ignored. Uses constant memory.",
                 ),
         )
         .arg(
-            Arg::new("precision")
-                .short('n')
-                .long("precision")
+            Arg::new("every")
+                .long("every")
+                .help("With --stream, print the statistics every N lines.")
+                .num_args(1)
+                .requires("stream")
+                .conflicts_with("interval")
+                .value_parser(|v: &str| {
+                    parse::<usize>(v)
+                        .filter(|v| *v > 0)
+                        .ok_or("every needs to be a positive integer")
+                })
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("interval")
+                .long("interval")
                 .help(
-                    "Sets the precision of the output. When this isn't set, \
-                    Rust decides how many digits to print. \
-                    The determination will be 4 decimal places long. \
-                    When this is set, all numbers are rounded.",
+                    "With --stream, print the statistics at most once per this many seconds, \
+                    when a line is read.",
                 )
                 .num_args(1)
-                .value_parser(clap::value_parser!(usize))
+                .requires("stream")
+                .value_parser(|v: &str| {
+                    parse::<f64>(v)
+                        .filter(|v| *v > 0. && v.is_finite())
+                        .ok_or("interval needs to be a positive number of seconds")
+                })
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("pivot")
+                .long("pivot")
+                .help(
+                    "Reshape long-format data, where one column is the name \
+                    of the metric and another its value, into one series per metric, \
+                    and print the statistics of each. \
+                    Columns are referenced by the name in the header or their index, \
+                    starting at 1 (e.g. `key=metric,value=y` or `key=1,value=3`). \
+                    Values are separated by commas, or whitespace if a line has no commas.",
+                )
+                .num_args(1)
+                .conflicts_with_all(["follow", "multiline"])
+                .value_parser(|v: &str| v.parse::<Pivot>())
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("group_by")
+                .long("group-by")
+                .help(
+                    "Split the rows by the value of this key column, \
+                    and print a table of the statistics of each group. \
+                    Columns are referenced by the name in the header or their index, \
+                    starting at 1. Values are separated by commas, \
+                    or whitespace if a line has no commas.",
+                )
+                .num_args(1)
+                .conflicts_with_all(["multiline", "pivot", "csv"])
+                .value_parser(|v: &str| Ok::<_, std::convert::Infallible>(ColumnRef::parse(v)))
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("value_column")
+                .long("value-column")
+                .help(
+                    "The column of the values of --group-by. \
+                    By default, the first column which isn't the key.",
+                )
+                .num_args(1)
+                .requires("group_by")
+                .value_parser(|v: &str| Ok::<_, std::convert::Infallible>(ColumnRef::parse(v)))
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("export_quantiles")
+                .long("export-quantiles")
+                .help(
+                    "Export a table of the value at every 1/N fraction (N + 1 rows, \
+                    including the min and max). \
+                    The table is printed instead of the statistics, \
+                    unless --export-out is given.",
+                )
+                .num_args(1)
+                .value_parser(|v: &str| {
+                    parse::<usize>(v)
+                        .filter(|v| *v > 0)
+                        .ok_or("export-quantiles needs to be a positive integer")
+                })
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("export_format")
+                .long("export-format")
+                .help("Format of the exported quantile table.")
+                .requires("export_quantiles")
+                .num_args(1)
+                .value_parser(["csv", "json"])
+                .default_value("csv"),
+        )
+        .arg(
+            Arg::new("export_out")
+                .long("export-out")
+                .help("Write the exported quantile table to this file.")
+                .requires("export_quantiles")
+                .num_args(1)
+                .value_hint(ValueHint::FilePath),
+        )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .help(
+                    "Print only these statistics, separated by commas, e.g. `mean,p99`. \
+                    Built in are count, sum, mean, std_dev, variance, min, max, median, \
+                    p25, p75, p90, p95, and p99. \
+                    Programs using std-dev as a library can register more.",
+                )
+                .num_args(1)
+                .value_delimiter(',')
+                .conflicts_with_all(["follow", "export_quantiles"])
+                .value_hint(ValueHint::Other),
+        )
+        .arg(
+            Arg::new("stats_format")
+                .long("stats-format")
+                .help("Format of the statistics selected by --stats.")
+                .requires("stats")
+                .num_args(1)
+                .value_parser(["text", "json"])
+                .default_value("text"),
+        )
+        .arg(
+            Arg::new("buckets")
+                .long("buckets")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["follow", "pivot", "export_quantiles"])
+                .help(
+                    "Read a bucketed histogram, as exported by Prometheus and OpenTelemetry. \
+                    Each line is the upper bound of a bucket (`le`, can be `inf`) and the \
+                    cumulative count of values below it. The mean and percentiles are \
+                    approximated, assuming values are evenly spread within each bucket.",
+                ),
+        )
+        .arg(
+            Arg::new("mode_bin_width")
+                .long("mode-bin-width")
+                .help(
+                    "Group values in bins of this width when finding the mode. \
+                    Useful for continuous data, where values seldom are exactly equal.",
+                )
+                .num_args(1)
+                .value_parser(|v: &str| {
+                    parse::<f64>(v)
+                        .filter(|v| *v > 0. && v.is_finite())
+                        .ok_or("mode-bin-width needs to be a positive number")
+                })
                 .value_hint(ValueHint::Other),
         );
 
@@ -207,6 +3228,464 @@ fn main() {
         app = clap_autocomplete::add_subcommand(app);
     }
 
+    #[cfg(feature = "decimal")]
+    {
+        app = app.arg(
+            Arg::new("decimal")
+                .long("decimal")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Parse the input as decimal numbers and calculate the sum, \
+                    standard deviation, mean, and percentiles exactly, \
+                    without binary floating point artifacts (e.g. 0.1 + 0.2 = 0.3). \
+                    Only the square root of the standard deviation is inexact. \
+                    Values can be in scientific notation (e.g. 1.5e-7), \
+                    within about ±7.9e28 and with at most 28 decimals. \
+                    If the squared deviations are too large for decimals, \
+                    the standard deviation is approximated with floats.",
+                ),
+        );
+    }
+
+    #[cfg(feature = "dp")]
+    {
+        app = app
+            .arg(
+                Arg::new("dp_epsilon")
+                    .long("dp-epsilon")
+                    .help(
+                        "Release the count, mean, median, and quadrilles with differential \
+                        privacy, using this privacy budget. The budget is split equally \
+                        between the statistics. Lower values give more privacy and more noise. \
+                        The standard deviation isn't released.",
+                    )
+                    .requires("dp_range")
+                    .num_args(1)
+                    .value_parser(|v: &str| {
+                        parse::<f64>(v)
+                            .filter(|v| *v > 0. && v.is_finite())
+                            .ok_or("dp-epsilon needs to be a positive number")
+                    })
+                    .value_hint(ValueHint::Other),
+            )
+            .arg(
+                Arg::new("dp_range")
+                    .long("dp-range")
+                    .help(
+                        "The range values are clamped to, as `<low>,<high>`. \
+                        This must not be derived from the data.",
+                    )
+                    .requires("dp_epsilon")
+                    .num_args(1)
+                    .allow_hyphen_values(true)
+                    .value_parser(|v: &str| {
+                        v.split_once(',')
+                            .and_then(|(low, high)| {
+                                Some((parse::<f64>(low.trim())?, parse::<f64>(high.trim())?))
+                            })
+                            .filter(|(low, high)| {
+                                low <= high && low.is_finite() && high.is_finite()
+                            })
+                            .ok_or("dp-range needs to be two finite numbers `<low>,<high>`")
+                    })
+                    .value_hint(ValueHint::Other),
+            );
+    }
+
+    app = app.subcommand(
+        clap::Command::new("summary")
+            .about(
+                "Summarize several files, printing a row per file and a combined row. \
+                Non-numeric values (e.g. headers) are ignored. \
+                The median and p95 are estimated using t-digests, so they can be combined.",
+            )
+            .arg(
+                Arg::new("files")
+                    .help("The files to summarize (`-` is stdin).")
+                    .num_args(1..)
+                    .required_unless_present("by_column")
+                    .value_hint(ValueHint::FilePath),
+            )
+            .arg(
+                Arg::new("by_column")
+                    .long("by-column")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with_all(["column", "merge_sketches", "save_sketch"])
+                    .help(
+                        "Print the count, mean, standard deviation, min, quartiles, median, \
+                        and max of each column of a file, instead of a row per file. \
+                        If the first line isn't numeric, it names the columns. \
+                        Reads stdin if no files are given.",
+                    ),
+            )
+            .arg(
+                Arg::new("column")
+                    .short('c')
+                    .long("column")
+                    .help(
+                        "Only use the values of this column (starting at 1). \
+                        By default, all values are used.",
+                    )
+                    .num_args(1)
+                    .conflicts_with("merge_sketches")
+                    .value_parser(|v: &str| {
+                        parse::<usize>(v)
+                            .filter(|v| *v > 0)
+                            .ok_or("column needs to be a positive integer")
+                    })
+                    .value_hint(ValueHint::Other),
+            )
+            .arg(
+                Arg::new("merge_sketches")
+                    .long("merge-sketches")
+                    .action(ArgAction::SetTrue)
+                    .help("The files are sketches previously saved using --save-sketch."),
+            )
+            .arg(
+                Arg::new("save_sketch")
+                    .long("save-sketch")
+                    .help(
+                        "Save a sketch of the combined data to this file. \
+                        Sketches can later be combined using --merge-sketches.",
+                    )
+                    .num_args(1)
+                    .value_hint(ValueHint::FilePath),
+            ),
+    );
+
+    let sketch_files = Arg::new("files")
+        .help("The sketch files.")
+        .num_args(1..)
+        .required(true)
+        .value_hint(ValueHint::FilePath);
+    let sketch_out = Arg::new("out")
+        .short('o')
+        .long("out")
+        .help("The file to write the sketch to.")
+        .num_args(1)
+        .required(true)
+        .value_hint(ValueHint::FilePath);
+    app = app.subcommand(
+        clap::Command::new("sketch")
+            .about(
+                "Save values as a sketch (the count, mean, standard deviation, and a t-digest \
+                of the quantiles), so they can be merged and queried later \
+                without the raw data. The files are compatible with `summary --merge-sketches`.",
+            )
+            .subcommand_required(true)
+            .subcommand(
+                clap::Command::new("build")
+                    .about("Create a sketch from values. Non-numeric values are ignored.")
+                    .arg(
+                        Arg::new("files")
+                            .help("Read the values from these files instead of stdin.")
+                            .num_args(1..)
+                            .value_hint(ValueHint::FilePath),
+                    )
+                    .arg(sketch_out.clone())
+                    .arg(
+                        Arg::new("column")
+                            .short('c')
+                            .long("column")
+                            .help(
+                                "Only use the values of this column (starting at 1). \
+                                By default, all values are used.",
+                            )
+                            .num_args(1)
+                            .value_parser(|v: &str| {
+                                parse::<usize>(v)
+                                    .filter(|v| *v > 0)
+                                    .ok_or("column needs to be a positive integer")
+                            })
+                            .value_hint(ValueHint::Other),
+                    )
+                    .arg(
+                        Arg::new("compression")
+                            .long("compression")
+                            .help(
+                                "The compression of the t-digest. Higher values are more \
+                                accurate, but make the sketch larger.",
+                            )
+                            .num_args(1)
+                            .default_value("100")
+                            .value_parser(|v: &str| {
+                                parse::<f64>(v)
+                                    .filter(|v| *v > 0.)
+                                    .ok_or("compression needs to be a positive number")
+                            })
+                            .value_hint(ValueHint::Other),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("merge")
+                    .about("Merge several sketches into one.")
+                    .arg(sketch_files.clone())
+                    .arg(sketch_out),
+            )
+            .subcommand(
+                clap::Command::new("query")
+                    .about("Estimate quantiles of the merged sketches.")
+                    .arg(sketch_files)
+                    .arg(
+                        Arg::new("quantile")
+                            .short('q')
+                            .long("quantile")
+                            .help("The quantiles (in [0, 1]) to estimate, e.g. 0.99.")
+                            .num_args(1..)
+                            .action(ArgAction::Append)
+                            .default_values(["0.5", "0.9", "0.99"])
+                            .value_parser(|v: &str| {
+                                parse::<f64>(v)
+                                    .filter(|v| (0.0..=1.0).contains(v))
+                                    .ok_or("quantile needs to be in the range [0, 1]")
+                            })
+                            .value_hint(ValueHint::Other),
+                    ),
+            ),
+    );
+
+    app = app.subcommand(
+        clap::Command::new("compare")
+            .about(
+                "Compare two series: the first is the baseline. \
+                Input is either two files, or pairs of values, one pair per line.",
+            )
+            .arg(
+                Arg::new("files")
+                    .help("The baseline and the new file.")
+                    .num_args(2)
+                    .value_hint(ValueHint::FilePath),
+            )
+            .arg(
+                Arg::new("column")
+                    .short('c')
+                    .long("column")
+                    .help(
+                        "Only use the values of this column (starting at 1) of the files. \
+                        By default, all values are used.",
+                    )
+                    .num_args(1)
+                    .requires("files")
+                    .value_parser(|v: &str| {
+                        parse::<usize>(v)
+                            .filter(|v| *v > 0)
+                            .ok_or("column needs to be a positive integer")
+                    })
+                    .value_hint(ValueHint::Other),
+            )
+            .arg(
+                Arg::new("paired")
+                    .long("paired")
+                    .action(ArgAction::SetTrue)
+                    .help(
+                        "Each value of the baseline corresponds to the value at the same \
+                        position of the other series, e.g. the same benchmark run before and \
+                        after a change. Analyzes the differences of the pairs, using the sign \
+                        and Wilcoxon signed-rank tests.",
+                    ),
+            ),
+    );
+
+    app = app.subcommand(
+        clap::Command::new("pipe")
+            .about(
+                "Read the input, and pass it through the stages given with --then. \
+                Transforms (e.g. filter) change the rows for the following stages, \
+                and analyzers (e.g. summary) report statistics of the current rows.",
+            )
+            .arg(
+                Arg::new("file")
+                    .help("Read this file instead of standard input.")
+                    .num_args(1)
+                    .value_hint(ValueHint::FilePath),
+            )
+            .arg(
+                Arg::new("then")
+                    .long("then")
+                    .help(
+                        "A stage. Transforms: `column:<n>`, `filter:[<n>=]<min>..<max>`, \
+                        `group:<n>`, `log`, `ln`, `abs`, and `scale:<factor>`. \
+                        Analyzers: `summary`, `percentile:<percent>`, `stat:<names>` \
+                        (see --stats), and `regression` (of the first two columns). \
+                        Columns start at 1.",
+                    )
+                    .num_args(1)
+                    .action(ArgAction::Append)
+                    .required(true)
+                    .value_parser(|v: &str| {
+                        v.parse::<pipeline::Stage>()
+                            .map(|_| v.to_owned())
+                            .map_err(|err| err.to_string())
+                    })
+                    .value_hint(ValueHint::Other),
+            )
+            .arg(
+                Arg::new("sink")
+                    .long("sink")
+                    .help("How to write the reports of the analyzers.")
+                    .num_args(1)
+                    .value_parser(["text", "json", "csv"])
+                    .default_value("text"),
+            ),
+    );
+
+    #[cfg(feature = "gate")]
+    {
+        app = app.subcommand(
+            clap::Command::new("gate")
+                .about(
+                    "Compare the input to a saved baseline, and exit with a non-zero code \
+                    if it has regressed. Use this to guard against e.g. performance regressions \
+                    in CI. Lower values are considered better, unless --higher-is-better is set.",
+                )
+                .arg(
+                    Arg::new("files")
+                        .help("Read the values from these files instead of stdin.")
+                        .num_args(1..)
+                        .value_hint(ValueHint::FilePath),
+                )
+                .arg(
+                    Arg::new("baseline")
+                        .long("baseline")
+                        .short('b')
+                        .help("The JSON file of the baseline summary.")
+                        .required(true)
+                        .num_args(1)
+                        .value_hint(ValueHint::FilePath),
+                )
+                .arg(
+                    Arg::new("save")
+                        .long("save")
+                        .action(ArgAction::SetTrue)
+                        .help(
+                            "Save the summary of the input as the baseline instead of comparing.",
+                        ),
+                )
+                .arg(
+                    Arg::new("column")
+                        .short('c')
+                        .long("column")
+                        .help(
+                            "Only use the values of this column (starting at 1). \
+                            By default, all values are used.",
+                        )
+                        .num_args(1)
+                        .value_parser(|v: &str| {
+                            parse::<usize>(v)
+                                .filter(|v| *v > 0)
+                                .ok_or("column needs to be a positive integer")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("metric")
+                        .long("metric")
+                        .short('m')
+                        .help(
+                            "The statistic to compare: mean, median, min, max, \
+                            standard-deviation, or a percentile like p95.",
+                        )
+                        .num_args(1)
+                        .default_value("mean")
+                        .value_parser(|v: &str| v.parse::<Metric>())
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("max_regression")
+                        .long("max-regression")
+                        .help(
+                            "The largest allowed regression of the metric. \
+                            Relative to the baseline if suffixed by %, e.g. 5%.",
+                        )
+                        .num_args(1)
+                        .default_value("0%")
+                        .value_parser(|v: &str| v.parse::<Threshold>())
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("significance")
+                        .long("significance")
+                        .help(
+                            "Only fail if the change of the mean is also statistically \
+                            significant (Welch's t-test) at this level, e.g. 0.05.",
+                        )
+                        .num_args(1)
+                        .value_parser(|v: &str| {
+                            parse::<f64>(v)
+                                .filter(|v| *v > 0. && *v < 1.)
+                                .ok_or("significance needs to be in the range (0, 1)")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("higher_is_better")
+                        .long("higher-is-better")
+                        .action(ArgAction::SetTrue)
+                        .help("A decrease of the metric is a regression, e.g. for throughput."),
+                ),
+        );
+    }
+
+    app = app.subcommand(
+        clap::Command::new("correlation")
+            .about(
+                "Measure how correlated two variables are. \
+                Input is pairs of values, one pair per line.",
+            )
+            .visible_alias("corr")
+            .arg(input_files.clone()),
+    );
+    #[cfg(feature = "plot")]
+    {
+        app = app.arg(
+            Arg::new("plot_file")
+                .long("plot-file")
+                .help(
+                    "Write a plot of the input to this SVG or PNG file. \
+                    Without a subcommand, this is a histogram and box plot. \
+                    With the regression subcommand, this is the points and the model, \
+                    and the residuals are plotted to the file with `-residuals` \
+                    appended to the name.",
+                )
+                .num_args(1)
+                .conflicts_with_all(["follow", "pivot", "buckets"])
+                .value_parser(|v: &str| {
+                    let path = std::path::PathBuf::from(v);
+                    if plot::is_png(&path) || path.extension().is_some_and(|ext| ext == "svg") {
+                        Ok(path)
+                    } else {
+                        Err("only SVG and PNG plots (ending in `.svg` or `.png`) are supported")
+                    }
+                })
+                .value_hint(ValueHint::FilePath),
+        );
+    }
+    app = app.subcommand(
+        clap::Command::new("histogram")
+            .about(
+                "Print a bar chart of how many values are in each bin, \
+                with their counts and percentages.",
+            )
+            .visible_alias("hist")
+            .arg(input_files.clone())
+            .arg(
+                Arg::new("bins")
+                    .long("bins")
+                    .short('b')
+                    .help(
+                        "The count of bins of equal width. \
+                        By default, the Freedman-Diaconis rule picks the width.",
+                    )
+                    .num_args(1)
+                    .value_parser(|v: &str| {
+                        parse::<usize>(v)
+                            .filter(|v| *v > 0)
+                            .ok_or("bins needs to be a positive integer")
+                    })
+                    .value_hint(ValueHint::Other),
+            ),
+    );
+
     #[cfg(feature = "regression")]
     {
         app = app.subcommand(
@@ -219,13 +3698,16 @@ fn main() {
                     from which we want a equation to get the \
                     outcomes - the dependant variables, usually `y` or `f(x)`.",
                 )
+                .arg(input_files.clone())
                 .group(
                     clap::ArgGroup::new("model")
                         .arg("degree")
+                        .arg("order")
                         .arg("linear")
                         .arg("power")
                         .arg("exponential")
                         .arg("logistic")
+                        .arg("segments")
                         .arg("sin")
                         .arg("cos")
                         .arg("tan")
@@ -238,7 +3720,8 @@ fn main() {
                         .arg("theil_sen")
                         .arg("spiral")
                         .arg("binary")
-                        .arg("ols"),
+                        .arg("ols")
+                        .arg("bayes"),
                 )
                 .arg(
                     Arg::new("degree")
@@ -249,6 +3732,49 @@ fn main() {
                         .value_parser(clap::value_parser!(usize))
                         .value_hint(ValueHint::Other),
                 )
+                .arg(
+                    Arg::new("order")
+                        .long("order")
+                        .help(
+                            "Choose the degree of polynomial: `auto` picks the one with the \
+                            lowest Bayesian information criterion, up to --max-order. \
+                            It's printed above the equation.",
+                        )
+                        .num_args(1)
+                        .value_parser(["auto"])
+                        .conflicts_with_all(["stream", "group_by"]),
+                )
+                .arg(
+                    Arg::new("max_order")
+                        .long("max-order")
+                        .help("The highest degree --order auto considers.")
+                        .requires("order")
+                        .num_args(1)
+                        .default_value("6")
+                        .value_parser(|v: &str| {
+                            parse::<usize>(v)
+                                .filter(|v| *v >= 1)
+                                .ok_or("max-order needs to be a positive integer")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("order_folds")
+                        .long("order-folds")
+                        .help(
+                            "With --order auto, choose the degree by k-fold cross-validation \
+                            with this many folds instead. A value larger than the count of \
+                            points is leave-one-out.",
+                        )
+                        .requires("order")
+                        .num_args(1)
+                        .value_parser(|v: &str| {
+                            parse::<usize>(v)
+                                .filter(|v| *v >= 2)
+                                .ok_or("order-folds needs at least 2 folds")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
                 .arg(
                     Arg::new("linear")
                         .short('l')
@@ -315,6 +3841,61 @@ fn main() {
                         })
                         .value_hint(ValueHint::Other),
                 )
+                .arg(
+                    Arg::new("segments")
+                        .long("segments")
+                        .help(
+                            "Fit this many lines to consecutive ranges of the predictors, \
+                            and find the breakpoints between them which minimize the squared \
+                            error. Use 2 to find e.g. the load where the throughput stops \
+                            increasing. Uses least squares.",
+                        )
+                        .num_args(1)
+                        .conflicts_with_all([
+                            "estimator",
+                            "trim",
+                            "bayes",
+                            "axes",
+                            "stream",
+                            "group_by",
+                        ])
+                        .value_parser(|v: &str| {
+                            parse::<usize>(v)
+                                .filter(|v| *v >= 1)
+                                .ok_or("segments needs to be a positive integer")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("rank")
+                        .long("rank")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["model", "axes", "stream", "group_by", "bayes"])
+                        .help(
+                            "When no model is specified, also print the models which weren't \
+                            chosen, with their R² and the score weighted by the heuristics \
+                            the best fit is chosen by.",
+                        ),
+                )
+                .arg(
+                    Arg::new("cross_validate")
+                        .long("cross-validate")
+                        .help(
+                            "When no model is specified, choose the one with the lowest error \
+                            of predicting the points left out, using k-fold cross-validation \
+                            with this many folds. A value larger than the count of points is \
+                            leave-one-out. Less prone to overfitting small and noisy datasets \
+                            than the default heuristics.",
+                        )
+                        .num_args(1)
+                        .conflicts_with_all(["model", "axes", "stream", "group_by", "rank"])
+                        .value_parser(|v: &str| {
+                            parse::<usize>(v)
+                                .filter(|v| *v >= 2)
+                                .ok_or("cross-validate needs at least 2 folds")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
                 .group(
                     clap::ArgGroup::new("required_spiral")
                         .arg("logistic")
@@ -375,26 +3956,159 @@ fn main() {
                         .help("Fit a cotangent function."),
                 )
                 .arg(
-                    Arg::new("trig_freq")
-                        .long("trig-frequency-limit")
+                    Arg::new("trig_freq")
+                        .long("trig-frequency-limit")
+                        .help(
+                            "Set the limit for frequency of the \
+                              fitted trigonometric function.",
+                        )
+                        .requires("trig")
+                        .default_value("1.0")
+                        .value_parser(|v: &str| {
+                            parse::<f64>(v)
+                                .filter(|v| *v > 0.)
+                                .ok_or("frequency needs to be a positive float")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("ols")
+                        .long("ols")
+                        .action(ArgAction::SetTrue)
+                        .help("Use the ordinary least squares estimator. Linear time complexity."),
+                )
+                .arg(
+                    Arg::new("through_origin")
+                        .long("through-origin")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "power",
+                            "exponential",
+                            "logistic",
+                            "segments",
+                            "trig",
+                            "theil_sen",
+                            "spiral",
+                            "binary",
+                            "descent",
+                            "simultaneous",
+                            "axes",
+                            "stream",
+                        ])
+                        .help(
+                            "Force the constant term to 0, so the line (or polynomial, with \
+                            --degree) passes through the origin. Uses least squares.",
+                        ),
+                )
+                .arg(
+                    Arg::new("bayes")
+                        .long("bayes")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["degree", "power", "exponential", "logistic", "trig"])
+                        .help(
+                            "Fit a line using Bayesian linear regression with a vague prior. \
+                            Prints credible intervals for the slope and y intercept, \
+                            and the posterior predictive interval at the mean of the predictors. \
+                            Linear time complexity.",
+                        ),
+                )
+                .arg(
+                    Arg::new("credibility")
+                        .long("credibility")
+                        .help("Probability mass of the intervals printed by --bayes.")
+                        .requires("bayes")
+                        .num_args(1)
+                        .default_value("0.95")
+                        .value_parser(|v: &str| {
+                            parse::<f64>(v)
+                                .filter(|v| *v > 0. && *v < 1.)
+                                .ok_or("credibility needs to be in the range (0, 1)")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("axes")
+                        .long("axes")
+                        .help(
+                            "Take the base 10 logarithm of the given axes, then fit a line. \
+                            Prints the line in the transformed space, its determination in \
+                            that space, and the equivalent equation: `a * x^b` for loglog, \
+                            `a * b^x` for semilogy, and `a * lg(x) + b` for semilogx. \
+                            Unlike --power and --exponential, values below 1 aren't offset; \
+                            non-positive values on a logarithmic axis are an error.",
+                        )
+                        .num_args(1)
+                        .conflicts_with_all([
+                            "degree",
+                            "power",
+                            "exponential",
+                            "logistic",
+                            "trig",
+                            "bayes",
+                        ])
+                        .value_parser(|v: &str| {
+                            v.parse::<std_dev::regression::derived::Axes>()
+                                .map_err(|_| {
+                                    "axes needs to be one of loglog, semilogx, or semilogy"
+                                })
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("trim")
+                        .long("trim")
                         .help(
-                            "Set the limit for frequency of the \
-                              fitted trigonometric function.",
+                            "Exclude this fraction of the points with the largest residuals, \
+                            then fit again. This is repeated until the excluded points \
+                            don't change. Only applies to lines and polynomials. \
+                            Use this if some of the data are outliers.",
                         )
-                        .requires("trig")
-                        .default_value("1.0")
+                        .num_args(1)
+                        .conflicts_with_all(["power", "exponential", "logistic", "trig", "bayes"])
                         .value_parser(|v: &str| {
                             parse::<f64>(v)
-                                .filter(|v| *v > 0.)
-                                .ok_or("frequency needs to be a positive float")
+                                .filter(|v| (0.0..0.5).contains(v))
+                                .ok_or("trim needs to be in the range [0, 0.5)")
                         })
                         .value_hint(ValueHint::Other),
                 )
                 .arg(
-                    Arg::new("ols")
-                        .long("ols")
-                        .action(ArgAction::SetTrue)
-                        .help("Use the ordinary least squares estimator. Linear time complexity."),
+                    Arg::new("estimator_kind")
+                        .long("estimator")
+                        .help(
+                            "The estimator of lines and polynomials. \
+                            `theil-sen` and `ransac` are robust against outliers, \
+                            and `huber` (lines only) against outliers in the outcomes. \
+                            `ransac` fits least squares to the largest set of points within \
+                            --ransac-threshold of a fit to a random subset.",
+                        )
+                        .num_args(1)
+                        .value_parser(["ols", "theil-sen", "huber", "ransac"])
+                        .conflicts_with_all([
+                            "theil_sen",
+                            "spiral",
+                            "binary",
+                            "ols",
+                            "bayes",
+                            "through_origin",
+                            "descent",
+                            "simultaneous",
+                        ]),
+                )
+                .arg(
+                    Arg::new("ransac_threshold")
+                        .long("ransac-threshold")
+                        .help(
+                            "The largest residual of an inlier of `--estimator ransac`. \
+                            Defaults to 3 times the MAD of the residuals of a least squares fit.",
+                        )
+                        .num_args(1)
+                        .value_parser(|v: &str| {
+                            parse::<f64>(v)
+                                .filter(|v| *v >= 0.)
+                                .ok_or("the threshold needs to be a non-negative number")
+                        })
+                        .value_hint(ValueHint::Other),
                 )
                 .arg(
                     Arg::new("theil_sen")
@@ -534,6 +4248,115 @@ fn main() {
                         .action(ArgAction::SetTrue)
                         .help("Plots the regression and input variables in a SVG."),
                 )
+                .arg(
+                    Arg::new("predict_at")
+                        .long("predict-at")
+                        .help(
+                            "Print the outcomes the model predicts at these predictors, \
+                            separated by commas (e.g. `1,2,5,100`).",
+                        )
+                        .num_args(1)
+                        .value_delimiter(',')
+                        .conflicts_with("group_by")
+                        .value_parser(|v: &str| {
+                            parse::<f64>(v.trim()).ok_or("predictors need to be numbers")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("prediction_interval")
+                        .long("prediction-interval")
+                        .help(
+                            "Also print the interval which contains a new outcome at each \
+                            predictor of --predict-at with this probability (e.g. 0.95). \
+                            Requires a line or polynomial (--linear or --degree), \
+                            fitted using least squares.",
+                        )
+                        .num_args(1)
+                        .requires("predict_at")
+                        .conflicts_with("estimator")
+                        .value_parser(|v: &str| {
+                            parse::<f64>(v)
+                                .filter(|v| *v > 0. && *v < 1.)
+                                .ok_or("the probability needs to be in the range (0, 1)")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("scatter")
+                        .long("scatter")
+                        .action(ArgAction::SetTrue)
+                        .help(
+                            "Print a scatter plot of the input in the terminal, \
+                            with the fitted model drawn over it.",
+                        ),
+                )
+                .arg(
+                    Arg::new("stream")
+                        .long("stream")
+                        .help(
+                            "Read the points from this file in two passes, without keeping them \
+                            in memory. This fits lines and polynomials (--degree) to files \
+                            larger than memory, using least squares.",
+                        )
+                        .num_args(1)
+                        .conflicts_with_all([
+                            "power",
+                            "exponential",
+                            "logistic",
+                            "trig",
+                            "bayes",
+                            "theil_sen",
+                            "spiral",
+                            "binary",
+                            "descent",
+                            "simultaneous",
+                            "trim",
+                            "axes",
+                            "group_by",
+                            "plot",
+                            "export_diagnostics",
+                        ])
+                        .value_hint(ValueHint::FilePath),
+                )
+                .arg(
+                    Arg::new("group_by")
+                        .long("group-by")
+                        .help(
+                            "Read 3 values per line, and use the values of this column \
+                            (starting at 1) as the group of each point. Fits a line per group, \
+                            prints their coefficients, and tests if the slopes differ.",
+                        )
+                        .num_args(1)
+                        .conflicts_with_all([
+                            "degree",
+                            "power",
+                            "exponential",
+                            "logistic",
+                            "trig",
+                            "bayes",
+                            "axes",
+                            "plot",
+                            "export_diagnostics",
+                        ])
+                        .value_parser(|v: &str| {
+                            parse::<usize>(v)
+                                .filter(|v| (1..=3).contains(v))
+                                .ok_or("group-by needs to be a column between 1 and 3")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("export_diagnostics")
+                        .long("export-diagnostics")
+                        .help(
+                            "Write the data of the four standard diagnostic plots as CSV files \
+                            to this directory: residuals vs fitted, normal Q-Q of the \
+                            standardized residuals, scale-location, and residuals vs leverage.",
+                        )
+                        .num_args(1)
+                        .value_hint(ValueHint::DirPath),
+                )
                 .arg(
                     Arg::new("plot_filename")
                         .long("plot-out")
@@ -578,8 +4401,64 @@ fn main() {
                         .value_hint(ValueHint::Other),
                 ),
         );
+        app = app.subcommand(
+            clap::Command::new("bench")
+                .about(
+                    "Fit the same points with each estimator, and print the equations, \
+                    their R², and how long the fits took side by side. \
+                    Input is pairs of values, one pair per line.",
+                )
+                .arg(input_files.clone())
+                .arg(
+                    Arg::new("degree")
+                        .short('d')
+                        .long("degree")
+                        .help("Degree of the fitted polynomials.")
+                        .num_args(1)
+                        .default_value("1")
+                        .value_parser(|v: &str| {
+                            parse::<usize>(v)
+                                .filter(|v| *v > 0)
+                                .ok_or("degree needs to be a positive integer")
+                        })
+                        .value_hint(ValueHint::Other),
+                )
+                .arg(
+                    Arg::new("estimators")
+                        .short('e')
+                        .long("estimators")
+                        .help(
+                            "Only fit with these estimators, separated by commas. \
+                            By default, all are used. \
+                            Siegel and Huber only fit lines, and spiral polynomials of \
+                            degree 1 & 2.",
+                        )
+                        .num_args(1)
+                        .value_delimiter(',')
+                        .value_parser(BENCH_ESTIMATORS),
+                )
+                .arg(
+                    Arg::new("runs")
+                        .short('r')
+                        .long("runs")
+                        .help("Fit this many times with each estimator, and print the median time.")
+                        .num_args(1)
+                        .default_value("1")
+                        .value_parser(|v: &str| {
+                            parse::<usize>(v)
+                                .filter(|v| *v > 0)
+                                .ok_or("runs needs to be a positive integer")
+                        })
+                        .value_hint(ValueHint::Other),
+                ),
+        );
     }
 
+    #[cfg(feature = "decimal")]
+    let decimal_subcommand_error = app.error(
+        clap::error::ErrorKind::ArgumentConflict,
+        "--decimal can't be used with subcommands",
+    );
     #[cfg(feature = "regression")]
     let spiral_polynomial_degree_error = app.error(
         clap::error::ErrorKind::InvalidValue,
@@ -588,7 +4467,24 @@ fn main() {
 
     #[cfg(feature = "completion")]
     let command = app.clone();
+    let prompt_app = app.clone();
     let matches = app.get_matches();
+    DECIMAL_COMMA.store(matches.get_flag("decimal_comma"), Ordering::Relaxed);
+    QUIET.store(
+        matches.get_flag("quiet") || matches.get_flag("porcelain"),
+        Ordering::Relaxed,
+    );
+    if let Some(delimiter) = matches.get_one::<Delimiter>("delimiter") {
+        if matches.get_flag("decimal_comma") && delimiter.matches(',') {
+            eprintln!("The delimiter can't be a comma with --decimal-comma.");
+            exit(1);
+        }
+        let _ = DELIMITER.set(*delimiter);
+    }
+    if let Some(seed) = matches.get_one::<u64>("seed") {
+        // Only set here.
+        let _ = SEED.set(*seed);
+    }
 
     #[cfg(feature = "completion")]
     {
@@ -608,21 +4504,171 @@ fn main() {
     );
 
     #[cfg(feature = "pretty")]
-    let tty = std::io::stdin().is_terminal();
+    let tty = std::io::stdin().is_terminal() && !QUIET.load(Ordering::Relaxed);
     #[cfg(not(feature = "pretty"))]
     let tty = false;
 
-    let mut last_prompt = Instant::now();
+    let mut prompt = Prompt::new();
+
+    if matches.get_flag("follow") {
+        follow(&matches, debug_performance);
+    }
+    if matches.get_flag("stream") {
+        if matches.subcommand_name().is_some() {
+            eprintln!("--stream can't be used with a subcommand.");
+            exit(1);
+        }
+        stream(&matches);
+    }
+
+    if let Some(key) = matches.get_one::<ColumnRef>("group_by") {
+        let value = matches.get_one::<ColumnRef>("value_column");
+        print_groups(read_groups(key, value), &matches);
+        exit(0);
+    }
+    if let Some(pivot) = matches.get_one::<Pivot>("pivot") {
+        for (key, values) in read_pivot(pivot) {
+            print!("{key}: ");
+            print_statistics(
+                std_dev::OwnedClusterList::new(values),
+                &matches,
+                debug_performance,
+            );
+        }
+        exit(0);
+    }
+
+    #[cfg(feature = "regression")]
+    if let Some(("regression", config)) = matches.subcommand() {
+        if let Some(path) = config.get_one::<String>("stream") {
+            regression_stream(path, config, &matches);
+            exit(0);
+        }
+    }
+    if let Some(("summary", config)) = matches.subcommand() {
+        summary(config, &matches);
+        exit(0);
+    }
+    if let Some(("sketch", config)) = matches.subcommand() {
+        sketch(config, &matches);
+        exit(0);
+    }
+    if let Some(("compare", config)) = matches.subcommand() {
+        compare(config, matches.get_one::<usize>("precision").copied());
+        exit(0);
+    }
+    if let Some(("pipe", config)) = matches.subcommand() {
+        pipe(config, &matches);
+        exit(0);
+    }
+    #[cfg(feature = "gate")]
+    if let Some(("gate", config)) = matches.subcommand() {
+        gate(config, matches.get_one::<usize>("precision").copied());
+    }
+
+    let files: Option<Vec<String>> = matches
+        .subcommand()
+        .map_or(&matches, |(_, config)| config)
+        .try_get_many::<String>("files")
+        .ok()
+        .flatten()
+        .map(|files| files.cloned().collect());
+    let csv = matches.get_flag("csv");
+    let csv_columns = CsvColumns::from_matches(&matches);
+    let binary = matches
+        .get_one::<String>("input_format")
+        .map(String::as_str)
+        == Some("f64le");
+    let stdin_file = ["-".to_owned()];
+
+    #[cfg(feature = "decimal")]
+    if matches.get_flag("decimal") {
+        if matches.subcommand_name().is_some() {
+            decimal_subcommand_error.exit();
+        }
+        if binary {
+            eprintln!("--decimal reads decimal text, not --input-format f64le.");
+            exit(1);
+        }
+        let multiline = matches.get_flag("multiline");
+        let p = matches.get_one::<usize>("precision").copied();
+        if files.is_some() || csv {
+            let files = files.as_deref().unwrap_or(&stdin_file);
+            let inputs = if csv {
+                read_csv(files, &csv_columns, multiline)
+            } else {
+                read_files(files, multiline)
+            };
+            for (label, input) in inputs {
+                if let Some(label) = label {
+                    print!("{label}: ");
+                }
+                print_decimal(input, p);
+            }
+            exit(0);
+        }
+        loop {
+            if let Some(input) = input(tty, debug_performance, multiline, &mut prompt) {
+                print_decimal(input, p);
+            } else if prompt.command.take().is_some() {
+                eprintln!("Commands can't be run with --decimal.");
+            }
+        }
+    }
+
+    let multiline = {
+        matches.get_flag("multiline")
+            || matches.get_flag("buckets")
+            || matches!(
+                matches.subcommand_name(),
+                Some("regression" | "correlation" | "bench")
+            )
+    };
+    if binary && multiline {
+        eprintln!(
+            "--input-format f64le has one value per point. \
+            It can't be used with --buckets or the regression and correlation subcommands."
+        );
+        exit(1);
+    }
+    let mut file_inputs = if binary {
+        let files = files.as_deref().unwrap_or(&stdin_file);
+        Some(read_binary_files(files).into_iter())
+    } else if csv {
+        let files = files.as_deref().unwrap_or(&stdin_file);
+        Some(read_csv(files, &csv_columns, multiline).into_iter())
+    } else {
+        files
+            .as_ref()
+            .map(|files| read_files(files, multiline).into_iter())
+    };
 
     'main: loop {
-        let multiline = {
-            matches.get_flag("multiline") || matches!(matches.subcommand_name(), Some("regression"))
-        };
-        let input = if let Some(i) = input(tty, debug_performance, multiline, &mut last_prompt) {
+        let mut rerun_matches = None;
+        let input = if let Some(inputs) = &mut file_inputs {
+            let Some((label, input)) = inputs.next() else {
+                exit(0);
+            };
+            if let Some(label) = label {
+                if matches.get_flag("porcelain") {
+                    println!("file\t{label}");
+                } else {
+                    print!("{label}: ");
+                }
+            }
+            input
+        } else if let Some(command) = prompt.command.take() {
+            let Some((matches, input)) = rerun(&prompt_app, &command, &prompt.dataset) else {
+                continue;
+            };
+            rerun_matches = Some(matches);
+            input
+        } else if let Some(i) = input(tty, debug_performance, multiline, &mut prompt) {
             i
         } else {
             continue;
         };
+        let matches = rerun_matches.as_ref().unwrap_or(&matches);
 
         match matches.subcommand() {
             #[cfg(feature = "regression")]
@@ -636,18 +4682,74 @@ fn main() {
                         InputValue::List(list) => {
                             // Higher dimensional analysis?:
                             // let dimension = list.first().unwrap().len();
-                            let dimension = 2;
-
-                            for item in &list {
-                                if item.len() != dimension {
-                                    eprintln!("Expected {dimension} values per line.");
+                            if config.contains_id("group_by") {
+                                if list.iter().any(|item| item.len() != 3) {
+                                    eprintln!("Expected 3 values per line.");
                                     continue 'main;
                                 }
+                            } else if list.iter().any(|item| !(2..=3).contains(&item.len())) {
+                                eprintln!("Expected 2 values per line, or 3 with a weight.");
+                                continue 'main;
                             }
                             list
                         }
                     }
                 };
+                // the third value of a point is its weight
+                let mut weights = None;
+                let values = if config.contains_id("group_by")
+                    || values.iter().all(|item| item.len() == 2)
+                {
+                    values
+                } else {
+                    let point_weights: Vec<f64> = values
+                        .iter()
+                        .map(|item| item.get(2).copied().unwrap_or(1.))
+                        .collect();
+                    if point_weights.iter().any(|w| *w < 0. || w.is_nan()) {
+                        eprintln!("Weights can't be negative.");
+                        continue 'main;
+                    }
+                    if point_weights.iter().all(|w| w.fract() == 0.) {
+                        // whole weights are the counts of the points, which all models support
+                        values
+                            .iter()
+                            .zip(&point_weights)
+                            .flat_map(|(item, w)| {
+                                std::iter::repeat(vec![item[0], item[1]]).take(*w as usize)
+                            })
+                            .collect()
+                    } else {
+                        weights = Some(point_weights);
+                        values
+                    }
+                };
+
+                let (values, weights) = if let Some(filter) =
+                    matches.get_one::<std_dev::outliers::Filter>("drop_outliers")
+                {
+                    // points with outlying outcomes
+                    let outcomes: Vec<f64> = values.iter().map(|d| d[1]).collect();
+                    let bounds = filter.bounds(&outcomes);
+                    let keep: Vec<bool> = outcomes.iter().map(|y| bounds.contains(y)).collect();
+                    let weights = weights.map(|weights| {
+                        weights
+                            .into_iter()
+                            .zip(&keep)
+                            .filter_map(|(w, keep)| keep.then_some(w))
+                            .collect()
+                    });
+                    let len = values.len();
+                    let values: Vec<_> = values
+                        .into_iter()
+                        .zip(&keep)
+                        .filter_map(|(d, keep)| keep.then_some(d))
+                        .collect();
+                    report_dropped(len - values.len());
+                    (values, weights)
+                } else {
+                    (values, weights)
+                };
 
                 let len = values.len();
                 let x_iter = values.iter().map(|d| d[0]);
@@ -655,6 +4757,38 @@ fn main() {
                 let mut x: Vec<f64> = x_iter.clone().collect();
                 let mut y: Vec<f64> = y_iter.clone().collect();
 
+                // `--order auto` chooses the degree, which is then used like `--degree`
+                let degree = if config.contains_id("order") {
+                    let max_degree = *config
+                        .get_one::<usize>("max_order")
+                        .expect("we've provided a default value");
+                    let folds = config.get_one::<usize>("order_folds").copied();
+                    let criterion = match folds {
+                        Some(folds) => DegreeCriterion::CrossValidation { folds },
+                        None => DegreeCriterion::Bic,
+                    };
+                    let Some(degree) = with_progress("Choosing the degree", || {
+                        std_dev::regression::select_degree(
+                            &x,
+                            &y,
+                            &std_dev::regression::OlsEstimator,
+                            max_degree,
+                            criterion,
+                        )
+                    }) else {
+                        eprintln!("Too few points to choose the degree; add more datapoints.");
+                        continue 'main;
+                    };
+                    let by = match folds {
+                        Some(folds) => format!("{folds}-fold cross-validation"),
+                        None => "BIC".into(),
+                    };
+                    println!("Order: {degree} (of 1 to {max_degree}, by {by})");
+                    Some(degree)
+                } else {
+                    config.get_one::<usize>("degree").copied()
+                };
+
                 let spiral_options = {
                     let level = *config
                         .get_one::<u8>("spiral_level")
@@ -683,10 +4817,34 @@ fn main() {
                 let trig_freq: f64 = *config
                     .get_one("trig_freq")
                     .expect("we provided a default value and have a validator");
+                let trim = config.get_one::<f64>("trim").copied();
 
+                // `--estimator`, other than OLS
+                let robust_estimator = config
+                    .get_one::<String>("estimator_kind")
+                    .map(String::as_str)
+                    .filter(|kind| *kind != "ols");
+                let ransac_threshold = |degree| {
+                    config
+                        .get_one::<f64>("ransac_threshold")
+                        .copied()
+                        .unwrap_or_else(|| ransac_threshold(&x, &y, degree))
+                };
                 let linear_estimator = {
-                    if config.get_flag("theil_sen") {
+                    if config.get_flag("through_origin") {
+                        std_dev::regression::ConstrainedOlsEstimator::through_origin()
+                            .boxed_linear()
+                    } else if config.get_flag("theil_sen") || robust_estimator == Some("theil-sen")
+                    {
                         std_dev::regression::LinearTheilSen.boxed_linear()
+                    } else if robust_estimator == Some("huber") {
+                        std_dev::regression::HuberEstimator::default().boxed_linear()
+                    } else if robust_estimator == Some("ransac") {
+                        std_dev::regression::ransac::Ransac::new(
+                            std_dev::regression::OlsEstimator,
+                            ransac_threshold(1),
+                        )
+                        .boxed_linear()
                     } else if config.get_flag("descent") {
                         GradientDescentParallelOptions::default().boxed_linear()
                     } else if config.get_flag("simultaneous") {
@@ -707,10 +4865,74 @@ fn main() {
                         }
                     }
                 };
+                let linear_estimator = if let Some(trim) = trim {
+                    Trimmed::new(linear_estimator, trim).boxed_linear()
+                } else {
+                    linear_estimator
+                };
+
+                if let Some(column) = config.get_one::<usize>("group_by") {
+                    let p = matches.get_one::<usize>("precision").copied();
+                    regression_grouped(&values, column - 1, &&*linear_estimator, p);
+                    continue 'main;
+                }
 
                 let now = Instant::now();
 
-                let model = if config.get_flag("power") {
+                let mut posterior = None;
+                let mut axes_fit = None;
+                let mut runners_up = None;
+
+                let model = if let Some(weights) = &weights {
+                    let degree = if config.get_flag("linear") {
+                        Some(1)
+                    } else {
+                        degree
+                    };
+                    let least_squares = (!config.contains_id("estimator")
+                        || config.get_flag("ols"))
+                        && robust_estimator.is_none()
+                        && !config.get_flag("through_origin")
+                        && !config.get_flag("descent")
+                        && !config.get_flag("simultaneous")
+                        && trim.is_none();
+                    match degree {
+                        Some(1) if least_squares => {
+                            std_dev::regression::weighted_linear(&x, &y, weights).boxed()
+                        }
+                        Some(degree) if least_squares => {
+                            std_dev::regression::weighted_polynomial(&x, &y, weights, degree)
+                                .boxed()
+                        }
+                        _ => {
+                            eprintln!(
+                                "Weights which aren't whole numbers are only supported for \
+                                lines and polynomials (--linear or --degree), \
+                                fitted using least squares."
+                            );
+                            continue 'main;
+                        }
+                    }
+                } else if config.get_flag("bayes") {
+                    let p = BayesianLinear::default().posterior(&x, &y);
+                    posterior = Some(p);
+                    p.boxed()
+                } else if let Some(axes) = config
+                    .get_one::<std_dev::regression::derived::Axes>("axes")
+                    .copied()
+                {
+                    match std_dev::regression::derived::fit_axes(&x, &y, axes, &&*linear_estimator)
+                    {
+                        Ok(fit) => {
+                            axes_fit = Some(fit);
+                            fit.boxed()
+                        }
+                        Err(err) => {
+                            eprintln!("Failed to fit on logarithmic axes: {err}.");
+                            continue 'main;
+                        }
+                    }
+                } else if config.get_flag("power") {
                     if config.get_flag("spiral") {
                         spiral_options.model_power(&x, &y).boxed()
                     } else if config.get_flag("binary") {
@@ -781,25 +5003,38 @@ fn main() {
                     } else {
                         binary_options.model_sine(&x, &y, trig_freq).boxed()
                     }
-                } else if config.get_flag("linear") || config.get_one::<usize>("degree").is_some() {
-                    let degree = {
-                        if let Some(degree) = config.get_one("degree") {
-                            *degree
-                        } else {
-                            1
-                        }
-                    };
+                } else if let Some(segments) = config.get_one::<usize>("segments").copied() {
+                    std_dev::regression::segmented(&x, &y, segments, 3).boxed()
+                } else if config.get_flag("linear")
+                    || config.get_flag("through_origin")
+                    || degree.is_some()
+                {
+                    let degree = degree.unwrap_or(1);
                     if degree + 1 > len {
                         eprintln!("Degree of polynomial is too large; add more datapoints.");
                         continue 'main;
                     }
 
                     if degree == 1 {
-                        linear_estimator.model_linear(&x, &y).boxed()
+                        with_progress("Fitting", || linear_estimator.model_linear(&x, &y)).boxed()
                     } else {
                         let estimator = {
-                            if config.get_flag("theil_sen") {
+                            if config.get_flag("through_origin") {
+                                std_dev::regression::ConstrainedOlsEstimator::through_origin()
+                                    .boxed_polynomial()
+                            } else if config.get_flag("theil_sen")
+                                || robust_estimator == Some("theil-sen")
+                            {
                                 std_dev::regression::PolynomialTheilSen.boxed_polynomial()
+                            } else if robust_estimator == Some("huber") {
+                                eprintln!("Huber regression only fits lines.");
+                                continue 'main;
+                            } else if robust_estimator == Some("ransac") {
+                                std_dev::regression::ransac::Ransac::new(
+                                    std_dev::regression::OlsEstimator,
+                                    ransac_threshold(degree),
+                                )
+                                .boxed_polynomial()
                             } else if config.get_flag("descent") {
                                 GradientDescentParallelOptions::default().boxed_polynomial()
                             } else if config.get_flag("simultaneous") {
@@ -827,16 +5062,130 @@ fn main() {
                                 }
                             }
                         };
+                        let estimator = if let Some(trim) = trim {
+                            Trimmed::new(estimator, trim).boxed_polynomial()
+                        } else {
+                            estimator
+                        };
 
-                        estimator.model_polynomial(&x, &y, degree).boxed()
+                        with_progress("Fitting", || estimator.model_polynomial(&x, &y, degree))
+                            .boxed()
                     }
+                } else if let Some(folds) = config.get_one::<usize>("cross_validate").copied() {
+                    with_progress("Cross-validating", || {
+                        std_dev::regression::best_fit_cv(&x, &y, &&*linear_estimator, folds)
+                    })
+                } else if config.get_flag("rank") {
+                    let mut ranked = with_progress("Fitting the models", || {
+                        std_dev::regression::best_fit_ranked(
+                            &x,
+                            &y,
+                            &&*linear_estimator,
+                            Some(&std_dev::regression::OlsEstimator),
+                            &Default::default(),
+                        )
+                    });
+                    let best = ranked.remove(0);
+                    runners_up = Some((best.score, ranked));
+                    best.model
                 } else {
-                    std_dev::regression::best_fit(&x, &y, &&*linear_estimator)
+                    with_progress("Fitting the models", || {
+                        std_dev::regression::best_fit(&x, &y, &&*linear_estimator)
+                    })
                 };
 
                 let p = matches.get_one::<usize>("precision").copied();
 
-                print_regression(&model, x_iter.clone(), y_iter.clone(), len, p);
+                if let Some(fit) = axes_fit {
+                    print_axes_fit(&fit, p);
+                } else {
+                    print_regression(&model, x_iter.clone(), y_iter.clone(), len, p);
+                }
+                if let Some(posterior) = posterior {
+                    let credibility: f64 = *config
+                        .get_one("credibility")
+                        .expect("we provided a default value and have a validator");
+                    print_posterior(&posterior, &x, credibility, p);
+                }
+                if let Some((score, runners_up)) = runners_up {
+                    print_runners_up(score, &runners_up, p);
+                }
+
+                if config.get_flag("scatter") {
+                    let x: Vec<f64> = x_iter.clone().collect();
+                    let y: Vec<f64> = y_iter.clone().collect();
+                    print_scatter(&model, &x, &y, p);
+                }
+                let confidence = matches.get_one::<f64>("confidence").copied();
+                let degree = if config.get_flag("linear") {
+                    Some(1)
+                } else {
+                    degree
+                };
+                let least_squares = (!config.contains_id("estimator") || config.get_flag("ols"))
+                    && robust_estimator.is_none()
+                    && !config.get_flag("through_origin")
+                    && !config.get_flag("descent")
+                    && !config.get_flag("simultaneous")
+                    && trim.is_none()
+                    && weights.is_none();
+                let with_uncertainty = || {
+                    let x: Vec<f64> = x_iter.clone().collect();
+                    let y: Vec<f64> = y_iter.clone().collect();
+                    degree.map(|degree| {
+                        std_dev::regression::polynomial_with_uncertainty(&x, &y, degree)
+                    })
+                };
+                if let Some(confidence) = confidence {
+                    match with_uncertainty().filter(|_| least_squares) {
+                        Some(fit) => print_coefficient_intervals(&fit, confidence, p),
+                        None => eprintln!(
+                            "Confidence intervals of the coefficients are only available for \
+                            lines and polynomials (--linear, --degree, or --order) fitted using \
+                            least squares."
+                        ),
+                    }
+                }
+                if let Some(predictors) = config.get_many::<f64>("predict_at") {
+                    let predictors: Vec<f64> = predictors.copied().collect();
+                    let level = config
+                        .get_one::<f64>("prediction_interval")
+                        .copied()
+                        .or(confidence.filter(|_| least_squares));
+                    let fit = match (level, degree) {
+                        (Some(_), Some(_)) if robust_estimator.is_some() => {
+                            eprintln!(
+                                "Prediction intervals are only available for fits using \
+                                least squares."
+                            );
+                            None
+                        }
+                        (Some(_), Some(_)) => with_uncertainty(),
+                        (Some(_), None) => {
+                            eprintln!(
+                                "Prediction intervals are only available for lines and \
+                                polynomials (--linear, --degree, or --order)."
+                            );
+                            None
+                        }
+                        (None, _) => None,
+                    };
+                    print_predictions(&model, &predictors, fit.as_ref().zip(level), p);
+                }
+
+                #[cfg(feature = "plot")]
+                if let Some(path) = matches.get_one::<std::path::PathBuf>("plot_file") {
+                    let x: Vec<f64> = x_iter.clone().collect();
+                    let y: Vec<f64> = y_iter.clone().collect();
+                    plot::regression(&model, &x, &y, p, path);
+                }
+
+                if let Some(dir) = config.get_one::<String>("export_diagnostics") {
+                    let x: Vec<f64> = x_iter.clone().collect();
+                    let y: Vec<f64> = y_iter.clone().collect();
+                    let plots = std_dev::regression::diagnostic_plots_data(&model, &x, &y);
+                    write_diagnostics(&plots, dir);
+                }
 
                 if debug_performance {
                     let elapsed = now.elapsed().as_micros();
@@ -861,9 +5210,7 @@ fn main() {
                             }
                         })
                         .unwrap_or(500);
-                    if config.get_flag("linear")
-                        || config.get_one::<usize>("degree").is_some_and(|o| *o == 1)
-                    {
+                    if config.get_flag("linear") || degree == Some(1) {
                         num_samples = 2;
                     }
 
@@ -950,98 +5297,90 @@ fn main() {
                     }
                 }
             }
-            Some(_) => unreachable!("invalid subcommand"),
-            None => {
-                let mut values = {
-                    match input {
-                        InputValue::Count(count) => std_dev::OwnedClusterList::new(count),
-                        InputValue::List(list) => {
-                            let mut count = Vec::with_capacity(list.len());
-                            for item in list {
-                                if item.len() != 1 && item.len() != 2 {
-                                    eprintln!("Expected one or two values per line.");
-                                    continue 'main;
-                                }
-                                let first = item[0];
-                                let second = item.get(1).map_or(1, |f| f.round() as usize);
-                                count.push((first, second))
-                            }
-                            std_dev::OwnedClusterList::new(count)
-                        }
-                    }
+            #[cfg(feature = "regression")]
+            Some(("bench", config)) => {
+                let InputValue::List(list) = input else {
+                    eprintln!("You cannot use `<value>x<count>` notation for point entry");
+                    continue 'main;
                 };
-
-                let now = Instant::now();
-
-                values = values.borrow().optimize_values();
-
-                if debug_performance {
-                    println!("Optimizing input took {}µs", now.elapsed().as_micros());
+                if list.iter().any(|item| item.len() != 2) {
+                    eprintln!("Expected 2 values per line.");
+                    continue 'main;
                 }
-
-                let now = Instant::now();
-
-                let mean = std_dev::standard_deviation_cluster(&values.borrow());
-
-                if debug_performance {
-                    println!(
-                        "Standard deviation & mean took {}µs",
-                        now.elapsed().as_micros()
-                    );
-                }
-                let now = Instant::now();
-
-                // Sort of clusters required.
-                values.sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
-
-                let median = std_dev::percentiles_cluster(&mut values);
-
-                if debug_performance {
-                    println!("Median & quadrilles took {}µs", now.elapsed().as_micros());
+                let degree = *config
+                    .get_one::<usize>("degree")
+                    .expect("we've provided a default value");
+                if degree + 1 > list.len() {
+                    eprintln!("Degree of polynomial is too large; add more datapoints.");
+                    continue 'main;
                 }
-
+                let x: Vec<f64> = list.iter().map(|d| d[0]).collect();
+                let y: Vec<f64> = list.iter().map(|d| d[1]).collect();
+                let estimators: Vec<&str> = match config.get_many::<String>("estimators") {
+                    Some(estimators) => estimators.map(String::as_str).collect(),
+                    None => BENCH_ESTIMATORS.to_vec(),
+                };
+                let runs = *config
+                    .get_one::<usize>("runs")
+                    .expect("we've provided a default value");
                 let p = matches.get_one::<usize>("precision").copied();
-
-                if let Some(p) = p {
-                    println!(
-                        "Standard deviation: {:.5$}, mean: {:.5$}, median: {:.5$}{}{}",
-                        mean.standard_deviation,
-                        mean.mean,
-                        median.median,
-                        median
-                            .lower_quadrille
-                            .as_ref()
-                            .map_or("".into(), |quadrille| {
-                                format!(", lower quadrille: {:.1$}", *quadrille, p)
-                            }),
-                        median
-                            .higher_quadrille
-                            .as_ref()
-                            .map_or("".into(), |quadrille| {
-                                format!(", upper quadrille: {:.1$}", *quadrille, p)
-                            }),
-                        p
-                    );
-                } else {
-                    println!(
-                        "Standard deviation: {}, mean: {}, median: {}{}{}",
-                        mean.standard_deviation,
-                        mean.mean,
-                        median.median,
-                        median
-                            .lower_quadrille
-                            .as_ref()
-                            .map_or("".into(), |quadrille| {
-                                format!(", lower quadrille: {}", *quadrille)
-                            }),
-                        median
-                            .higher_quadrille
-                            .as_ref()
-                            .map_or("".into(), |quadrille| {
-                                format!(", upper quadrille: {}", *quadrille)
-                            }),
-                    );
+                bench(&x, &y, degree, &estimators, runs, p);
+            }
+            Some(("correlation", _)) => {
+                let InputValue::List(list) = input else {
+                    eprintln!("You cannot use `<value>x<count>` notation for point entry");
+                    continue 'main;
+                };
+                if list.iter().any(|item| item.len() != 2) {
+                    eprintln!("Expected 2 values per line.");
+                    continue 'main;
+                }
+                let x: Vec<f64> = list.iter().map(|d| d[0]).collect();
+                let y: Vec<f64> = list.iter().map(|d| d[1]).collect();
+                let p = matches.get_one::<usize>("precision").copied().unwrap_or(4);
+                println!(
+                    "Covariance: {:.p$}, Pearson's r: {:.p$}, Spearman's ρ: {:.p$}, \
+                    Kendall's τ: {:.p$}",
+                    std_dev::correlation::covariance(&x, &y),
+                    std_dev::correlation::pearson_r(&x, &y),
+                    std_dev::correlation::spearman_rho(&x, &y),
+                    std_dev::correlation::kendall_tau(&x, &y),
+                );
+            }
+            Some(("histogram", config)) => {
+                let Some(values) = clusters(input) else {
+                    continue 'main;
+                };
+                let bins = config
+                    .get_one::<usize>("bins")
+                    .map_or(std_dev::histogram::Bins::FreedmanDiaconis, |bins| {
+                        std_dev::histogram::Bins::Count(*bins)
+                    });
+                print_histogram(&values.borrow(), bins, matches);
+            }
+            Some(_) => unreachable!("invalid subcommand"),
+            None if matches.get_flag("buckets") => {
+                let InputValue::List(list) = input else {
+                    unreachable!("buckets are read as multiple lines")
+                };
+                print_buckets(list, matches.get_one::<usize>("precision").copied());
+            }
+            None => {
+                let Some(mut values) = clusters(input) else {
+                    continue 'main;
+                };
+                if let Some(filter) = matches.get_one::<std_dev::outliers::Filter>("drop_outliers")
+                {
+                    report_dropped(filter.remove_cluster(&mut values));
+                    if values.is_empty() {
+                        continue 'main;
+                    }
+                }
+                #[cfg(feature = "plot")]
+                if let Some(path) = matches.get_one::<std::path::PathBuf>("plot_file") {
+                    plot::summary(&values, path);
                 }
+                print_statistics(values, matches, debug_performance);
             }
         }
     }
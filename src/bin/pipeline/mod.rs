@@ -0,0 +1,519 @@
+//! The `pipe` subcommand: input is read by a [`Source`], passed through the stages given with
+//! `--then`, and the reports of the analyzers among them are written by a [`Sink`].
+//!
+//! A stage is either a [`Transform`], which changes the rows the following stages see
+//! (e.g. `filter:0..100`), or an [`Analyzer`], which reports statistics of the current rows
+//! (e.g. `summary`). Stages compose, so `--then summary --then log --then summary` reports the
+//! statistics before and after taking the logarithm.
+//!
+//! To add a stage, implement [`Transform`] or [`Analyzer`] and parse it in [`Stage::from_str`].
+//!
+//! The sources also read the input files of the other subcommands, and the sinks write the
+//! tables of `--group-by` and the output of `--porcelain`.
+use std::fmt::{self, Display};
+use std::io::{self, BufRead, Read, Write};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use std_dev::percentile::Fraction;
+use std_dev::regression::{Determination, LinearEstimator, OlsEstimator};
+use std_dev::statistic::Registry;
+use std_dev::units::ValueFormat;
+
+use super::InputValue;
+
+/// A set of rows, optionally named by the `group` stage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub name: Option<String>,
+    pub rows: Vec<Vec<f64>>,
+}
+impl Group {
+    /// All values of all rows.
+    pub fn values(&self) -> Vec<f64> {
+        self.rows.iter().flatten().copied().collect()
+    }
+}
+
+/// Reads the datasets of the input, one at a time.
+pub trait Source<T = f64> {
+    /// Reads the next dataset, or returns [`None`] at the end of the input.
+    fn next_dataset(&mut self) -> Option<io::Result<InputValue<T>>>;
+}
+/// Reads text line by line, parsed like the input of the prompt, so `--delimiter`,
+/// `--decimal-comma`, and units apply.
+///
+/// When `multiline`, each line is a row, and datasets are separated by empty lines. Else, all
+/// the values are one dataset, which can use the `<value>x<count>` notation.
+pub struct Lines {
+    lines: io::Lines<Box<dyn BufRead>>,
+    multiline: bool,
+}
+impl Lines {
+    pub fn new(reader: Box<dyn BufRead>, multiline: bool) -> Self {
+        Self {
+            lines: reader.lines(),
+            multiline,
+        }
+    }
+    /// Opens the file at `path`, or standard input if it's `-`.
+    pub fn open(path: &str, multiline: bool) -> io::Result<Self> {
+        let reader: Box<dyn BufRead> = if path == "-" {
+            Box::new(io::stdin().lock())
+        } else {
+            Box::new(io::BufReader::new(std::fs::File::open(path)?))
+        };
+        Ok(Self::new(reader, multiline))
+    }
+}
+impl<T: FromStr> Source<T> for Lines
+where
+    T::Err: Display,
+{
+    fn next_dataset(&mut self) -> Option<io::Result<InputValue<T>>> {
+        if self.multiline {
+            let mut rows = Vec::new();
+            for line in &mut self.lines {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(err) => return Some(Err(err)),
+                };
+                if !line.trim().is_empty() {
+                    rows.push(super::parse_row(&line));
+                } else if !rows.is_empty() {
+                    break;
+                }
+            }
+            (!rows.is_empty()).then_some(Ok(InputValue::List(rows)))
+        } else {
+            let mut values = Vec::new();
+            for line in &mut self.lines {
+                match line {
+                    Ok(line) => values.extend(super::parse_counted(&line)),
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+            (!values.is_empty()).then_some(Ok(InputValue::Count(values)))
+        }
+    }
+}
+/// Reads raw little-endian `f64`s (`--input-format f64le`) as one dataset.
+pub struct Binary(Option<Box<dyn Read>>);
+impl Binary {
+    pub fn new(reader: Box<dyn Read>) -> Self {
+        Self(Some(reader))
+    }
+}
+impl Source for Binary {
+    fn next_dataset(&mut self) -> Option<io::Result<InputValue>> {
+        let reader = self.0.take()?;
+        let values = std_dev::input::F64Le::new(reader)
+            .enumerate()
+            .map(|(idx, value)| match value {
+                Ok(v) if v.is_nan() => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("value {} is NaN", idx + 1),
+                )),
+                value => value.map(|v| (v, 1)),
+            })
+            .collect::<io::Result<Vec<_>>>();
+        match values {
+            Ok(values) if values.is_empty() => None,
+            values => Some(values.map(InputValue::Count)),
+        }
+    }
+}
+/// Rows which have already been read, e.g. by `--csv`, as one dataset.
+pub struct Rows(pub Vec<Vec<f64>>);
+impl Source for Rows {
+    fn next_dataset(&mut self) -> Option<io::Result<InputValue>> {
+        let rows = std::mem::take(&mut self.0);
+        (!rows.is_empty()).then_some(Ok(InputValue::List(rows)))
+    }
+}
+
+/// Changes the groups the following stages see.
+pub trait Transform {
+    fn apply(&self, groups: Vec<Group>) -> Vec<Group>;
+}
+/// Keeps only the value of the column (starting at 0) of each row.
+struct Column(usize);
+impl Transform for Column {
+    fn apply(&self, groups: Vec<Group>) -> Vec<Group> {
+        map_rows(groups, |row| row.get(self.0).map(|v| vec![*v]))
+    }
+}
+/// Keeps the rows whose value in the column (starting at 0) is in the range.
+struct Filter {
+    column: usize,
+    range: RangeInclusive<f64>,
+}
+impl Transform for Filter {
+    fn apply(&self, groups: Vec<Group>) -> Vec<Group> {
+        map_rows(groups, |row| {
+            let keep = row.get(self.column).is_some_and(|v| self.range.contains(v));
+            keep.then_some(row)
+        })
+    }
+}
+/// Applies the function to each value.
+struct Derive(Box<dyn Fn(f64) -> f64>);
+impl Transform for Derive {
+    fn apply(&self, groups: Vec<Group>) -> Vec<Group> {
+        map_rows(groups, |row| Some(row.into_iter().map(&self.0).collect()))
+    }
+}
+/// Splits each group by the value of the column (starting at 0), which is removed from the rows.
+struct GroupBy(usize);
+impl Transform for GroupBy {
+    fn apply(&self, groups: Vec<Group>) -> Vec<Group> {
+        let mut split: Vec<Group> = Vec::new();
+        for group in groups {
+            let first = split.len();
+            for mut row in group.rows {
+                if self.0 >= row.len() {
+                    continue;
+                }
+                let key = row.remove(self.0).to_string();
+                let name = match &group.name {
+                    Some(parent) => format!("{parent}/{key}"),
+                    None => key,
+                };
+                // keep the groups in the order the keys first appear
+                match split[first..]
+                    .iter_mut()
+                    .find(|g| g.name.as_ref() == Some(&name))
+                {
+                    Some(group) => group.rows.push(row),
+                    None => split.push(Group {
+                        name: Some(name),
+                        rows: vec![row],
+                    }),
+                }
+            }
+        }
+        split
+    }
+}
+fn map_rows(groups: Vec<Group>, mut f: impl FnMut(Vec<f64>) -> Option<Vec<f64>>) -> Vec<Group> {
+    groups
+        .into_iter()
+        .map(|group| Group {
+            name: group.name,
+            rows: group.rows.into_iter().filter_map(&mut f).collect(),
+        })
+        .collect()
+}
+
+/// Computes named values of a group's rows.
+pub trait Analyzer {
+    fn analyze(&self, group: &Group) -> Vec<(String, f64)>;
+}
+/// The statistics of the [`Registry`] with the names, of all values.
+struct Statistics(Vec<String>);
+impl Analyzer for Statistics {
+    fn analyze(&self, group: &Group) -> Vec<(String, f64)> {
+        let registry = Registry::global()
+            .read()
+            .expect("the registry is never poisoned");
+        let values = group.values();
+        self.0
+            .iter()
+            .filter_map(|name| registry.get(name))
+            .map(|statistic| {
+                (
+                    statistic.name().to_owned(),
+                    statistic.compute_slice(&values),
+                )
+            })
+            .collect()
+    }
+}
+/// A line fitted with least squares to the first (x) and second (y) column.
+struct Regression;
+impl Analyzer for Regression {
+    fn analyze(&self, group: &Group) -> Vec<(String, f64)> {
+        let (x, y): (Vec<f64>, Vec<f64>) = group
+            .rows
+            .iter()
+            .filter(|row| row.len() >= 2)
+            .map(|row| (row[0], row[1]))
+            .unzip();
+        if x.len() < 2 {
+            return ["slope", "intercept", "r2"]
+                .map(|name| (name.to_owned(), f64::NAN))
+                .to_vec();
+        }
+        let line = OlsEstimator.model_linear(&x, &y);
+        let determination = line.determination_slice(&x, &y);
+        vec![
+            ("slope".into(), line.k),
+            ("intercept".into(), line.m),
+            ("r2".into(), determination),
+        ]
+    }
+}
+
+/// A stage given to `--then`.
+pub enum Stage {
+    Transform(Box<dyn Transform>),
+    Analyzer(Box<dyn Analyzer>),
+}
+/// Returned from [`Stage::from_str`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStageError(String);
+impl Display for ParseStageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+impl std::error::Error for ParseStageError {}
+/// Parses a column starting at 1, returning the index starting at 0.
+fn parse_column(s: &str) -> Result<usize, ParseStageError> {
+    s.parse::<usize>()
+        .ok()
+        .filter(|c| *c > 0)
+        .map(|c| c - 1)
+        .ok_or_else(|| ParseStageError(format!("{s:?} isn't a column (starting at 1)")))
+}
+impl FromStr for Stage {
+    type Err = ParseStageError;
+    /// Parses `<name>` or `<name>:<argument>`:
+    ///
+    /// - `column:<n>`: keep only column `n` (starting at 1)
+    /// - `filter:<min>..<max>` or `filter:<n>=<min>..<max>`: keep the rows whose value in column
+    ///   `n` (1 by default) is in the range. Either bound can be left out.
+    /// - `group:<n>`: split the rows by the value of column `n`
+    /// - `log`, `ln`, `abs`, `scale:<factor>`: change each value
+    /// - `summary`: the count, mean, standard deviation, min, median, and max
+    /// - `percentile:<percent>`, e.g. `percentile:99`
+    /// - `stat:<names>`: the statistics with the comma separated names (see `--stats`)
+    /// - `regression`: a line fitted to the first two columns
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (s.trim(), None),
+        };
+        let arg = || arg.ok_or_else(|| ParseStageError(format!("`{name}` requires an argument")));
+        let transform = |t: Box<dyn Transform>| Ok(Self::Transform(t));
+        let analyzer = |a: Box<dyn Analyzer>| Ok(Self::Analyzer(a));
+        match name {
+            "column" => transform(Box::new(Column(parse_column(arg()?)?))),
+            "filter" => {
+                let (column, range) = match arg()?.split_once('=') {
+                    Some((column, range)) => (parse_column(column)?, range),
+                    None => (0, arg()?),
+                };
+                let (min, max) = range
+                    .split_once("..")
+                    .ok_or_else(|| ParseStageError("expected a range `<min>..<max>`".into()))?;
+                let bound = |s: &str, default| match s.trim() {
+                    "" => Ok(default),
+                    s => s
+                        .parse()
+                        .map_err(|_| ParseStageError(format!("{s:?} isn't a number"))),
+                };
+                let range = bound(min, f64::NEG_INFINITY)?..=bound(max, f64::INFINITY)?;
+                transform(Box::new(Filter { column, range }))
+            }
+            "group" => transform(Box::new(GroupBy(parse_column(arg()?)?))),
+            "log" => transform(Box::new(Derive(Box::new(f64::log10)))),
+            "ln" => transform(Box::new(Derive(Box::new(f64::ln)))),
+            "abs" => transform(Box::new(Derive(Box::new(f64::abs)))),
+            "scale" => {
+                let factor: f64 = arg()?
+                    .parse()
+                    .map_err(|_| ParseStageError("the factor needs to be a number".into()))?;
+                transform(Box::new(Derive(Box::new(move |v| v * factor))))
+            }
+            "summary" => analyzer(Box::new(Statistics(
+                ["count", "mean", "std_dev", "min", "median", "max"]
+                    .map(String::from)
+                    .to_vec(),
+            ))),
+            "percentile" => {
+                let percent: f64 = arg()?
+                    .parse()
+                    .ok()
+                    .filter(|p| (0.0..=100.).contains(p))
+                    .ok_or_else(|| ParseStageError("the percent needs to be in [0, 100]".into()))?;
+                let name = format!("p{percent}");
+                let mut registry = Registry::global()
+                    .write()
+                    .expect("the registry is never poisoned");
+                if registry.get(&name).is_none() {
+                    let target = Fraction::new((percent * 1000.).round() as u64, 100_000);
+                    registry.register(std_dev::statistic::Percentile::new(target));
+                }
+                analyzer(Box::new(Statistics(vec![name])))
+            }
+            "stat" => {
+                let names: Vec<String> = arg()?.split(',').map(|n| n.trim().to_owned()).collect();
+                let registry = Registry::global()
+                    .read()
+                    .expect("the registry is never poisoned");
+                if let Some(name) = names.iter().find(|name| registry.get(name).is_none()) {
+                    return Err(ParseStageError(format!(
+                        "unknown statistic {name:?}. Available: {}",
+                        registry.names().collect::<Vec<_>>().join(", ")
+                    )));
+                }
+                analyzer(Box::new(Statistics(names)))
+            }
+            "regression" => analyzer(Box::new(Regression)),
+            _ => Err(ParseStageError(format!("unknown stage {name:?}"))),
+        }
+    }
+}
+
+/// The values computed by an analyzer of a group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub group: Option<String>,
+    pub values: Vec<(String, f64)>,
+}
+
+/// Writes the reports.
+pub trait Sink {
+    fn write(&mut self, reports: &[Report]) -> io::Result<()>;
+}
+/// One line per report, e.g. `a: mean 4.5, std_dev 1.2`.
+pub struct Text(pub ValueFormat);
+impl Sink for Text {
+    fn write(&mut self, reports: &[Report]) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        for report in reports {
+            if let Some(group) = &report.group {
+                write!(out, "{group}: ")?;
+            }
+            let fields: Vec<String> = report
+                .values
+                .iter()
+                .map(|(name, value)| format!("{name} {}", self.0.format(*value)))
+                .collect();
+            writeln!(out, "{}", fields.join(", "))?;
+        }
+        Ok(())
+    }
+}
+/// An array of objects, one per report. The group is under the key `group`.
+pub struct Json;
+impl Sink for Json {
+    fn write(&mut self, reports: &[Report]) -> io::Result<()> {
+        let objects: Vec<String> = reports
+            .iter()
+            .map(|report| {
+                let group = report.group.as_ref().map(|g| format!("\"group\":{g:?}"));
+                let fields = report.values.iter().map(|(name, value)| {
+                    // JSON doesn't support NaN or infinities
+                    if value.is_finite() {
+                        format!("{name:?}:{value}")
+                    } else {
+                        format!("{name:?}:null")
+                    }
+                });
+                format!(
+                    "{{{}}}",
+                    group
+                        .into_iter()
+                        .chain(fields)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                )
+            })
+            .collect();
+        writeln!(io::stdout().lock(), "[{}]", objects.join(","))
+    }
+}
+/// The columns `group,name,value`.
+pub struct Csv;
+impl Sink for Csv {
+    fn write(&mut self, reports: &[Report]) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        writeln!(out, "group,name,value")?;
+        for report in reports {
+            for (name, value) in &report.values {
+                let group = report.group.as_deref().unwrap_or("");
+                writeln!(out, "{group},{name},{value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A row per report, aligned under a header of the value names, e.g. the output of `--group-by`.
+/// Counts are written as integers.
+pub struct Table {
+    /// The header of the column of the group names.
+    pub key: &'static str,
+    pub format: ValueFormat,
+}
+impl Sink for Table {
+    fn write(&mut self, reports: &[Report]) -> io::Result<()> {
+        let Some(first) = reports.first() else {
+            return Ok(());
+        };
+        let header: Vec<&str> = std::iter::once(self.key)
+            .chain(first.values.iter().map(|(name, _)| name.as_str()))
+            .collect();
+        let rows: Vec<Vec<String>> = reports
+            .iter()
+            .map(|report| {
+                let values = report.values.iter().map(|(name, value)| {
+                    if name == "count" {
+                        value.to_string()
+                    } else {
+                        self.format.format(*value)
+                    }
+                });
+                std::iter::once(report.group.clone().unwrap_or_default())
+                    .chain(values)
+                    .collect()
+            })
+            .collect();
+        super::print_table(&header, &rows);
+        Ok(())
+    }
+}
+/// A `<name>\t<value>` line per value, with all the digits, for scripts (`--porcelain`).
+pub struct Porcelain;
+impl Sink for Porcelain {
+    fn write(&mut self, reports: &[Report]) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        for report in reports {
+            for (name, value) in &report.values {
+                writeln!(out, "{name}\t{value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads all of `source`, runs `stages` in order, and returns the reports of the analyzers.
+///
+/// The datasets of `source` are joined, and rows without any values (e.g. headers) are skipped.
+pub fn run(source: &mut dyn Source, stages: &[Stage]) -> io::Result<Vec<Report>> {
+    let mut rows = Vec::new();
+    while let Some(dataset) = source.next_dataset() {
+        match dataset? {
+            InputValue::List(list) => rows.extend(list.into_iter().filter(|row| !row.is_empty())),
+            InputValue::Count(values) => rows.extend(
+                values
+                    .into_iter()
+                    .flat_map(|(v, count)| std::iter::repeat(vec![v]).take(count)),
+            ),
+        }
+    }
+    let mut groups = vec![Group { name: None, rows }];
+    let mut reports = Vec::new();
+    for stage in stages {
+        match stage {
+            Stage::Transform(transform) => groups = transform.apply(groups),
+            Stage::Analyzer(analyzer) => reports.extend(groups.iter().map(|group| Report {
+                group: group.name.clone(),
+                values: analyzer.analyze(group),
+            })),
+        }
+    }
+    Ok(reports)
+}
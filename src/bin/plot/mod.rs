@@ -0,0 +1,268 @@
+//! SVG and PNG plots of the statistics and regressions, written with `--plot-file`.
+use std::error::Error;
+#[cfg(feature = "regression")]
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+/// The size of the plots, in pixels.
+const SIZE: (u32, u32) = (800, 500);
+
+type DrawResult = Result<(), Box<dyn Error>>;
+
+/// A plot, which can be drawn to any backend.
+trait Draw {
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> DrawResult
+    where
+        DB::ErrorType: 'static;
+}
+
+/// If `path` is a PNG file. Else, it's an SVG file.
+pub fn is_png(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "png")
+}
+
+/// Draws `plot` to `path`, as a PNG or SVG file depending on its extension, or exits if it can't
+/// be written.
+fn write(path: &Path, plot: &impl Draw) {
+    fn present<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, plot: &impl Draw) -> DrawResult
+    where
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+        plot.draw(&root)?;
+        root.present()?;
+        Ok(())
+    }
+
+    let result = if is_png(path) {
+        present(BitMapBackend::new(path, SIZE).into_drawing_area(), plot)
+    } else {
+        present(SVGBackend::new(path, SIZE).into_drawing_area(), plot)
+    };
+    if let Err(err) = result {
+        eprintln!("Failed to write plot {}: {err}", path.display());
+        std::process::exit(1);
+    }
+}
+
+/// The range from the smallest to the largest of `values`, widened by 5% on both sides so
+/// points at the ends aren't cut off.
+fn padded_range(values: impl Iterator<Item = f64>) -> Range<f64> {
+    let (min, max) = values
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+    if min > max {
+        return 0.0..1.0;
+    }
+    let padding = if min == max { 0.5 } else { (max - min) * 0.05 };
+    min - padding..max + padding
+}
+
+/// A histogram with a box plot (the quartiles, median, and whiskers to the smallest and largest
+/// value) above it.
+struct Summary {
+    /// The lower and upper edge and the count of each bin.
+    bins: Vec<(f64, f64, usize)>,
+    min: f64,
+    max: f64,
+    q1: f64,
+    median: f64,
+    q3: f64,
+}
+impl Draw for Summary {
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> DrawResult
+    where
+        DB::ErrorType: 'static,
+    {
+        let max_count = self.bins.iter().map(|bin| bin.2).max().unwrap_or(0).max(1) as f64;
+        let (low, mid, high) = (max_count * 1.1, max_count * 1.2, max_count * 1.3);
+        let x_range = if self.min < self.max {
+            self.min..self.max
+        } else {
+            padded_range([self.min, self.max].into_iter())
+        };
+
+        let mut chart = ChartBuilder::on(root)
+            .caption("Distribution", ("sans-serif", 24))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(x_range, 0.0..max_count * 1.4)?;
+        chart
+            .configure_mesh()
+            .x_desc("value")
+            .y_desc("count")
+            .draw()?;
+
+        let bars = BLUE.mix(0.6).filled();
+        chart
+            .draw_series(self.bins.iter().map(|(lower, upper, count)| {
+                Rectangle::new([(*lower, 0.), (*upper, *count as f64)], bars)
+            }))?
+            .label("count")
+            .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 15, y + 5)], bars));
+
+        let box_style = RED.stroke_width(2);
+        chart
+            .draw_series([Rectangle::new([(self.q1, low), (self.q3, high)], box_style)])?
+            .label("quartiles")
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 15, y)], box_style));
+        let median_style = BLACK.stroke_width(2);
+        chart
+            .draw_series(LineSeries::new(
+                [(self.median, low), (self.median, high)],
+                median_style,
+            ))?
+            .label(format!("median = {}", self.median))
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 15, y)], median_style));
+        for whisker in [
+            [(self.min, mid), (self.q1, mid)],
+            [(self.q3, mid), (self.max, mid)],
+        ] {
+            chart.draw_series(LineSeries::new(whisker, box_style))?;
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+        Ok(())
+    }
+}
+
+/// Plots a histogram of `values`, with a box plot (the quartiles, median, and whiskers to the
+/// smallest and largest value) above it.
+pub fn summary(values: &std_dev::OwnedClusterList, path: &Path) {
+    use std_dev::histogram::{Bins, Histogram};
+
+    let histogram = Histogram::from_clusters(&values.borrow(), Bins::FreedmanDiaconis);
+    let edges = histogram.edges();
+    let (min, max) = (edges[0], edges[edges.len() - 1]);
+
+    let mut owned = values.clone();
+    let percentiles = crate::percentiles(&mut owned);
+    let median = percentiles.median;
+    let summary = Summary {
+        bins: histogram.iter().collect(),
+        min,
+        max,
+        q1: percentiles.lower_quadrille.unwrap_or(median),
+        median,
+        q3: percentiles.higher_quadrille.unwrap_or(median),
+    };
+    write(path, &summary);
+}
+
+/// Points, with a line drawn over them.
+struct Scatter<'a> {
+    caption: &'a str,
+    y_desc: &'a str,
+    points: Vec<(f64, f64)>,
+    points_label: &'a str,
+    line: Vec<(f64, f64)>,
+    line_label: String,
+}
+impl Draw for Scatter<'_> {
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> DrawResult
+    where
+        DB::ErrorType: 'static,
+    {
+        let all = || self.points.iter().chain(&self.line);
+        let mut chart = ChartBuilder::on(root)
+            .caption(self.caption, ("sans-serif", 24))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(
+                padded_range(all().map(|(x, _)| *x)),
+                padded_range(all().map(|(_, y)| *y)),
+            )?;
+        chart
+            .configure_mesh()
+            .x_desc("predictors")
+            .y_desc(self.y_desc)
+            .draw()?;
+
+        let line_style = RED.stroke_width(2);
+        chart
+            .draw_series(LineSeries::new(self.line.iter().copied(), line_style))?
+            .label(&self.line_label)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 15, y)], line_style));
+        let point_style = BLUE.filled();
+        chart
+            .draw_series(
+                self.points
+                    .iter()
+                    .map(|point| Circle::new(*point, 3, point_style)),
+            )?
+            .label(self.points_label)
+            .legend(move |(x, y)| Circle::new((x + 7, y), 3, point_style));
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+        Ok(())
+    }
+}
+
+/// The path of the residual plot next to `path`, e.g. `fit-residuals.svg` for `fit.svg`.
+fn residuals_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = if is_png(path) { "png" } else { "svg" };
+    path.with_file_name(format!("{stem}-residuals.{extension}"))
+}
+
+/// Plots the points with `model` drawn over them to `path`, and the residuals of the points to
+/// the file next to it with `-residuals` appended to the name.
+#[cfg(feature = "regression")]
+pub fn regression(
+    model: &(impl std_dev::regression::Predictive + Display),
+    x: &[f64],
+    y: &[f64],
+    precision: Option<usize>,
+    path: &Path,
+) {
+    const SAMPLES: usize = 200;
+
+    let x_min = x.iter().copied().fold(f64::INFINITY, f64::min);
+    let x_max = x.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let curve = (0..SAMPLES)
+        .map(|i| {
+            let x = x_min + (x_max - x_min) * i as f64 / (SAMPLES - 1) as f64;
+            (x, model.predict_outcome(x))
+        })
+        .collect();
+    let points: Vec<(f64, f64)> = x.iter().copied().zip(y.iter().copied()).collect();
+    let residuals = points
+        .iter()
+        .map(|(x, y)| (*x, y - model.predict_outcome(*x)))
+        .collect();
+
+    let fit = Scatter {
+        caption: "Regression",
+        y_desc: "outcomes",
+        points,
+        points_label: "points",
+        line: curve,
+        line_label: format!("{model:.*}", precision.unwrap_or(2)),
+    };
+    write(path, &fit);
+    let residuals = Scatter {
+        caption: "Residuals",
+        y_desc: "residual",
+        points: residuals,
+        points_label: "residuals",
+        line: vec![(x_min, 0.), (x_max, 0.)],
+        line_label: "0".to_owned(),
+    };
+    write(&residuals_path(path), &residuals);
+}
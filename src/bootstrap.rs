@@ -0,0 +1,221 @@
+//! [Bootstrap](https://en.wikipedia.org/wiki/Bootstrapping_(statistics)) confidence intervals
+//! for any statistic.
+//!
+//! The values are resampled with replacement many times, and the statistic is computed of each
+//! resample. The spread of those replicates estimates the uncertainty of the statistic, without
+//! assuming a distribution of the values. The intervals use the percentile method: the
+//! `(1 - confidence) / 2` and `(1 + confidence) / 2` quantiles of the replicates.
+//!
+//! The statistic is a closure, so this works the same for the mean, median, standard deviation,
+//! or e.g. the slope of a regression (resample the `(x, y)` pairs, see
+//! [`confidence_interval`]).
+//!
+//! Enabled by the `bootstrap` feature.
+use rand::Rng;
+use std::fmt::{self, Display};
+
+use crate::percentile::Interpolation;
+use crate::{Cluster, ClusterList, OwnedClusterList};
+
+/// The count of resamples commonly used for confidence intervals.
+pub const DEFAULT_RESAMPLES: usize = 2000;
+
+/// A statistic with a bootstrap confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceInterval {
+    /// The statistic of the original values.
+    pub estimate: f64,
+    pub lower: f64,
+    pub upper: f64,
+    /// The probability the interval contains the true value, e.g. `0.95`.
+    pub confidence: f64,
+}
+impl ConfidenceInterval {
+    /// The difference between the bounds.
+    pub fn width(&self) -> f64 {
+        self.upper - self.lower
+    }
+    /// Whether `value` lies in the interval.
+    pub fn contains(&self, value: f64) -> bool {
+        self.lower <= value && value <= self.upper
+    }
+}
+impl Display for ConfidenceInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = self.confidence * 100.;
+        match f.precision() {
+            Some(precision) => write!(
+                f,
+                "{:.3$} ({percent}% CI {:.3$} to {:.3$})",
+                self.estimate, self.lower, self.upper, precision
+            ),
+            None => write!(
+                f,
+                "{} ({percent}% CI {} to {})",
+                self.estimate, self.lower, self.upper
+            ),
+        }
+    }
+}
+
+/// Draws `values.len()` values from `values` with replacement.
+pub fn resample<T: Clone>(values: &[T], rng: &mut impl Rng) -> Vec<T> {
+    (0..values.len())
+        .map(|_| values[rng.random_range(0..values.len())].clone())
+        .collect()
+}
+/// Draws [`ClusterList::len`] values from `values` with replacement, keeping them clustered.
+///
+/// O(n log m), where m is the number of [`Cluster`]s.
+pub fn resample_cluster(values: &ClusterList, rng: &mut impl Rng) -> OwnedClusterList {
+    let len = values.len();
+    // the count of values before and including each cluster
+    let ends: Vec<usize> = values
+        .list
+        .iter()
+        .scan(0, |end, (_, count)| {
+            *end += count;
+            Some(*end)
+        })
+        .collect();
+    let mut counts = vec![0; values.list.len()];
+    for _ in 0..len {
+        let index = rng.random_range(0..len);
+        counts[ends.partition_point(|end| *end <= index)] += 1;
+    }
+    let list: Vec<Cluster> = values
+        .list
+        .iter()
+        .zip(counts)
+        .filter(|(_, count)| *count > 0)
+        .map(|((v, _), count)| (*v, count))
+        .collect();
+    OwnedClusterList::new(list)
+}
+
+/// The interval from the replicates of the statistic.
+fn percentile_interval(
+    estimate: f64,
+    mut replicates: Vec<f64>,
+    confidence: f64,
+) -> ConfidenceInterval {
+    replicates.retain(|v| !v.is_nan());
+    if replicates.is_empty() {
+        return ConfidenceInterval {
+            estimate,
+            lower: f64::NAN,
+            upper: f64::NAN,
+            confidence,
+        };
+    }
+    replicates.sort_unstable_by(f64::total_cmp);
+    let quantile = |quantile| {
+        Interpolation::Linear
+            .index(quantile, replicates.len())
+            .map(|index| replicates[index])
+            .resolve()
+    };
+    ConfidenceInterval {
+        estimate,
+        lower: quantile((1. - confidence) / 2.),
+        upper: quantile((1. + confidence) / 2.),
+        confidence,
+    }
+}
+fn assert_confidence(confidence: f64) {
+    assert!(
+        confidence > 0. && confidence < 1.,
+        "confidence must be in the range (0, 1)"
+    );
+}
+
+/// The `confidence` interval of `statistic` of `values`, from `resamples` resamples.
+///
+/// Replicates for which `statistic` returns `NaN` are ignored. The bounds are `NaN` if all are.
+///
+/// To get the interval of a regression's slope, resample the points:
+///
+/// ```
+/// use std_dev::bootstrap::confidence_interval;
+/// use std_dev::correlation::covariance;
+///
+/// let points: Vec<(f64, f64)> = (0..50).map(|x| (x as f64, 2. * x as f64 + (x % 3) as f64)).collect();
+/// let slope = |points: &[(f64, f64)]| {
+///     let (x, y): (Vec<f64>, Vec<f64>) = points.iter().copied().unzip();
+///     covariance(&x, &y) / covariance(&x, &x)
+/// };
+/// let interval = confidence_interval(&points, slope, 500, 0.95, &mut rand::rng());
+/// assert!(interval.contains(2.));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `confidence` isn't in the range `(0, 1)`.
+pub fn confidence_interval<T: Clone>(
+    values: &[T],
+    mut statistic: impl FnMut(&[T]) -> f64,
+    resamples: usize,
+    confidence: f64,
+    rng: &mut impl Rng,
+) -> ConfidenceInterval {
+    assert_confidence(confidence);
+    let estimate = statistic(values);
+    let replicates = (0..resamples)
+        .map(|_| statistic(&resample(values, rng)))
+        .collect();
+    percentile_interval(estimate, replicates, confidence)
+}
+/// Same as [`confidence_interval`], but for clustered `values`.
+///
+/// # Panics
+///
+/// Panics if `confidence` isn't in the range `(0, 1)`.
+pub fn confidence_interval_cluster(
+    values: &ClusterList,
+    mut statistic: impl FnMut(&ClusterList) -> f64,
+    resamples: usize,
+    confidence: f64,
+    rng: &mut impl Rng,
+) -> ConfidenceInterval {
+    assert_confidence(confidence);
+    let estimate = statistic(values);
+    let replicates = (0..resamples)
+        .map(|_| statistic(&resample_cluster(values, rng).borrow()))
+        .collect();
+    percentile_interval(estimate, replicates, confidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn intervals() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let values: Vec<f64> = (0..200).map(|v| (v % 20) as f64).collect();
+        let interval = confidence_interval(
+            &values,
+            |v| crate::mean(v),
+            DEFAULT_RESAMPLES,
+            0.95,
+            &mut rng,
+        );
+        assert_eq!(interval.estimate, 9.5);
+        assert!(interval.contains(9.5));
+        // the standard error is about 5.8 / √200 = 0.41
+        assert!(
+            interval.width() > 1. && interval.width() < 2.2,
+            "{interval}"
+        );
+
+        let clusters = [(1., 50), (2., 100), (3., 50)];
+        let clusters = ClusterList::new(&clusters);
+        let resampled = resample_cluster(&clusters, &mut rng);
+        assert_eq!(resampled.borrow().len(), 200);
+        let interval =
+            confidence_interval_cluster(&clusters, crate::mean_cluster, 500, 0.9, &mut rng);
+        assert_eq!(interval.estimate, 2.);
+        assert!(interval.lower < 2. && interval.upper > 2.);
+    }
+}
@@ -0,0 +1,258 @@
+//! Bucketed histograms, as exported by Prometheus and OpenTelemetry.
+//!
+//! These are common for latencies: instead of every value, only the count of values under a set of
+//! upper bounds (`le`, "less than or equal") is kept. The bounds are often exponential, e.g.
+//! `0.005, 0.01, 0.025, 0.05, 0.1, ...`.
+//!
+//! The original values are lost, so all statistics are approximations. The
+//! [quantiles](BucketHistogram::quantile) use the same linear interpolation as Prometheus'
+//! `histogram_quantile`, which assumes the values are evenly distributed within each bucket.
+//! [`BucketHistogram::quantile_bounded`] and [`BucketHistogram::percentiles`] also return the
+//! bounds of the bucket, which the exact value is within.
+use crate::{ApproximatePercentilesOutput, Approximation};
+use std::fmt;
+
+/// The error returned by [`BucketHistogram::from_cumulative`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketError {
+    /// No buckets were given.
+    Empty,
+    /// The upper bound at this index is not larger than the previous, or is `NaN`.
+    UnsortedBounds(usize),
+    /// The cumulative count at this index is smaller than the previous.
+    DecreasingCount(usize),
+}
+impl fmt::Display for BucketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no buckets"),
+            Self::UnsortedBounds(idx) => {
+                write!(f, "bucket {} has a bound not above the previous", idx + 1)
+            }
+            Self::DecreasingCount(idx) => {
+                write!(f, "bucket {} has a count below the previous", idx + 1)
+            }
+        }
+    }
+}
+impl std::error::Error for BucketError {}
+
+/// A histogram of cumulative buckets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketHistogram {
+    /// `(upper bound, cumulative count)`, sorted by the bound.
+    buckets: Vec<(f64, u64)>,
+}
+impl BucketHistogram {
+    /// Creates a histogram from `(le, cumulative count)` pairs, where the count is the number of
+    /// values `<= le`. The last bound is usually `+Inf`.
+    ///
+    /// The buckets must be sorted by their bound.
+    pub fn from_cumulative(buckets: Vec<(f64, u64)>) -> Result<Self, BucketError> {
+        if buckets.is_empty() {
+            return Err(BucketError::Empty);
+        }
+        for (idx, window) in buckets.windows(2).enumerate() {
+            let (bound, count) = window[0];
+            let (next_bound, next_count) = window[1];
+            if next_bound.partial_cmp(&bound) != Some(std::cmp::Ordering::Greater) {
+                return Err(BucketError::UnsortedBounds(idx + 1));
+            }
+            if next_count < count {
+                return Err(BucketError::DecreasingCount(idx + 1));
+            }
+        }
+        if buckets[0].0.is_nan() {
+            return Err(BucketError::UnsortedBounds(0));
+        }
+        Ok(Self { buckets })
+    }
+    /// The `(le, cumulative count)` pairs.
+    pub fn buckets(&self) -> &[(f64, u64)] {
+        &self.buckets
+    }
+    /// The total count of values.
+    pub fn len(&self) -> u64 {
+        self.buckets.last().map_or(0, |b| b.1)
+    }
+    /// If there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// The lower and upper bound of the bucket at `idx`.
+    ///
+    /// Like Prometheus, the first bucket is assumed to start at 0 if its bound is positive.
+    fn bounds(&self, idx: usize) -> (f64, f64) {
+        let upper = self.buckets[idx].0;
+        let lower = if idx == 0 {
+            if upper > 0. {
+                0.
+            } else {
+                upper
+            }
+        } else {
+            self.buckets[idx - 1].0
+        };
+        (lower, upper)
+    }
+    /// Iterates the lower and upper bound, and the (non-cumulative) count of each bucket.
+    pub fn iter(&self) -> impl Iterator<Item = (f64, f64, u64)> + '_ {
+        (0..self.buckets.len()).map(|idx| {
+            let (lower, upper) = self.bounds(idx);
+            let previous = if idx == 0 { 0 } else { self.buckets[idx - 1].1 };
+            (lower, upper, self.buckets[idx].1 - previous)
+        })
+    }
+    /// Approximates the quantile `q` (in `[0, 1]`) by interpolating linearly within the bucket it
+    /// falls in.
+    ///
+    /// If it falls in the `+Inf` bucket, the largest finite bound is returned.
+    /// Returns `NaN` if the histogram is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is outside `[0, 1]`.
+    pub fn quantile(&self, q: f64) -> f64 {
+        self.quantile_bounded(q).value
+    }
+    /// Same as [`Self::quantile`], but also returns the bounds of the bucket the quantile falls
+    /// in. The exact quantile is within these bounds.
+    ///
+    /// The upper bound is infinite if it falls in the `+Inf` bucket.
+    /// Like [`Self::quantile`], the lower bound of the first bucket is assumed to be 0 if its
+    /// bound is positive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is outside `[0, 1]`.
+    pub fn quantile_bounded(&self, q: f64) -> Approximation {
+        assert!((0.0..=1.0).contains(&q), "q must be in the range [0, 1]");
+        if self.is_empty() {
+            return Approximation::exact(f64::NAN);
+        }
+        let rank = q * self.len() as f64;
+        let idx = self
+            .buckets
+            .iter()
+            .position(|(_, count)| *count as f64 >= rank)
+            .unwrap_or(self.buckets.len() - 1);
+        let (lower, upper) = self.bounds(idx);
+        let previous = if idx == 0 { 0 } else { self.buckets[idx - 1].1 };
+        let count = self.buckets[idx].1 - previous;
+        let value = if upper.is_infinite() {
+            lower
+        } else if count == 0 {
+            upper
+        } else {
+            lower + (upper - lower) * (rank - previous as f64) / count as f64
+        };
+        Approximation {
+            value,
+            lower_bound: lower,
+            upper_bound: upper,
+        }
+    }
+    /// The median and quadrilles, with their bounds. See [`Self::quantile_bounded`].
+    ///
+    /// Like [`crate::percentiles_cluster`], the quadrilles are only calculated if there are at
+    /// least 4 values.
+    pub fn percentiles(&self) -> ApproximatePercentilesOutput {
+        let quadrilles = self.len() >= 4;
+        ApproximatePercentilesOutput {
+            median: self.quantile_bounded(0.5),
+            lower_quadrille: quadrilles.then(|| self.quantile_bounded(0.25)),
+            higher_quadrille: quadrilles.then(|| self.quantile_bounded(0.75)),
+        }
+    }
+    /// Approximates the mean by assuming all values are in the middle of their bucket.
+    ///
+    /// Values in the `+Inf` bucket are assumed to be at the largest finite bound, which
+    /// underestimates the mean.
+    /// Returns `NaN` if the histogram is empty.
+    pub fn mean(&self) -> f64 {
+        let sum: f64 = self
+            .iter()
+            .map(|(lower, upper, count)| {
+                let middle = if upper.is_infinite() {
+                    lower
+                } else {
+                    (lower + upper) / 2.
+                };
+                middle * count as f64
+            })
+            .sum();
+        sum / self.len() as f64
+    }
+    /// Approximates the distribution as [`Cluster`](crate::Cluster)s in the middle of each
+    /// bucket, for use with the other functions of this crate.
+    ///
+    /// Buckets with a count of 0 are skipped.
+    pub fn to_clusters(&self) -> crate::OwnedClusterList {
+        let list = self
+            .iter()
+            .filter(|(_, _, count)| *count > 0)
+            .map(|(lower, upper, count)| {
+                let middle = if upper.is_infinite() {
+                    lower
+                } else {
+                    (lower + upper) / 2.
+                };
+                (middle, count as usize)
+            })
+            .collect();
+        crate::OwnedClusterList::new(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram() -> BucketHistogram {
+        BucketHistogram::from_cumulative(vec![
+            (0.1, 10),
+            (0.2, 30),
+            (0.4, 90),
+            (f64::INFINITY, 100),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn quantile() {
+        let h = histogram();
+        assert_eq!(h.len(), 100);
+        assert!((h.quantile(0.05) - 0.05).abs() < 1e-12);
+        assert!((h.quantile(0.5) - (0.2 + 0.2 * 20. / 60.)).abs() < 1e-12);
+        assert_eq!(h.quantile(0.99), 0.4);
+    }
+    #[test]
+    fn mean() {
+        let h = histogram();
+        let expected = (0.05 * 10. + 0.15 * 20. + 0.3 * 60. + 0.4 * 10.) / 100.;
+        assert!((h.mean() - expected).abs() < 1e-12);
+    }
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            BucketHistogram::from_cumulative(vec![(1., 5), (2., 3)]),
+            Err(BucketError::DecreasingCount(1))
+        );
+        assert_eq!(
+            BucketHistogram::from_cumulative(vec![(1., 5), (1., 6)]),
+            Err(BucketError::UnsortedBounds(1))
+        );
+    }
+    #[test]
+    fn bounds() {
+        let h = histogram();
+        let median = h.quantile_bounded(0.5);
+        assert_eq!((median.lower_bound, median.upper_bound), (0.2, 0.4));
+        assert!(!median.is_exact());
+        assert!((median.error() - (0.4 - median.value)).abs() < 1e-12);
+        let output = h.percentiles();
+        assert_eq!(output.higher_quadrille.unwrap().upper_bound, 0.4);
+        assert_eq!(h.quantile_bounded(0.99).upper_bound, f64::INFINITY);
+        assert!(!output.is_exact());
+    }
+}
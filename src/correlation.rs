@@ -0,0 +1,274 @@
+//! How correlated two variables are.
+//!
+//! All functions take the paired values as two slices, like the [regression](crate::regression)
+//! functions.
+//!
+//! - [`covariance`] and [`pearson_r`] measure linear correlation.
+//!   [`covariance_cluster`] and [`pearson_r_cluster`] take pairs with a count, similar to
+//!   [`crate::Cluster`].
+//! - [`spearman_rho`] measures monotonic correlation, using the [`ranks`] of the values.
+//!   Use this when the relationship isn't linear, or when there are outliers.
+//! - [`kendall_tau`] also measures monotonic correlation, by counting the pairs of points which
+//!   agree on the order. It's less sensitive to errors in the data than [`spearman_rho`].
+
+/// A pair of values and how many times it occurs.
+/// The paired equivalent of [`crate::Cluster`].
+pub type PairCluster = ((f64, f64), usize);
+
+fn assert_same_len(x: &[f64], y: &[f64]) {
+    assert_eq!(
+        x.len(),
+        y.len(),
+        "x and y must have the same number of items"
+    );
+}
+
+/// The sums needed for the covariance and correlation:
+/// `(n, Σ(x-x̄)(y-ȳ), Σ(x-x̄)², Σ(y-ȳ)²)`.
+fn co_moments(pairs: impl Iterator<Item = PairCluster> + Clone) -> (usize, f64, f64, f64) {
+    let (mut n, mut x_sum, mut y_sum) = (0, 0., 0.);
+    for ((x, y), count) in pairs.clone() {
+        n += count;
+        x_sum += x * count as f64;
+        y_sum += y * count as f64;
+    }
+    let x_mean = x_sum / n as f64;
+    let y_mean = y_sum / n as f64;
+    let (mut xy, mut xx, mut yy) = (0., 0., 0.);
+    for ((x, y), count) in pairs {
+        let dx = x - x_mean;
+        let dy = y - y_mean;
+        let count = count as f64;
+        xy += dx * dy * count;
+        xx += dx * dx * count;
+        yy += dy * dy * count;
+    }
+    (n, xy, xx, yy)
+}
+
+/// The sample covariance of `x` and `y` (divided by `n - 1`, like [`crate::standard_deviation`]).
+///
+/// O(n)
+///
+/// # Panics
+///
+/// `x` and `y` must have the same length.
+pub fn covariance(x: &[f64], y: &[f64]) -> f64 {
+    assert_same_len(x, y);
+    let (n, xy, _, _) = co_moments(x.iter().copied().zip(y.iter().copied()).map(|p| (p, 1)));
+    xy / (n.max(2) - 1) as f64
+}
+/// [Pearson's correlation coefficient](https://en.wikipedia.org/wiki/Pearson_correlation_coefficient),
+/// `r`. It's in the range `[-1, 1]`, where `1` means a perfect positive linear relationship,
+/// `-1` a perfect negative, and `0` no linear relationship.
+///
+/// Returns `NaN` if either of the variables is constant.
+///
+/// O(n)
+///
+/// # Panics
+///
+/// `x` and `y` must have the same length.
+pub fn pearson_r(x: &[f64], y: &[f64]) -> f64 {
+    assert_same_len(x, y);
+    let (_, xy, xx, yy) = co_moments(x.iter().copied().zip(y.iter().copied()).map(|p| (p, 1)));
+    (xy / (xx * yy).sqrt()).clamp(-1., 1.)
+}
+/// Same as [`covariance`], but each pair is weighted by its count.
+///
+/// O(m), where m is the count of [`PairCluster`]s.
+pub fn covariance_cluster(pairs: &[PairCluster]) -> f64 {
+    let (n, xy, _, _) = co_moments(pairs.iter().copied());
+    xy / (n.max(2) - 1) as f64
+}
+/// Same as [`pearson_r`], but each pair is weighted by its count.
+///
+/// O(m), where m is the count of [`PairCluster`]s.
+pub fn pearson_r_cluster(pairs: &[PairCluster]) -> f64 {
+    let (_, xy, xx, yy) = co_moments(pairs.iter().copied());
+    (xy / (xx * yy).sqrt()).clamp(-1., 1.)
+}
+
+/// The rank (position when sorted, starting at 1) of each of `values`.
+/// Tied values get the average of their ranks: `[10, 20, 20, 30]` gives `[1, 2.5, 2.5, 4]`.
+///
+/// `NaN`s are ranked lowest.
+///
+/// O(n log n)
+pub fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_unstable_by(|a, b| crate::F64OrdHash::f64_cmp(values[*a], values[*b]));
+    let mut ranks = vec![0.; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let value = crate::F64OrdHash(values[order[start]]);
+        let mut end = start + 1;
+        while end < order.len() && crate::F64OrdHash(values[order[end]]) == value {
+            end += 1;
+        }
+        // the mean of the ranks start+1..=end
+        let rank = (start + 1 + end) as f64 / 2.;
+        for idx in &order[start..end] {
+            ranks[*idx] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+/// [Spearman's rank correlation coefficient](https://en.wikipedia.org/wiki/Spearman%27s_rank_correlation_coefficient),
+/// `ρ`. It's [`pearson_r`] of the [`ranks`] of `x` and `y`, so it's `1` if `y` always increases
+/// when `x` does, regardless of how much.
+///
+/// Ties get the average rank, which is the standard way to handle them.
+///
+/// O(n log n)
+///
+/// # Panics
+///
+/// `x` and `y` must have the same length.
+pub fn spearman_rho(x: &[f64], y: &[f64]) -> f64 {
+    assert_same_len(x, y);
+    pearson_r(&ranks(x), &ranks(y))
+}
+
+/// Counts the pairs of `a` which are out of order while merge sorting it.
+/// `buffer` must have the same length as `a`.
+fn sort_count_swaps(a: &mut [f64], buffer: &mut [f64]) -> u64 {
+    let len = a.len();
+    if len < 2 {
+        return 0;
+    }
+    let mid = len / 2;
+    let mut swaps = sort_count_swaps(&mut a[..mid], &mut buffer[..mid])
+        + sort_count_swaps(&mut a[mid..], &mut buffer[mid..]);
+    let (mut left, mut right) = (0, mid);
+    for slot in buffer.iter_mut() {
+        // take from the left on ties, so they aren't counted
+        if right >= len || (left < mid && crate::F64OrdHash::f64_cmp(a[left], a[right]).is_le()) {
+            *slot = a[left];
+            left += 1;
+        } else {
+            *slot = a[right];
+            // all the remaining on the left are greater
+            swaps += (mid - left) as u64;
+            right += 1;
+        }
+    }
+    a.copy_from_slice(buffer);
+    swaps
+}
+/// The count of pairs in the sorted `values` which are tied.
+fn tied_pairs<T>(values: &[T], mut eq: impl FnMut(&T, &T) -> bool) -> u64 {
+    let mut pairs = 0;
+    let mut run = 1_u64;
+    for window in values.windows(2) {
+        if eq(&window[0], &window[1]) {
+            run += 1;
+        } else {
+            pairs += run * (run - 1) / 2;
+            run = 1;
+        }
+    }
+    pairs + run * (run - 1) / 2
+}
+/// [Kendall's rank correlation coefficient](https://en.wikipedia.org/wiki/Kendall_rank_correlation_coefficient),
+/// `τ`. It's the difference between the count of concordant pairs of points (where `y` increases
+/// with `x`) and discordant pairs, normalized to `[-1, 1]`.
+///
+/// This is the `τ-b` variant, which adjusts for ties in `x` or `y`.
+/// Returns `NaN` if either of the variables is constant.
+///
+/// Uses [Knight's algorithm](https://doi.org/10.2307/2282833), which counts the discordant pairs
+/// while merge sorting, instead of comparing all pairs.
+///
+/// O(n log n)
+///
+/// # Panics
+///
+/// `x` and `y` must have the same length.
+pub fn kendall_tau(x: &[f64], y: &[f64]) -> f64 {
+    use crate::F64OrdHash;
+
+    assert_same_len(x, y);
+    let n = x.len() as u64;
+    let mut pairs: Vec<(f64, f64)> = x.iter().copied().zip(y.iter().copied()).collect();
+    pairs
+        .sort_unstable_by(|a, b| F64OrdHash::f64_cmp(a.0, b.0).then(F64OrdHash::f64_cmp(a.1, b.1)));
+
+    let total = n * n.saturating_sub(1) / 2;
+    let x_ties = tied_pairs(&pairs, |a, b| F64OrdHash(a.0) == F64OrdHash(b.0));
+    let joint_ties = tied_pairs(&pairs, |a, b| {
+        F64OrdHash(a.0) == F64OrdHash(b.0) && F64OrdHash(a.1) == F64OrdHash(b.1)
+    });
+
+    let mut ys: Vec<f64> = pairs.iter().map(|p| p.1).collect();
+    let mut buffer = vec![0.; ys.len()];
+    let swaps = sort_count_swaps(&mut ys, &mut buffer);
+    let y_ties = tied_pairs(&ys, |a, b| F64OrdHash(*a) == F64OrdHash(*b));
+
+    // concordant - discordant
+    let difference =
+        total as f64 - x_ties as f64 - y_ties as f64 + joint_ties as f64 - 2. * swaps as f64;
+    let denominator = ((total - x_ties) as f64 * (total - y_ties) as f64).sqrt();
+    (difference / denominator).clamp(-1., 1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pearson() {
+        let x = [1., 2., 3., 4., 5.];
+        let y = [2., 4., 6., 8., 10.];
+        assert!((pearson_r(&x, &y) - 1.).abs() < 1e-12);
+        assert!((covariance(&x, &y) - 5.).abs() < 1e-12);
+        let y = [5., 3., 4., 1., 2.];
+        assert!((pearson_r(&x, &y) + 0.8).abs() < 1e-12);
+        assert!(pearson_r(&x, &[1.; 5]).is_nan());
+    }
+    #[test]
+    fn weighted() {
+        let x = [1., 1., 2., 3., 3., 3.];
+        let y = [2., 2., 1., 5., 5., 5.];
+        let pairs = [((1., 2.), 2), ((2., 1.), 1), ((3., 5.), 3)];
+        assert!((pearson_r_cluster(&pairs) - pearson_r(&x, &y)).abs() < 1e-12);
+        assert!((covariance_cluster(&pairs) - covariance(&x, &y)).abs() < 1e-12);
+    }
+    #[test]
+    fn spearman() {
+        assert_eq!(ranks(&[10., 30., 20., 20.]), [1., 4., 2.5, 2.5]);
+        let x = [1., 2., 3., 4., 5.];
+        let y = x.map(|x: f64| x.exp());
+        assert!((spearman_rho(&x, &y) - 1.).abs() < 1e-12);
+        // rank correlation of [1, 2, 3, 4, 5] and [1, 2.5, 2.5, 4, 5]
+        let y = [1., 2., 2., 3., 4.];
+        let expected = pearson_r(&x, &[1., 2.5, 2.5, 4., 5.]);
+        assert!((spearman_rho(&x, &y) - expected).abs() < 1e-12);
+    }
+    /// Kendall's τ-b by comparing all pairs.
+    fn kendall_naive(x: &[f64], y: &[f64]) -> f64 {
+        let (mut difference, mut x_untied, mut y_untied) = (0., 0., 0.);
+        for i in 0..x.len() {
+            for j in i + 1..x.len() {
+                let dx = (x[i] - x[j]).signum() * (x[i] != x[j]) as u8 as f64;
+                let dy = (y[i] - y[j]).signum() * (y[i] != y[j]) as u8 as f64;
+                difference += dx * dy;
+                x_untied += dx.abs();
+                y_untied += dy.abs();
+            }
+        }
+        difference / (x_untied * y_untied).sqrt()
+    }
+    #[test]
+    fn kendall() {
+        let x = [1., 2., 3., 4., 5.];
+        assert!((kendall_tau(&x, &[3., 4., 5., 6., 70.]) - 1.).abs() < 1e-12);
+        assert!((kendall_tau(&x, &[5., 4., 3., 2., 1.]) + 1.).abs() < 1e-12);
+        assert!(kendall_tau(&x, &[1.; 5]).is_nan());
+
+        let x = [1., 3., 2., 2., 5., 4., 4., 1., 7., 3.];
+        let y = [2., 1., 2., 4., 5., 3., 3., 2., 6., 1.];
+        assert!((kendall_tau(&x, &y) - kendall_naive(&x, &y)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,216 @@
+//! Exact statistics on decimal numbers, using [`Decimal`].
+//!
+//! Binary floats can't represent most decimal fractions, which is why `0.1 + 0.2` is
+//! `0.30000000000000004`. When summing money, that's not acceptable.
+//! [`Decimal`] is a 96-bit fixed-point decimal number, so sums are exact
+//! and means and medians are exact up to 28 significant digits.
+//!
+//! The percentile functions in [`crate::percentile`] work on [`Decimal`] too, as it implements
+//! [`Ord`] and [`PercentileResolve`](crate::percentile::PercentileResolve).
+pub use rust_decimal::Decimal;
+
+use crate::percentile::{self, OrderedListIndex};
+use crate::{PercentilesOutput, StandardDeviationOutput};
+use rust_decimal::MathematicalOps;
+
+#[cfg(not(feature = "generic-impls"))]
+impl percentile::PercentileResolve for Decimal {
+    #[inline]
+    fn mean(a: Self, b: Self) -> Self {
+        (a + b) / Decimal::TWO
+    }
+}
+
+/// Parses `s` like [`Decimal`]'s [`FromStr`](core::str::FromStr), but also accepts scientific
+/// notation, e.g. `1.5e-7`.
+///
+/// Values outside of the range of [`Decimal`] (about ±7.9e28), or with more than 28 decimals,
+/// are errors.
+pub fn parse(s: &str) -> Result<Decimal, rust_decimal::Error> {
+    if !s.contains(['e', 'E']) {
+        return s.parse();
+    }
+    Decimal::from_scientific(s).map_err(|err| match err {
+        // `from_scientific` reports a too large or small exponent as a too large scale
+        rust_decimal::Error::ScaleExceedsMaximumPrecision(_) => {
+            let (mantissa, exponent) = s.split_once(['e', 'E']).unwrap_or((s, ""));
+            if exponent.starts_with('-') {
+                rust_decimal::Error::Underflow
+            } else if mantissa.starts_with('-') {
+                rust_decimal::Error::LessThanMinimumPossibleValue
+            } else {
+                rust_decimal::Error::ExceedsMaximumPossibleValue
+            }
+        }
+        err => err,
+    })
+}
+
+/// Sum of `values`. This is exact.
+///
+/// Returns [`None`] if the sum overflows [`Decimal::MAX`].
+pub fn sum(values: &[Decimal]) -> Option<Decimal> {
+    values
+        .iter()
+        .try_fold(Decimal::ZERO, |sum, v| sum.checked_add(*v))
+}
+/// Mean of `values`.
+///
+/// Returns [`None`] if the sum overflows.
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+pub fn mean(values: &[Decimal]) -> Option<Decimal> {
+    assert!(!values.is_empty(), "can't take the mean of no values");
+    Some(sum(values)? / Decimal::from(values.len()))
+}
+/// Get the standard deviation of `values`.
+/// The mean is also returned from this, because it's required to compute the standard deviation.
+///
+/// The square root is the only inexact operation.
+///
+/// Returns [`None`] if any intermediate value overflows, e.g. when the squared deviations are
+/// larger than [`Decimal::MAX`].
+///
+/// O(n)
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+pub fn standard_deviation(values: &[Decimal]) -> Option<StandardDeviationOutput<Decimal>> {
+    let m = mean(values)?;
+    let squared_deviations = values.iter().try_fold(Decimal::ZERO, |sum, v| {
+        let diff = v.checked_sub(m)?;
+        sum.checked_add(diff.checked_mul(diff)?)
+    })?;
+    // So we don't divide by 0 if 1 value is supplied.
+    let denominator = Decimal::from(values.len() - 1).max(Decimal::ONE);
+    let variance = squared_deviations / denominator;
+    Some(StandardDeviationOutput {
+        standard_deviation: variance.sqrt().expect("variance is never negative"),
+        mean: m,
+    })
+}
+/// Get the percentile of `values` at `target`. `values` is reordered.
+///
+/// When the percentile is between two values, their mean is returned, which is exact.
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+pub fn percentile(values: &mut [Decimal], target: impl OrderedListIndex) -> Decimal {
+    percentile::percentile_default_pivot(values, target).resolve()
+}
+/// Get the median of `values`. `values` is reordered.
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+pub fn median(values: &mut [Decimal]) -> Decimal {
+    percentile(values, crate::Fraction::HALF)
+}
+
+/// Same as [`PercentilesOutput`], but for [`Decimal`]s.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DecimalPercentilesOutput {
+    pub median: Decimal,
+    pub lower_quadrille: Option<Decimal>,
+    pub higher_quadrille: Option<Decimal>,
+}
+impl From<DecimalPercentilesOutput> for PercentilesOutput {
+    fn from(output: DecimalPercentilesOutput) -> Self {
+        use rust_decimal::prelude::ToPrimitive;
+        let to_f64 = |d: Decimal| d.to_f64().expect("decimals are always in range of f64");
+        Self {
+            median: to_f64(output.median),
+            lower_quadrille: output.lower_quadrille.map(to_f64),
+            higher_quadrille: output.higher_quadrille.map(to_f64),
+        }
+    }
+}
+/// Get a collection of percentiles from `values`.
+/// Like [`crate::percentiles_cluster`], the quadrilles are only calculated if there are at least 4
+/// values.
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+pub fn percentiles(values: &mut [Decimal]) -> DecimalPercentilesOutput {
+    let (lower, higher) = if values.len() >= 4 {
+        (
+            Some(percentile(values, crate::Fraction::ONE_QUARTER)),
+            Some(percentile(values, crate::Fraction::THREE_QUARTERS)),
+        )
+    } else {
+        (None, None)
+    };
+    DecimalPercentilesOutput {
+        median: median(values),
+        lower_quadrille: lower,
+        higher_quadrille: higher,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn exact_sum() {
+        assert_eq!(sum(&[d("0.1"), d("0.2")]), Some(d("0.3")));
+        assert_eq!(mean(&[d("0.1"), d("0.2"), d("0.3")]), Some(d("0.2")));
+    }
+    #[test]
+    fn overflow() {
+        assert_eq!(sum(&[Decimal::MAX, Decimal::ONE]), None);
+        assert_eq!(mean(&[Decimal::MAX, Decimal::ONE]), None);
+        assert_eq!(
+            sum(&[Decimal::MAX, Decimal::NEGATIVE_ONE]),
+            Some(Decimal::MAX - Decimal::ONE)
+        );
+        // the squared deviations, 2 * 10³⁰, overflow
+        let values = [d("1000000000000000"), d("-1000000000000000")];
+        assert_eq!(standard_deviation(&values), None);
+        assert_eq!(standard_deviation(&[Decimal::MAX, Decimal::ONE]), None);
+    }
+    #[test]
+    fn scientific() {
+        assert_eq!(parse("1.5e-7"), Ok(d("0.00000015")));
+        assert_eq!(parse("2.5E3"), Ok(d("2500")));
+        assert_eq!(parse("-3e28"), Ok(d("-30000000000000000000000000000")));
+        assert_eq!(parse("0.1"), Ok(d("0.1")));
+        assert_eq!(
+            parse("1e30"),
+            Err(rust_decimal::Error::ExceedsMaximumPossibleValue)
+        );
+        assert_eq!(
+            parse("-1e30"),
+            Err(rust_decimal::Error::LessThanMinimumPossibleValue)
+        );
+        assert_eq!(parse("1e-40"), Err(rust_decimal::Error::Underflow));
+        assert_eq!(
+            parse("8e28"),
+            Err(rust_decimal::Error::ExceedsMaximumPossibleValue)
+        );
+        assert!(parse("1sec").is_err());
+    }
+    #[test]
+    fn exact_median() {
+        let mut values = [d("0.3"), d("0.1"), d("0.2"), d("0.4")];
+        assert_eq!(median(&mut values), d("0.25"));
+        let output = percentiles(&mut values);
+        assert_eq!(output.lower_quadrille, Some(d("0.15")));
+        assert_eq!(output.higher_quadrille, Some(d("0.35")));
+    }
+    #[test]
+    fn std_dev() {
+        let output = standard_deviation(&[d("2"), d("4"), d("4"), d("4"), d("6")]).unwrap();
+        assert_eq!(output.mean, d("4"));
+        assert_eq!(output.standard_deviation, d("2").sqrt().unwrap());
+    }
+}
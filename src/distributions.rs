@@ -0,0 +1,651 @@
+//! Probability distributions used for intervals and p-values throughout the crate.
+//!
+//! The special functions these are built on live in [`special`].
+//! They are implemented from the formulas (mostly following Numerical Recipes), so you can read
+//! how they work without digging through a math library.
+
+/// Special functions needed to evaluate the distributions.
+pub mod special {
+    /// The natural logarithm of the gamma function, `ln Γ(x)`, for `x > 0`.
+    ///
+    /// Uses the [Lanczos approximation](https://en.wikipedia.org/wiki/Lanczos_approximation)
+    /// with `g = 7`, which is accurate to ~15 significant digits.
+    pub fn ln_gamma(x: f64) -> f64 {
+        const COEFFICIENTS: [f64; 9] = [
+            0.999_999_999_999_809_9,
+            676.520_368_121_885_1,
+            -1_259.139_216_722_402_8,
+            771.323_428_777_653_1,
+            -176.615_029_162_140_6,
+            12.507_343_278_686_905,
+            -0.138_571_095_265_720_12,
+            9.984_369_578_019_572e-6,
+            1.505_632_735_149_311_6e-7,
+        ];
+        if x < 0.5 {
+            // reflection formula, Γ(x)Γ(1-x) = π / sin(πx)
+            let pi = std::f64::consts::PI;
+            return (pi / (pi * x).sin()).abs().ln() - ln_gamma(1. - x);
+        }
+        let x = x - 1.;
+        let mut sum = COEFFICIENTS[0];
+        for (i, c) in COEFFICIENTS.iter().enumerate().skip(1) {
+            sum += c / (x + i as f64);
+        }
+        let t = x + 7.5;
+        0.5 * (2. * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+    }
+
+    /// The [regularized incomplete beta function](https://en.wikipedia.org/wiki/Beta_function#Incomplete_beta_function)
+    /// `I_x(a, b)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` is outside `[0, 1]`.
+    pub fn regularized_incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+        assert!((0.0..=1.0).contains(&x), "x must be in the range [0, 1]");
+        if x == 0. || x == 1. {
+            return x;
+        }
+        let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1. - x).ln();
+        let front = ln_front.exp();
+        // The continued fraction converges rapidly for x < (a+1)/(a+b+2).
+        // Else, use the symmetry relation I_x(a, b) = 1 - I_{1-x}(b, a).
+        if x < (a + 1.) / (a + b + 2.) {
+            front * beta_continued_fraction(a, b, x) / a
+        } else {
+            1. - front * beta_continued_fraction(b, a, 1. - x) / b
+        }
+    }
+    /// Evaluates the continued fraction of the incomplete beta function using the modified
+    /// Lentz's method.
+    fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+        const MAX_ITERATIONS: usize = 300;
+        const EPSILON: f64 = 1e-15;
+        const TINY: f64 = 1e-300;
+
+        let qab = a + b;
+        let qap = a + 1.;
+        let qam = a - 1.;
+        let mut c = 1.;
+        let mut d = 1. - qab * x / qap;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        d = 1. / d;
+        let mut h = d;
+        for m in 1..=MAX_ITERATIONS {
+            let m = m as f64;
+            let m2 = 2. * m;
+            // even step
+            let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+            d = 1. + aa * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1. + aa / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1. / d;
+            h *= d * c;
+            // odd step
+            let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+            d = 1. + aa * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1. + aa / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1. / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.).abs() < EPSILON {
+                break;
+            }
+        }
+        h
+    }
+
+    /// The [regularized lower incomplete gamma function](https://en.wikipedia.org/wiki/Incomplete_gamma_function#Regularized_gamma_functions_and_Poisson_random_variables)
+    /// `P(a, x)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a <= 0` or `x < 0`.
+    pub fn regularized_gamma_p(a: f64, x: f64) -> f64 {
+        assert!(a > 0. && x >= 0., "requires a > 0 and x >= 0");
+        if x == 0. {
+            0.
+        } else if x < a + 1. {
+            gamma_series(a, x)
+        } else {
+            1. - gamma_continued_fraction(a, x)
+        }
+    }
+    /// The regularized upper incomplete gamma function `Q(a, x) = 1 - P(a, x)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a <= 0` or `x < 0`.
+    pub fn regularized_gamma_q(a: f64, x: f64) -> f64 {
+        assert!(a > 0. && x >= 0., "requires a > 0 and x >= 0");
+        if x == 0. {
+            1.
+        } else if x < a + 1. {
+            1. - gamma_series(a, x)
+        } else {
+            gamma_continued_fraction(a, x)
+        }
+    }
+    /// `P(a, x)` by its series, which converges rapidly for `x < a + 1`.
+    fn gamma_series(a: f64, x: f64) -> f64 {
+        let mut term = 1. / a;
+        let mut sum = term;
+        let mut denominator = a;
+        for _ in 0..1000 {
+            denominator += 1.;
+            term *= x / denominator;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-16 {
+                break;
+            }
+        }
+        sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+    }
+    /// `Q(a, x)` by its continued fraction (using the modified Lentz's method), which converges
+    /// rapidly for `x > a + 1`.
+    fn gamma_continued_fraction(a: f64, x: f64) -> f64 {
+        const TINY: f64 = 1e-300;
+
+        let mut b = x + 1. - a;
+        let mut c = 1. / TINY;
+        let mut d = 1. / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let i = i as f64;
+            let an = -i * (i - a);
+            b += 2.;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1. / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.).abs() < 1e-16 {
+                break;
+            }
+        }
+        (-x + a * x.ln() - ln_gamma(a)).exp() * h
+    }
+
+    /// The [error function](https://en.wikipedia.org/wiki/Error_function), `erf(x) = P(½, x²)`.
+    pub fn erf(x: f64) -> f64 {
+        let p = regularized_gamma_p(0.5, x * x);
+        if x < 0. {
+            -p
+        } else {
+            p
+        }
+    }
+    /// The complementary error function, `erfc(x) = 1 - erf(x)`.
+    ///
+    /// Use this instead of `1 - erf(x)` for large `x`, where `erf(x)` rounds to 1.
+    pub fn erfc(x: f64) -> f64 {
+        if x < 0. {
+            1. + regularized_gamma_p(0.5, x * x)
+        } else {
+            regularized_gamma_q(0.5, x * x)
+        }
+    }
+    /// The inverse of [`erf`]: returns `y` where `erf(y) = x`, for `x` in `[-1, 1]`.
+    ///
+    /// Computed from the quantile of the standard normal distribution, then refined using
+    /// Newton's method, so it's accurate even for `x` close to 0.
+    pub fn erfinv(x: f64) -> f64 {
+        if x == 0. || x.abs() == 1. {
+            return x * f64::INFINITY;
+        }
+        let sign = x.signum();
+        let x = x.abs();
+        // erf(y) = 2Φ(y√2) - 1
+        let mut y = super::standard_normal_quantile((1. + x) / 2.) / std::f64::consts::SQRT_2;
+        for _ in 0..2 {
+            // for large `x`, compute the error from `erfc` to keep its precision
+            let error = if x > 0.5 {
+                (1. - x) - erfc(y)
+            } else {
+                erf(y) - x
+            };
+            let derivative = 2. / std::f64::consts::PI.sqrt() * (-y * y).exp();
+            y -= error / derivative;
+        }
+        sign * y
+    }
+    /// The inverse of [`erfc`]: returns `y` where `erfc(y) = x`, for `x` in `[0, 2]`.
+    ///
+    /// Use this instead of `erfinv(1 - x)` for small `x`.
+    pub fn erfcinv(x: f64) -> f64 {
+        // erfc(y) = 2Φ(-y√2)
+        -super::standard_normal_quantile(x / 2.) / std::f64::consts::SQRT_2
+    }
+}
+
+/// Finds `x` where `cdf(x) = p`, given a monotonically increasing `cdf` with the derivative `pdf`.
+///
+/// First finds a bracket around the solution, then uses Newton's method, falling back to
+/// bisection when a Newton step leaves the bracket.
+fn invert_cdf(p: f64, start: f64, cdf: impl Fn(f64) -> f64, pdf: impl Fn(f64) -> f64) -> f64 {
+    let mut low = start - 1.;
+    let mut high = start + 1.;
+    while cdf(low) > p {
+        low -= (high - low) * 2.;
+    }
+    while cdf(high) < p {
+        high += (high - low) * 2.;
+    }
+    let mut x = start.clamp(low, high);
+    for _ in 0..200 {
+        let error = cdf(x) - p;
+        if error.abs() < 1e-14 {
+            break;
+        }
+        if error < 0. {
+            low = x;
+        } else {
+            high = x;
+        }
+        let newton = x - error / pdf(x);
+        x = if newton > low && newton < high && newton.is_finite() {
+            newton
+        } else {
+            (low + high) / 2.
+        };
+        if high - low < 1e-15 * x.abs().max(1.) {
+            break;
+        }
+    }
+    x
+}
+
+/// The [normal distribution](https://en.wikipedia.org/wiki/Normal_distribution).
+///
+/// Used for intervals and z-tests when the variance is known (or there are many values), and for
+/// normal Q-Q plots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Normal {
+    /// The mean, `μ`.
+    pub mean: f64,
+    /// The standard deviation, `σ`.
+    pub standard_deviation: f64,
+}
+impl Normal {
+    /// The standard normal distribution, with a mean of 0 and a standard deviation of 1.
+    pub const STANDARD: Self = Self {
+        mean: 0.,
+        standard_deviation: 1.,
+    };
+
+    /// # Panics
+    ///
+    /// Panics if `standard_deviation <= 0`.
+    pub fn new(mean: f64, standard_deviation: f64) -> Self {
+        assert!(
+            standard_deviation > 0.,
+            "the standard deviation must be positive"
+        );
+        Self {
+            mean,
+            standard_deviation,
+        }
+    }
+    /// Probability density function.
+    pub fn pdf(&self, x: f64) -> f64 {
+        let z = (x - self.mean) / self.standard_deviation;
+        (-z * z / 2.).exp() / (self.standard_deviation * (2. * std::f64::consts::PI).sqrt())
+    }
+    /// Cumulative distribution function, `P(X <= x)`.
+    ///
+    /// Accurate far into the lower tail, as it's computed from [`special::erfc`].
+    pub fn cdf(&self, x: f64) -> f64 {
+        let z = (x - self.mean) / self.standard_deviation;
+        0.5 * special::erfc(-z / std::f64::consts::SQRT_2)
+    }
+    /// The inverse of [`Self::cdf`]: returns `x` where `P(X <= x) = p`.
+    ///
+    /// Returns -∞ for `p = 0` and ∞ for `p = 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` isn't in the range `[0, 1]`.
+    pub fn quantile(&self, p: f64) -> f64 {
+        assert!((0.0..=1.0).contains(&p), "p must be in the range [0, 1]");
+        self.mean + self.standard_deviation * standard_normal_quantile(p)
+    }
+    /// The value `z` where `P(μ - zσ <= X <= μ + zσ) = confidence`, e.g. `1.96` for 95%.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `confidence` isn't in the range `(0, 1)`.
+    pub fn two_sided_critical_value(&self, confidence: f64) -> f64 {
+        assert!(
+            confidence > 0. && confidence < 1.,
+            "confidence must be in the range (0, 1)"
+        );
+        standard_normal_quantile(0.5 + confidence / 2.)
+    }
+}
+/// The inverse of the cumulative distribution function of the standard normal distribution.
+///
+/// Uses the [rational approximation of Acklam](https://web.archive.org/web/20151030215612/http://home.online.no/~pjacklam/notes/invnorm/)
+/// (accurate to ~1e-9), refined to full precision with a step of Halley's method.
+fn standard_normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: f64 = 0.024_25;
+    if p <= 0. {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1. {
+        return f64::INFINITY;
+    }
+    let polynomial =
+        |coefficients: &[f64], x: f64| coefficients.iter().fold(0., |acc, c| acc * x + c);
+    // the tails, for `q = √(-2 ln p)`
+    let tail = |q: f64| polynomial(&C, q) / (polynomial(&D, q) * q + 1.);
+    let x = if p < P_LOW {
+        tail((-2. * p.ln()).sqrt())
+    } else if p <= 1. - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        polynomial(&A, r) * q / (polynomial(&B, r) * r + 1.)
+    } else {
+        -tail((-2. * (1. - p).ln()).sqrt())
+    };
+    let error = 0.5 * special::erfc(-x / std::f64::consts::SQRT_2) - p;
+    let u = error * (2. * std::f64::consts::PI).sqrt() * (x * x / 2.).exp();
+    x - u / (1. + x * u / 2.)
+}
+
+/// [Student's t-distribution](https://en.wikipedia.org/wiki/Student%27s_t-distribution).
+///
+/// Used for intervals of estimates when the variance of the population is unknown and estimated
+/// from the data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StudentT {
+    /// The degrees of freedom, `ν`. Doesn't have to be an integer.
+    pub degrees_of_freedom: f64,
+}
+impl StudentT {
+    /// # Panics
+    ///
+    /// Panics if `degrees_of_freedom <= 0`.
+    pub fn new(degrees_of_freedom: f64) -> Self {
+        assert!(
+            degrees_of_freedom > 0.,
+            "the t-distribution requires positive degrees of freedom"
+        );
+        Self { degrees_of_freedom }
+    }
+    /// Probability density function.
+    pub fn pdf(&self, t: f64) -> f64 {
+        let v = self.degrees_of_freedom;
+        let ln = special::ln_gamma((v + 1.) / 2.)
+            - special::ln_gamma(v / 2.)
+            - 0.5 * (v * std::f64::consts::PI).ln()
+            - (v + 1.) / 2. * (1. + t * t / v).ln();
+        ln.exp()
+    }
+    /// Cumulative distribution function, `P(T <= t)`.
+    pub fn cdf(&self, t: f64) -> f64 {
+        let v = self.degrees_of_freedom;
+        let tail = 0.5 * special::regularized_incomplete_beta(v / 2., 0.5, v / (v + t * t));
+        if t > 0. {
+            1. - tail
+        } else {
+            tail
+        }
+    }
+    /// The inverse of [`Self::cdf`]. Returns `t` where `P(T <= t) = p`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` isn't in the range `(0, 1)`.
+    pub fn quantile(&self, p: f64) -> f64 {
+        assert!(p > 0. && p < 1., "p must be in the range (0, 1)");
+        if p == 0.5 {
+            return 0.;
+        }
+        invert_cdf(p, 0., |t| self.cdf(t), |t| self.pdf(t))
+    }
+    /// The value `t` where `P(-t <= T <= t) = confidence`.
+    /// Multiply this with the standard error to get the half-width of a confidence interval.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `confidence` isn't in the range `(0, 1)`.
+    pub fn two_sided_critical_value(&self, confidence: f64) -> f64 {
+        assert!(
+            confidence > 0. && confidence < 1.,
+            "confidence must be in the range (0, 1)"
+        );
+        self.quantile(0.5 + confidence / 2.)
+    }
+}
+
+/// The confidence interval `(lower, upper)` of the mean of `count` values, from their `mean` and
+/// (sample) `standard_deviation`: `mean ± t s / √n`, with `n - 1` degrees of freedom.
+///
+/// Returns [`None`] if there are fewer than 2 values.
+///
+/// # Panics
+///
+/// Panics if `confidence` isn't in the range `(0, 1)`.
+pub fn mean_interval(
+    mean: f64,
+    standard_deviation: f64,
+    count: usize,
+    confidence: f64,
+) -> Option<(f64, f64)> {
+    if count < 2 {
+        return None;
+    }
+    let t = StudentT::new((count - 1) as f64).two_sided_critical_value(confidence);
+    let half_width = t * standard_deviation / (count as f64).sqrt();
+    Some((mean - half_width, mean + half_width))
+}
+
+/// The [F-distribution](https://en.wikipedia.org/wiki/F-distribution), of the ratio of two
+/// variances.
+///
+/// Used for comparing several groups, e.g. if the slopes of several lines differ.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FisherF {
+    /// The degrees of freedom of the numerator, `d₁`.
+    pub numerator_degrees_of_freedom: f64,
+    /// The degrees of freedom of the denominator, `d₂`.
+    pub denominator_degrees_of_freedom: f64,
+}
+impl FisherF {
+    /// # Panics
+    ///
+    /// Panics if either of the degrees of freedom isn't positive.
+    pub fn new(numerator_degrees_of_freedom: f64, denominator_degrees_of_freedom: f64) -> Self {
+        assert!(
+            numerator_degrees_of_freedom > 0. && denominator_degrees_of_freedom > 0.,
+            "the F-distribution requires positive degrees of freedom"
+        );
+        Self {
+            numerator_degrees_of_freedom,
+            denominator_degrees_of_freedom,
+        }
+    }
+    /// Probability density function.
+    pub fn pdf(&self, f: f64) -> f64 {
+        if f <= 0. {
+            return 0.;
+        }
+        let (d1, d2) = (
+            self.numerator_degrees_of_freedom,
+            self.denominator_degrees_of_freedom,
+        );
+        let ln_beta = special::ln_gamma(d1 / 2.) + special::ln_gamma(d2 / 2.)
+            - special::ln_gamma((d1 + d2) / 2.);
+        let ln = 0.5 * (d1 * (d1 * f).ln() + d2 * d2.ln() - (d1 + d2) * (d1 * f + d2).ln())
+            - f.ln()
+            - ln_beta;
+        ln.exp()
+    }
+    /// Cumulative distribution function, `P(F <= f)`.
+    pub fn cdf(&self, f: f64) -> f64 {
+        if f <= 0. {
+            return 0.;
+        }
+        let (d1, d2) = (
+            self.numerator_degrees_of_freedom,
+            self.denominator_degrees_of_freedom,
+        );
+        special::regularized_incomplete_beta(d1 / 2., d2 / 2., d1 * f / (d1 * f + d2))
+    }
+    /// The probability of a value at least `f`, `P(F >= f)`: the p-value of an F-test.
+    ///
+    /// More accurate than `1 - cdf(f)` for small probabilities.
+    pub fn upper_tail(&self, f: f64) -> f64 {
+        if f <= 0. {
+            return 1.;
+        }
+        let (d1, d2) = (
+            self.numerator_degrees_of_freedom,
+            self.denominator_degrees_of_freedom,
+        );
+        special::regularized_incomplete_beta(d2 / 2., d1 / 2., d2 / (d1 * f + d2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64, tolerance: f64) {
+        assert!((a - b).abs() < tolerance, "{a} is not close to {b}");
+    }
+
+    #[test]
+    fn ln_gamma() {
+        assert_close(special::ln_gamma(5.), 24f64.ln(), 1e-12);
+        assert_close(
+            special::ln_gamma(0.5),
+            std::f64::consts::PI.sqrt().ln(),
+            1e-12,
+        );
+    }
+    #[test]
+    fn incomplete_beta() {
+        assert_close(
+            special::regularized_incomplete_beta(2., 3., 0.5),
+            0.6875,
+            1e-12,
+        );
+    }
+    #[test]
+    fn incomplete_gamma() {
+        assert_close(
+            special::regularized_gamma_p(3., 2.),
+            0.323_323_583_816_936_6,
+            1e-14,
+        );
+        assert_close(
+            special::regularized_gamma_q(3., 5.),
+            0.124_652_019_483_081_7,
+            1e-14,
+        );
+        assert_close(special::erf(1.), 0.842_700_792_949_714_9, 1e-14);
+        assert_close(special::erf(-0.5), -0.520_499_877_813_046_5, 1e-14);
+        assert_close(special::erfc(2.), 0.004_677_734_981_047_266, 1e-16);
+    }
+    #[test]
+    fn normal() {
+        assert_close(special::erfinv(0.5), 0.476_936_276_204_469_8, 1e-14);
+        assert_close(special::erfinv(-0.9), -1.163_087_153_676_674, 1e-14);
+        let tiny = special::erfinv(1e-10);
+        assert_close(tiny / 8.862_269_254_527_58e-11, 1., 1e-14);
+        assert_close(special::erfcinv(1e-20), 6.601_580_622_355_141, 1e-12);
+
+        let standard = Normal::STANDARD;
+        assert_close(standard.quantile(0.975), 1.959_963_984_540_054, 1e-14);
+        assert_close(standard.quantile(1e-12), -7.034_483_825_301_132, 1e-12);
+        assert_close(standard.cdf(-3.), 0.001_349_898_031_630_094_5, 1e-16);
+        assert_close(
+            standard.two_sided_critical_value(0.95),
+            1.959_963_984_540_054,
+            1e-14,
+        );
+        let normal = Normal::new(10., 2.);
+        assert_close(normal.pdf(11.), 0.176_032_663_382_149_76, 1e-15);
+        assert_close(normal.quantile(normal.cdf(13.)), 13., 1e-12);
+    }
+    #[test]
+    fn mean_confidence() {
+        let values: Vec<f64> = (1..=10).map(f64::from).collect();
+        let std_dev = crate::standard_deviation(&values);
+        let (low, high) =
+            mean_interval(std_dev.mean, std_dev.standard_deviation, 10, 0.95).unwrap();
+        assert_close(low, 3.334_149_410_331_83, 1e-9);
+        assert_close(high, 7.665_850_589_668_17, 1e-9);
+        assert_eq!(mean_interval(1., 0., 1, 0.95), None);
+    }
+    #[test]
+    fn student_t() {
+        // Cauchy distribution
+        assert_close(StudentT::new(1.).cdf(1.), 0.75, 1e-12);
+        assert_close(StudentT::new(10.).cdf(2.), 0.963_305_982_6, 1e-9);
+        assert_close(StudentT::new(10.).quantile(0.975), 2.228_138_851_986, 1e-9);
+        assert_close(StudentT::new(3.).quantile(0.05), -2.353_363_434_801, 1e-9);
+    }
+    #[test]
+    fn fisher_f() {
+        let f = FisherF::new(2., 10.);
+        // the critical value at 5%
+        assert_close(f.cdf(4.102_821), 0.95, 1e-6);
+        assert_close(f.upper_tail(4.102_821), 0.05, 1e-6);
+        assert_close(
+            FisherF::new(1., 1.).pdf(1.),
+            1. / (2. * std::f64::consts::PI),
+            1e-12,
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! [Differentially private](https://en.wikipedia.org/wiki/Differential_privacy) releases of
+//! summary statistics.
+//!
+//! Use this when publishing aggregates of sensitive data. Each function perturbs its result with
+//! random noise calibrated to `epsilon`, the privacy budget: lower values give more privacy
+//! and more noise. Releasing several statistics of the same data spends the sum of their
+//! epsilons, so split your budget between them.
+//!
+//! - [`count`] and [`mean`] use the [Laplace mechanism](https://en.wikipedia.org/wiki/Additive_noise_differential_privacy_mechanisms#Laplace_Mechanism).
+//! - [`quantile`] uses the [exponential mechanism](https://en.wikipedia.org/wiki/Exponential_mechanism)
+//!   as described by [Smith (2011)](https://doi.org/10.1145/1993636.1993743), which doesn't
+//!   require adding noise to the value itself.
+//!
+//! Values are clamped to a `range` supplied by you. The range must not be derived from the data,
+//! as that would leak information.
+//!
+//! The guarantees assume the count of values is public (neighbouring datasets differ by replacing
+//! one value), except for [`count`], which assumes one value is added or removed.
+//!
+//! Note that naive floating point implementations of these mechanisms
+//! [are subtly vulnerable](https://doi.org/10.1145/2382196.2382264). This module is for the
+//! common case of publishing rough aggregates, not for adversarial settings.
+use crate::{Cluster, ClusterList, Fraction, OwnedClusterList};
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+/// A differentially private value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Release<T> {
+    /// The perturbed value.
+    pub value: T,
+    /// The privacy budget spent on this value.
+    pub epsilon: f64,
+    /// The scale of the noise.
+    ///
+    /// For the Laplace mechanism, this is the scale parameter `b` of the added noise
+    /// (its standard deviation is `√2 b`).
+    /// For [`quantile`], this is in ranks: the probability of releasing a value decreases by a
+    /// factor of `e` for each `noise_scale` ranks it's away from the requested quantile.
+    pub noise_scale: f64,
+}
+
+fn assert_epsilon(epsilon: f64) {
+    assert!(
+        epsilon > 0. && epsilon.is_finite(),
+        "epsilon must be positive and finite"
+    );
+}
+fn assert_range(range: &RangeInclusive<f64>) {
+    assert!(
+        range.start() <= range.end() && range.start().is_finite() && range.end().is_finite(),
+        "range must be finite and not empty"
+    );
+}
+
+/// Samples the [Laplace distribution](https://en.wikipedia.org/wiki/Laplace_distribution)
+/// centered at 0 with `scale`.
+pub fn laplace_noise(scale: f64, rng: &mut impl Rng) -> f64 {
+    // inverse of the CDF, with u in (-0.5, 0.5)
+    let u: f64 = rng.random::<f64>() - 0.5;
+    -scale * u.signum() * (1. - 2. * u.abs()).ln()
+}
+
+/// Releases the count of values.
+///
+/// The sensitivity is 1, so the noise scale is `1 / epsilon`.
+///
+/// # Panics
+///
+/// Panics if `epsilon` isn't positive.
+pub fn count(count: usize, epsilon: f64, rng: &mut impl Rng) -> Release<f64> {
+    assert_epsilon(epsilon);
+    let noise_scale = 1. / epsilon;
+    Release {
+        value: count as f64 + laplace_noise(noise_scale, rng),
+        epsilon,
+        noise_scale,
+    }
+}
+
+/// Releases the mean of `values`, each clamped to `range`.
+///
+/// The sensitivity is `(range.end - range.start) / n`.
+/// The result isn't clamped, so it can end up outside `range`.
+///
+/// # Panics
+///
+/// Panics if `values` is empty, `epsilon` isn't positive, or `range` is empty or infinite.
+pub fn mean(
+    values: &[f64],
+    range: RangeInclusive<f64>,
+    epsilon: f64,
+    rng: &mut impl Rng,
+) -> Release<f64> {
+    assert!(!values.is_empty(), "can't release the mean of no values");
+    let sum: f64 = values
+        .iter()
+        .map(|v| v.clamp(*range.start(), *range.end()))
+        .sum();
+    mean_of_sum(sum, values.len(), range, epsilon, rng)
+}
+/// Same as [`mean`] but for [`ClusterList`]s.
+pub fn mean_cluster(
+    values: &ClusterList,
+    range: RangeInclusive<f64>,
+    epsilon: f64,
+    rng: &mut impl Rng,
+) -> Release<f64> {
+    assert!(!values.is_empty(), "can't release the mean of no values");
+    let sum: f64 = values
+        .list
+        .iter()
+        .map(|(v, count)| v.clamp(*range.start(), *range.end()) * *count as f64)
+        .sum();
+    mean_of_sum(sum, values.len(), range, epsilon, rng)
+}
+fn mean_of_sum(
+    sum: f64,
+    len: usize,
+    range: RangeInclusive<f64>,
+    epsilon: f64,
+    rng: &mut impl Rng,
+) -> Release<f64> {
+    assert_epsilon(epsilon);
+    assert_range(&range);
+    let sensitivity = (range.end() - range.start()) / len as f64;
+    let noise_scale = sensitivity / epsilon;
+    Release {
+        value: sum / len as f64 + laplace_noise(noise_scale, rng),
+        epsilon,
+        noise_scale,
+    }
+}
+
+/// Releases the `quantile` of `values`, each clamped to `range`, using the exponential mechanism.
+///
+/// The released value is always in `range`.
+///
+/// `O(n log n)`, as the values are sorted.
+///
+/// # Panics
+///
+/// Panics if `epsilon` isn't positive or `range` is empty or infinite.
+pub fn quantile(
+    values: &[f64],
+    quantile: Fraction,
+    range: RangeInclusive<f64>,
+    epsilon: f64,
+    rng: &mut impl Rng,
+) -> Release<f64> {
+    let clusters = values.iter().map(|v| (*v, 1)).collect();
+    quantile_cluster(
+        &mut OwnedClusterList::new(clusters),
+        quantile,
+        range,
+        epsilon,
+        rng,
+    )
+}
+/// Same as [`quantile`] but for [`ClusterList`]s.
+///
+/// `values` is sorted in place.
+///
+/// `O(m log m)`, where `m` is the count of clusters.
+pub fn quantile_cluster(
+    values: &mut OwnedClusterList,
+    quantile: Fraction,
+    range: RangeInclusive<f64>,
+    epsilon: f64,
+    rng: &mut impl Rng,
+) -> Release<f64> {
+    assert_epsilon(epsilon);
+    assert_range(&range);
+    let (low, high) = (*range.start(), *range.end());
+    values.sort_unstable_by(|a, b| crate::F64OrdHash::f64_cmp(a.0, b.0));
+
+    let target =
+        values.borrow().len() as f64 * quantile.numerator as f64 / quantile.denominator as f64;
+    // Each gap between two consecutive values is an interval where all points have the same rank
+    // (the count of values before it). Its utility is how far that rank is from the target.
+    // Work in log space, as the weights easily underflow.
+    let mut intervals: Vec<(f64, f64, f64)> = Vec::with_capacity(values.len() + 1);
+    let mut rank = 0;
+    let mut start = low;
+    let clamped = values
+        .iter()
+        .map(|(v, count): &Cluster| (v.clamp(low, high), *count))
+        .chain(std::iter::once((high, 0)));
+    for (value, count) in clamped {
+        if value > start {
+            let utility = -(rank as f64 - target).abs();
+            let log_weight = (value - start).ln() + epsilon * utility / 2.;
+            intervals.push((start, value, log_weight));
+        }
+        start = value;
+        rank += count;
+    }
+
+    let noise_scale = 2. / epsilon;
+    if intervals.is_empty() {
+        // the range is a single point
+        return Release {
+            value: low,
+            epsilon,
+            noise_scale,
+        };
+    }
+    let max = intervals
+        .iter()
+        .map(|i| i.2)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let total: f64 = intervals.iter().map(|i| (i.2 - max).exp()).sum();
+    let mut choice = rng.random::<f64>() * total;
+    let mut chosen = intervals[intervals.len() - 1];
+    for interval in &intervals {
+        let weight = (interval.2 - max).exp();
+        if choice < weight {
+            chosen = *interval;
+            break;
+        }
+        choice -= weight;
+    }
+    Release {
+        value: rng.random_range(chosen.0..=chosen.1),
+        epsilon,
+        noise_scale,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn laplace_scale() {
+        let mut rng = rand::rng();
+        let n = 20_000;
+        let mean_abs: f64 = (0..n)
+            .map(|_| laplace_noise(3., &mut rng).abs())
+            .sum::<f64>()
+            / n as f64;
+        // E[|X|] = b
+        assert!((mean_abs - 3.).abs() < 0.2);
+    }
+    #[test]
+    fn mean_release() {
+        let values: Vec<f64> = (0..1000).map(|v| (v % 10) as f64).collect();
+        let release = mean(&values, 0.0..=10., 1., &mut rand::rng());
+        assert!((release.noise_scale - 0.01).abs() < 1e-12);
+        assert!((release.value - 4.5).abs() < 0.5);
+    }
+    #[test]
+    fn quantile_release() {
+        let values: Vec<f64> = (0..1001).map(|v| v as f64).collect();
+        let release = quantile(&values, Fraction::HALF, 0.0..=2000., 1., &mut rand::rng());
+        assert!((release.value - 500.).abs() < 50.);
+        let release = quantile(&values, Fraction::HALF, 0.0..=0., 1., &mut rand::rng());
+        assert_eq!(release.value, 0.);
+    }
+}
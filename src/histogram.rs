@@ -0,0 +1,193 @@
+//! Histograms: counts of values in bins.
+//!
+//! Choose how the bins are created with [`Bins`]. If you don't know how many bins you need,
+//! [`Bins::FreedmanDiaconis`] is a good default, and [`Bins::Sturges`] works well for small,
+//! normally distributed data sets.
+//!
+//! See also [`crate::buckets`], for when you only have the counts of already binned values.
+use crate::{ClusterList, OwnedClusterList};
+
+/// How to create the bins of a [`Histogram`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bins {
+    /// This count of bins of equal width, between the smallest and largest value.
+    Count(usize),
+    /// Bins of this width, starting at the smallest value.
+    Width(f64),
+    /// Custom edges, which must be sorted. `n + 1` edges create `n` bins.
+    /// Values outside the edges aren't counted.
+    Edges(Vec<f64>),
+    /// [Sturges' rule](https://en.wikipedia.org/wiki/Histogram#Sturges's_formula):
+    /// `⌈log₂ n⌉ + 1` bins. Assumes the values are approximately normally distributed.
+    Sturges,
+    /// The [Freedman–Diaconis rule](https://en.wikipedia.org/wiki/Freedman%E2%80%93Diaconis_rule):
+    /// bins of width `2 IQR / ∛n`. Robust to outliers and skewed data.
+    ///
+    /// Falls back to [`Self::Sturges`] if there are less than 4 values or the interquartile
+    /// range is 0.
+    FreedmanDiaconis,
+}
+
+/// Bins and the count of values in each.
+///
+/// All bins are half-open (`[lower, upper)`), except the last which includes its upper edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    edges: Vec<f64>,
+    counts: Vec<usize>,
+}
+impl Histogram {
+    /// Creates a histogram of `values`.
+    ///
+    /// O(n log b), where b is the count of bins.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty, or `bins` is [`Bins::Count(0)`](Bins::Count), a
+    /// [`Bins::Width`] which isn't positive, or [`Bins::Edges`] with less than 2 edges or unsorted
+    /// edges.
+    pub fn new(values: &[f64], bins: Bins) -> Self {
+        let list = values.iter().map(|v| (*v, 1)).collect();
+        Self::from_clusters(&OwnedClusterList::new(list).borrow(), bins)
+    }
+    /// Same as [`Self::new`], but for clustered values.
+    ///
+    /// O(m log b), where m is the count of clusters and b the count of bins.
+    pub fn from_clusters(values: &ClusterList, bins: Bins) -> Self {
+        assert!(!values.is_empty(), "can't create a histogram of no values");
+        let (min, max) = values
+            .list
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (v, _)| {
+                (min.min(*v), max.max(*v))
+            });
+        let edges = match bins {
+            Bins::Count(count) => equal_width(min, max, count),
+            Bins::Width(width) => {
+                assert!(width > 0., "the width of bins must be positive");
+                let count = ((max - min) / width).ceil().max(1.) as usize;
+                (0..=count).map(|i| min + width * i as f64).collect()
+            }
+            Bins::Edges(edges) => {
+                assert!(edges.len() >= 2, "at least 2 edges are required");
+                assert!(
+                    edges.windows(2).all(|w| w[0] < w[1]),
+                    "the edges must be sorted"
+                );
+                edges
+            }
+            Bins::Sturges => equal_width(min, max, sturges(values.len())),
+            Bins::FreedmanDiaconis => {
+                let mut owned = OwnedClusterList::new(values.list.to_vec());
+                let percentiles = crate::percentiles_cluster(&mut owned);
+                let iqr = percentiles
+                    .higher_quadrille
+                    .zip(percentiles.lower_quadrille)
+                    .map(|(high, low)| high - low);
+                match iqr {
+                    Some(iqr) if iqr > 0. && max > min => {
+                        let width = freedman_diaconis_width(iqr, values.len());
+                        equal_width(min, max, ((max - min) / width).ceil().max(1.) as usize)
+                    }
+                    _ => equal_width(min, max, sturges(values.len())),
+                }
+            }
+        };
+
+        let bins = edges.len() - 1;
+        let mut counts = vec![0; bins];
+        let last = edges[edges.len() - 1];
+        for (v, count) in values.list {
+            if *v < edges[0] || *v > last || v.is_nan() {
+                continue;
+            }
+            // the count of edges <= v
+            let idx = edges.partition_point(|edge| edge <= v);
+            counts[(idx - 1).min(bins - 1)] += count;
+        }
+        Self { edges, counts }
+    }
+    /// The edges of the bins. Bin `i` is between `edges[i]` and `edges[i + 1]`.
+    pub fn edges(&self) -> &[f64] {
+        &self.edges
+    }
+    /// The count of values in each bin.
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+    /// The count of bins.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+    /// If there are no bins. This is never the case for histograms created by this module.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+    /// The count of all values in the bins.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+    /// Iterates the lower and upper edge, and the count of each bin.
+    pub fn iter(&self) -> impl Iterator<Item = (f64, f64, usize)> + '_ {
+        self.edges
+            .windows(2)
+            .zip(&self.counts)
+            .map(|(edges, count)| (edges[0], edges[1], *count))
+    }
+}
+
+fn equal_width(min: f64, max: f64, count: usize) -> Vec<f64> {
+    assert!(count > 0, "a histogram must have at least 1 bin");
+    if min == max {
+        return vec![min, max];
+    }
+    let width = (max - min) / count as f64;
+    let mut edges: Vec<f64> = (0..count).map(|i| min + width * i as f64).collect();
+    // avoid rounding errors excluding the largest value
+    edges.push(max);
+    edges
+}
+/// The count of bins according to [Sturges' rule](Bins::Sturges) for `n` values.
+pub fn sturges(n: usize) -> usize {
+    (n.max(1) as f64).log2().ceil() as usize + 1
+}
+/// The width of bins according to the [Freedman–Diaconis rule](Bins::FreedmanDiaconis), given
+/// the interquartile range `iqr` of `n` values.
+pub fn freedman_diaconis_width(iqr: f64, n: usize) -> f64 {
+    2. * iqr / (n as f64).cbrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count() {
+        let h = Histogram::new(
+            &[0., 1., 2., 3., 4., 5., 6., 7., 8., 9., 10.],
+            Bins::Count(5),
+        );
+        assert_eq!(h.edges(), [0., 2., 4., 6., 8., 10.]);
+        // the last bin includes 10
+        assert_eq!(h.counts(), [2, 2, 2, 2, 3]);
+        let h = Histogram::new(&[1., 1., 1.], Bins::Count(5));
+        assert_eq!(h.counts(), [3]);
+    }
+    #[test]
+    fn edges() {
+        let values = [(0.5, 3), (1.5, 2), (2., 1), (5., 10)];
+        let h = Histogram::from_clusters(&ClusterList::new(&values), Bins::Edges(vec![0., 1., 3.]));
+        assert_eq!(h.counts(), [3, 3]);
+        assert_eq!(h.total(), 6);
+    }
+    #[test]
+    fn automatic() {
+        assert_eq!(sturges(100), 8);
+        let values: Vec<f64> = (0..1000).map(|v| v as f64).collect();
+        let h = Histogram::new(&values, Bins::FreedmanDiaconis);
+        // IQR ≈ 500, so the width is 2 * 500 / 10 = 100
+        assert_eq!(h.len(), 10);
+        assert_eq!(h.total(), 1000);
+        assert_eq!(Histogram::new(&values, Bins::Sturges).len(), 11);
+    }
+}
@@ -0,0 +1,385 @@
+//! Parsing of comma/space separated values, which keeps going on malformed input.
+//!
+//! Instead of failing on the first bad value, the functions here return a [`Parsed`] with the
+//! values which could be parsed and a [`ParseError`] for each which couldn't. This lets e.g. an
+//! editor show the statistics of the good rows while highlighting the bad ones.
+//!
+//! [`csv_records`] instead reads proper CSV, with quoted fields, and [`F64Le`] reads a binary
+//! stream of `f64`s, which skips the cost of parsing text for large datasets.
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::io::{self, BufReader, Read};
+use std::num::ParseFloatError;
+
+/// Why a token couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorReason {
+    /// The token isn't a number.
+    InvalidNumber(ParseFloatError),
+    /// The line doesn't have the requested column.
+    MissingColumn,
+}
+impl Display for ParseErrorReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNumber(err) => write!(f, "invalid number: {err}"),
+            Self::MissingColumn => f.write_str("missing column"),
+        }
+    }
+}
+
+/// A value which couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line of the value, starting at 1.
+    pub line: usize,
+    /// The column of the value in the line, starting at 1.
+    pub column: usize,
+    /// The text of the value. Empty if the column is missing.
+    pub token: String,
+    pub reason: ParseErrorReason,
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        if !self.token.is_empty() {
+            write!(f, "{:?}: ", self.token)?;
+        }
+        write!(f, "{}", self.reason)
+    }
+}
+impl std::error::Error for ParseError {}
+
+/// The values which could be parsed, and the errors of those which couldn't.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parsed<T> {
+    pub values: Vec<T>,
+    /// Sorted by the line and column.
+    pub errors: Vec<ParseError>,
+}
+impl<T> Parsed<T> {
+    /// If all values were parsed.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// Returns the values if all were parsed, else the first error.
+    pub fn into_result(self) -> Result<Vec<T>, ParseError> {
+        match self.errors.into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(self.values),
+        }
+    }
+}
+
+/// The values of `line`, separated by commas and/or whitespace.
+fn tokens(line: &str) -> impl Iterator<Item = &str> {
+    line.split(',').flat_map(|s| s.split_whitespace())
+}
+
+/// Parses all the values of `content`, or only those in `column` (starting at 0) of each line.
+///
+/// Empty lines are skipped. When a `column` is given, lines without it are reported as
+/// [`ParseErrorReason::MissingColumn`].
+pub fn parse_values(content: &str, column: Option<usize>) -> Parsed<f64> {
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut push = |column: usize, token: &str| match token.parse() {
+            Ok(v) => values.push(v),
+            Err(err) => errors.push(ParseError {
+                line: line_idx + 1,
+                column: column + 1,
+                token: token.to_owned(),
+                reason: ParseErrorReason::InvalidNumber(err),
+            }),
+        };
+        if let Some(column) = column {
+            match tokens(line).nth(column) {
+                Some(token) => push(column, token),
+                None => errors.push(ParseError {
+                    line: line_idx + 1,
+                    column: column + 1,
+                    token: String::new(),
+                    reason: ParseErrorReason::MissingColumn,
+                }),
+            }
+        } else {
+            for (column, token) in tokens(line).enumerate() {
+                push(column, token);
+            }
+        }
+    }
+    Parsed { values, errors }
+}
+/// Parses each line of `content` as a row of values, e.g. the pairs of predictors and outcomes of
+/// a regression.
+///
+/// Empty lines are skipped. Rows with any invalid value are left out, so the values of the
+/// returned rows stay aligned.
+pub fn parse_rows(content: &str) -> Parsed<Vec<f64>> {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut row = Vec::with_capacity(2);
+        let mut valid = true;
+        for (column, token) in tokens(line).enumerate() {
+            match token.parse() {
+                Ok(v) => row.push(v),
+                Err(err) => {
+                    valid = false;
+                    errors.push(ParseError {
+                        line: line_idx + 1,
+                        column: column + 1,
+                        token: token.to_owned(),
+                        reason: ParseErrorReason::InvalidNumber(err),
+                    });
+                }
+            }
+        }
+        if valid {
+            rows.push(row);
+        }
+    }
+    Parsed {
+        values: rows,
+        errors,
+    }
+}
+
+/// Whether `c` separates groups of digits, like the no-break and thin spaces in `1 000`.
+///
+/// These are whitespace, but not separators of values.
+pub fn is_digit_group_separator(c: char) -> bool {
+    matches!(c, '_' | '\u{a0}' | '\u{2009}' | '\u{202f}')
+}
+/// Whether the byte at `index` of `s` is a plain space separating groups of digits, like in
+/// `1 000,5`: it's preceded by a digit, and followed by exactly three digits.
+///
+/// Plain spaces usually separate values, so these are only separators of digit groups in numbers
+/// with a decimal comma.
+pub fn is_digit_group_space(s: &str, index: usize) -> bool {
+    let (before, after) = s.as_bytes().split_at(index);
+    let Some((b' ', after)) = after.split_first() else {
+        return false;
+    };
+    before.last().is_some_and(u8::is_ascii_digit)
+        && after.len() >= 3
+        && after[..3].iter().all(u8::is_ascii_digit)
+        && !after.get(3).is_some_and(u8::is_ascii_digit)
+}
+/// Removes separators of digit groups (see [`is_digit_group_separator`]) from `token`, so it can
+/// be parsed.
+///
+/// With `decimal_comma`, the token is formatted like `1.000,5` or `1 000,5`: periods and plain
+/// spaces (see [`is_digit_group_space`]) separate digit groups, and the comma is the decimal
+/// separator.
+pub fn normalize_number(token: &str, decimal_comma: bool) -> Cow<'_, str> {
+    let changed =
+        |c: char| is_digit_group_separator(c) || (decimal_comma && matches!(c, '.' | ',' | ' '));
+    if !token.contains(changed) {
+        return Cow::Borrowed(token);
+    }
+    let normalized = token
+        .char_indices()
+        .filter(|(i, c)| {
+            !(is_digit_group_separator(*c)
+                || (decimal_comma && (*c == '.' || is_digit_group_space(token, *i))))
+        })
+        .map(|(_, c)| if decimal_comma && c == ',' { '.' } else { c })
+        .collect();
+    Cow::Owned(normalized)
+}
+
+/// Splits CSV `content` into the fields of each record, following RFC 4180.
+///
+/// Fields are separated by commas and records by newlines (`\n` or `\r\n`). A field in double
+/// quotes can contain commas, newlines, and quotes, which are escaped by doubling them (`""`).
+/// Whitespace around fields is kept. Empty lines are skipped.
+pub fn csv_records(content: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    let mut end_record = |record: &mut Vec<String>, field: &mut String| {
+        record.push(std::mem::take(field));
+        if record.len() > 1 || !record[0].is_empty() {
+            records.push(std::mem::take(record));
+        } else {
+            record.clear();
+        }
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.next_if_eq(&'"').is_some() {
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            _ if quoted => field.push(c),
+            '"' if field.is_empty() => quoted = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => end_record(&mut record, &mut field),
+            _ => field.push(c),
+        }
+    }
+    if !record.is_empty() || !field.is_empty() {
+        end_record(&mut record, &mut field);
+    }
+    records
+}
+
+/// Reads raw little-endian `f64`s (8 bytes each, without any header) from a reader.
+///
+/// The reader is buffered. A stream which doesn't end on a whole value yields an
+/// [`io::ErrorKind::UnexpectedEof`] error for the trailing bytes. After an error, the iterator
+/// ends.
+#[derive(Debug)]
+pub struct F64Le<R> {
+    reader: BufReader<R>,
+    done: bool,
+}
+impl<R: Read> F64Le<R> {
+    /// Reads the values of `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::with_capacity(1 << 16, reader),
+            done: false,
+        }
+    }
+}
+impl<R: Read> Iterator for F64Le<R> {
+    type Item = io::Result<f64>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut bytes = [0; 8];
+        let mut filled = 0;
+        while filled < bytes.len() {
+            match self.reader.read(&mut bytes[filled..]) {
+                Ok(0) => {
+                    self.done = true;
+                    return (filled > 0).then(|| {
+                        Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            format!("{filled} trailing bytes, the length isn't a multiple of 8"),
+                        ))
+                    });
+                }
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        Some(Ok(f64::from_le_bytes(bytes)))
+    }
+}
+/// Reads all the values of `reader`. See [`F64Le`].
+pub fn read_f64le(reader: impl Read) -> io::Result<Vec<f64>> {
+    F64Le::new(reader).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        let parsed = parse_values("time,size\n1.5, 2\n\n3 abc 4e1\n", None);
+        assert_eq!(parsed.values, [1.5, 2., 3., 40.]);
+        let positions: Vec<_> = parsed
+            .errors
+            .iter()
+            .map(|err| (err.line, err.column, err.token.as_str()))
+            .collect();
+        assert_eq!(positions, [(1, 1, "time"), (1, 2, "size"), (4, 2, "abc")]);
+        assert!(parsed.clone().into_result().is_err());
+
+        let parsed = parse_values("1 2\n3\n5 x", Some(1));
+        assert_eq!(parsed.values, [2.]);
+        assert_eq!(parsed.errors[0].reason, ParseErrorReason::MissingColumn);
+        assert_eq!(
+            parsed.errors[1].to_string(),
+            "line 3, column 2: \"x\": invalid number: invalid float literal"
+        );
+    }
+    #[test]
+    fn rows() {
+        let parsed = parse_rows("1 2\n3 ?\n4,5");
+        assert_eq!(parsed.values, [vec![1., 2.], vec![4., 5.]]);
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].line, 2);
+        assert!(parse_rows("1 2").is_complete());
+    }
+    #[test]
+    fn csv() {
+        let records = csv_records(
+            "name,\"value, in ms\"\r\n\"a \"\"b\"\"\",1.5\n\n\"multi\nline\",,3\nlast,2",
+        );
+        assert_eq!(
+            records,
+            [
+                vec!["name", "value, in ms"],
+                vec!["a \"b\"", "1.5"],
+                vec!["multi\nline", "", "3"],
+                vec!["last", "2"],
+            ]
+        );
+        assert!(csv_records("\n\n").is_empty());
+    }
+    #[test]
+    fn numbers() {
+        assert_eq!(normalize_number("3.14", false), "3.14");
+        assert_eq!(normalize_number("1_000.5", false), "1000.5");
+        assert_eq!(normalize_number("3,14", true), "3.14");
+        assert_eq!(normalize_number("1.000,5", true), "1000.5");
+        assert_eq!(normalize_number("1\u{a0}000,5", true), "1000.5");
+        assert_eq!(normalize_number("-2e3", true), "-2e3");
+        assert_eq!(normalize_number("1 000,5", true), "1000.5");
+        assert_eq!(normalize_number("-12 345 678,9", true), "-12345678.9");
+        assert_eq!(normalize_number("1 000", false), "1 000");
+        assert_eq!(normalize_number("1 00,5", true), "1 00.5");
+    }
+    #[test]
+    fn digit_group_space() {
+        let s = "1 000,5 2 3 4000 12 345";
+        let spaces: Vec<_> = s
+            .char_indices()
+            .filter(|(i, _)| is_digit_group_space(s, *i))
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(spaces, [1, 19]);
+        assert!(!is_digit_group_space("1 000", 0));
+        assert!(!is_digit_group_space("1 00", 1));
+        assert!(!is_digit_group_space("a 000", 1));
+        assert!(!is_digit_group_space("1 000", 5));
+    }
+    #[test]
+    fn f64le() {
+        let bytes: Vec<u8> = [1.5_f64, -2., 1e300]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert_eq!(read_f64le(&bytes[..]).unwrap(), [1.5, -2., 1e300]);
+        assert!(read_f64le(&[][..]).unwrap().is_empty());
+        let err = read_f64le(&bytes[..12]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let mut values = F64Le::new(&bytes[..12]);
+        assert_eq!(values.next().unwrap().unwrap(), 1.5);
+        assert!(values.next().unwrap().is_err());
+        assert!(values.next().is_none());
+    }
+}
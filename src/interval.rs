@@ -0,0 +1,306 @@
+//! Statistics with rigorous bounds on the rounding errors of [`f64`] arithmetic.
+//!
+//! Every operation on an [`Interval`] rounds its lower bound down and its upper bound up, so the
+//! exact result (computed with real numbers from the same inputs) is always contained in the
+//! returned interval. Use this when results have to be reported with guaranteed enclosures, e.g.
+//! in verification.
+//!
+//! The bounds are widened by one ULP per operation, as the hardware rounding mode can't be
+//! changed from Rust. The intervals are therefore somewhat wider than with directed rounding,
+//! but still rigorous, since IEEE 754 operations are correctly rounded.
+//!
+//! Enabled by the `interval` feature.
+use core::fmt::{self, Display};
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::math;
+
+/// The next representable value towards positive infinity.
+fn next_up(v: f64) -> f64 {
+    if v.is_nan() || v == f64::INFINITY {
+        return v;
+    }
+    if v == 0. {
+        return f64::from_bits(1);
+    }
+    let bits = v.to_bits();
+    f64::from_bits(if v > 0. { bits + 1 } else { bits - 1 })
+}
+/// The next representable value towards negative infinity.
+fn next_down(v: f64) -> f64 {
+    -next_up(-v)
+}
+
+/// A closed interval of real numbers, `[lower, upper]`.
+///
+/// The arithmetic operators return intervals containing all results of the operation on any
+/// values in the operands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub lower: f64,
+    pub upper: f64,
+}
+impl Interval {
+    /// The interval `[-∞, ∞]`, returned when a result can't be bounded (e.g. when dividing by an
+    /// interval containing 0).
+    pub const ENTIRE: Self = Self {
+        lower: f64::NEG_INFINITY,
+        upper: f64::INFINITY,
+    };
+
+    /// # Panics
+    ///
+    /// Panics if `lower > upper` or either is `NaN`.
+    pub fn new(lower: f64, upper: f64) -> Self {
+        assert!(lower <= upper, "the lower bound must not exceed the upper");
+        Self { lower, upper }
+    }
+    /// The interval containing only `value`.
+    pub fn point(value: f64) -> Self {
+        Self {
+            lower: value,
+            upper: value,
+        }
+    }
+    /// Rounds the bounds of an interval computed with the default rounding outwards.
+    fn outward(lower: f64, upper: f64) -> Self {
+        Self {
+            lower: next_down(lower),
+            upper: next_up(upper),
+        }
+    }
+    /// The difference between the bounds, rounded up.
+    pub fn width(&self) -> f64 {
+        next_up(self.upper - self.lower)
+    }
+    /// The value in the middle of the interval.
+    pub fn midpoint(&self) -> f64 {
+        if self.lower == f64::NEG_INFINITY || self.upper == f64::INFINITY {
+            return if self.lower == -self.upper {
+                0.
+            } else {
+                self.lower + self.upper
+            };
+        }
+        self.lower / 2. + self.upper / 2.
+    }
+    /// Whether `value` lies in the interval.
+    pub fn contains(&self, value: f64) -> bool {
+        self.lower <= value && value <= self.upper
+    }
+    /// The interval of the squares of the values in `self`. Narrower than `self * self` when
+    /// `self` contains 0.
+    pub fn square(self) -> Self {
+        let lower = if self.contains(0.) {
+            0.
+        } else {
+            let min = math::fabs(self.lower).min(math::fabs(self.upper));
+            next_down(min * min).max(0.)
+        };
+        let max = math::fabs(self.lower).max(math::fabs(self.upper));
+        Self {
+            lower,
+            upper: next_up(max * max),
+        }
+    }
+    /// The interval of the square roots of the non-negative values in `self`.
+    ///
+    /// Returns `NaN` bounds if `self` is entirely negative.
+    pub fn sqrt(self) -> Self {
+        if self.upper < 0. {
+            return Self {
+                lower: f64::NAN,
+                upper: f64::NAN,
+            };
+        }
+        Self {
+            lower: next_down(math::sqrt(self.lower.max(0.))).max(0.),
+            upper: next_up(math::sqrt(self.upper)),
+        }
+    }
+}
+impl From<f64> for Interval {
+    fn from(value: f64) -> Self {
+        Self::point(value)
+    }
+}
+impl Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "[{:.2$}, {:.2$}]", self.lower, self.upper, precision),
+            None => write!(f, "[{}, {}]", self.lower, self.upper),
+        }
+    }
+}
+impl Neg for Interval {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            lower: -self.upper,
+            upper: -self.lower,
+        }
+    }
+}
+impl Add for Interval {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::outward(self.lower + rhs.lower, self.upper + rhs.upper)
+    }
+}
+impl Sub for Interval {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::outward(self.lower - rhs.upper, self.upper - rhs.lower)
+    }
+}
+impl Mul for Interval {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        let products = [
+            self.lower * rhs.lower,
+            self.lower * rhs.upper,
+            self.upper * rhs.lower,
+            self.upper * rhs.upper,
+        ];
+        let lower = products.iter().copied().fold(f64::INFINITY, f64::min);
+        let upper = products.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Self::outward(lower, upper)
+    }
+}
+impl Div for Interval {
+    type Output = Self;
+    /// Returns [`Interval::ENTIRE`] if `rhs` contains 0.
+    fn div(self, rhs: Self) -> Self {
+        if rhs.contains(0.) {
+            return Self::ENTIRE;
+        }
+        let quotients = [
+            self.lower / rhs.lower,
+            self.lower / rhs.upper,
+            self.upper / rhs.lower,
+            self.upper / rhs.upper,
+        ];
+        let lower = quotients.iter().copied().fold(f64::INFINITY, f64::min);
+        let upper = quotients.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Self::outward(lower, upper)
+    }
+}
+
+/// The sum of `values`.
+pub fn sum(values: &[f64]) -> Interval {
+    values
+        .iter()
+        .fold(Interval::point(0.), |acc, v| acc + Interval::point(*v))
+}
+/// The mean of `values`. `NaN` bounds if `values` is empty.
+pub fn mean(values: &[f64]) -> Interval {
+    if values.is_empty() {
+        return Interval {
+            lower: f64::NAN,
+            upper: f64::NAN,
+        };
+    }
+    sum(values) / Interval::point(values.len() as f64)
+}
+/// The sample variance of `values`, with the same denominator as
+/// [`standard_deviation`](crate::standard_deviation) (`n - 1`, but at least 1).
+pub fn variance(values: &[f64]) -> Interval {
+    let mean = mean(values);
+    let squared_deviations = values.iter().fold(Interval::point(0.), |acc, v| {
+        acc + (Interval::point(*v) - mean).square()
+    });
+    let denominator = (values.len().max(2) - 1) as f64;
+    squared_deviations / Interval::point(denominator)
+}
+/// The sample standard deviation of `values`. See [`variance`].
+pub fn standard_deviation(values: &[f64]) -> Interval {
+    variance(values).sqrt()
+}
+
+/// The coefficients of a line fitted with least squares, with bounds on each.
+///
+/// See [`linear_fit`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntervalLinearCoefficients {
+    /// slope, x coefficient
+    pub k: Interval,
+    /// y intersect, additive
+    pub m: Interval,
+}
+impl IntervalLinearCoefficients {
+    /// The line through the midpoints of the coefficients.
+    #[cfg(feature = "regression")]
+    pub fn midpoint(&self) -> crate::regression::LinearCoefficients {
+        crate::regression::LinearCoefficients {
+            k: self.k.midpoint(),
+            m: self.m.midpoint(),
+        }
+    }
+}
+impl Display for IntervalLinearCoefficients {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "{:.2$}x + {:.2$}", self.k, self.m, precision),
+            None => write!(f, "{}x + {}", self.k, self.m),
+        }
+    }
+}
+
+/// Fits a line to the points (`predictors`, `outcomes`) using ordinary least squares, bounding
+/// the rounding errors of the slope and intercept.
+///
+/// The slope is [`Interval::ENTIRE`] if all predictors could be equal.
+///
+/// # Panics
+///
+/// Panics if `predictors` and `outcomes` have different lengths.
+pub fn linear_fit(predictors: &[f64], outcomes: &[f64]) -> IntervalLinearCoefficients {
+    assert_eq!(
+        predictors.len(),
+        outcomes.len(),
+        "predictors and outcomes must have the same length"
+    );
+    let x_mean = mean(predictors);
+    let y_mean = mean(outcomes);
+    let mut sxx = Interval::point(0.);
+    let mut sxy = Interval::point(0.);
+    for (x, y) in predictors.iter().zip(outcomes) {
+        let dx = Interval::point(*x) - x_mean;
+        sxx = sxx + dx.square();
+        sxy = sxy + dx * (Interval::point(*y) - y_mean);
+    }
+    let k = sxy / sxx;
+    let m = y_mean - k * x_mean;
+    IntervalLinearCoefficients { k, m }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enclosures() {
+        let values = [0.1, 0.2, 0.3, 1e16, -1e16];
+        let sum = sum(&values);
+        // `0.6000000000000001` in `f64`
+        assert!(sum.contains(0.6));
+        assert!(sum.lower < sum.upper);
+
+        let values = [2., 4., 4., 4., 5., 5., 7., 9.];
+        let output = crate::standard_deviation(&values);
+        assert!(mean(&values).contains(output.mean));
+        assert!(standard_deviation(&values).contains(output.standard_deviation));
+        assert!(variance(&values).width() < 1e-12);
+
+        let x = [1., 2., 3., 4.];
+        let y = [3.1, 5.1, 7.1, 9.1];
+        let line = linear_fit(&x, &y);
+        assert!(line.k.contains(2.) && line.m.contains(1.1));
+        assert!(line.k.width() < 1e-12);
+        assert_eq!(linear_fit(&[1., 1.], &[1., 2.]).k, Interval::ENTIRE);
+        assert_eq!(
+            (Interval::new(-1., 2.)).square(),
+            Interval::new(0., next_up(4.))
+        );
+    }
+}
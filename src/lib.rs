@@ -1,13 +1,64 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
+#![cfg_attr(not(feature = "std"), no_std)]
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("without the `std` feature, the `libm` feature is required for the math functions");
+
+extern crate alloc;
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+use core::time::Duration;
+use core::{hash, ops};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
 use std::collections::HashMap;
-use std::ops::{Deref, DerefMut};
-use std::{hash, ops};
 
 #[cfg(feature = "regression")]
 #[path = "regression.rs"]
 pub mod regression;
 
+#[cfg(feature = "bootstrap")]
+pub mod bootstrap;
+#[cfg(feature = "std")]
+pub mod buckets;
+#[cfg(feature = "std")]
+pub mod correlation;
+#[cfg(feature = "decimal")]
+pub mod decimal;
+#[cfg(feature = "std")]
+pub mod distributions;
+#[cfg(feature = "dp")]
+pub mod dp;
+#[cfg(feature = "std")]
+pub mod histogram;
+#[cfg(feature = "std")]
+pub mod input;
+#[cfg(feature = "interval")]
+pub mod interval;
+#[cfg(feature = "std")]
+pub mod online;
+#[cfg(feature = "std")]
+pub mod outliers;
+#[cfg(feature = "parallel")]
+mod parallel;
 pub mod percentile;
+#[cfg(feature = "std")]
+pub mod robust;
+#[cfg(feature = "sample")]
+pub mod sample;
+#[cfg(feature = "simd")]
+mod simd;
+#[cfg(feature = "std")]
+pub mod statistic;
+#[cfg(feature = "std")]
+pub mod summary;
+#[cfg(feature = "std")]
+pub mod tests;
+#[cfg(feature = "std")]
+pub mod units;
 
 #[cfg(feature = "percentile-rand")]
 pub use percentile::percentile_rand;
@@ -19,6 +70,36 @@ pub use regression::{Determination, Predictive};
 
 use self::percentile::cluster;
 
+/// Math functions of floats, from the standard library, or `libm` without it.
+mod math {
+    macro_rules! math {
+        ($($name:ident($t:ty) => $method:ident, )+) => {
+            $(
+            #[allow(dead_code)]
+            #[inline]
+            pub(crate) fn $name(v: $t) -> $t {
+                #[cfg(feature = "std")]
+                {
+                    v.$method()
+                }
+                #[cfg(not(feature = "std"))]
+                {
+                    libm::$name(v)
+                }
+            }
+            )+
+        };
+    }
+    math!(
+        sqrt(f64) => sqrt,
+        sqrtf(f32) => sqrt,
+        floor(f64) => floor,
+        ceil(f64) => ceil,
+        fabs(f64) => abs,
+        trunc(f64) => trunc,
+    );
+}
+
 /// > As all algorithms are executed in linear time now, this is not as useful, but nevertheless an interesting feature.
 /// > If you already have clustered data, this feature is great.
 ///
@@ -32,7 +113,14 @@ pub type Cluster = (f64, usize);
 /// Use [`Self::borrow`] to get a [`ClusterList`].
 /// The inner slice is accessible through the [`Deref`] and [`DerefMut`], which means you can use
 /// this as a mutable slice.
-#[derive(Debug)]
+///
+/// With the `serde` feature, this is serialized as the list of clusters.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(from = "Vec<Cluster>", into = "Vec<Cluster>")
+)]
 pub struct OwnedClusterList {
     list: Vec<Cluster>,
     len: usize,
@@ -43,12 +131,99 @@ impl OwnedClusterList {
         let len = ClusterList::size(&list);
         Self { list, len }
     }
-    pub fn borrow(&self) -> ClusterList {
+    /// Rounds each of `values` to the nearest multiple of `resolution`, and counts the rounded
+    /// values. Measured values rarely are exactly equal, so this makes far fewer clusters than
+    /// [`ClusterList::optimize_values`], at the cost of the precision of the values.
+    ///
+    /// Values which aren't finite, or whose multiple of `resolution` doesn't fit in an [`i64`],
+    /// are skipped.
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `resolution` isn't positive and finite.
+    pub fn from_values_binned(values: impl IntoIterator<Item = f64>, resolution: f64) -> Self {
+        // 2⁶³, the first value outside the range of `i64`
+        const I64_END: f64 = 9_223_372_036_854_775_808.;
+        assert!(
+            resolution > 0. && resolution.is_finite(),
+            "resolution must be positive and finite"
+        );
+        let mut counts: HashMap<i64, usize> = HashMap::new();
+        for v in values {
+            let multiple = math::floor(v / resolution + 0.5);
+            // also false for NaN
+            if (-I64_END..I64_END).contains(&multiple) {
+                *counts.entry(multiple as i64).or_insert(0) += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(multiple, count)| (multiple as f64 * resolution, count))
+            .collect()
+    }
+    pub fn borrow(&self) -> ClusterList<'_> {
         ClusterList {
             list: &self.list,
             len: self.len,
         }
     }
+    /// Appends the clusters of `other`, e.g. to combine the values of several files.
+    ///
+    /// Clusters with the same value aren't grouped. Call [`Self::optimize_values`] afterwards to
+    /// do so.
+    ///
+    /// O(m), where m is the number of clusters in `other`.
+    pub fn merge(&mut self, other: OwnedClusterList) {
+        self.list.extend(other.list);
+        self.len += other.len;
+    }
+    /// Same as [`ClusterList::iter_values`].
+    pub fn iter_values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.borrow().iter_values()
+    }
+    /// Same as [`ClusterList::optimize_values`], but in place.
+    ///
+    /// O(m)
+    pub fn optimize_values(&mut self) {
+        *self = self.borrow().optimize_values();
+    }
+}
+impl Extend<Cluster> for OwnedClusterList {
+    fn extend<I: IntoIterator<Item = Cluster>>(&mut self, iter: I) {
+        for (v, count) in iter {
+            self.list.push((v, count));
+            self.len += count;
+        }
+    }
+}
+impl FromIterator<Cluster> for OwnedClusterList {
+    fn from_iter<I: IntoIterator<Item = Cluster>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+/// Every value is a [`Cluster`] with a count of 1.
+impl FromIterator<f64> for OwnedClusterList {
+    fn from_iter<I: IntoIterator<Item = f64>>(iter: I) -> Self {
+        iter.into_iter().map(|v| (v, 1)).collect()
+    }
+}
+/// Every value is added as a [`Cluster`] with a count of 1.
+impl Extend<f64> for OwnedClusterList {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|v| (v, 1)));
+    }
+}
+impl From<Vec<Cluster>> for OwnedClusterList {
+    fn from(list: Vec<Cluster>) -> Self {
+        Self::new(list)
+    }
+}
+impl From<OwnedClusterList> for Vec<Cluster> {
+    fn from(list: OwnedClusterList) -> Self {
+        list.list
+    }
 }
 impl Deref for OwnedClusterList {
     type Target = [Cluster];
@@ -64,7 +239,7 @@ impl DerefMut for OwnedClusterList {
 
 /// F64 wrapper that implements [`Ord`] and [`Hash`].
 ///
-/// When [`PartialOrd`] returns [`None`], we return [`std::cmp::Ordering::Equal`].
+/// When [`PartialOrd`] returns [`None`], we return [`core::cmp::Ordering::Equal`].
 ///
 /// You should probably not be using this unless you know what you're doing.
 #[derive(Debug, Copy, Clone)]
@@ -78,7 +253,7 @@ impl F64OrdHash {
 
     /// Compares two `f64`s using our ordering.
     #[inline(always)]
-    pub fn f64_cmp(a: f64, b: f64) -> std::cmp::Ordering {
+    pub fn f64_cmp(a: f64, b: f64) -> core::cmp::Ordering {
         Self(a).cmp(&Self(b))
     }
 }
@@ -100,19 +275,94 @@ impl PartialEq for F64OrdHash {
 impl Eq for F64OrdHash {}
 impl PartialOrd for F64OrdHash {
     #[inline]
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
 impl Ord for F64OrdHash {
     #[inline]
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.0
+            .partial_cmp(&other.0)
+            .unwrap_or_else(|| match (self.0.is_nan(), other.0.is_nan()) {
+                (true, true) | (false, false) => core::cmp::Ordering::Equal,
+                (true, false) => core::cmp::Ordering::Less,
+                (false, true) => core::cmp::Ordering::Greater,
+            })
+    }
+}
+
+/// Floating point numbers, which can be ordered and hashed using [`FloatOrdHash`].
+///
+/// Implemented for [`f32`] and [`f64`].
+pub trait Float: Copy + PartialOrd {
+    fn is_nan(self) -> bool;
+    /// The bits of the value, used for hashing and equality.
+    fn to_bits_u64(self) -> u64;
+}
+macro_rules! impl_float {
+    ($($t:ty, )+) => {
+        $(
+        impl Float for $t {
+            #[inline(always)]
+            fn is_nan(self) -> bool {
+                <$t>::is_nan(self)
+            }
+            #[inline(always)]
+            fn to_bits_u64(self) -> u64 {
+                self.to_bits() as u64
+            }
+        }
+        )+
+    };
+}
+impl_float!(f32, f64,);
+
+/// Generic variant of [`F64OrdHash`], for any [`Float`] (e.g. [`f32`]).
+///
+/// When [`PartialOrd`] returns [`None`], we return [`core::cmp::Ordering::Equal`] (`NaN`s are the
+/// smallest values).
+#[derive(Debug, Copy, Clone)]
+#[repr(transparent)]
+pub struct FloatOrdHash<F>(pub F);
+impl<F: Float> FloatOrdHash<F> {
+    /// Compares two floats using our ordering.
+    #[inline(always)]
+    pub fn float_cmp(a: F, b: F) -> core::cmp::Ordering {
+        Self(a).cmp(&Self(b))
+    }
+}
+impl<F: Float> hash::Hash for FloatOrdHash<F> {
+    #[inline]
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: hash::Hasher,
+    {
+        self.0.to_bits_u64().hash(state)
+    }
+}
+impl<F: Float> PartialEq for FloatOrdHash<F> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits_u64() == other.0.to_bits_u64()
+    }
+}
+impl<F: Float> Eq for FloatOrdHash<F> {}
+impl<F: Float> PartialOrd for FloatOrdHash<F> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<F: Float> Ord for FloatOrdHash<F> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.0
             .partial_cmp(&other.0)
             .unwrap_or_else(|| match (self.0.is_nan(), other.0.is_nan()) {
-                (true, true) | (false, false) => std::cmp::Ordering::Equal,
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
+                (true, true) | (false, false) => core::cmp::Ordering::Equal,
+                (true, false) => core::cmp::Ordering::Less,
+                (false, true) => core::cmp::Ordering::Greater,
             })
     }
 }
@@ -127,6 +377,37 @@ pub struct ClusterList<'a> {
     list: &'a [Cluster],
     len: usize,
 }
+/// Σ `v * count` of the clusters.
+fn cluster_sum(list: &[Cluster]) -> f64 {
+    #[cfg(feature = "simd")]
+    {
+        simd::cluster_sum(list)
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        let mut sum = 0.0;
+        for (v, count) in list.iter() {
+            sum += v * *count as f64;
+        }
+        sum
+    }
+}
+/// Σ `(v - base)² * count` of the clusters.
+fn cluster_sum_squared_diff(list: &[Cluster], base: f64) -> f64 {
+    #[cfg(feature = "simd")]
+    {
+        simd::cluster_sum_squared_diff(list, base)
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        let mut sum = 0.0;
+        for (v, count) in list.iter() {
+            sum += (v - base) * (v - base) * *count as f64;
+        }
+        sum
+    }
+}
+
 impl<'a> ClusterList<'a> {
     /// The float is the value. The integer is the count.
     pub fn new(list: &'a [Cluster]) -> Self {
@@ -148,18 +429,59 @@ impl<'a> ClusterList<'a> {
     }
     /// O(m)
     pub fn sum(&self) -> f64 {
-        let mut sum = 0.0;
-        for (v, count) in self.list.iter() {
-            sum += v * *count as f64;
+        #[cfg(feature = "parallel")]
+        {
+            parallel::map_reduce(self.list, cluster_sum, |a, b| a + b)
         }
-        sum
+        #[cfg(not(feature = "parallel"))]
+        {
+            cluster_sum(self.list)
+        }
+    }
+    /// The sum of the squares of the values.
+    ///
+    /// O(m)
+    pub fn sum_squares(&self) -> f64 {
+        self.sum_squared_diff(0.)
+    }
+    /// The smallest value, or [`None`] if empty. `NaN`s are ignored.
+    ///
+    /// O(m)
+    pub fn min(&self) -> Option<f64> {
+        self.values().reduce(f64::min)
+    }
+    /// The largest value, or [`None`] if empty. `NaN`s are ignored.
+    ///
+    /// O(m)
+    pub fn max(&self) -> Option<f64> {
+        self.values().reduce(f64::max)
+    }
+    /// The difference between [`Self::max`] and [`Self::min`], or [`None`] if empty.
+    ///
+    /// O(m)
+    pub fn range(&self) -> Option<f64> {
+        Some(self.max()? - self.min()?)
+    }
+    /// The unique values, skipping clusters with a count of 0.
+    fn values(&self) -> impl Iterator<Item = f64> + 'a {
+        self.list
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(v, _)| *v)
     }
     fn sum_squared_diff(&self, base: f64) -> f64 {
-        let mut sum = 0.0;
-        for (v, count) in self.list.iter() {
-            sum += (v - base).powi(2) * *count as f64;
+        #[cfg(feature = "parallel")]
+        {
+            parallel::map_reduce(
+                self.list,
+                |list| cluster_sum_squared_diff(list, base),
+                |a, b| a + b,
+            )
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            cluster_sum_squared_diff(self.list, base)
         }
-        sum
     }
     /// Can be used in [`Self::new`].
     pub fn split_start(&self, len: usize) -> OwnedClusterList {
@@ -184,15 +506,23 @@ impl<'a> ClusterList<'a> {
         for (v, count) in self.list.iter().rev() {
             sum += count;
             if sum >= len {
-                list.insert(0, (*v, *count - (len - sum)));
+                list.push((*v, *count - (sum - len)));
                 break;
             } else {
-                list.insert(0, (*v, *count))
+                list.push((*v, *count))
             }
         }
+        // pushing and reversing is O(m), inserting at the start would be O(m²)
+        list.reverse();
         debug_assert_eq!(len, Self::size(&list));
         OwnedClusterList { list, len }
     }
+    /// Iterates every value, repeating the value of each cluster by its count.
+    pub fn iter_values(&self) -> impl Iterator<Item = f64> + 'a {
+        self.list
+            .iter()
+            .flat_map(|(v, count)| core::iter::repeat(*v).take(*count))
+    }
     /// Returns the value at `idx`. This iterates the clusters to get the value.
     ///
     /// # Panics
@@ -217,11 +547,23 @@ impl<'a> ClusterList<'a> {
     ///
     /// O(n)
     pub fn optimize_values(self) -> OwnedClusterList {
-        let mut collected = HashMap::with_capacity(16);
-        for (v, count) in self.list {
-            let c = collected.entry(F64OrdHash(*v)).or_insert(0);
-            *c += count;
+        fn collect(list: &[Cluster]) -> HashMap<F64OrdHash, usize> {
+            let mut collected = HashMap::new();
+            for (v, count) in list {
+                let c = collected.entry(F64OrdHash(*v)).or_insert(0);
+                *c += count;
+            }
+            collected
         }
+        #[cfg(feature = "parallel")]
+        let collected = parallel::map_reduce(self.list, collect, |mut a, b| {
+            for (v, count) in b {
+                *a.entry(v).or_insert(0) += count;
+            }
+            a
+        });
+        #[cfg(not(feature = "parallel"))]
+        let collected = collect(self.list);
         let list = collected.into_iter().map(|(f, c)| (f.0, c)).collect();
         OwnedClusterList {
             list,
@@ -232,29 +574,99 @@ impl<'a> ClusterList<'a> {
 
 /// Returned from [`standard_deviation`] and similar functions.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StandardDeviationOutput<T> {
     pub standard_deviation: T,
     pub mean: T,
 }
 /// Returned from [`percentiles_cluster`] and similar functions.
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PercentilesOutput {
     pub median: f64,
     pub lower_quadrille: Option<f64>,
     pub higher_quadrille: Option<f64>,
 }
+/// An approximated value, and the range the exact value is known to be within.
+///
+/// Returned when the original values aren't available, such as with
+/// [binned input](buckets::BucketHistogram).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Approximation {
+    /// The best estimate.
+    pub value: f64,
+    /// The exact value is at least this.
+    pub lower_bound: f64,
+    /// The exact value is at most this. Can be infinite.
+    pub upper_bound: f64,
+}
+impl Approximation {
+    /// A value which isn't approximated.
+    pub fn exact(value: f64) -> Self {
+        Self {
+            value,
+            lower_bound: value,
+            upper_bound: value,
+        }
+    }
+    /// If the bounds are equal, so [`Self::value`] is exact.
+    pub fn is_exact(&self) -> bool {
+        self.lower_bound == self.upper_bound
+    }
+    /// The largest possible distance between [`Self::value`] and the exact value,
+    /// i.e. the `±` of the value.
+    pub fn error(&self) -> f64 {
+        (self.value - self.lower_bound).max(self.upper_bound - self.value)
+    }
+}
+impl From<f64> for Approximation {
+    fn from(value: f64) -> Self {
+        Self::exact(value)
+    }
+}
+/// Same as [`PercentilesOutput`], but with the error bounds of each percentile.
+/// Returned when computing percentiles from binned input, such as
+/// [`buckets::BucketHistogram::percentiles`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ApproximatePercentilesOutput {
+    pub median: Approximation,
+    pub lower_quadrille: Option<Approximation>,
+    pub higher_quadrille: Option<Approximation>,
+}
+impl ApproximatePercentilesOutput {
+    /// If all the percentiles are [exact](Approximation::is_exact).
+    pub fn is_exact(&self) -> bool {
+        [
+            Some(self.median),
+            self.lower_quadrille,
+            self.higher_quadrille,
+        ]
+        .iter()
+        .flatten()
+        .all(Approximation::is_exact)
+    }
+}
+impl From<PercentilesOutput> for ApproximatePercentilesOutput {
+    fn from(output: PercentilesOutput) -> Self {
+        Self {
+            median: output.median.into(),
+            lower_quadrille: output.lower_quadrille.map(Into::into),
+            higher_quadrille: output.higher_quadrille.map(Into::into),
+        }
+    }
+}
 
 /// Helper-trait for types used by [`mean`].
 ///
 /// This is implemented generically when the feature `generic-impl` is enabled.
-pub trait Mean<'a, D>: std::iter::Sum<&'a Self> + ops::Div<Output = D>
+pub trait Mean<'a, D>: core::iter::Sum<&'a Self> + ops::Div<Output = D>
 where
     Self: 'a,
 {
     fn from_usize(n: usize) -> Self;
 }
 #[cfg(feature = "generic-impls")]
-impl<'a, T: std::iter::Sum<&'a Self> + ops::Div + num_traits::FromPrimitive> Mean<'a, T::Output>
+impl<'a, T: core::iter::Sum<&'a Self> + ops::Div + num_traits::FromPrimitive> Mean<'a, T::Output>
     for T
 where
     T: 'a,
@@ -284,8 +696,8 @@ impl_mean!(f32, f64, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, is
 pub trait StandardDeviation<'a>:
     Copy
     + Mean<'a, Self>
-    + std::iter::Sum<&'a Self>
-    + std::iter::Sum
+    + core::iter::Sum<&'a Self>
+    + core::iter::Sum
     + ops::Div<Output = Self>
     + ops::Sub<Output = Self>
     + ops::Mul<Output = Self>
@@ -302,8 +714,8 @@ impl<
         T: Copy
             + Mean<'a, Self>
             + PartialOrd
-            + std::iter::Sum<&'a Self>
-            + std::iter::Sum
+            + core::iter::Sum<&'a Self>
+            + core::iter::Sum
             + ops::Div<Output = Self>
             + ops::Sub<Output = Self>
             + ops::Mul<Output = Self>
@@ -329,14 +741,14 @@ where
 }
 #[cfg(not(feature = "generic-impls"))]
 macro_rules! impl_std_dev {
-    ($($t:ty, )+) => {
+    ($($t:ty: $sqrt:ident, )+) => {
         $(
         impl<'a> StandardDeviation<'a> for $t {
             fn one() -> Self {
                 1.0
             }
             fn sqrt(self) -> Self {
-                <$t>::sqrt(self)
+                math::$sqrt(self)
             }
             fn max(self, other: Self) -> Self {
                 if self < other {
@@ -350,7 +762,7 @@ macro_rules! impl_std_dev {
     };
 }
 #[cfg(not(feature = "generic-impls"))]
-impl_std_dev!(f32, f64,);
+impl_std_dev!(f32: sqrtf, f64: sqrt,);
 
 /// Mean of clustered `values`.
 pub fn mean_cluster(values: &ClusterList) -> f64 {
@@ -370,7 +782,7 @@ pub fn standard_deviation_cluster(values: &ClusterList) -> StandardDeviationOutp
     let squared_deviations = values.sum_squared_diff(m);
     let variance: f64 = squared_deviations / (values.len() - 1).max(1) as f64;
     StandardDeviationOutput {
-        standard_deviation: variance.sqrt(),
+        standard_deviation: math::sqrt(variance),
         mean: m,
     }
 }
@@ -402,34 +814,690 @@ pub fn standard_deviation<'a, T: StandardDeviation<'a>>(
     }
 }
 
+/// Statistics of a flat list of values, without constructing a [`OwnedClusterList`].
+///
+/// Implemented for `[f64]` (and therefore [`Vec<f64>`]) and for cloneable iterators of [`f64`].
+/// The iterators are cloned and collected when the statistic needs all values at once.
+///
+/// All statistics are `NaN` if there are no values.
+///
+/// ```
+/// use std_dev::{Fraction, StatisticsExt};
+///
+/// let data = [4., 1., 3., 2.];
+/// assert_eq!(data.mean(), 2.5);
+/// assert_eq!(data.median(), 2.5);
+/// assert_eq!(data.percentile(Fraction::new(3, 4)), 3.5);
+/// assert_eq!(data.iter().map(|v| v * 2.).median(), 5.);
+/// ```
+pub trait StatisticsExt {
+    /// The arithmetic mean.
+    ///
+    /// O(n)
+    fn mean(&self) -> f64;
+    /// The sample standard deviation.
+    ///
+    /// O(n)
+    fn std_dev(&self) -> f64;
+    /// The percentile at `target`. If it lies between two values, their mean is returned.
+    ///
+    /// The values are copied, as finding the percentile reorders them.
+    ///
+    /// O(n)
+    fn percentile(&self, target: Fraction) -> f64;
+    /// Convenience method for [`Self::percentile`] with the 50% mark as the target.
+    ///
+    /// O(n)
+    fn median(&self) -> f64 {
+        self.percentile(Fraction::HALF)
+    }
+}
+impl StatisticsExt for [f64] {
+    fn mean(&self) -> f64 {
+        mean(self)
+    }
+    fn std_dev(&self) -> f64 {
+        if self.is_empty() {
+            return f64::NAN;
+        }
+        standard_deviation(self).standard_deviation
+    }
+    fn percentile(&self, target: Fraction) -> f64 {
+        if self.is_empty() {
+            return f64::NAN;
+        }
+        let mut values = self.to_vec();
+        percentile::percentile_float(&mut values, target).resolve()
+    }
+}
+impl<I: Iterator<Item = f64> + Clone> StatisticsExt for I {
+    fn mean(&self) -> f64 {
+        let (sum, len) = self
+            .clone()
+            .fold((0., 0_usize), |(sum, len), v| (sum + v, len + 1));
+        sum / len as f64
+    }
+    fn std_dev(&self) -> f64 {
+        self.clone().collect::<Vec<_>>().std_dev()
+    }
+    fn percentile(&self, target: Fraction) -> f64 {
+        let mut values: Vec<_> = self.clone().collect();
+        if values.is_empty() {
+            return f64::NAN;
+        }
+        percentile::percentile_float(&mut values, target).resolve()
+    }
+}
+
+/// Statistics of [`Duration`]s, e.g. timings of benchmarks, returned as [`Duration`]s.
+///
+/// Implemented for `[Duration]` (and therefore [`Vec<Duration>`]) and for cloneable iterators of
+/// [`Duration`]. The arithmetic is done on integer nanoseconds, so no precision is lost to
+/// floats. The results are truncated to whole nanoseconds.
+/// The only exception is [`Self::std_dev`] of very long or many durations, where the squared
+/// deviations don't fit in a [`u128`] and floats are used instead.
+///
+/// All statistics are [`None`] if there are no values.
+///
+/// ```
+/// use std::time::Duration;
+/// use std_dev::{DurationStatisticsExt, Fraction};
+///
+/// let timings = [3, 1, 4, 2].map(Duration::from_millis);
+/// assert_eq!(timings.mean(), Some(Duration::from_micros(2500)));
+/// assert_eq!(timings.median(), Some(Duration::from_micros(2500)));
+/// assert_eq!(timings.percentile(Fraction::new(1, 4)), Some(Duration::from_micros(1500)));
+/// assert_eq!(timings.std_dev(), Some(Duration::from_nanos(1_290_994)));
+/// assert_eq!(timings.iter().copied().max_duration(), Some(Duration::from_millis(4)));
+/// ```
+pub trait DurationStatisticsExt {
+    /// The arithmetic mean.
+    ///
+    /// O(n)
+    fn mean(&self) -> Option<Duration>;
+    /// The sample standard deviation.
+    ///
+    /// O(n)
+    fn std_dev(&self) -> Option<Duration>;
+    /// The percentile at `target`. If it lies between two values, their mean is returned.
+    ///
+    /// The values are copied, as finding the percentile reorders them.
+    ///
+    /// O(n)
+    fn percentile(&self, target: Fraction) -> Option<Duration>;
+    /// Convenience method for [`Self::percentile`] with the 50% mark as the target.
+    ///
+    /// O(n)
+    fn median(&self) -> Option<Duration> {
+        self.percentile(Fraction::HALF)
+    }
+    /// The shortest duration.
+    ///
+    /// O(n)
+    fn min_duration(&self) -> Option<Duration>;
+    /// The longest duration.
+    ///
+    /// O(n)
+    fn max_duration(&self) -> Option<Duration>;
+}
+/// Converts nanoseconds to a [`Duration`], saturating at [`Duration::MAX`].
+fn duration_from_nanos(nanos: u128) -> Duration {
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+    match u64::try_from(nanos / NANOS_PER_SEC) {
+        Ok(secs) => Duration::new(secs, (nanos % NANOS_PER_SEC) as u32),
+        Err(_) => Duration::MAX,
+    }
+}
+/// The square root of `v`, rounded down.
+fn integer_sqrt(v: u128) -> u128 {
+    if v < 2 {
+        return v;
+    }
+    // Newton's method, starting above the root
+    let mut x = 1_u128 << ((128 - v.leading_zeros() + 1) / 2);
+    loop {
+        let next = (x + v / x) / 2;
+        if next >= x {
+            return x;
+        }
+        x = next;
+    }
+}
+impl DurationStatisticsExt for [Duration] {
+    fn mean(&self) -> Option<Duration> {
+        if self.is_empty() {
+            return None;
+        }
+        let sum: u128 = self.iter().map(Duration::as_nanos).sum();
+        Some(duration_from_nanos(sum / self.len() as u128))
+    }
+    fn std_dev(&self) -> Option<Duration> {
+        if self.is_empty() {
+            return None;
+        }
+        let len = self.len() as u128;
+        let sum: u128 = self.iter().map(Duration::as_nanos).sum();
+        // The deviations are scaled by `len`, to keep the fraction of the mean.
+        // They're divided by `len²` after the sum, as to not truncate each of them.
+        let variance = self
+            .iter()
+            .try_fold(0_u128, |acc, v| {
+                let diff = v.as_nanos().checked_mul(len)?.abs_diff(sum);
+                acc.checked_add(diff.checked_mul(diff)?)
+            })
+            .and_then(|squared_deviations| {
+                let denominator = len.checked_mul(len)?.checked_mul((len - 1).max(1))?;
+                Some(squared_deviations / denominator)
+            });
+        let std_dev = match variance {
+            Some(variance) => duration_from_nanos(integer_sqrt(variance)),
+            None => {
+                let nanos: Vec<f64> = self.iter().map(|v| v.as_nanos() as f64).collect();
+                // the cast saturates
+                duration_from_nanos(standard_deviation(&nanos).standard_deviation as u128)
+            }
+        };
+        Some(std_dev)
+    }
+    fn percentile(&self, target: Fraction) -> Option<Duration> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut values = self.to_vec();
+        let value = match percentile::percentile_default_pivot(&mut values, target) {
+            percentile::MeanValue::Single(v) => v,
+            percentile::MeanValue::Mean(a, b) => {
+                let (a, b) = (a.as_nanos(), b.as_nanos());
+                duration_from_nanos(a.min(b) + a.abs_diff(b) / 2)
+            }
+        };
+        Some(value)
+    }
+    fn min_duration(&self) -> Option<Duration> {
+        self.iter().min().copied()
+    }
+    fn max_duration(&self) -> Option<Duration> {
+        self.iter().max().copied()
+    }
+}
+impl<I: Iterator<Item = Duration> + Clone> DurationStatisticsExt for I {
+    fn mean(&self) -> Option<Duration> {
+        let (sum, len) = self.clone().fold((0_u128, 0_u128), |(sum, len), v| {
+            (sum + v.as_nanos(), len + 1)
+        });
+        sum.checked_div(len).map(duration_from_nanos)
+    }
+    fn std_dev(&self) -> Option<Duration> {
+        self.clone().collect::<Vec<_>>().std_dev()
+    }
+    fn percentile(&self, target: Fraction) -> Option<Duration> {
+        self.clone().collect::<Vec<_>>().percentile(target)
+    }
+    fn min_duration(&self) -> Option<Duration> {
+        self.clone().min()
+    }
+    fn max_duration(&self) -> Option<Duration> {
+        self.clone().max()
+    }
+}
+
+/// The [z-score](https://en.wikipedia.org/wiki/Standard_score) of each of `values`: how many
+/// standard deviations it's from the mean.
+///
+/// The z-scores have a mean of 0 and a standard deviation of 1, which is useful before comparing
+/// variables of different scales or feeding them to regression estimators.
+/// If all values are equal, their standard deviation is 0, and all the z-scores are 0.
+///
+/// O(n)
+pub fn z_scores(values: &[f64]) -> Vec<f64> {
+    let mut values = values.to_vec();
+    standardize_in_place(&mut values);
+    values
+}
+/// Same as [`z_scores`], but replaces `values` with their z-scores.
+///
+/// O(n)
+pub fn standardize_in_place(values: &mut [f64]) {
+    let StandardDeviationOutput {
+        standard_deviation,
+        mean,
+    } = standard_deviation(values);
+    // Rounding of the mean can make the standard deviation slightly positive, so compare the
+    // values instead.
+    if standard_deviation == 0. || values.windows(2).all(|w| w[0] == w[1]) {
+        values.fill(0.);
+        return;
+    }
+    for v in values {
+        *v = (*v - mean) / standard_deviation;
+    }
+}
+/// Same as [`z_scores`], but for clustered `values`. The counts are kept.
+///
+/// O(m), where m is the number of [`Cluster`]s.
+pub fn z_scores_cluster(values: &ClusterList) -> OwnedClusterList {
+    let StandardDeviationOutput {
+        standard_deviation,
+        mean,
+    } = standard_deviation_cluster(values);
+    let constant = standard_deviation == 0. || values.list.windows(2).all(|w| w[0].0 == w[1].0);
+    let list = values
+        .list
+        .iter()
+        .map(|(v, count)| {
+            let z = if constant {
+                0.
+            } else {
+                (v - mean) / standard_deviation
+            };
+            (z, *count)
+        })
+        .collect();
+    OwnedClusterList::new(list)
+}
+
 /// Get a collection of percentiles from `values`.
 pub fn percentiles_cluster(values: &mut OwnedClusterList) -> PercentilesOutput {
-    fn percentile(
-        values: &mut OwnedClusterList,
-        target: impl percentile::OrderedListIndex,
-    ) -> percentile::MeanValue<f64> {
-        #[cfg(feature = "percentile-rand")]
-        {
-            cluster::percentile_rand(values, target)
+    #[cfg(feature = "percentile-rand")]
+    let mut pivot_fn = cluster::pivot_fn::rand();
+    #[cfg(not(feature = "percentile-rand"))]
+    let mut pivot_fn = cluster::pivot_fn::middle();
+    percentiles_cluster_by(values, &mut pivot_fn)
+}
+/// Same as [`percentiles_cluster`], but picking the pivots with `rng`.
+/// Use a seeded `rng` to get reproducible runs.
+#[cfg(feature = "percentile-rand")]
+pub fn percentiles_cluster_with_rng(
+    values: &mut OwnedClusterList,
+    rng: &mut (impl rand::Rng + ?Sized),
+) -> PercentilesOutput {
+    percentiles_cluster_by(values, &mut cluster::pivot_fn::rand_with(rng))
+}
+fn percentiles_cluster_by(
+    values: &mut OwnedClusterList,
+    pivot_fn: &mut impl FnMut(&ClusterList) -> f64,
+) -> PercentilesOutput {
+    if values.borrow().len() >= 4 {
+        let percentiles = percentiles_cluster_list_by(
+            values,
+            pivot_fn,
+            &[
+                Fraction::ONE_QUARTER,
+                Fraction::HALF,
+                Fraction::THREE_QUARTERS,
+            ],
+        );
+        PercentilesOutput {
+            median: percentiles[1],
+            lower_quadrille: Some(percentiles[0]),
+            higher_quadrille: Some(percentiles[2]),
+        }
+    } else {
+        PercentilesOutput {
+            median: cluster::median(values).resolve(),
+            lower_quadrille: None,
+            higher_quadrille: None,
+        }
+    }
+}
+/// Get the percentiles at `targets` (e.g. p90, p95, and p99) of `values`, in the same order as
+/// `targets`.
+///
+/// The work of partitioning the values is shared between the targets, which makes this faster
+/// than getting the percentiles one at a time.
+///
+/// O(m log t), where t is the count of targets.
+pub fn percentiles_cluster_list(values: &mut OwnedClusterList, targets: &[Fraction]) -> Vec<f64> {
+    #[cfg(feature = "percentile-rand")]
+    let mut pivot_fn = cluster::pivot_fn::rand();
+    #[cfg(not(feature = "percentile-rand"))]
+    let mut pivot_fn = cluster::pivot_fn::middle();
+    percentiles_cluster_list_by(values, &mut pivot_fn, targets)
+}
+/// Same as [`percentiles_cluster_list`], but picking the pivots with `rng`.
+#[cfg(feature = "percentile-rand")]
+pub fn percentiles_cluster_list_with_rng(
+    values: &mut OwnedClusterList,
+    targets: &[Fraction],
+    rng: &mut (impl rand::Rng + ?Sized),
+) -> Vec<f64> {
+    percentiles_cluster_list_by(values, &mut cluster::pivot_fn::rand_with(rng), targets)
+}
+fn percentiles_cluster_list_by(
+    values: &mut OwnedClusterList,
+    pivot_fn: &mut impl FnMut(&ClusterList) -> f64,
+    targets: &[Fraction],
+) -> Vec<f64> {
+    cluster::percentiles_by(values, targets, pivot_fn, &mut F64OrdHash::f64_cmp)
+        .into_iter()
+        .map(|v| v.resolve())
+        .collect()
+}
+
+/// Returned from [`mode_cluster`] and [`mode_cluster_binned`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct ModeOutput {
+    /// The most frequent values, in ascending order. Contains several values if they're tied.
+    pub modes: Vec<f64>,
+    /// How many times each of the [`Self::modes`] occur.
+    pub count: usize,
+}
+impl ModeOutput {
+    /// If there are several modes.
+    pub fn is_multimodal(&self) -> bool {
+        self.modes.len() > 1
+    }
+}
+/// Get the most frequent value(s) of `values`.
+/// All values are returned if they're tied.
+///
+/// Clusters with the same value don't have to be [merged](ClusterList::optimize_values) first.
+///
+/// O(m)
+pub fn mode_cluster(values: &ClusterList) -> ModeOutput {
+    let mut counts: HashMap<F64OrdHash, usize> = HashMap::new();
+    for (v, count) in values.list {
+        *counts.entry(F64OrdHash(*v)).or_insert(0) += count;
+    }
+    modes(counts.into_iter().map(|(v, count)| (v.0, count)))
+}
+/// Like [`mode_cluster`], but for continuous data, where values seldom are exactly equal.
+///
+/// The values are grouped in bins of `bin_width`, starting at 0.
+/// The returned modes are the centers of the most frequent bins.
+/// Like in [`OwnedClusterList::from_values_binned`], values which aren't finite, or whose bin
+/// doesn't fit in an [`i64`], are skipped.
+///
+/// O(m)
+///
+/// # Panics
+///
+/// Panics if `bin_width` isn't positive and finite.
+pub fn mode_cluster_binned(values: &ClusterList, bin_width: f64) -> ModeOutput {
+    // 2⁶³, the first value outside the range of `i64`
+    const I64_END: f64 = 9_223_372_036_854_775_808.;
+    assert!(
+        bin_width > 0. && bin_width.is_finite(),
+        "bin_width must be positive and finite"
+    );
+    let mut counts: HashMap<i64, usize> = HashMap::new();
+    for (v, count) in values.list {
+        let bin = math::floor(v / bin_width);
+        // also false for NaN
+        if (-I64_END..I64_END).contains(&bin) {
+            *counts.entry(bin as i64).or_insert(0) += count;
+        }
+    }
+    modes(
+        counts
+            .into_iter()
+            .map(|(bin, count)| ((bin as f64 + 0.5) * bin_width, count)),
+    )
+}
+fn modes(counts: impl Iterator<Item = Cluster>) -> ModeOutput {
+    let mut modes = Vec::new();
+    let mut max = 0;
+    for (v, count) in counts {
+        match count.cmp(&max) {
+            core::cmp::Ordering::Greater => {
+                max = count;
+                modes.clear();
+                modes.push(v);
+            }
+            core::cmp::Ordering::Equal => modes.push(v),
+            core::cmp::Ordering::Less => {}
+        }
+    }
+    modes.sort_unstable_by(|a, b| F64OrdHash::f64_cmp(*a, *b));
+    ModeOutput { modes, count: max }
+}
+
+// `tests` is the public module of hypothesis tests, so the unit tests of this file are here.
+#[cfg(test)]
+mod lib_tests {
+    use super::*;
+
+    #[test]
+    fn cluster_merge() {
+        let mut list = OwnedClusterList::new(vec![(1., 2), (2., 1)]);
+        list.merge(OwnedClusterList::new(vec![(2., 3), (5., 1)]));
+        assert_eq!(list.borrow().len(), 7);
+        assert_eq!(list.len(), 4);
+
+        list.extend([(1., 1)]);
+        list.extend([5., 6.]);
+        assert_eq!(list.borrow().len(), 10);
+
+        list.optimize_values();
+        list.sort_unstable_by(|a, b| F64OrdHash::f64_cmp(a.0, b.0));
+        assert_eq!(*list, [(1., 3), (2., 4), (5., 2), (6., 1)]);
+        assert_eq!(list.borrow().len(), 10);
+        assert_eq!(cluster::median(&mut list).resolve(), 2.);
+    }
+    #[test]
+    fn cluster_from_iter() {
+        let list: OwnedClusterList = [(1., 2), (3., 1), (2., 0)].into_iter().collect();
+        assert_eq!(list.borrow().len(), 3);
+        assert_eq!(list.iter_values().collect::<Vec<_>>(), [1., 1., 3.]);
+
+        let values = [4., 2., 2., 8.];
+        let list: OwnedClusterList = values.iter().copied().collect();
+        assert_eq!(list.borrow().len(), 4);
+        assert!(list.iter_values().eq(values));
+    }
+    #[test]
+    fn cluster_binned() {
+        let values = [1.02, 0.98, 1.04, 2.51, 2.49, -0.01, 0.51];
+        let mut list = OwnedClusterList::from_values_binned(values, 0.5);
+        list.sort_unstable_by(|a, b| F64OrdHash::f64_cmp(a.0, b.0));
+        assert_eq!(*list, [(0., 1), (0.5, 1), (1., 3), (2.5, 2)]);
+        assert_eq!(list.borrow().len(), values.len());
+
+        let values = [f64::NAN, 1., f64::INFINITY, f64::NEG_INFINITY, 1e300];
+        let list = OwnedClusterList::from_values_binned(values, 0.1);
+        assert_eq!(*list, [(1., 1)]);
+        assert_eq!(list.borrow().len(), 1);
+        assert!(OwnedClusterList::from_values_binned([], 1.).is_empty());
+    }
+    #[test]
+    #[should_panic = "resolution must be positive and finite"]
+    fn cluster_binned_zero_resolution() {
+        OwnedClusterList::from_values_binned([1.], 0.);
+    }
+    #[test]
+    #[should_panic = "resolution must be positive and finite"]
+    fn cluster_binned_negative_resolution() {
+        OwnedClusterList::from_values_binned([1.], -0.5);
+    }
+    #[test]
+    #[should_panic = "resolution must be positive and finite"]
+    fn cluster_binned_nan_resolution() {
+        OwnedClusterList::from_values_binned([1.], f64::NAN);
+    }
+    #[test]
+    fn cluster_extremes() {
+        let list = [(3., 2), (-1., 1), (10., 0), (f64::NAN, 1), (2., 4)];
+        let list = ClusterList::new(&list[..]);
+        assert_eq!(list.min(), Some(-1.));
+        assert_eq!(list.max(), Some(3.));
+        assert_eq!(list.range(), Some(4.));
+        assert!(ClusterList::new(&[]).min().is_none());
+
+        let list = [(3., 2), (-1., 1), (2., 4)];
+        assert_eq!(ClusterList::new(&list).sum_squares(), 35.);
+    }
+    #[test]
+    fn cluster_split() {
+        let list = [(1., 2), (2., 3), (3., 1), (4., 4)];
+        let list = ClusterList::new(&list);
+        assert_eq!(*list.split_start(3), [(1., 2), (2., 1)]);
+        assert_eq!(*list.split_end(6), [(2., 1), (3., 1), (4., 4)]);
+        assert_eq!(*list.split_end(2), [(4., 2)]);
+        assert_eq!(*list.split_end(10), *list.split_start(10));
+    }
+    #[test]
+    fn mode() {
+        let empty = mode_cluster(&ClusterList::new(&[]));
+        assert_eq!(
+            empty,
+            ModeOutput {
+                modes: vec![],
+                count: 0
+            }
+        );
+        assert!(!empty.is_multimodal());
+
+        let single = mode_cluster(&ClusterList::new(&[(4., 5)]));
+        assert_eq!(single.modes, [4.]);
+        assert_eq!(single.count, 5);
+        assert!(!single.is_multimodal());
+
+        // clusters of the same value are summed
+        let tied = mode_cluster(&ClusterList::new(&[
+            (3., 2),
+            (2., 1),
+            (0.5, 1),
+            (1., 2),
+            (2., 1),
+        ]));
+        assert_eq!(tied.modes, [1., 2., 3.]);
+        assert_eq!(tied.count, 2);
+        assert!(tied.is_multimodal());
+    }
+    #[test]
+    fn mode_binned() {
+        assert!(mode_cluster_binned(&ClusterList::new(&[]), 1.)
+            .modes
+            .is_empty());
+
+        let single = mode_cluster_binned(&ClusterList::new(&[(0.3, 4)]), 0.5);
+        assert_eq!(single.modes, [0.25]);
+        assert_eq!(single.count, 4);
+
+        // a value on a boundary belongs to the bin above it
+        let list = [(0.5, 1), (0.99, 1), (0.49, 1), (-0.5, 1), (-0.01, 1)];
+        let modes = mode_cluster_binned(&ClusterList::new(&list), 0.5);
+        assert_eq!(modes.modes, [-0.25, 0.75]);
+        assert_eq!(modes.count, 2);
+
+        let list = [(1., 2), (0.999, 1), (-0.5, 3), (2.1, 1), (1.4, 1)];
+        let modes = mode_cluster_binned(&ClusterList::new(&list), 0.5);
+        assert_eq!(modes.modes, [-0.25, 1.25]);
+        assert_eq!(modes.count, 3);
+    }
+    #[test]
+    fn mode_binned_non_finite() {
+        let list = [
+            (f64::NAN, 5),
+            (f64::INFINITY, 5),
+            (f64::NEG_INFINITY, 5),
+            (1e300, 5),
+            (0.2, 2),
+            (0.7, 1),
+        ];
+        let modes = mode_cluster_binned(&ClusterList::new(&list), 0.5);
+        assert_eq!(modes.modes, [0.25]);
+        assert_eq!(modes.count, 2);
+    }
+    #[test]
+    #[should_panic = "bin_width must be positive and finite"]
+    fn mode_binned_zero_width() {
+        mode_cluster_binned(&ClusterList::new(&[(1., 1)]), 0.);
+    }
+    #[test]
+    #[should_panic = "bin_width must be positive and finite"]
+    fn mode_binned_infinite_width() {
+        mode_cluster_binned(&ClusterList::new(&[(1., 1)]), f64::INFINITY);
+    }
+    #[test]
+    fn z_score() {
+        // mean 3, sample variance (4 + 4 + 1 + 9) / 3 = 6
+        let values = [1., 1., 4., 6.];
+        let sd = math::sqrt(6.);
+        let expected = [-2. / sd, -2. / sd, 1. / sd, 3. / sd];
+        let z = z_scores(&values);
+        for (z, expected) in z.iter().zip(expected) {
+            assert!((z - expected).abs() < 1e-12, "{z} != {expected}");
         }
-        #[cfg(not(feature = "percentile-rand"))]
+        let mut in_place = values;
+        standardize_in_place(&mut in_place);
+        assert_eq!(in_place[..], z[..]);
+
+        let list = z_scores_cluster(&ClusterList::new(&[(1., 2), (4., 1), (6., 1)]));
+        for ((z, count), (expected, expected_count)) in
+            list.iter().zip([(-2. / sd, 2), (1. / sd, 1), (3. / sd, 1)])
         {
-            cluster::percentile(values, target, &mut cluster::pivot_fn::middle())
+            assert!((z - expected).abs() < 1e-12, "{z} != {expected}");
+            assert_eq!(*count, expected_count);
         }
+
+        assert!(z_scores(&[]).is_empty());
     }
-    let lower = if values.borrow().len() >= 4 {
-        Some(percentile(values, Fraction::new(1, 4)).resolve())
-    } else {
-        None
-    };
-    let higher = if values.borrow().len() >= 4 {
-        Some(percentile(values, Fraction::new(3, 4)).resolve())
-    } else {
-        None
-    };
-    PercentilesOutput {
-        median: cluster::median(values).resolve(),
-        lower_quadrille: lower,
-        higher_quadrille: higher,
+    #[test]
+    fn z_score_constant() {
+        // the mean of these isn't exactly 0.1
+        assert_eq!(z_scores(&[0.1; 3]), [0.; 3]);
+        assert_eq!(z_scores(&[5.]), [0.]);
+        let mut values = [0.7; 10];
+        standardize_in_place(&mut values);
+        assert_eq!(values, [0.; 10]);
+        assert_eq!(
+            *z_scores_cluster(&ClusterList::new(&[(0.1, 3), (0.1, 4)])),
+            [(0., 3), (0., 4)]
+        );
+    }
+    #[test]
+    fn statistics_ext() {
+        let data = [2., 4., 4., 4., 5., 5., 7., 9.];
+        assert_eq!(data.mean(), 5.);
+        // sqrt(32 / 7)
+        assert!((data.std_dev() - 2.138_089_935_299_395).abs() < 1e-12);
+        assert_eq!(data.median(), 4.5);
+        assert_eq!(data.iter().copied().std_dev(), data.std_dev());
+        assert_eq!(data.iter().copied().percentile(Fraction::new(1, 4)), 4.);
+        assert_eq!([3.].std_dev(), 0.);
+
+        let empty: [f64; 0] = [];
+        assert!(empty.mean().is_nan());
+        assert!(empty.std_dev().is_nan());
+        assert!(empty.median().is_nan());
+        assert!(empty.iter().copied().median().is_nan());
+    }
+    #[test]
+    fn duration_statistics_ext() {
+        let data = [2, 4, 4, 4, 5, 5, 7, 9].map(Duration::from_secs);
+        assert_eq!(data.mean(), Some(Duration::from_secs(5)));
+        // sqrt(32 / 7) s, truncated to nanoseconds
+        assert_eq!(data.std_dev(), Some(Duration::from_nanos(2_138_089_935)));
+        assert_eq!(data.median(), Some(Duration::from_millis(4500)));
+        assert_eq!(data.iter().copied().std_dev(), data.std_dev());
+        assert_eq!(
+            data.iter().copied().min_duration(),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(data.max_duration(), Some(Duration::from_secs(9)));
+        assert_eq!([Duration::from_secs(3)].std_dev(), Some(Duration::ZERO));
+
+        let empty: [Duration; 0] = [];
+        assert_eq!(empty.mean(), None);
+        assert_eq!(empty.std_dev(), None);
+        assert_eq!(empty.median(), None);
+        assert_eq!(empty.iter().copied().mean(), None);
+        assert_eq!(empty.min_duration(), None);
+    }
+    #[test]
+    fn duration_std_dev_overflow() {
+        // the squared deviations of these overflow a `u128`
+        let data = [Duration::ZERO, Duration::MAX];
+        let std_dev = data.std_dev().unwrap().as_secs_f64();
+        let expected = Duration::MAX.as_secs_f64() / core::f64::consts::SQRT_2;
+        assert!(
+            (std_dev / expected - 1.).abs() < 1e-9,
+            "{std_dev} != {expected}"
+        );
+
+        let data = [Duration::MAX; 3];
+        assert_eq!(data.std_dev(), Some(Duration::ZERO));
+        assert_eq!(data.mean(), Some(Duration::MAX));
     }
 }
@@ -0,0 +1,1004 @@
+//! Incremental (online) statistics, updated one value at a time.
+//!
+//! These are useful when the data arrives continuously, e.g. when following a log file, or when
+//! the data is split across several machines. All accumulators are mergeable, so you can
+//! calculate the statistics of parts in parallel and [`Accumulator::merge`] them afterwards.
+//!
+//! [`Cache`] keeps one accumulator per key (e.g. column or group) and tracks which have changed,
+//! so only those need to be recomputed and reported.
+//!
+//! [`TDigest`] estimates quantiles, and unlike exact percentiles, digests of different parts can
+//! be merged.
+//!
+//! [`Sketch`] can be saved to a file and merged with others later.
+//!
+//! [`TwoPassPolynomial`] fits polynomials to data larger than memory, by reading it twice.
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+use crate::StandardDeviationOutput;
+
+/// Something which incrementally summarizes items.
+pub trait Accumulator {
+    /// The type of the items added.
+    type Item;
+    /// Adds `item` to the summary.
+    fn push(&mut self, item: Self::Item);
+    /// Merges `other` into `self`.
+    /// The result is the same (up to floating point errors) as if all the items pushed to `other`
+    /// were pushed to `self`.
+    fn merge(&mut self, other: &Self);
+}
+
+/// Count, mean, variance, min, and max, updated in `O(1)` per value.
+///
+/// Uses [Welford's algorithm](https://en.wikipedia.org/wiki/Algorithms_for_calculating_variance#Welford's_online_algorithm),
+/// which is numerically stable, unlike keeping the sum of squares.
+/// Merging uses the parallel variant by Chan et al.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunningStats {
+    count: usize,
+    mean: f64,
+    /// Sum of squared differences from the mean.
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+impl Default for RunningStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl RunningStats {
+    /// Creates an empty summary.
+    pub fn new() -> Self {
+        Self {
+            count: 0,
+            mean: 0.,
+            m2: 0.,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+    /// Creates a summary from its raw parts.
+    /// `m2` is the sum of squared differences from the mean.
+    ///
+    /// Used to restore a summary previously saved using the getters.
+    pub fn from_parts(count: usize, mean: f64, m2: f64, min: f64, max: f64) -> Self {
+        Self {
+            count,
+            mean,
+            m2,
+            min,
+            max,
+        }
+    }
+    /// The count of values.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+    /// If no values have been added.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+    /// The mean of the values. `NaN` if empty.
+    pub fn mean(&self) -> f64 {
+        if self.is_empty() {
+            f64::NAN
+        } else {
+            self.mean
+        }
+    }
+    /// The sum of the values.
+    pub fn sum(&self) -> f64 {
+        self.mean * self.count as f64
+    }
+    /// The sum of squared differences from the mean.
+    pub fn m2(&self) -> f64 {
+        self.m2
+    }
+    /// The sample variance (divided by `n - 1`), like [`crate::standard_deviation`].
+    pub fn variance(&self) -> f64 {
+        self.m2 / (self.count.max(2) - 1) as f64
+    }
+    /// The sample standard deviation and the mean.
+    pub fn standard_deviation(&self) -> StandardDeviationOutput<f64> {
+        StandardDeviationOutput {
+            standard_deviation: self.variance().sqrt(),
+            mean: self.mean(),
+        }
+    }
+    /// The smallest value. Infinity if empty.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+    /// The largest value. Negative infinity if empty.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+}
+impl Accumulator for RunningStats {
+    type Item = f64;
+    fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+    fn merge(&mut self, other: &Self) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count * other.count) as f64 / count as f64;
+        self.count = count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+}
+impl Extend<f64> for RunningStats {
+    fn extend<T: IntoIterator<Item = f64>>(&mut self, iter: T) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+/// A linear regression, updated in `O(1)` per point.
+///
+/// Gives the same line as [`crate::regression::OlsEstimator`], but keeps the co-moments of the
+/// points (like [`RunningStats`]) instead of the points themselves.
+#[cfg(feature = "regression")]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct IncrementalLinear {
+    count: usize,
+    mean_x: f64,
+    mean_y: f64,
+    m2_x: f64,
+    m2_y: f64,
+    /// Sum of the products of the differences from the means.
+    c_xy: f64,
+}
+#[cfg(feature = "regression")]
+impl IncrementalLinear {
+    /// Creates an empty regression.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The count of points.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+    /// If no points have been added.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+    /// The least squares line. Contains `NaN` if there are less than 2 distinct predictors.
+    pub fn coefficients(&self) -> crate::regression::LinearCoefficients {
+        let k = self.c_xy / self.m2_x;
+        crate::regression::LinearCoefficients {
+            k,
+            m: self.mean_y - k * self.mean_x,
+        }
+    }
+    /// The R² of [`Self::coefficients`].
+    /// Equal to [`crate::regression::Determination::determination`] of the points.
+    pub fn determination(&self) -> f64 {
+        let r2 = self.c_xy * self.c_xy / (self.m2_x * self.m2_y);
+        if r2.is_nan() {
+            // all outcomes are the same, which the line perfectly fits
+            1.
+        } else {
+            r2
+        }
+    }
+}
+#[cfg(feature = "regression")]
+impl Accumulator for IncrementalLinear {
+    type Item = (f64, f64);
+    fn push(&mut self, (x, y): (f64, f64)) {
+        self.count += 1;
+        let n = self.count as f64;
+        let dx = x - self.mean_x;
+        let dy = y - self.mean_y;
+        self.mean_x += dx / n;
+        self.mean_y += dy / n;
+        self.m2_x += dx * (x - self.mean_x);
+        self.m2_y += dy * (y - self.mean_y);
+        self.c_xy += dx * (y - self.mean_y);
+    }
+    fn merge(&mut self, other: &Self) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let factor = (self.count * other.count) as f64 / count as f64;
+        let dx = other.mean_x - self.mean_x;
+        let dy = other.mean_y - self.mean_y;
+        self.mean_x += dx * other.count as f64 / count as f64;
+        self.mean_y += dy * other.count as f64 / count as f64;
+        self.m2_x += other.m2_x + dx * dx * factor;
+        self.m2_y += other.m2_y + dy * dy * factor;
+        self.c_xy += other.c_xy + dx * dy * factor;
+        self.count = count;
+    }
+}
+
+/// A least squares polynomial regression of data too large to keep in memory, computed in two
+/// passes over the points.
+///
+/// The first pass summarizes the predictors and outcomes in [`RunningStats`]. These are used to
+/// center and scale the predictors to `[-1, 1]` and center the outcomes, which keeps the normal
+/// equations well-conditioned. The second pass [pushes](Accumulator::push) each point, which
+/// adds it to the normal equations in `O(degree)`. Memory use is `O(degree)`, independent of
+/// the count of points.
+///
+/// See [`TwoPassPolynomial::fit`] to run both passes.
+#[cfg(feature = "regression")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwoPassPolynomial {
+    degree: usize,
+    center: f64,
+    scale: f64,
+    outcome_mean: f64,
+    count: usize,
+    /// Σ zʲ for j in `0..=2 * degree`, where z is the scaled predictor.
+    powers: Vec<f64>,
+    /// Σ zʲ y for j in `0..=degree`, where y is the centered outcome.
+    moments: Vec<f64>,
+    /// Σ y², where y is the centered outcome.
+    sum_squares: f64,
+}
+#[cfg(feature = "regression")]
+impl TwoPassPolynomial {
+    /// Creates an empty regression, given the summaries of the first pass.
+    pub fn new(degree: usize, predictors: &RunningStats, outcomes: &RunningStats) -> Self {
+        let half_range = (predictors.max() - predictors.min()) / 2.;
+        Self {
+            degree,
+            center: (predictors.max() + predictors.min()) / 2.,
+            scale: if half_range > 0. && half_range.is_finite() {
+                half_range
+            } else {
+                1.
+            },
+            outcome_mean: outcomes.mean(),
+            count: 0,
+            powers: vec![0.; 2 * degree + 1],
+            moments: vec![0.; degree + 1],
+            sum_squares: 0.,
+        }
+    }
+    /// Runs both passes over the points returned by `points`, which is called twice.
+    ///
+    /// Returns [`None`] if there are no points.
+    pub fn fit<I: Iterator<Item = (f64, f64)>>(
+        mut points: impl FnMut() -> I,
+        degree: usize,
+    ) -> Option<Self> {
+        let mut predictors = RunningStats::new();
+        let mut outcomes = RunningStats::new();
+        for (x, y) in points() {
+            predictors.push(x);
+            outcomes.push(y);
+        }
+        if predictors.is_empty() {
+            return None;
+        }
+        let mut regression = Self::new(degree, &predictors, &outcomes);
+        points().for_each(|point| regression.push(point));
+        Some(regression)
+    }
+    /// The count of points in the second pass.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+    /// If no points have been added in the second pass.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+    /// The degree of the polynomial.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+    /// The coefficients of the scaled predictors and centered outcomes, from the normal
+    /// equations. [`None`] if they have no single solution, e.g. when there are fewer distinct
+    /// predictors than coefficients.
+    fn scaled_coefficients(&self) -> Option<Vec<f64>> {
+        let size = self.degree + 1;
+        let mut matrix: Vec<Vec<f64>> = (0..size)
+            .map(|row| self.powers[row..row + size].to_vec())
+            .collect();
+        let mut rhs = self.moments.clone();
+        // Gaussian elimination with partial pivoting
+        for column in 0..size {
+            let pivot = (column..size).max_by(|a, b| {
+                crate::F64OrdHash::f64_cmp(matrix[*a][column].abs(), matrix[*b][column].abs())
+            })?;
+            if matrix[pivot][column].abs() < 1e-12 * self.count as f64 {
+                return None;
+            }
+            matrix.swap(column, pivot);
+            rhs.swap(column, pivot);
+            let pivot_row = matrix[column].clone();
+            for row in column + 1..size {
+                let factor = matrix[row][column] / pivot_row[column];
+                for (v, pivot) in matrix[row][column..].iter_mut().zip(&pivot_row[column..]) {
+                    *v -= factor * pivot;
+                }
+                rhs[row] -= factor * rhs[column];
+            }
+        }
+        let mut solution = vec![0.; size];
+        for row in (0..size).rev() {
+            let rest: f64 = (row + 1..size).map(|i| matrix[row][i] * solution[i]).sum();
+            solution[row] = (rhs[row] - rest) / matrix[row][row];
+        }
+        Some(solution)
+    }
+    /// The least squares polynomial. [`None`] if there's no single solution, e.g. when there
+    /// are fewer distinct predictors than coefficients.
+    pub fn coefficients(&self) -> Option<crate::regression::PolynomialCoefficients> {
+        let scaled = self.scaled_coefficients()?;
+        // expand Σ bⱼ ((x - c) / s)ʲ using the binomial theorem
+        let mut coefficients = vec![0.; self.degree + 1];
+        for (j, b) in scaled.iter().enumerate() {
+            let b = b / self.scale.powi(j as i32);
+            let mut binomial = 1.;
+            for (i, coefficient) in coefficients.iter_mut().enumerate().take(j + 1) {
+                *coefficient += b * binomial * (-self.center).powi((j - i) as i32);
+                binomial = binomial * (j - i) as f64 / (i + 1) as f64;
+            }
+        }
+        coefficients[0] += self.outcome_mean;
+        Some(coefficients.into())
+    }
+    /// The R² of [`Self::coefficients`]. `NaN` if there's no single solution.
+    pub fn determination(&self) -> f64 {
+        let Some(b) = self.scaled_coefficients() else {
+            return f64::NAN;
+        };
+        let size = self.degree + 1;
+        // Σ(y - ŷ)² = Σy² - 2bᵀZᵀy + bᵀZᵀZb
+        let explained: f64 = (0..size).map(|i| b[i] * self.moments[i]).sum();
+        let quadratic: f64 = (0..size)
+            .flat_map(|i| (0..size).map(move |j| (i, j)))
+            .map(|(i, j)| b[i] * b[j] * self.powers[i + j])
+            .sum();
+        let residuals = (self.sum_squares - 2. * explained + quadratic).max(0.);
+        let total = self.sum_squares - self.moments[0] * self.moments[0] / self.count as f64;
+        if total > 0. {
+            1. - residuals / total
+        } else {
+            // all outcomes are the same, which the polynomial perfectly fits
+            1.
+        }
+    }
+}
+#[cfg(feature = "regression")]
+impl Accumulator for TwoPassPolynomial {
+    type Item = (f64, f64);
+    fn push(&mut self, (x, y): (f64, f64)) {
+        let z = (x - self.center) / self.scale;
+        let y = y - self.outcome_mean;
+        self.count += 1;
+        let mut power = 1.;
+        for (j, sum) in self.powers.iter_mut().enumerate() {
+            *sum += power;
+            if let Some(moment) = self.moments.get_mut(j) {
+                *moment += power * y;
+            }
+            power *= z;
+        }
+        self.sum_squares += y * y;
+    }
+    /// # Panics
+    ///
+    /// Panics if `other` was created from another first pass, or with another degree.
+    fn merge(&mut self, other: &Self) {
+        assert!(
+            self.degree == other.degree
+                && self.center == other.center
+                && self.scale == other.scale
+                && self.outcome_mean == other.outcome_mean,
+            "merged regressions must be created from the same first pass"
+        );
+        self.count += other.count;
+        for (a, b) in self.powers.iter_mut().zip(&other.powers) {
+            *a += b;
+        }
+        for (a, b) in self.moments.iter_mut().zip(&other.moments) {
+            *a += b;
+        }
+        self.sum_squares += other.sum_squares;
+    }
+}
+
+/// A [t-digest](https://arxiv.org/abs/1902.04023) by Dunning & Ertl: approximate quantiles in
+/// bounded memory, which can be merged with other digests.
+///
+/// Values are grouped into centroids (a mean and a weight). Centroids near the extremes are kept
+/// small, so the tails (e.g. p99) are more accurate than the middle. The count of centroids is
+/// at most about [`Self::compression`].
+///
+/// Use this to estimate quantiles of data split across shards or machines: create a digest per
+/// part, [merge](Accumulator::merge) them, and query the [quantiles](Self::quantile).
+#[derive(Debug, Clone)]
+pub struct TDigest {
+    compression: f64,
+    /// `(mean, weight)`, sorted by the mean.
+    centroids: Vec<(f64, f64)>,
+    /// Values and centroids which haven't been merged into [`Self::centroids`] yet.
+    buffer: Vec<(f64, f64)>,
+    weight: f64,
+    min: f64,
+    max: f64,
+}
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_COMPRESSION)
+    }
+}
+impl TDigest {
+    /// The compression used by [`Self::default`].
+    pub const DEFAULT_COMPRESSION: f64 = 100.;
+
+    /// Creates an empty digest. A higher `compression` uses more centroids, which is more
+    /// accurate but uses more memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `compression` isn't positive.
+    pub fn new(compression: f64) -> Self {
+        assert!(compression > 0., "compression must be positive");
+        Self {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            weight: 0.,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+    /// Creates a digest from its raw parts: the `(mean, weight)` of the centroids, and the
+    /// smallest and largest value.
+    ///
+    /// Used to restore a digest previously saved using [`Self::centroids`].
+    pub fn from_parts(compression: f64, centroids: Vec<(f64, f64)>, min: f64, max: f64) -> Self {
+        let mut me = Self::new(compression);
+        me.weight = centroids.iter().map(|(_, weight)| weight).sum();
+        me.buffer = centroids;
+        me.compress();
+        me.min = min;
+        me.max = max;
+        me
+    }
+    pub fn compression(&self) -> f64 {
+        self.compression
+    }
+    /// The count of values.
+    pub fn len(&self) -> usize {
+        self.weight.round() as usize
+    }
+    /// If no values have been added.
+    pub fn is_empty(&self) -> bool {
+        self.weight == 0.
+    }
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+    /// The `(mean, weight)` of the centroids, sorted by the mean.
+    pub fn centroids(&self) -> Cow<'_, [(f64, f64)]> {
+        if self.buffer.is_empty() {
+            Cow::Borrowed(&self.centroids)
+        } else {
+            let mut all = self.centroids.clone();
+            all.extend_from_slice(&self.buffer);
+            Cow::Owned(self.merge_centroids(all))
+        }
+    }
+    /// Merges the buffered values into the centroids.
+    ///
+    /// This is done automatically when the buffer is full.
+    pub fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all = std::mem::take(&mut self.centroids);
+        all.append(&mut self.buffer);
+        self.centroids = self.merge_centroids(all);
+    }
+    /// The scale function k₁, which limits the size of centroids near the quantile `q`.
+    fn scale(&self, q: f64) -> f64 {
+        self.compression / (2. * std::f64::consts::PI) * (2. * q - 1.).clamp(-1., 1.).asin()
+    }
+    fn merge_centroids(&self, mut all: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+        all.sort_by(|a, b| crate::F64OrdHash::f64_cmp(a.0, b.0));
+        let total: f64 = all.iter().map(|(_, weight)| weight).sum();
+        let mut merged = Vec::with_capacity(self.compression as usize);
+        let mut iter = all.into_iter();
+        let Some(mut current) = iter.next() else {
+            return merged;
+        };
+        let mut weight_before = 0.;
+        let mut k_left = self.scale(0.);
+        for next in iter {
+            let q = (weight_before + current.1 + next.1) / total;
+            if self.scale(q) - k_left <= 1. {
+                let weight = current.1 + next.1;
+                current.0 += (next.0 - current.0) * next.1 / weight;
+                current.1 = weight;
+            } else {
+                weight_before += current.1;
+                k_left = self.scale(weight_before / total);
+                merged.push(current);
+                current = next;
+            }
+        }
+        merged.push(current);
+        merged
+    }
+    /// The estimated value at `quantile` (in `[0, 1]`), or [`None`] if the digest is empty.
+    ///
+    /// Interpolates linearly between the centroids, and between the outermost centroids and the
+    /// [`Self::min`] and [`Self::max`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quantile` is outside `[0, 1]`.
+    pub fn quantile(&self, quantile: f64) -> Option<f64> {
+        assert!(
+            (0.0..=1.0).contains(&quantile),
+            "quantile must be in [0, 1]"
+        );
+        let centroids = self.centroids();
+        let first = centroids.first()?;
+        let last = centroids[centroids.len() - 1];
+        let total = self.weight;
+        let rank = quantile * total;
+
+        let value = if rank <= first.1 / 2. {
+            let t = rank / (first.1 / 2.);
+            self.min + t * (first.0 - self.min)
+        } else if rank >= total - last.1 / 2. {
+            let t = (rank - (total - last.1 / 2.)) / (last.1 / 2.);
+            last.0 + t * (self.max - last.0)
+        } else {
+            let mut cumulative = 0.;
+            let mut value = last.0;
+            for pair in centroids.windows(2) {
+                let (left, right) = (pair[0], pair[1]);
+                let left_center = cumulative + left.1 / 2.;
+                let right_center = cumulative + left.1 + right.1 / 2.;
+                if rank <= right_center {
+                    let t = (rank - left_center) / (right_center - left_center);
+                    value = left.0 + t * (right.0 - left.0);
+                    break;
+                }
+                cumulative += left.1;
+            }
+            value
+        };
+        Some(value.clamp(self.min, self.max))
+    }
+}
+impl Accumulator for TDigest {
+    type Item = f64;
+    /// `NaN`s are ignored.
+    fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.buffer.push((value, 1.));
+        self.weight += 1.;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if self.buffer.len() >= (self.compression as usize).max(10) * 5 {
+            self.compress();
+        }
+    }
+    fn merge(&mut self, other: &Self) {
+        self.buffer.extend_from_slice(&other.centroids);
+        self.buffer.extend_from_slice(&other.buffer);
+        self.weight += other.weight;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.compress();
+    }
+}
+impl Extend<f64> for TDigest {
+    fn extend<T: IntoIterator<Item = f64>>(&mut self, iter: T) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+/// Digests are equal if they have the same centroids, compression, and extremes, regardless of
+/// whether the values have been [compressed](TDigest::compress).
+impl PartialEq for TDigest {
+    fn eq(&self, other: &Self) -> bool {
+        self.compression == other.compression
+            && self.weight == other.weight
+            && self.min == other.min
+            && self.max == other.max
+            && self.centroids() == other.centroids()
+    }
+}
+
+/// One [`Accumulator`] per key, which tracks which keys have changed.
+///
+/// Use this to only recompute and report the statistics of the columns/groups which changed,
+/// when following a continuous stream of data.
+#[derive(Debug, Clone)]
+pub struct Cache<K, A> {
+    entries: HashMap<K, A>,
+    dirty: BTreeSet<K>,
+}
+impl<K: Hash + Ord + Clone, A: Accumulator + Default> Default for Cache<K, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<K: Hash + Ord + Clone, A: Accumulator + Default> Cache<K, A> {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            dirty: BTreeSet::new(),
+        }
+    }
+    /// Adds `item` to the accumulator of `key`, creating it if needed, and marks it as changed.
+    pub fn push(&mut self, key: K, item: A::Item) {
+        if !self.dirty.contains(&key) {
+            self.dirty.insert(key.clone());
+        }
+        self.entries.entry(key).or_default().push(item);
+    }
+    /// Merges `other` into the accumulator of `key` and marks it as changed.
+    pub fn merge(&mut self, key: K, other: &A) {
+        if !self.dirty.contains(&key) {
+            self.dirty.insert(key.clone());
+        }
+        self.entries.entry(key).or_default().merge(other);
+    }
+    /// Gets the accumulator of `key`.
+    pub fn get(&self, key: &K) -> Option<&A> {
+        self.entries.get(key)
+    }
+    /// Iterates all the keys and their accumulators, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &A)> {
+        self.entries.iter()
+    }
+    /// If any key has changed since the last call to [`Self::take_changed`].
+    pub fn has_changed(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+    /// Returns the keys (in ascending order) which changed since the last call, and marks them
+    /// as unchanged.
+    pub fn take_changed(&mut self) -> Vec<K> {
+        std::mem::take(&mut self.dirty).into_iter().collect()
+    }
+}
+
+/// A mergeable summary which can be saved to and loaded from text, to combine the statistics of
+/// data processed in different places (map-reduce style).
+///
+/// The text format is line based, with a header, followed by `<name> <value>` pairs, and the
+/// `centroid <mean> <weight>` of the [`TDigest`]:
+///
+/// ```text
+/// std-dev sketch v2
+/// count 3
+/// mean 2
+/// m2 2
+/// min 1
+/// max 3
+/// compression 100
+/// centroid 1 1
+/// centroid 2 1
+/// centroid 3 1
+/// ```
+///
+/// Floats are written with enough digits to be read back exactly. Sketches of the first version
+/// (with the header `std-dev sketch v1`) have no digest, so the quantiles are unknown.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Sketch {
+    /// The running statistics of the values.
+    pub stats: RunningStats,
+    /// The quantiles of the values.
+    pub digest: TDigest,
+}
+impl Sketch {
+    const HEADER: &'static str = "std-dev sketch v2";
+    const HEADER_V1: &'static str = "std-dev sketch v1";
+
+    /// Creates an empty sketch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+impl Accumulator for Sketch {
+    type Item = f64;
+    fn push(&mut self, value: f64) {
+        self.stats.push(value);
+        self.digest.push(value);
+    }
+    fn merge(&mut self, other: &Self) {
+        self.stats.merge(&other.stats);
+        self.digest.merge(&other.digest);
+    }
+}
+impl Extend<f64> for Sketch {
+    fn extend<T: IntoIterator<Item = f64>>(&mut self, iter: T) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+impl fmt::Display for Sketch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = &self.stats;
+        writeln!(f, "{}", Self::HEADER)?;
+        writeln!(f, "count {}", s.len())?;
+        writeln!(f, "mean {:?}", s.mean)?;
+        writeln!(f, "m2 {:?}", s.m2)?;
+        writeln!(f, "min {:?}", s.min)?;
+        writeln!(f, "max {:?}", s.max)?;
+        writeln!(f, "compression {:?}", self.digest.compression())?;
+        for (mean, weight) in self.digest.centroids().iter() {
+            writeln!(f, "centroid {mean:?} {weight:?}")?;
+        }
+        Ok(())
+    }
+}
+/// The error returned when parsing a [`Sketch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSketchError {
+    /// The line (starting at 1) where the error occurred.
+    pub line: usize,
+    /// What was wrong.
+    pub reason: &'static str,
+}
+impl fmt::Display for ParseSketchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid sketch on line {}: {}", self.line, self.reason)
+    }
+}
+impl std::error::Error for ParseSketchError {}
+impl FromStr for Sketch {
+    type Err = ParseSketchError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+        let version_1 = match lines.next() {
+            Some((_, header)) if header.trim() == Self::HEADER => false,
+            Some((_, header)) if header.trim() == Self::HEADER_V1 => true,
+            Some((idx, _)) => {
+                return Err(ParseSketchError {
+                    line: idx + 1,
+                    reason: "expected the header `std-dev sketch v2`",
+                })
+            }
+            None => {
+                return Err(ParseSketchError {
+                    line: 1,
+                    reason: "empty sketch",
+                })
+            }
+        };
+        let mut count = None;
+        let (mut mean, mut m2, mut min, mut max) = (None, None, None, None);
+        let mut compression = None;
+        let mut centroids = Vec::new();
+        for (idx, line) in lines {
+            let err = |reason| ParseSketchError {
+                line: idx + 1,
+                reason,
+            };
+            let (name, value) = line
+                .trim()
+                .split_once(' ')
+                .ok_or_else(|| err("expected `<name> <value>`"))?;
+            let value = value.trim();
+            if name == "count" {
+                count = Some(value.parse().map_err(|_| err("invalid count"))?);
+                continue;
+            }
+            if name == "centroid" && !version_1 {
+                let centroid = value
+                    .split_once(' ')
+                    .and_then(|(mean, weight)| {
+                        Some((mean.parse().ok()?, weight.trim().parse().ok()?))
+                    })
+                    .ok_or_else(|| err("expected `centroid <mean> <weight>`"))?;
+                centroids.push(centroid);
+                continue;
+            }
+            let value: f64 = value.parse().map_err(|_| err("invalid number"))?;
+            match name {
+                "mean" => mean = Some(value),
+                "m2" => m2 = Some(value),
+                "min" => min = Some(value),
+                "max" => max = Some(value),
+                "compression" if !version_1 && value > 0. => compression = Some(value),
+                _ => return Err(err("unknown field")),
+            }
+        }
+        let line = s.lines().count();
+        let missing = |reason| ParseSketchError { line, reason };
+        let stats = RunningStats::from_parts(
+            count.ok_or_else(|| missing("missing count"))?,
+            mean.ok_or_else(|| missing("missing mean"))?,
+            m2.ok_or_else(|| missing("missing m2"))?,
+            min.ok_or_else(|| missing("missing min"))?,
+            max.ok_or_else(|| missing("missing max"))?,
+        );
+        let digest = if version_1 {
+            TDigest::default()
+        } else {
+            let compression = compression.ok_or_else(|| missing("missing compression"))?;
+            TDigest::from_parts(compression, centroids, stats.min(), stats.max())
+        };
+        Ok(Self { stats, digest })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_stats() {
+        let values = [2., 4., 4., 4., 5., 5., 7., 9.];
+        let mut stats = RunningStats::new();
+        stats.extend(values.iter().copied());
+        let expected = crate::standard_deviation(&values);
+        assert!((stats.mean() - expected.mean).abs() < 1e-12);
+        assert!(
+            (stats.standard_deviation().standard_deviation - expected.standard_deviation).abs()
+                < 1e-12
+        );
+        assert_eq!(stats.min(), 2.);
+        assert_eq!(stats.max(), 9.);
+
+        let mut first = RunningStats::new();
+        first.extend(values[..3].iter().copied());
+        let mut second = RunningStats::new();
+        second.extend(values[3..].iter().copied());
+        first.merge(&second);
+        assert_eq!(first.len(), stats.len());
+        assert!((first.variance() - stats.variance()).abs() < 1e-12);
+    }
+    #[cfg(feature = "regression")]
+    #[test]
+    fn incremental_linear() {
+        use crate::regression::{Determination, LinearCoefficients};
+        let x = [1., 2., 3., 4., 5., 6.];
+        let y = [2.1, 3.9, 6.2, 7.8, 10.1, 12.];
+        let mut first = IncrementalLinear::new();
+        let mut second = IncrementalLinear::new();
+        for (i, point) in x.iter().copied().zip(y.iter().copied()).enumerate() {
+            if i % 2 == 0 {
+                first.push(point);
+            } else {
+                second.push(point);
+            }
+        }
+        first.merge(&second);
+        let LinearCoefficients { k, m } = first.coefficients();
+        assert!((k - 1.991_428_571_4).abs() < 1e-9);
+        assert!((m - 0.046_666_666_7).abs() < 1e-9);
+        let determination = first.coefficients().determination_slice(&x, &y);
+        assert!((first.determination() - determination).abs() < 1e-12);
+    }
+    #[test]
+    #[cfg(feature = "regression")]
+    fn two_pass_polynomial() {
+        use crate::regression::Predictive;
+
+        let points: Vec<(f64, f64)> = (0..50)
+            .map(|i| {
+                let x = 1000. + i as f64 * 0.5;
+                (
+                    x,
+                    2. * x * x - 3. * x + 4. + if i % 2 == 0 { 0.1 } else { -0.1 },
+                )
+            })
+            .collect();
+        let regression = TwoPassPolynomial::fit(|| points.iter().copied(), 2).unwrap();
+        assert_eq!(regression.len(), 50);
+        let coefficients = regression.coefficients().unwrap();
+        let prediction = coefficients.predict_outcome(1010.);
+        assert!((prediction - (2. * 1010. * 1010. - 3. * 1010. + 4.)).abs() < 0.1);
+        assert!(regression.determination() > 0.999_999);
+
+        // the same line as `IncrementalLinear`
+        let linear = TwoPassPolynomial::fit(|| points.iter().copied(), 1).unwrap();
+        let mut incremental = IncrementalLinear::new();
+        points.iter().for_each(|p| incremental.push(*p));
+        let expected = incremental.coefficients();
+        let coefficients = linear.coefficients().unwrap();
+        assert!((coefficients[1] - expected.k).abs() < 1e-6);
+        assert!((coefficients[0] - expected.m).abs() < 1e-3);
+        assert!((linear.determination() - incremental.determination()).abs() < 1e-9);
+
+        assert!(
+            TwoPassPolynomial::fit(|| [(1., 2.), (1., 3.)].into_iter(), 1)
+                .unwrap()
+                .coefficients()
+                .is_none()
+        );
+    }
+    #[test]
+    fn cache() {
+        let mut cache: Cache<usize, RunningStats> = Cache::new();
+        cache.push(1, 2.);
+        cache.push(0, 1.);
+        cache.push(1, 4.);
+        assert_eq!(cache.take_changed(), [0, 1]);
+        assert!(!cache.has_changed());
+        cache.push(1, 6.);
+        assert_eq!(cache.take_changed(), [1]);
+        assert_eq!(cache.get(&1).unwrap().mean(), 4.);
+    }
+    #[test]
+    fn t_digest() {
+        // a permutation of 0..10_000
+        let values: Vec<f64> = (0..10_000).map(|i| ((i * 7919) % 10_000) as f64).collect();
+        let mut whole = TDigest::default();
+        whole.extend(values.iter().copied());
+        let mut merged = TDigest::default();
+        for part in values.chunks(2500) {
+            let mut digest = TDigest::default();
+            digest.extend(part.iter().copied());
+            merged.merge(&digest);
+        }
+        for digest in [&whole, &merged] {
+            assert_eq!(digest.len(), 10_000);
+            assert!(digest.centroids().len() <= 100);
+            assert_eq!(digest.quantile(0.), Some(0.));
+            assert_eq!(digest.quantile(1.), Some(9999.));
+            assert!((digest.quantile(0.5).unwrap() - 5000.).abs() < 50.);
+            assert!((digest.quantile(0.99).unwrap() - 9900.).abs() < 10.);
+            assert!((digest.quantile(0.001).unwrap() - 10.).abs() < 5.);
+        }
+        assert_eq!(TDigest::default().quantile(0.5), None);
+    }
+    #[test]
+    fn sketch_roundtrip() {
+        let mut sketch = Sketch::new();
+        sketch.extend([0.1, 0.2, 1e300, -3.]);
+        let parsed: Sketch = sketch.to_string().parse().unwrap();
+        assert_eq!(parsed, sketch);
+        assert_eq!(
+            "std-dev sketch v1\ncount 1"
+                .parse::<Sketch>()
+                .unwrap_err()
+                .reason,
+            "missing mean"
+        );
+        let v1: Sketch = "std-dev sketch v1\ncount 1\nmean 1\nm2 0\nmin 1\nmax 1"
+            .parse()
+            .unwrap();
+        assert_eq!(v1.stats.len(), 1);
+        assert_eq!(v1.digest.quantile(0.5), None);
+        assert_eq!(parsed.digest.quantile(0.), Some(-3.));
+    }
+}
@@ -0,0 +1,216 @@
+//! Detection and removal of outliers, values far from the bulk of the data.
+//!
+//! A [`Filter`] gets the [bounds](Filter::bounds) of the values which aren't outliers, using one
+//! of the [`Method`]s and a threshold:
+//!
+//! - [`Method::Iqr`]: outside `[Q1 - t IQR, Q3 + t IQR]` (Tukey's fences), where IQR is the
+//!   interquartile range. The default threshold is `1.5`.
+//! - [`Method::ZScore`]: more than `t` standard deviations from the mean. The default threshold
+//!   is `3`. The mean and standard deviation are themselves pulled by the outliers, so this
+//!   doesn't work well on small datasets.
+//! - [`Method::Mad`]: more than `t` times the [MAD](crate::robust::mad) from the median. The
+//!   default threshold is `3.5`. This is the most robust of the three.
+use std::fmt::{self, Display};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::{percentile::cluster, ClusterList, Fraction, OwnedClusterList};
+
+/// How to tell outliers apart from the rest of the values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Distance from the quartiles, in interquartile ranges.
+    Iqr,
+    /// Distance from the mean, in standard deviations.
+    ZScore,
+    /// Distance from the median, in median absolute deviations.
+    Mad,
+}
+impl Method {
+    /// The threshold commonly used with this method.
+    pub fn default_threshold(self) -> f64 {
+        match self {
+            Self::Iqr => 1.5,
+            Self::ZScore => 3.,
+            Self::Mad => 3.5,
+        }
+    }
+}
+
+/// A [`Method`] and its threshold. See the [module-level documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Filter {
+    pub method: Method,
+    pub threshold: f64,
+}
+impl Filter {
+    /// Uses the [default threshold](Method::default_threshold) of `method`.
+    pub fn new(method: Method) -> Self {
+        Self {
+            method,
+            threshold: method.default_threshold(),
+        }
+    }
+    /// The range of `values` which aren't outliers.
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    pub fn bounds(&self, values: &[f64]) -> RangeInclusive<f64> {
+        let clusters: OwnedClusterList = values.iter().copied().collect();
+        self.bounds_cluster(&clusters.borrow())
+    }
+    /// Same as [`Self::bounds`], but for clustered `values`.
+    ///
+    /// O(m), where m is the number of [`crate::Cluster`]s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    pub fn bounds_cluster(&self, values: &ClusterList) -> RangeInclusive<f64> {
+        assert!(!values.list.is_empty(), "can't find outliers of no values");
+        let t = self.threshold;
+        match self.method {
+            Method::Iqr => {
+                let mut owned = OwnedClusterList::new(values.list.to_vec());
+                let quartiles = crate::percentiles_cluster_list(
+                    &mut owned,
+                    &[Fraction::ONE_QUARTER, Fraction::THREE_QUARTERS],
+                );
+                let iqr = quartiles[1] - quartiles[0];
+                (quartiles[0] - t * iqr)..=(quartiles[1] + t * iqr)
+            }
+            Method::ZScore => {
+                let out = crate::standard_deviation_cluster(values);
+                let distance = t * out.standard_deviation;
+                (out.mean - distance)..=(out.mean + distance)
+            }
+            Method::Mad => {
+                let mut owned = OwnedClusterList::new(values.list.to_vec());
+                let median = cluster::median(&mut owned).resolve();
+                let mut deviations = OwnedClusterList::new(
+                    values
+                        .list
+                        .iter()
+                        .map(|(v, count)| ((v - median).abs(), *count))
+                        .collect(),
+                );
+                let mad = 1.4826 * cluster::median(&mut deviations).resolve();
+                (median - t * mad)..=(median + t * mad)
+            }
+        }
+    }
+    /// Removes the outliers from `values`, and returns how many were removed.
+    ///
+    /// Does nothing if `values` is empty.
+    pub fn remove(&self, values: &mut Vec<f64>) -> usize {
+        if values.is_empty() {
+            return 0;
+        }
+        let bounds = self.bounds(values);
+        let len = values.len();
+        values.retain(|v| bounds.contains(v));
+        len - values.len()
+    }
+    /// Same as [`Self::remove`], but for clustered `values`. The returned count includes the
+    /// counts of the removed clusters.
+    pub fn remove_cluster(&self, values: &mut OwnedClusterList) -> usize {
+        if values.is_empty() {
+            return 0;
+        }
+        let bounds = self.bounds_cluster(&values.borrow());
+        let len = values.len;
+        let mut list = std::mem::take(&mut values.list);
+        list.retain(|(v, _)| bounds.contains(v));
+        *values = OwnedClusterList::new(list);
+        len - values.len
+    }
+}
+impl Default for Filter {
+    /// [`Method::Iqr`] with a threshold of 1.5.
+    fn default() -> Self {
+        Self::new(Method::Iqr)
+    }
+}
+/// Returned from [`Filter::from_str`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseFilterError {
+    /// The method isn't one of `iqr`, `zscore`, or `mad`.
+    Method,
+    /// The threshold isn't a positive number.
+    Threshold,
+}
+impl Display for ParseFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Method => f.write_str("expected one of `iqr`, `zscore`, or `mad`"),
+            Self::Threshold => f.write_str("the threshold must be a positive number"),
+        }
+    }
+}
+impl std::error::Error for ParseFilterError {}
+impl FromStr for Filter {
+    type Err = ParseFilterError;
+    /// Parses `<method>[:<threshold>]`, where the method is `iqr`, `zscore` (or `z`), or `mad`,
+    /// e.g. `iqr` or `mad:3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (method, threshold) = match s.split_once(':') {
+            Some((method, threshold)) => (method, Some(threshold)),
+            None => (s, None),
+        };
+        let method = match method.trim().to_ascii_lowercase().as_str() {
+            "iqr" => Method::Iqr,
+            "zscore" | "z-score" | "z" => Method::ZScore,
+            "mad" => Method::Mad,
+            _ => return Err(ParseFilterError::Method),
+        };
+        let threshold = match threshold {
+            Some(threshold) => threshold
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|t| *t > 0. && t.is_finite())
+                .ok_or(ParseFilterError::Threshold)?,
+            None => method.default_threshold(),
+        };
+        Ok(Self { method, threshold })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove() {
+        let values = [10., 11., 12., 9., 10.5, 11.5, 9.5, 10., 100., -50.];
+        for method in [Method::Iqr, Method::Mad] {
+            let mut kept = values.to_vec();
+            assert_eq!(Filter::new(method).remove(&mut kept), 2, "{method:?}");
+            assert!(kept.iter().all(|v| (9.0..=12.).contains(v)));
+        }
+        // the outliers pull the standard deviation too much
+        let mut kept = values.to_vec();
+        assert_eq!(Filter::new(Method::ZScore).remove(&mut kept), 0);
+        assert_eq!("z:1".parse::<Filter>().unwrap().remove(&mut kept), 2);
+
+        let mut clusters = OwnedClusterList::new(vec![(1., 5), (2., 10), (3., 5), (50., 2)]);
+        assert_eq!(Filter::default().remove_cluster(&mut clusters), 2);
+        assert_eq!(clusters.borrow().len(), 20);
+    }
+    #[test]
+    fn parse() {
+        assert_eq!("mad".parse(), Ok(Filter::new(Method::Mad)));
+        assert_eq!(
+            "IQR:3".parse(),
+            Ok(Filter {
+                method: Method::Iqr,
+                threshold: 3.
+            })
+        );
+        assert_eq!("iqr:-1".parse::<Filter>(), Err(ParseFilterError::Threshold));
+        assert_eq!("sd".parse::<Filter>(), Err(ParseFilterError::Method));
+    }
+}
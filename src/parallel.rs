@@ -0,0 +1,169 @@
+//! Splitting work on large inputs between threads. Enabled by the `parallel` feature, or its
+//! alias `rayon`.
+//!
+//! The work is run on [`rayon`]'s global thread pool.
+//! Inputs shorter than [`MIN_CHUNK`] per thread are processed on the current thread, as sending
+//! them to other threads costs more than it saves for them.
+use rayon::prelude::*;
+
+/// The least count of items processed by each thread.
+const MIN_CHUNK: usize = 1 << 16;
+
+/// The length of the chunks `len` items are split into, with at least `min_chunk` items per
+/// chunk. `len` if it's not worth splitting.
+fn chunk_len(len: usize, min_chunk: usize) -> usize {
+    let threads = rayon::current_num_threads();
+    ((len + threads - 1) / threads).max(min_chunk).max(1)
+}
+
+/// Applies `map` to chunks of `items` on separate threads, and combines the results using
+/// `reduce`.
+pub(crate) fn map_reduce<T: Sync, R: Send>(
+    items: &[T],
+    map: impl Fn(&[T]) -> R + Sync + Send,
+    reduce: impl Fn(R, R) -> R + Sync + Send,
+) -> R {
+    map_reduce_min_chunk(items, MIN_CHUNK, map, reduce)
+}
+/// Same as [`map_reduce`], but with at least `min_chunk` items per thread. Use this when each
+/// item is a lot of work.
+pub(crate) fn map_reduce_min_chunk<T: Sync, R: Send>(
+    items: &[T],
+    min_chunk: usize,
+    map: impl Fn(&[T]) -> R + Sync + Send,
+    reduce: impl Fn(R, R) -> R + Sync + Send,
+) -> R {
+    let chunk_len = chunk_len(items.len(), min_chunk);
+    if chunk_len >= items.len() {
+        return map(items);
+    }
+    items
+        .par_chunks(chunk_len)
+        .map(map)
+        .reduce_with(reduce)
+        .expect("there's at least one chunk")
+}
+
+/// Calls `f` with chunks of `items` on separate threads.
+/// The first argument to `f` is the index of the chunk's first item in `items`.
+#[cfg(feature = "ols")]
+pub(crate) fn for_each_chunk_mut<T: Send>(
+    items: &mut [T],
+    f: impl Fn(usize, &mut [T]) + Sync + Send,
+) {
+    let chunk_len = chunk_len(items.len(), MIN_CHUNK);
+    if chunk_len >= items.len() {
+        f(0, items);
+        return;
+    }
+    items
+        .par_chunks_mut(chunk_len)
+        .enumerate()
+        .for_each(|(idx, chunk)| f(idx * chunk_len, chunk));
+}
+
+/// The median of all the values in `chunks`, ordered by [`F64OrdHash`].
+///
+/// Uses the idea of the [Floyd-Rivest](https://en.wikipedia.org/wiki/Floyd%E2%80%93Rivest_algorithm)
+/// algorithm: the bounds of the median are estimated from a sample, and only the few values
+/// between the bounds are collected (in parallel, one thread per chunk) and selected from.
+/// If the median isn't between the bounds, all values are collected instead.
+///
+/// # Panics
+///
+/// Panics if `chunks` contains no values.
+#[cfg(feature = "regression")]
+pub(crate) fn median_of_chunks(chunks: &[Vec<f64>]) -> f64 {
+    use crate::F64OrdHash;
+    use std::cmp::Ordering;
+
+    fn median_of(mut values: Vec<f64>) -> f64 {
+        crate::percentile::percentile_default_pivot_by(
+            &mut values,
+            crate::Fraction::HALF,
+            &mut |a, b| F64OrdHash::f64_cmp(*a, *b),
+        )
+        .resolve()
+    }
+
+    let len: usize = chunks.iter().map(Vec::len).sum();
+    assert!(len > 0, "can't take the median of no values");
+    if chunks.len() == 1 || len < MIN_CHUNK {
+        return median_of(chunks.concat());
+    }
+    // the ranks of the median (the same if `len` is odd)
+    let (low, high) = ((len - 1) / 2, len / 2);
+
+    let sample_len = ((len as f64).powf(2. / 3.) as usize).clamp(1, len);
+    let stride = len / sample_len;
+    let mut sample: Vec<f64> = chunks.iter().flatten().step_by(stride).copied().collect();
+    sample.par_sort_unstable_by(|a, b| F64OrdHash::f64_cmp(*a, *b));
+    // ~6 standard deviations of the rank of the median in the sample
+    let margin = 3 * (sample.len() as f64).sqrt() as usize + 1;
+    let middle = sample.len() / 2;
+    let lower = sample[middle.saturating_sub(margin)];
+    let upper = sample[(middle + margin).min(sample.len() - 1)];
+
+    let (below, mut candidates) = chunks
+        .par_iter()
+        .map(|chunk| {
+            let mut below = 0;
+            let mut candidates = Vec::new();
+            for v in chunk {
+                if F64OrdHash::f64_cmp(*v, lower) == Ordering::Less {
+                    below += 1;
+                } else if F64OrdHash::f64_cmp(*v, upper) != Ordering::Greater {
+                    candidates.push(*v);
+                }
+            }
+            (below, candidates)
+        })
+        .reduce(
+            || (0, Vec::new()),
+            |(below, mut candidates), (b, c)| {
+                candidates.extend(c);
+                (below + b, candidates)
+            },
+        );
+    if below > low || below + candidates.len() <= high {
+        // the sample was unrepresentative
+        return median_of(chunks.concat());
+    }
+    candidates.par_sort_unstable_by(|a, b| F64OrdHash::f64_cmp(*a, *b));
+    let (a, b) = (candidates[low - below], candidates[high - below]);
+    if low == high {
+        a
+    } else {
+        (a + b) / 2.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks() {
+        let items: Vec<u64> = (0..(MIN_CHUNK as u64 * 5 + 3)).collect();
+        let len = items.len() as u64;
+        let sum = map_reduce(&items, |chunk| chunk.iter().sum::<u64>(), |a, b| a + b);
+        assert_eq!(sum, len * (len - 1) / 2);
+        assert_eq!(map_reduce(&[] as &[u64], |c| c.len(), |a, b| a + b), 0);
+    }
+    #[test]
+    #[cfg(feature = "regression")]
+    fn median() {
+        let chunks: Vec<Vec<f64>> = (0..4)
+            .map(|chunk| {
+                (0..MIN_CHUNK as u64)
+                    .map(|v| ((v * 7919 + chunk) % 100_003) as f64)
+                    .collect()
+            })
+            .collect();
+        let mut all = chunks.concat();
+        all.sort_unstable_by(f64::total_cmp);
+        let len = all.len();
+        let expected = (all[len / 2 - 1] + all[len / 2]) / 2.;
+        assert_eq!(median_of_chunks(&chunks), expected);
+    }
+}
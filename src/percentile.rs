@@ -3,15 +3,28 @@
 //! - `O(n log n)` [`naive_percentile`] (simple to understand)
 //! - probabilistic `O(n)` [`percentile`] (recommended, fastest, and also quite simple to understand)
 //! - deterministic `O(n)` [`median_of_medians`] (harder to understand, probably slower than the
-//!     probabilistic version. However guarantees linear time, so useful in critical applications.)
+//!   probabilistic version. However guarantees linear time, so useful in critical applications.)
 //!
 //! You should probably use [`percentile_rand`].
 //!
+//! The functions taking an [`OrderedListIndex`] take the mean of the two closest values when the
+//! percentile lies between them. To choose how to interpolate (e.g. to match NumPy), use
+//! [`percentile_interpolated`] and [`Interpolation`].
+//!
+//! For integers in a limited range (e.g. counts or milliseconds), [`counting`] is faster. Use
+//! [`percentile_with`] and [`Accuracy::Auto`] to use it when possible.
+//!
+//! To estimate a percentile of a stream of values without storing them, use [`P2Estimator`].
+//!
 //! The linear time algoritms are implementations following [this blogpost](https://rcoh.me/posts/linear-time-median-finding/).
+use alloc::borrow::Cow;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
 #[cfg(feature = "percentile-rand")]
 use rand::Rng;
-use std::borrow::Cow;
-use std::cmp;
+
+use crate::math;
 
 /// The result of a percentile (e.g. median) lookup.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -76,7 +89,7 @@ where
 }
 
 #[cfg(feature = "generic-impls")]
-impl<T: num_traits::identities::One + std::ops::Add<Output = T> + std::ops::Div<Output = T>>
+impl<T: num_traits::identities::One + core::ops::Add<Output = T> + core::ops::Div<Output = T>>
     PercentileResolve for T
 {
     #[inline]
@@ -199,6 +212,9 @@ impl OrderedListIndex for Fraction {
             assert_ne!(denominator, 0);
         }
         assert_not_zero(self.denominator);
+        if len == 1 {
+            return MeanValue::Single(0);
+        }
         fn power_of_two(me: Fraction, len: usize) -> MeanValue<usize> {
             if me.denominator == 2 {
                 if len % 2 == 0 {
@@ -225,15 +241,16 @@ impl OrderedListIndex for Fraction {
         // exception for when self.denominator.is_power_of_two(), as we want quartiles and median
         // to be the mean of two values sometimes.
         if self.denominator == 1 {
-            MeanValue::Single(self.numerator as _)
+            // 0/1 is the first, 1/1 the last
+            MeanValue::Single((self.numerator as usize * len).saturating_sub(1))
         } else if self.denominator.is_power_of_two() {
             power_of_two(*self, len)
         } else {
             // ceil(len * percentile) - 1
             let m = len * self.numerator as usize;
             let rem = m % self.denominator as usize;
-            let rem = usize::from(rem > 1);
-            MeanValue::Single((m / self.denominator as usize + rem - 1).min(len))
+            let rem = usize::from(rem > 0);
+            MeanValue::Single((m / self.denominator as usize + rem).saturating_sub(1))
         }
     }
 }
@@ -247,7 +264,7 @@ impl PartialEq for Fraction {
 impl Eq for Fraction {}
 impl Ord for Fraction {
     #[inline]
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
         // we don't need to simplify, as [`Self::new`] always does it, there's no way to not get a
         // simplified `Fraction`.
 
@@ -261,7 +278,7 @@ impl Ord for Fraction {
 }
 impl PartialOrd for Fraction {
     #[inline]
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
@@ -365,6 +382,19 @@ pub fn percentile_rand<T: Ord + Clone>(
 ) -> MeanValue<T> {
     percentile(values, target, &mut pivot_fn::rand())
 }
+/// Same as [`percentile_rand`], but picking the pivots with `rng`.
+///
+/// The result doesn't depend on the pivots, but the time taken does. Use a seeded `rng` to make
+/// runs reproducible.
+#[cfg(feature = "percentile-rand")]
+#[inline]
+pub fn percentile_rand_with<T: Ord + Clone>(
+    values: &mut [T],
+    target: impl OrderedListIndex,
+    rng: &mut (impl Rng + ?Sized),
+) -> MeanValue<T> {
+    percentile(values, target, &mut pivot_fn::rand_with(rng))
+}
 /// Get the value at `target` in `values`.
 /// Uses the best method available ([`percentile_rand`] if feature `percentile-rand` is enabled,
 /// else [`pivot_fn::middle`])
@@ -401,6 +431,367 @@ pub fn percentile_default_pivot_by<T: Clone>(
 pub fn median<T: Ord + Clone>(values: &mut [T]) -> MeanValue<T> {
     percentile_default_pivot(values, Fraction::HALF)
 }
+/// Get the value at `target` in floating point `values` (e.g. [`f32`]), which don't implement
+/// [`Ord`].
+///
+/// Uses the same pivot function as [`percentile_default_pivot`], and the ordering of
+/// [`crate::FloatOrdHash`].
+#[inline]
+pub fn percentile_float<F: crate::Float>(
+    values: &mut [F],
+    target: impl OrderedListIndex,
+) -> MeanValue<F> {
+    percentile_default_pivot_by(values, target, &mut |a, b| {
+        crate::FloatOrdHash::float_cmp(*a, *b)
+    })
+}
+/// Convenience function for [`percentile_float`] with the 50% mark as the target.
+#[inline]
+pub fn median_float<F: crate::Float>(values: &mut [F]) -> MeanValue<F> {
+    percentile_float(values, Fraction::HALF)
+}
+
+/// How to get a percentile which lies between two values.
+///
+/// These match the methods of the same names in
+/// [NumPy](https://numpy.org/doc/stable/reference/generated/numpy.percentile.html)
+/// (and Pandas). The percentile is at the position `quantile * (len - 1)` of the sorted values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// The closest value below.
+    Lower,
+    /// The closest value above.
+    Higher,
+    /// The closest value. Ties are rounded to the even index.
+    Nearest,
+    /// The mean of the closest values below and above.
+    Midpoint,
+    /// Linear interpolation between the closest values below and above.
+    /// This is the default of NumPy.
+    #[default]
+    Linear,
+}
+impl Interpolation {
+    /// Returns the indices of the sorted list of length `len` at `quantile`, and the weight of
+    /// the higher index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quantile` is outside `[0, 1]` or `len == 0`.
+    pub fn index(self, quantile: f64, len: usize) -> Interpolated<usize> {
+        assert!(
+            (0.0..=1.0).contains(&quantile),
+            "quantile must be in [0, 1]"
+        );
+        assert!(len > 0, "can't get a percentile of no values");
+        let position = quantile * (len - 1) as f64;
+        let lower = (math::floor(position) as usize).min(len - 1);
+        let higher = (math::ceil(position) as usize).min(len - 1);
+        let fraction = position - lower as f64;
+        let weight = match self {
+            Self::Lower => 0.,
+            Self::Higher => 1.,
+            Self::Nearest => {
+                if fraction > 0.5 || (fraction == 0.5 && lower % 2 == 1) {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            Self::Midpoint => 0.5,
+            Self::Linear => fraction,
+        };
+        let weight = if lower == higher { 0. } else { weight };
+        Interpolated {
+            lower,
+            higher,
+            weight,
+        }
+    }
+}
+/// A percentile which lies between two values, returned from [`percentile_interpolated`].
+///
+/// Use [`Self::resolve`] to get the value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interpolated<T> {
+    pub lower: T,
+    pub higher: T,
+    /// The weight of [`Self::higher`], in `[0, 1]`.
+    pub weight: f64,
+}
+impl<T> Interpolated<T> {
+    #[inline]
+    pub fn map<O>(self, mut f: impl FnMut(T) -> O) -> Interpolated<O> {
+        Interpolated {
+            lower: f(self.lower),
+            higher: f(self.higher),
+            weight: self.weight,
+        }
+    }
+}
+impl<T: Into<f64>> Interpolated<T> {
+    /// `lower + weight * (higher - lower)`.
+    #[inline]
+    pub fn resolve(self) -> f64 {
+        let lower = self.lower.into();
+        if self.weight == 0. {
+            return lower;
+        }
+        let higher = self.higher.into();
+        if self.weight == 1. {
+            return higher;
+        }
+        lower + self.weight * (higher - lower)
+    }
+}
+impl Interpolated<crate::F64OrdHash> {
+    /// `lower + weight * (higher - lower)`.
+    #[inline]
+    pub fn resolve(self) -> f64 {
+        self.map(|v| v.0).resolve()
+    }
+}
+
+/// Get the value at `quantile` (in `[0, 1]`) of `values`, interpolated using `interpolation`.
+///
+/// Uses the same pivot function as [`percentile_default_pivot`].
+/// See [`percentile_interpolated_by`] for a custom comparator and pivot function.
+///
+/// # Panics
+///
+/// Panics if `values` is empty or `quantile` is outside `[0, 1]`.
+#[inline]
+pub fn percentile_interpolated<T: Ord + Clone>(
+    values: &mut [T],
+    quantile: f64,
+    interpolation: Interpolation,
+) -> Interpolated<T> {
+    #[cfg(feature = "percentile-rand")]
+    let mut pivot_fn = pivot_fn::rand();
+    #[cfg(not(feature = "percentile-rand"))]
+    let mut pivot_fn = pivot_fn::middle();
+    percentile_interpolated_by(values, quantile, interpolation, &mut pivot_fn, &mut a_cmp_b)
+}
+/// Same as [`percentile_interpolated`] but with a custom pivot and comparator function.
+///
+/// O(n)
+pub fn percentile_interpolated_by<T: Clone>(
+    values: &mut [T],
+    quantile: f64,
+    interpolation: Interpolation,
+    pivot_fn: &mut impl FnMut(&mut [T]) -> Cow<'_, T>,
+    compare: &mut impl FnMut(&T, &T) -> cmp::Ordering,
+) -> Interpolated<T> {
+    let index = interpolation.index(quantile, values.len());
+    let lower = quickselect(values, index.lower, &mut *pivot_fn, &mut *compare).clone();
+    let higher = if index.higher == index.lower {
+        lower.clone()
+    } else {
+        quickselect(values, index.higher, pivot_fn, compare).clone()
+    };
+    Interpolated {
+        lower,
+        higher,
+        weight: index.weight,
+    }
+}
+/// Convenience function for [`percentile_interpolated`] with the 50% mark as the target.
+#[inline]
+pub fn median_interpolated<T: Ord + Clone>(
+    values: &mut [T],
+    interpolation: Interpolation,
+) -> Interpolated<T> {
+    percentile_interpolated(values, 0.5, interpolation)
+}
+/// The algorithm used by [`percentile_with`]. All of them give the exact percentile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Accuracy {
+    /// Use [`Self::Counting`] if all values are integers and the range of the values is at most
+    /// the count of values (and [`counting::AUTO_MAX_RANGE`]). Else, use [`Self::Selection`].
+    #[default]
+    Auto,
+    /// Quickselect, see [`percentile`]. O(n) time, and no extra memory.
+    Selection,
+    /// Count the occurrences of each integer, see [`counting`]. O(n + r) time and O(r) memory,
+    /// where r is the range of the values.
+    Counting,
+}
+/// Get the value at `target` in `values`, using the algorithm chosen by `accuracy`.
+///
+/// `values` is reordered when using [`Accuracy::Selection`].
+///
+/// # Panics
+///
+/// Panics if `values` is empty, or if `accuracy` is [`Accuracy::Counting`] and the values aren't
+/// all integers.
+pub fn percentile_with(
+    values: &mut [f64],
+    target: impl OrderedListIndex,
+    accuracy: Accuracy,
+) -> MeanValue<f64> {
+    assert!(!values.is_empty(), "can't get a percentile of no values");
+    let counts = match accuracy {
+        Accuracy::Auto => {
+            counting::Counts::from_f64(values, values.len().min(counting::AUTO_MAX_RANGE))
+        }
+        Accuracy::Selection => None,
+        Accuracy::Counting => Some(
+            counting::Counts::from_f64(values, usize::MAX)
+                .expect("values must be integers to use counting"),
+        ),
+    };
+    match counts {
+        Some(counts) => counts.percentile(target).map(|v| v as f64),
+        None => percentile_default_pivot_by(values, target, &mut |a, b| {
+            crate::F64OrdHash::f64_cmp(*a, *b)
+        }),
+    }
+}
+/// Streaming estimate of a quantile using the
+/// [P² algorithm](https://doi.org/10.1145/4372.4378) by Jain & Chlamtac, in O(1) memory.
+///
+/// Five markers track the minimum, the maximum, the quantile, and the points halfway between the
+/// quantile and the extremes. For each value, the markers are moved towards their desired
+/// positions, and their heights adjusted using piecewise-parabolic interpolation.
+///
+/// The estimate is exact for the first 5 values. For large data sets, the error is usually small
+/// for quantiles which aren't too extreme. Create one estimator per quantile to track several
+/// (e.g. p50, p95, and p99).
+#[derive(Debug, Clone, PartialEq)]
+pub struct P2Estimator {
+    quantile: f64,
+    count: usize,
+    heights: [f64; 5],
+    /// The actual positions of the markers, starting at 1.
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+impl P2Estimator {
+    /// Estimates `quantile` (in `[0, 1]`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quantile` is outside `[0, 1]`.
+    pub fn new(quantile: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&quantile),
+            "quantile must be in [0, 1]"
+        );
+        let p = quantile;
+        Self {
+            quantile,
+            count: 0,
+            heights: [0.; 5],
+            positions: [1., 2., 3., 4., 5.],
+            desired: [1., 1. + 2. * p, 1. + 4. * p, 3. + 2. * p, 5.],
+            increments: [0., p / 2., p, (1. + p) / 2., 1.],
+        }
+    }
+    /// The quantile this estimates.
+    pub fn target(&self) -> f64 {
+        self.quantile
+    }
+    /// The count of values pushed.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+    /// If no values have been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+    /// Adds `value` to the estimate. `NaN`s are ignored.
+    ///
+    /// O(1)
+    pub fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        if self.count < 5 {
+            self.heights[self.count] = value;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights
+                    .sort_unstable_by(|a, b| crate::F64OrdHash::f64_cmp(*a, *b));
+            }
+            return;
+        }
+        self.count += 1;
+
+        let h = &mut self.heights;
+        // the cell the value is in
+        let k = if value < h[0] {
+            h[0] = value;
+            0
+        } else if value >= h[4] {
+            h[4] = value;
+            3
+        } else {
+            // h[0] <= value < h[4]
+            (1..5).find(|i| value < h[*i]).expect("value < h[4]") - 1
+        };
+        for position in &mut self.positions[k + 1..] {
+            *position += 1.;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        for i in 1..4 {
+            let d = self.desired[i] - self.positions[i];
+            let n = &self.positions;
+            if (d >= 1. && n[i + 1] - n[i] > 1.) || (d <= -1. && n[i - 1] - n[i] < -1.) {
+                let d = if d > 0. { 1. } else { -1. };
+                let parabolic = self.parabolic(i, d);
+                let h = &self.heights;
+                self.heights[i] = if h[i - 1] < parabolic && parabolic < h[i + 1] {
+                    parabolic
+                } else {
+                    self.linear(i, d)
+                };
+                self.positions[i] += d;
+            }
+        }
+    }
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + d / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        let other = if d > 0. { i + 1 } else { i - 1 };
+        q[i] + d * (q[other] - q[i]) / (n[other] - n[i])
+    }
+    /// The current estimate, or [`None`] if no values have been pushed.
+    ///
+    /// For the first 5 values, this is the exact quantile using [`Interpolation::Linear`].
+    pub fn estimate(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            1..=4 => {
+                let mut values = self.heights[..self.count].to_vec();
+                values.sort_unstable_by(|a, b| crate::F64OrdHash::f64_cmp(*a, *b));
+                Some(
+                    Interpolation::Linear
+                        .index(self.quantile, values.len())
+                        .map(|idx| values[idx])
+                        .resolve(),
+                )
+            }
+            _ => Some(self.heights[2]),
+        }
+    }
+}
+impl Extend<f64> for P2Estimator {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, iter: I) {
+        for v in iter {
+            self.push(v);
+        }
+    }
+}
+
 /// Low level function used by this module.
 fn quickselect<T: Clone>(
     values: &mut [T],
@@ -549,6 +940,20 @@ pub mod pivot_fn {
             Cow::Borrowed(slice.get(idx).unwrap())
         }
     }
+    /// Same as [`rand`](fn@rand), but picking the pivots with `rng`.
+    /// Use a seeded `rng` to get reproducible runs.
+    #[cfg(feature = "percentile-rand")]
+    #[inline]
+    pub fn rand_with<T: Clone, S: SliceSubset<T> + ?Sized, R: Rng + ?Sized>(
+        rng: &mut R,
+    ) -> impl FnMut(&mut S) -> Cow<'_, T> + '_ {
+        move |slice| {
+            let idx = rng.random_range(0..slice.len());
+            // UNWRAP: it's less than `slice.len`.
+            // We assume `!slice.is_empty()`.
+            Cow::Borrowed(slice.get(idx).unwrap())
+        }
+    }
     #[inline]
     pub fn middle<T: Clone, S: SliceSubset<T> + ?Sized>() -> impl FnMut(&mut S) -> Cow<'_, T> {
         #[inline(always)]
@@ -605,7 +1010,7 @@ pub mod pivot_fn {
 pub mod cluster {
     use super::*;
     use crate::{Cluster, ClusterList, OwnedClusterList};
-    use std::ops::{Deref, DerefMut};
+    use core::ops::{Deref, DerefMut};
 
     // `TODO`: use `super::pivot_fn` instead. That doesn't however seem to work, due to idiotic
     // lifetime requirements.
@@ -622,6 +1027,17 @@ pub mod cluster {
                 *slice.index(idx)
             }
         }
+        /// Same as [`rand`](fn@rand), but picking the pivots with `rng`.
+        #[cfg(feature = "percentile-rand")]
+        #[inline]
+        pub fn rand_with<R: Rng + ?Sized>(rng: &mut R) -> impl FnMut(&ClusterList) -> f64 + '_ {
+            move |slice| {
+                let idx = rng.random_range(0..slice.len());
+                // Panic (index call): it's less than `slice.len`.
+                // We assume `!slice.is_empty()`.
+                *slice.index(idx)
+            }
+        }
         #[inline]
         pub fn middle() -> impl FnMut(&ClusterList) -> f64 {
             #[inline(always)]
@@ -696,6 +1112,16 @@ pub mod cluster {
     ) -> MeanValue<f64> {
         percentile(values, target, &mut pivot_fn::rand())
     }
+    /// Same as [`percentile_rand`], but picking the pivots with `rng`.
+    #[cfg(feature = "percentile-rand")]
+    #[inline]
+    pub fn percentile_rand_with(
+        values: &mut OwnedClusterList,
+        target: impl OrderedListIndex,
+        rng: &mut (impl Rng + ?Sized),
+    ) -> MeanValue<f64> {
+        percentile(values, target, &mut pivot_fn::rand_with(rng))
+    }
     /// Get the value at `target` in `values`.
     /// Uses the best method available ([`percentile_rand`] if feature `percentile-rand` is enabled,
     /// else [`pivot_fn::middle`])
@@ -732,6 +1158,38 @@ pub mod cluster {
     pub fn median(values: &mut OwnedClusterList) -> MeanValue<f64> {
         percentile_default_pivot(values, Fraction::HALF)
     }
+    /// Get the percentiles at several `targets` at once, in the same order as `targets`.
+    ///
+    /// This is faster than calling [`percentile`] for each target, as the partitioning work is
+    /// shared: after each partition, we only continue in the parts which contain targets.
+    ///
+    /// `pivot_fn` must return a value from the supplied slice.
+    pub fn percentiles_by<T: OrderedListIndex>(
+        values: &mut OwnedClusterList,
+        targets: &[T],
+        pivot_fn: &mut impl FnMut(&ClusterList) -> f64,
+        compare: &mut impl FnMut(f64, f64) -> cmp::Ordering,
+    ) -> Vec<MeanValue<f64>> {
+        let len = values.borrow().len();
+        let indices: Vec<MeanValue<usize>> = targets.iter().map(|t| t.index(len)).collect();
+        let mut ks: Vec<usize> = indices
+            .iter()
+            .flat_map(|index| match index {
+                MeanValue::Single(k) => [*k, *k],
+                MeanValue::Mean(a, b) => [*a, *b],
+            })
+            .collect();
+        ks.sort_unstable();
+        ks.dedup();
+        let mut selected = vec![0.; ks.len()];
+        multi_quickselect(values.into(), &ks, 0, &mut selected, pivot_fn, compare);
+        indices
+            .into_iter()
+            .map(|index| {
+                index.map(|k| selected[ks.binary_search(&k).expect("we added all indices to `ks`")])
+            })
+            .collect()
+    }
 
     struct ClusterMut<'a> {
         list: &'a mut [Cluster],
@@ -770,7 +1228,7 @@ pub mod cluster {
     }
     impl ClusterMut<'_> {
         #[inline]
-        fn list(&self) -> ClusterList {
+        fn list(&self) -> ClusterList<'_> {
             ClusterList::from(self)
         }
     }
@@ -808,6 +1266,56 @@ pub mod cluster {
             )
         }
     }
+    /// Selects all the sorted `ks` in one pass, writing the values to `out`.
+    ///
+    /// The `ks` are indices into `values` after subtracting `offset`, so they don't have to be
+    /// copied for every partition.
+    fn multi_quickselect(
+        values: ClusterMut<'_>,
+        ks: &[usize],
+        offset: usize,
+        out: &mut [f64],
+        pivot_fn: &mut impl FnMut(&ClusterList) -> f64,
+        compare: &mut impl FnMut(f64, f64) -> cmp::Ordering,
+    ) {
+        if ks.is_empty() {
+            return;
+        }
+        if values.len() == 1 {
+            out.fill(values[0].0);
+            return;
+        }
+
+        let pivot = pivot_fn(&values.list());
+
+        // shorten the lifetime, so `split_include` can borrow it
+        let mut values = ClusterMut {
+            list: values.list,
+            len: values.len,
+        };
+        let (lows, mut highs_inclusive) =
+            split_include(&mut values, |v| compare(v, pivot) == cmp::Ordering::Less);
+        let (highs, pivots) = split_include(&mut highs_inclusive, |v| {
+            compare(v, pivot) == cmp::Ordering::Greater
+        });
+        let lows_len = lows.list().len();
+        let pivots_end = lows_len + pivots.list().len();
+
+        let lows_end = ks.partition_point(|k| *k - offset < lows_len);
+        let highs_start = ks.partition_point(|k| *k - offset < pivots_end);
+        let (low_out, out) = out.split_at_mut(lows_end);
+        let (pivot_out, high_out) = out.split_at_mut(highs_start - lows_end);
+        pivot_out.fill(pivots[0].0);
+        multi_quickselect(lows, &ks[..lows_end], offset, low_out, pivot_fn, compare);
+        multi_quickselect(
+            highs,
+            &ks[highs_start..],
+            offset + pivots_end,
+            high_out,
+            pivot_fn,
+            compare,
+        );
+    }
     #[inline]
     fn split_include<'a>(
         slice: &'a mut ClusterMut<'a>,
@@ -842,8 +1350,113 @@ pub mod cluster {
     }
 }
 
+/// Exact percentiles of integers by counting the occurrences of each value (counting sort).
+///
+/// This takes O(n + r) time and O(r) memory, where r is the range of the values
+/// (`max - min + 1`). When the range is small compared to the count of values, this is
+/// considerably faster than selection.
+pub mod counting {
+    use super::*;
+
+    /// The largest range [`Accuracy::Auto`] uses counting for. The counts then use at most 8 MiB.
+    pub const AUTO_MAX_RANGE: usize = 1 << 20;
+
+    /// The count of each integer between the smallest and largest value.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Counts {
+        min: i64,
+        counts: Vec<usize>,
+        len: usize,
+    }
+    impl Counts {
+        /// Counts `values`.
+        ///
+        /// Returns [`None`] if `values` is empty or the range of the values is larger than
+        /// `max_range`.
+        pub fn new(values: &[i64], max_range: usize) -> Option<Self> {
+            let min = *values.iter().min()?;
+            let max = *values.iter().max()?;
+            let range = usize::try_from(max.abs_diff(min)).ok()?.checked_add(1)?;
+            if range > max_range {
+                return None;
+            }
+            let mut counts = vec![0; range];
+            for v in values {
+                counts[(v.abs_diff(min)) as usize] += 1;
+            }
+            Some(Self {
+                min,
+                counts,
+                len: values.len(),
+            })
+        }
+        /// Counts `values`, if they're all integers.
+        ///
+        /// Returns [`None`] if `values` is empty, any value isn't an integer (or is larger than
+        /// 2⁵³, where not all integers can be represented), or the range of the values is larger
+        /// than `max_range`.
+        pub fn from_f64(values: &[f64], max_range: usize) -> Option<Self> {
+            const MAX: f64 = (1_u64 << 53) as f64;
+            let mut min = f64::INFINITY;
+            let mut max = f64::NEG_INFINITY;
+            for v in values {
+                if math::trunc(*v) != *v || math::fabs(*v) > MAX || v.is_nan() {
+                    return None;
+                }
+                min = min.min(*v);
+                max = max.max(*v);
+            }
+            if values.is_empty() || max - min + 1. > max_range as f64 {
+                return None;
+            }
+            let mut counts = vec![0; (max - min) as usize + 1];
+            for v in values {
+                counts[(v - min) as usize] += 1;
+            }
+            Some(Self {
+                min: min as i64,
+                counts,
+                len: values.len(),
+            })
+        }
+        /// The count of values.
+        pub fn len(&self) -> usize {
+            self.len
+        }
+        /// If there are no values. This is never the case for counts created by this module.
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+        /// The `k`-th smallest value (starting at 0).
+        ///
+        /// O(r)
+        ///
+        /// # Panics
+        ///
+        /// Panics if `k >= self.len()`.
+        pub fn kth_smallest(&self, k: usize) -> i64 {
+            assert!(k < self.len, "k must be less than the count of values");
+            let mut seen = 0;
+            for (idx, count) in self.counts.iter().enumerate() {
+                seen += count;
+                if seen > k {
+                    return self.min + idx as i64;
+                }
+            }
+            unreachable!("the counts sum to the count of values")
+        }
+        /// Get the value at `target`.
+        ///
+        /// O(r)
+        pub fn percentile(&self, target: impl OrderedListIndex) -> MeanValue<i64> {
+            target.index(self.len).map(|k| self.kth_smallest(k))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{Interpolation, MeanValue, OrderedListIndex};
     use crate::Fraction;
 
     fn raw_fraction(n: u64, d: u64) -> Fraction {
@@ -864,4 +1477,196 @@ mod tests {
     fn fraction_3() {
         assert_eq!(raw_fraction(29, 41).simplify(), Fraction::new(29, 41));
     }
+    #[test]
+    fn fraction_index() {
+        assert_eq!(Fraction::new(0, 5).index(10), MeanValue::Single(0));
+        assert_eq!(Fraction::new(5, 5).index(10), MeanValue::Single(9));
+        assert_eq!(Fraction::new(1, 3).index(10), MeanValue::Single(3));
+        assert_eq!(Fraction::new(1, 3).index(1), MeanValue::Single(0));
+        assert_eq!(Fraction::new(1, 4).index(8), MeanValue::Mean(1, 2));
+        assert_eq!(Fraction::new(3, 4).index(1), MeanValue::Single(0));
+    }
+    /// The index is `ceil(len * fraction) - 1`. Before `--export-quantiles`, a remainder of
+    /// exactly 1 was rounded down, and `n/1` was `n`.
+    #[test]
+    fn fraction_index_rounding() {
+        let index = |n, d, len| Fraction::new(n, d).index(len);
+        // changed: a remainder of 1
+        assert_eq!(index(90, 100, 9), MeanValue::Single(8)); // was 7
+        assert_eq!(index(95, 100, 19), MeanValue::Single(18)); // was 17
+        assert_eq!(index(99, 100, 99), MeanValue::Single(98)); // was 97
+        assert_eq!(index(1, 3, 10), MeanValue::Single(3)); // was 2
+
+        // changed: a denominator of 1, where `1/1` was the index 1 and `0/1` underflowed
+        assert_eq!(index(1, 1, 10), MeanValue::Single(9));
+        assert_eq!(index(0, 1, 10), MeanValue::Single(0));
+
+        // unchanged: other remainders
+        assert_eq!(index(90, 100, 11), MeanValue::Single(9));
+        assert_eq!(index(90, 100, 100), MeanValue::Single(89));
+        assert_eq!(index(95, 100, 100), MeanValue::Single(94));
+        assert_eq!(index(99, 100, 101), MeanValue::Single(99));
+        assert_eq!(index(99, 100, 1000), MeanValue::Single(989));
+    }
+    #[test]
+    fn interpolation() {
+        use super::{median_interpolated, percentile_interpolated};
+
+        // compared to `numpy.percentile([1, 2, 3, 4], 40, method=...)`
+        let expected = [
+            (Interpolation::Lower, 2.),
+            (Interpolation::Higher, 3.),
+            (Interpolation::Nearest, 2.),
+            (Interpolation::Midpoint, 2.5),
+            (Interpolation::Linear, 2.2),
+        ];
+        for (interpolation, expected) in expected {
+            let mut values = [4, 1, 3, 2];
+            let v = percentile_interpolated(&mut values, 0.4, interpolation).resolve();
+            assert!((v - expected).abs() < 1e-12, "{interpolation:?}: {v}");
+        }
+        let mut values: Vec<i32> = (0..100).rev().collect();
+        let v = percentile_interpolated(&mut values, 0.9, Interpolation::Linear).resolve();
+        assert!((v - 89.1).abs() < 1e-9);
+        // ties are rounded to the even index: 1.5 -> 2
+        let mut values = [1, 2, 3, 4];
+        assert_eq!(
+            median_interpolated(&mut values, Interpolation::Nearest).resolve(),
+            3.
+        );
+        let values = [1.];
+        assert_eq!(
+            median_interpolated(&mut values.map(crate::F64OrdHash), Interpolation::Linear)
+                .resolve(),
+            1.
+        );
+    }
+    #[test]
+    fn counting() {
+        use super::{counting::Counts, percentile_with, Accuracy};
+
+        let values: Vec<i64> = (0..1000).map(|i| (i * 7919) % 250 - 50).collect();
+        let counts = Counts::new(&values, 1000).unwrap();
+        for target in [Fraction::new(1, 10), Fraction::HALF, Fraction::new(99, 100)] {
+            let mut sorted = values.clone();
+            assert_eq!(
+                counts.percentile(target),
+                super::naive_percentile(&mut sorted, target).clone_inner()
+            );
+        }
+        assert_eq!(Counts::new(&values, 100), None);
+
+        let mut values: Vec<f64> = values.iter().map(|v| *v as f64).collect();
+        let expected = percentile_with(&mut values.clone(), Fraction::HALF, Accuracy::Selection);
+        assert_eq!(
+            percentile_with(&mut values, Fraction::HALF, Accuracy::Counting),
+            expected
+        );
+        assert_eq!(
+            percentile_with(&mut values, Fraction::HALF, Accuracy::Auto),
+            expected
+        );
+        // not integers, so selection is used
+        let mut values = [0.5, 1.5, 2.];
+        assert_eq!(Counts::from_f64(&values, usize::MAX), None);
+        assert_eq!(
+            percentile_with(&mut values, Fraction::HALF, Accuracy::Auto),
+            MeanValue::Single(1.5)
+        );
+    }
+    #[test]
+    fn p2() {
+        use super::P2Estimator;
+
+        let mut estimator = P2Estimator::new(0.5);
+        assert_eq!(estimator.estimate(), None);
+        estimator.extend([3., 1., 2.]);
+        assert_eq!(estimator.estimate(), Some(2.));
+
+        let values: Vec<f64> = (0..10_000).map(|i| ((i * 7919) % 10_000) as f64).collect();
+        for (quantile, expected) in [(0.5, 5000.), (0.95, 9500.), (0.99, 9900.)] {
+            let mut estimator = P2Estimator::new(quantile);
+            estimator.extend(values.iter().copied());
+            let estimate = estimator.estimate().unwrap();
+            assert!(
+                (estimate - expected).abs() < 100.,
+                "p{quantile}: {estimate}"
+            );
+        }
+    }
+    #[test]
+    fn float_generic() {
+        use super::{median_float, percentile_float};
+
+        let mut values: Vec<f32> = vec![4.5, f32::NAN, 1.5, 3., 2., 10.];
+        // NaN is the smallest value
+        assert!(percentile_float(&mut values, Fraction::new(0, 1))
+            .into_single()
+            .unwrap()
+            .is_nan());
+        assert_eq!(median_float(&mut values).resolve(), 2.5);
+        values.retain(|v| !v.is_nan());
+        let std_dev = crate::standard_deviation(&values);
+        assert!((std_dev.mean - 4.2).abs() < 1e-6);
+        assert_eq!(crate::mean(&[1_f32, 2., 3.]), 2.);
+    }
+    #[test]
+    fn cluster_percentiles() {
+        use super::cluster;
+        use crate::OwnedClusterList;
+
+        let list: Vec<_> = (0..200)
+            .map(|i| (((i * 37) % 101) as f64, i % 3 + 1))
+            .collect();
+        let targets = [
+            Fraction::new(1, 10),
+            Fraction::ONE_QUARTER,
+            Fraction::HALF,
+            Fraction::new(9, 10),
+            Fraction::new(99, 100),
+        ];
+        let expected: Vec<_> = targets
+            .iter()
+            .map(|t| cluster::naive_percentile(&mut OwnedClusterList::new(list.clone()), *t))
+            .collect();
+        let selected = cluster::percentiles_by(
+            &mut OwnedClusterList::new(list),
+            &targets,
+            &mut cluster::pivot_fn::middle(),
+            &mut crate::F64OrdHash::f64_cmp,
+        );
+        assert_eq!(selected, expected);
+    }
+    #[test]
+    #[cfg(feature = "percentile-rand")]
+    fn seeded_rand() {
+        use super::{cluster, percentile_rand_with};
+        use crate::OwnedClusterList;
+        use rand::SeedableRng;
+
+        let values: Vec<u32> = (0..100).map(|i| (i * 37) % 101).collect();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let median = percentile_rand_with(&mut values.clone(), Fraction::HALF, &mut rng);
+        let mut sorted = values.clone();
+        sorted.sort_unstable();
+        assert_eq!(median, MeanValue::Mean(sorted[49], sorted[50]));
+
+        let list: Vec<_> = values.iter().map(|v| (*v as f64, 2)).collect();
+        let run = |seed| {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            cluster::percentile_rand_with(
+                &mut OwnedClusterList::new(list.clone()),
+                Fraction::new(9, 10),
+                &mut rng,
+            )
+        };
+        assert_eq!(run(5), run(5));
+        assert_eq!(
+            run(5),
+            cluster::naive_percentile(
+                &mut OwnedClusterList::new(list.clone()),
+                Fraction::new(9, 10)
+            )
+        );
+    }
 }
@@ -27,7 +27,9 @@
 //! above. Then, I transform the returned variables to fit the target model.
 //!
 //! This is not very good, as the errors of large values are reduced compared to small values when
-//! taking the logarithm. I have plans to address this bias in the future.
+//! taking the logarithm. [`derived::power_nls`] and [`derived::exponential_nls`] address this bias
+//! by refining the result with nonlinear least squares, minimizing the error of the untransformed
+//! values.
 //! The current behaviour is however still probably the desired behaviour, as small values are
 //! often relatively important to larger.
 //!
@@ -46,15 +48,27 @@ pub use models::*;
 
 pub use binary_search::Options as BinarySearchOptions;
 #[cfg(feature = "ols")]
-pub use derived::{exponential_ols, power_ols};
+pub use derived::{exponential_ols, logarithmic_ols, power_ols};
+pub use diagnostics::diagnostic_plots_data;
 pub use gradient_descent::{
     ParallelOptions as GradientDescentParallelOptions,
     SimultaneousOptions as GradientDescentSimultaneousOptions,
 };
+pub use huber::HuberEstimator;
+pub use lasso::LassoEstimator;
+pub use loess::{Loess, LoessCurve};
 #[cfg(feature = "ols")]
-pub use ols::OlsEstimator;
+pub use ols::{
+    linear_with_uncertainty, polynomial_with_uncertainty, weighted_linear, weighted_polynomial,
+    ConstrainedOlsEstimator, OlsEstimator, WithUncertainty,
+};
+#[cfg(feature = "ols")]
+pub use ridge::RidgeEstimator;
+pub use segmented::{segmented, SegmentedCoefficients};
 pub use spiral::{SpiralLinear, SpiralLogisticWithCeiling};
-pub use theil_sen::{LinearTheilSen, PolynomialTheilSen};
+#[cfg(feature = "rand")]
+pub use theil_sen::SampledTheilSen;
+pub use theil_sen::{LinearSiegel, LinearTheilSen, PolynomialTheilSen};
 
 trait Model: Predictive + Display {}
 impl<T: Predictive + Display> Model for T {}
@@ -121,19 +135,33 @@ pub trait Determination: Predictive {
         outcomes: impl Iterator<Item = f64> + Clone,
         len: usize,
     ) -> f64 {
-        let outcomes_mean = outcomes.clone().sum::<f64>() / len as f64;
-        let residuals = predictors
-            .zip(outcomes.clone())
-            .map(|(pred, out)| out - self.predict_outcome(pred));
-
-        // Sum of the square of the residuals
-        let res: f64 = residuals.map(|residual| residual * residual).sum();
-        let tot: f64 = outcomes
-            .map(|out| {
-                let diff = out - outcomes_mean;
-                diff * diff
-            })
-            .sum();
+        #[cfg(feature = "simd")]
+        let (res, tot) = {
+            let outcomes_mean = crate::simd::sum(outcomes.clone()) / len as f64;
+            let predicted = predictors.map(|pred| self.predict_outcome(pred));
+            (
+                // Sum of the square of the residuals
+                crate::simd::sum_squared_diff(outcomes.clone(), predicted),
+                crate::simd::sum_squared_diff(outcomes, std::iter::repeat(outcomes_mean)),
+            )
+        };
+        #[cfg(not(feature = "simd"))]
+        let (res, tot) = {
+            let outcomes_mean = outcomes.clone().sum::<f64>() / len as f64;
+            let residuals = predictors
+                .zip(outcomes.clone())
+                .map(|(pred, out)| out - self.predict_outcome(pred));
+
+            // Sum of the square of the residuals
+            let res: f64 = residuals.map(|residual| residual * residual).sum();
+            let tot: f64 = outcomes
+                .map(|out| {
+                    let diff = out - outcomes_mean;
+                    diff * diff
+                })
+                .sum();
+            (res, tot)
+        };
 
         let mut diff = res / tot;
 
@@ -165,6 +193,8 @@ impl<T: Predictive> Determination for T {}
 /// You can naturally implement these yourself.
 pub mod models {
     use super::*;
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
     use std::f64::consts::E;
 
     pub use trig::*;
@@ -203,24 +233,32 @@ pub mod models {
                     (**self).$model(predictors, outcomes, $($($arg),*)?)
                 }
             }
+            impl<T: $name + ?Sized> $name for Box<T> {
+                fn $model(&self, predictors: &[f64], outcomes: &[f64], $($($arg:$ty),*)?) -> $item {
+                    (**self).$model(predictors, outcomes, $($($arg),*)?)
+                }
+            }
             )+
         };
     }
 
     /// The coefficients of a line.
+    ///
+    /// Generic over the [`Scalar`] type, see [`LinearEstimator`].
     #[derive(Debug, Clone, Copy, PartialEq)]
-    pub struct LinearCoefficients {
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct LinearCoefficients<F = f64> {
         /// slope, x coefficient
-        pub k: f64,
+        pub k: F,
         /// y intersect, additive
-        pub m: f64,
+        pub m: F,
     }
     impl Predictive for LinearCoefficients {
         fn predict_outcome(&self, predictor: f64) -> f64 {
             self.k * predictor + self.m
         }
     }
-    impl Display for LinearCoefficients {
+    impl<F: Display> Display for LinearCoefficients<F> {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             let p = f.precision().unwrap_or(5);
             write!(f, "{:.2$}x + {:.2$}", self.k, self.m, p)
@@ -230,7 +268,8 @@ pub mod models {
     /// The length of the inner vector is `degree + 1`.
     ///
     /// The inner list is in order of smallest exponent to largest: `[0, 2, 1]` means `y = 1x² + 2x + 0`.
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Debug, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
     pub struct PolynomialCoefficients {
         pub(crate) coefficients: Vec<f64>,
     }
@@ -322,6 +361,93 @@ pub mod models {
                 coefficients: coeffs,
             }
         }
+        /// Returns the real roots (the `x` where `y = 0`) in ascending order.
+        ///
+        /// The roots of the [derivative](Self::derivative) split the real line into ranges where
+        /// the polynomial is monotonic. These are found recursively, and the ranges where the sign
+        /// changes are then bisected. Roots where the curve only touches 0 are the roots of the
+        /// derivative which evaluate to (about) 0.
+        ///
+        /// The extrema are the roots of the derivative: `coefficients.derivative().roots()`.
+        ///
+        /// Returns an empty list for a constant, even if it's 0.
+        pub fn roots(&self) -> Vec<f64> {
+            let len = self
+                .coefficients
+                .iter()
+                .rposition(|c| *c != 0.)
+                .map_or(0, |last| last + 1);
+            let coefficients = &self.coefficients[..len];
+            match len {
+                0 | 1 => return Vec::new(),
+                2 => return vec![-coefficients[0] / coefficients[1]],
+                _ => {}
+            }
+            let trimmed = Self::from(coefficients);
+            // Cauchy's bound, all roots are in (-bound, bound)
+            let leading = coefficients[len - 1];
+            let bound = 1.
+                + coefficients[..len - 1]
+                    .iter()
+                    .map(|c| (c / leading).abs())
+                    .fold(0., f64::max);
+            // the rounding error of evaluating the polynomial at `x`
+            let tolerance = |x: f64| -> f64 {
+                let mut power = 1.;
+                let mut scale = 0.;
+                for c in coefficients {
+                    scale += (c * power).abs();
+                    power *= x;
+                }
+                scale * 1e-12
+            };
+
+            let mut points = vec![-bound];
+            points.extend(
+                trimmed
+                    .derivative()
+                    .roots()
+                    .into_iter()
+                    .filter(|x| x.abs() < bound),
+            );
+            points.push(bound);
+
+            let mut roots = Vec::new();
+            for (index, window) in points.windows(2).enumerate() {
+                let (mut low, mut high) = (window[0], window[1]);
+                if index > 0 && trimmed.predict_outcome(low).abs() <= tolerance(low) {
+                    // a critical point at 0
+                    roots.push(low);
+                    continue;
+                }
+                let (y_low, y_high) = (trimmed.predict_outcome(low), trimmed.predict_outcome(high));
+                if y_low.is_sign_positive() == y_high.is_sign_positive()
+                    || high.abs() < bound && y_high.abs() <= tolerance(high)
+                {
+                    continue;
+                }
+                let rising = y_high > y_low;
+                loop {
+                    let mid = (low + high) / 2.;
+                    if mid <= low || mid >= high {
+                        roots.push(mid);
+                        break;
+                    }
+                    let y = trimmed.predict_outcome(mid);
+                    if y == 0. {
+                        roots.push(mid);
+                        break;
+                    }
+                    if (y > 0.) == rising {
+                        high = mid;
+                    } else {
+                        low = mid;
+                    }
+                }
+            }
+            roots.dedup();
+            roots
+        }
     }
     impl Predictive for PolynomialCoefficients {
         #[cfg(feature = "arbitrary-precision")]
@@ -356,6 +482,7 @@ pub mod models {
     }
     /// The coefficients of a power (also called growth) function (`kx^e`).
     #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct PowerCoefficients {
         /// Constant
         pub k: f64,
@@ -414,6 +541,7 @@ pub mod models {
 
     /// The coefficients of a exponential function (`kb^x`).
     #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct ExponentialCoefficients {
         /// Constant
         pub k: f64,
@@ -456,8 +584,45 @@ pub mod models {
         }
     }
 
+    /// The coefficients of a logarithmic function (`a + b ln(x)`).
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct LogarithmicCoefficients {
+        /// Constant
+        pub a: f64,
+        /// The coefficient of the logarithm
+        pub b: f64,
+        /// If the predictors needs to have an offset applied to remove values under 1.
+        ///
+        /// Defaults to 0.
+        pub predictor_additive: f64,
+    }
+    impl Predictive for LogarithmicCoefficients {
+        fn predict_outcome(&self, predictor: f64) -> f64 {
+            self.a + self.b * (predictor + self.predictor_additive).ln()
+        }
+    }
+    impl Display for LogarithmicCoefficients {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let p = f.precision().unwrap_or(5);
+            write!(
+                f,
+                "{:.2$} + {:.2$} * ln{x}",
+                self.a,
+                self.b,
+                p,
+                x = if self.predictor_additive != 0. {
+                    format!("(x + {:.1$})", self.predictor_additive, p)
+                } else {
+                    "(x)".to_string()
+                },
+            )
+        }
+    }
+
     /// The coefficients of a [logistic function](https://en.wikipedia.org/wiki/Logistic_function).
     #[derive(Debug, Clone, Copy, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct LogisticCoefficients {
         /// The x value of the curve's midpoint
         pub x0: f64,
@@ -494,10 +659,374 @@ pub mod models {
         }
     }
 
-    estimator!(
-        /// Implemented by all estimators yielding a linear 2 variable regression (a line).
-        LinearEstimator -> LinearCoefficients, model_linear, boxed_linear
+    /// A fitted model of one of the common kinds.
+    ///
+    /// Unlike [`DynModel`], the kind and coefficients can be inspected, and with the `serde`
+    /// feature, the model can be saved (e.g. as JSON) and loaded later for prediction.
+    /// It's serialized as `{ "model": "<kind>", "coefficients": <coefficients> }`.
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "serde",
+        derive(Serialize, Deserialize),
+        serde(tag = "model", content = "coefficients", rename_all = "snake_case")
+    )]
+    pub enum ModelEnum {
+        /// See [`LinearCoefficients`].
+        Linear(LinearCoefficients),
+        /// See [`PolynomialCoefficients`].
+        Polynomial(PolynomialCoefficients),
+        /// See [`PowerCoefficients`].
+        Power(PowerCoefficients),
+        /// See [`ExponentialCoefficients`].
+        Exponential(ExponentialCoefficients),
+        /// See [`LogarithmicCoefficients`].
+        Logarithmic(LogarithmicCoefficients),
+        /// See [`LogisticCoefficients`].
+        Logistic(LogisticCoefficients),
+        /// See [`SegmentedCoefficients`].
+        Segmented(SegmentedCoefficients),
+    }
+    macro_rules! model_enum {
+        ($($variant:ident($ty:ty)),+) => {
+            impl Predictive for ModelEnum {
+                fn predict_outcome(&self, predictor: f64) -> f64 {
+                    match self {
+                        $(Self::$variant(model) => model.predict_outcome(predictor),)+
+                    }
+                }
+            }
+            impl Display for ModelEnum {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    match self {
+                        $(Self::$variant(model) => model.fmt(f),)+
+                    }
+                }
+            }
+            $(
+            impl From<$ty> for ModelEnum {
+                fn from(model: $ty) -> Self {
+                    Self::$variant(model)
+                }
+            }
+            )+
+        };
+    }
+    model_enum!(
+        Linear(LinearCoefficients),
+        Polynomial(PolynomialCoefficients),
+        Power(PowerCoefficients),
+        Exponential(ExponentialCoefficients),
+        Logarithmic(LogarithmicCoefficients),
+        Logistic(LogisticCoefficients),
+        Segmented(SegmentedCoefficients)
+    );
+
+    /// Returned from the [`FromStr`](std::str::FromStr) implementations of the coefficients when
+    /// the equation doesn't have the syntax their [`Display`] implementations write.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ParseModelError {
+        /// The kind of model which was expected, e.g. `"linear"`.
+        pub expected: &'static str,
+    }
+    impl Display for ParseModelError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "invalid {} equation", self.expected)
+        }
+    }
+    impl std::error::Error for ParseModelError {}
+
+    /// Reads equations with the whitespace removed.
+    struct Cursor<'a>(&'a str);
+    impl Cursor<'_> {
+        fn eat(&mut self, token: &str) -> bool {
+            if let Some(rest) = self.0.strip_prefix(token) {
+                self.0 = rest;
+                true
+            } else {
+                false
+            }
+        }
+        /// The longest prefix which is a number.
+        fn number(&mut self) -> Option<f64> {
+            for special in ["NaN", "inf", "-inf"] {
+                if self.eat(special) {
+                    return special.parse().ok();
+                }
+            }
+            let max = self
+                .0
+                .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+                .unwrap_or(self.0.len());
+            let (len, value) = (1..=max)
+                .rev()
+                .find_map(|len| Some((len, self.0[..len].parse().ok()?)))?;
+            self.0 = &self.0[len..];
+            Some(value)
+        }
+        fn integer(&mut self) -> Option<usize> {
+            let len = self
+                .0
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(self.0.len());
+            let value = self.0[..len].parse().ok()?;
+            self.0 = &self.0[len..];
+            Some(value)
+        }
+        /// `+` or `-`, as the sign of the following term.
+        fn operator(&mut self) -> Option<f64> {
+            if self.eat("+") {
+                Some(1.)
+            } else if self.eat("-") {
+                Some(-1.)
+            } else {
+                None
+            }
+        }
+        /// `x` or `(x + additive)`.
+        fn predictor(&mut self) -> Option<f64> {
+            if self.eat("x") {
+                return Some(0.);
+            }
+            if !self.eat("(x") {
+                return None;
+            }
+            let sign = self.operator()?;
+            let additive = sign * self.number()?;
+            self.eat(")").then_some(additive)
+        }
+        /// An optional `- additive` at the end.
+        fn outcome_additive(&mut self) -> Option<f64> {
+            if self.eat("-") {
+                self.number()
+            } else {
+                Some(0.)
+            }
+        }
+    }
+    fn parse_equation<T>(
+        s: &str,
+        expected: &'static str,
+        parse: impl FnOnce(&mut Cursor) -> Option<T>,
+    ) -> Result<T, ParseModelError> {
+        let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut cursor = Cursor(&s);
+        parse(&mut cursor)
+            .filter(|_| cursor.0.is_empty())
+            .ok_or(ParseModelError { expected })
+    }
+
+    /// Parses the syntax written by [`Display`], e.g. `2.5x + 1.0`.
+    impl std::str::FromStr for LinearCoefficients {
+        type Err = ParseModelError;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            parse_equation(s, "linear", |c| {
+                let k = c.number()?;
+                c.eat("x").then_some(())?;
+                let sign = c.operator()?;
+                let m = sign * c.number()?;
+                Some(Self { k, m })
+            })
+        }
+    }
+    /// Parses the syntax written by [`Display`], e.g. `3.0x^2 - 1.0x + 4.0`.
+    /// The terms can be in any order, and a missing coefficient of `x` is 1.
+    impl std::str::FromStr for PolynomialCoefficients {
+        type Err = ParseModelError;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            parse_equation(s, "polynomial", |c| {
+                let mut coefficients = Vec::new();
+                let mut sign = 1.;
+                let mut first = true;
+                while !c.0.is_empty() {
+                    if !first {
+                        sign = c.operator()?;
+                    }
+                    first = false;
+                    let coefficient = c.number();
+                    let degree = if c.eat("x") {
+                        if c.eat("^{") {
+                            let degree = c.integer()?;
+                            c.eat("}").then_some(degree)?
+                        } else if c.eat("^") {
+                            c.integer()?
+                        } else {
+                            1
+                        }
+                    } else {
+                        coefficient?;
+                        0
+                    };
+                    if coefficients.len() <= degree {
+                        coefficients.resize(degree + 1, 0.);
+                    }
+                    coefficients[degree] += sign * coefficient.unwrap_or(1.);
+                }
+                Some(Self { coefficients })
+            })
+        }
+    }
+    /// Parses the syntax written by [`Display`], e.g. `3.1 * x^2.0` or
+    /// `3.1 * (x + 1.0)^2.0 - 1.0`.
+    impl std::str::FromStr for PowerCoefficients {
+        type Err = ParseModelError;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            parse_equation(s, "power", |c| {
+                let k = c.number()?;
+                c.eat("*").then_some(())?;
+                let predictor_additive = c.predictor()?;
+                c.eat("^").then_some(())?;
+                let e = c.number()?;
+                let outcome_additive = c.outcome_additive()?;
+                Some(Self {
+                    k,
+                    e,
+                    predictor_additive,
+                    outcome_additive,
+                })
+            })
+        }
+    }
+    /// Parses the syntax written by [`Display`], e.g. `3.0 * 1.2^x` or
+    /// `3.0 * 1.2^(x + 1.0) - 1.0`.
+    impl std::str::FromStr for ExponentialCoefficients {
+        type Err = ParseModelError;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            parse_equation(s, "exponential", |c| {
+                let k = c.number()?;
+                c.eat("*").then_some(())?;
+                let b = c.number()?;
+                c.eat("^").then_some(())?;
+                let predictor_additive = c.predictor()?;
+                let outcome_additive = c.outcome_additive()?;
+                Some(Self {
+                    k,
+                    b,
+                    predictor_additive,
+                    outcome_additive,
+                })
+            })
+        }
+    }
+    /// Parses the syntax written by [`Display`], e.g. `1.0 + 2.0 * ln(x)` or
+    /// `1.0 + 2.0 * ln(x + 1.0)`.
+    impl std::str::FromStr for LogarithmicCoefficients {
+        type Err = ParseModelError;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            parse_equation(s, "logarithmic", |c| {
+                let a = c.number()?;
+                let sign = c.operator()?;
+                let b = sign * c.number()?;
+                c.eat("*ln").then_some(())?;
+                let predictor_additive = if c.eat("(x)") { 0. } else { c.predictor()? };
+                Some(Self {
+                    a,
+                    b,
+                    predictor_additive,
+                })
+            })
+        }
+    }
+    /// Parses the syntax written by [`Display`], e.g. `5.0 / (1 + e^(-2.0(x - 3.0)))`.
+    impl std::str::FromStr for LogisticCoefficients {
+        type Err = ParseModelError;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            parse_equation(s, "logistic", |c| {
+                let l = c.number()?;
+                c.eat("/(1+e^(").then_some(())?;
+                let k = -c.number()?;
+                c.eat("(x").then_some(())?;
+                let x0 = -c.operator()? * c.number()?;
+                c.eat(")))").then_some(())?;
+                Some(Self { x0, l, k })
+            })
+        }
+    }
+    /// Parses the syntax written by [`Display`], e.g.
+    /// `x < 2.0: 1.0x + 0.0; x ≥ 2.0: 3.0x + -4.0`.
+    impl std::str::FromStr for SegmentedCoefficients {
+        type Err = ParseModelError;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let err = ParseModelError {
+                expected: "segmented",
+            };
+            let mut breakpoints = Vec::new();
+            let mut segments = Vec::new();
+            let parts: Vec<&str> = s.split(';').collect();
+            for (index, part) in parts.iter().enumerate() {
+                let line = match part.split_once(':') {
+                    Some((condition, line)) => {
+                        if index + 1 < parts.len() {
+                            let end = condition.rsplit_once('<').ok_or(err)?.1;
+                            breakpoints.push(end.trim().parse().map_err(|_| err)?);
+                        }
+                        line
+                    }
+                    None if parts.len() == 1 => part,
+                    None => return Err(err),
+                };
+                segments.push(line.parse().map_err(|_| err)?);
+            }
+            Ok(Self {
+                breakpoints,
+                segments,
+            })
+        }
+    }
+    /// Tries to parse each kind of model, in the order of the variants. A line is therefore
+    /// parsed as [`ModelEnum::Linear`], not [`ModelEnum::Polynomial`].
+    impl std::str::FromStr for ModelEnum {
+        type Err = ParseModelError;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            s.parse()
+                .map(Self::Linear)
+                .or_else(|_| s.parse().map(Self::Polynomial))
+                .or_else(|_| s.parse().map(Self::Power))
+                .or_else(|_| s.parse().map(Self::Exponential))
+                .or_else(|_| s.parse().map(Self::Logarithmic))
+                .or_else(|_| s.parse().map(Self::Logistic))
+                .or_else(|_| s.parse().map(Self::Segmented))
+                .map_err(|_| ParseModelError { expected: "model" })
+        }
+    }
+
+    /// Implemented by all estimators yielding a linear 2 variable regression (a line).
+    ///
+    /// Generic over the [`Scalar`] type `F` of the data, which defaults to [`f64`]. [`OlsEstimator`]
+    /// and [`LinearTheilSen`] are also implemented for [`f32`] and, with the
+    /// `arbitrary-precision` feature, `arbitrary_linear_algebra::FloatWrapper`.
+    pub trait LinearEstimator<F = f64> {
+        /// Model the [`LinearCoefficients`] from `predictors` and `outcomes`.
+        ///
+        /// # Panics
+        ///
+        /// The two slices must have the same length.
+        fn model_linear(&self, predictors: &[F], outcomes: &[F]) -> LinearCoefficients<F>;
+        /// Put this estimator in a box.
+        /// This is useful for conditionally choosing different estimators.
+        fn boxed_linear(self) -> Box<dyn LinearEstimator<F>>
+        where
+            Self: Sized + 'static,
+        {
+            Box::new(self)
+        }
+    }
+    impl<F, T: LinearEstimator<F> + ?Sized> LinearEstimator<F> for &T {
+        fn model_linear(&self, predictors: &[F], outcomes: &[F]) -> LinearCoefficients<F> {
+            (**self).model_linear(predictors, outcomes)
+        }
+    }
+    impl<F, T: LinearEstimator<F> + ?Sized> LinearEstimator<F> for Box<T> {
+        fn model_linear(&self, predictors: &[F], outcomes: &[F]) -> LinearCoefficients<F> {
+            (**self).model_linear(predictors, outcomes)
+        }
+    }
 
+    /// The numbers the generic estimators work with, e.g. [`f64`], [`f32`], or (with the
+    /// `arbitrary-precision` feature) `arbitrary_linear_algebra::FloatWrapper`.
+    pub trait Scalar: Clone + PartialOrd + num_traits::Num + num_traits::FromPrimitive {}
+    impl<T: Clone + PartialOrd + num_traits::Num + num_traits::FromPrimitive> Scalar for T {}
+
+    estimator!(
         /// Implemented by all estimators yielding a polynomial regression.
         PolynomialEstimator -> PolynomialCoefficients,
         /// Also takes a `degree` of the target polynomial. Some estimators may panic when `degree`
@@ -615,11 +1144,248 @@ pub mod models {
             CotangentEstimator -> CotangentCoefficients, (max_frequency: f64), model_cotangent, boxed_cotangent
         );
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn polynomial_calculus() {
+            // (x - 1)(x + 2)(x - 3) = x³ - 2x² - 5x + 6
+            let p = PolynomialCoefficients::from([6., -5., -2., 1.]);
+            assert_eq!(*p.derivative(), [-5., -4., 3.]);
+            assert_eq!(*p.integral(), [0., 6., -2.5, -2. / 3., 0.25]);
+            let roots = p.roots();
+            assert_eq!(roots.len(), 3, "{roots:?}");
+            for (root, expected) in roots.iter().zip([-2., 1., 3.]) {
+                assert!((root - expected).abs() < 1e-12, "{roots:?}");
+            }
+            // the extrema, (2 ± √19) / 3
+            let extrema = p.derivative().roots();
+            assert!((extrema[0] - (2. - 19f64.sqrt()) / 3.).abs() < 1e-12);
+            assert!((extrema[1] - (2. + 19f64.sqrt()) / 3.).abs() < 1e-12);
+
+            // touches 0 at 2: (x - 2)²(x² + 1)
+            let p = PolynomialCoefficients::from([4., -4., 5., -4., 1.]);
+            let roots = p.roots();
+            assert_eq!(roots.len(), 1, "{roots:?}");
+            assert!((roots[0] - 2.).abs() < 1e-6, "{roots:?}");
+
+            assert!(PolynomialCoefficients::from([1., 0., 1.])
+                .roots()
+                .is_empty());
+            assert!(PolynomialCoefficients::from([3.]).roots().is_empty());
+            // trailing zeros are ignored
+            assert_eq!(PolynomialCoefficients::from([2., -1., 0.]).roots(), [2.]);
+        }
+        #[test]
+        fn parse() {
+            fn round_trip<T: Display + std::str::FromStr + PartialEq + fmt::Debug>(model: T)
+            where
+                T::Err: fmt::Debug,
+            {
+                let s = model.to_string();
+                assert_eq!(s.parse::<T>().unwrap(), model, "{s}");
+            }
+            round_trip(LinearCoefficients { k: 2.5, m: -1. });
+            round_trip(PolynomialCoefficients::from([4., -1., 0., 3.]));
+            round_trip(PolynomialCoefficients::from([
+                0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 2.,
+            ]));
+            round_trip(PowerCoefficients {
+                k: 3.1,
+                e: 2.,
+                predictor_additive: 0.,
+                outcome_additive: 0.,
+            });
+            round_trip(PowerCoefficients {
+                k: 3.1,
+                e: -0.5,
+                predictor_additive: 1.5,
+                outcome_additive: 2.,
+            });
+            round_trip(ExponentialCoefficients {
+                k: 3.,
+                b: 1.2,
+                predictor_additive: 1.,
+                outcome_additive: 0.5,
+            });
+            round_trip(LogarithmicCoefficients {
+                a: 10.,
+                b: -4.,
+                predictor_additive: 0.,
+            });
+            round_trip(LogisticCoefficients {
+                x0: -3.,
+                l: 5.,
+                k: 2.,
+            });
+            round_trip(SegmentedCoefficients {
+                breakpoints: vec![2., 5.],
+                segments: vec![
+                    LinearCoefficients { k: 1., m: 0. },
+                    LinearCoefficients { k: 3., m: -4. },
+                    LinearCoefficients { k: 0., m: 11. },
+                ],
+            });
+
+            let line: LinearCoefficients = "2x - 3".parse().unwrap();
+            assert_eq!(line, LinearCoefficients { k: 2., m: -3. });
+            let polynomial: PolynomialCoefficients = "x^2 + 1".parse().unwrap();
+            assert_eq!(*polynomial, [1., 0., 1.]);
+            assert!("2x +".parse::<LinearCoefficients>().is_err());
+
+            let model: ModelEnum = "3.1 * x^2.0".parse().unwrap();
+            assert!(matches!(model, ModelEnum::Power(_)));
+            assert_eq!(model.predict_outcome(2.), 3.1 * 4.);
+            let model: ModelEnum = "2.5x + 1.0".parse().unwrap();
+            assert!(matches!(model, ModelEnum::Linear(_)));
+            let model: ModelEnum = "3.0 * 1.2^x".parse().unwrap();
+            assert!(matches!(model, ModelEnum::Exponential(_)));
+            assert_eq!(
+                "y = 2x".parse::<ModelEnum>(),
+                Err(ParseModelError { expected: "model" })
+            );
+        }
+        #[test]
+        fn model_enum() {
+            let models: [ModelEnum; 3] = [
+                LinearCoefficients { k: 2., m: 1. }.into(),
+                PolynomialCoefficients::from([1., 0., 3.]).into(),
+                LogarithmicCoefficients {
+                    a: 1.,
+                    b: 2.,
+                    predictor_additive: 0.,
+                }
+                .into(),
+            ];
+            assert_eq!(models[0].predict_outcome(3.), 7.);
+            assert_eq!(models[1].predict_outcome(2.), 13.);
+            assert_eq!(format!("{:.1}", models[2]), "1.0 + 2.0 * ln(x)");
+
+            #[cfg(feature = "serde_json")]
+            {
+                let json = serde_json::to_string(&models[1]).unwrap();
+                assert_eq!(
+                    json,
+                    r#"{"model":"polynomial","coefficients":[1.0,0.0,3.0]}"#
+                );
+                for model in &models {
+                    let json = serde_json::to_string(model).unwrap();
+                    assert_eq!(&serde_json::from_str::<ModelEnum>(&json).unwrap(), model);
+                }
+            }
+        }
+    }
+}
+
+/// A polynomial of degree 2 or higher considered by [`best_fit_with_options`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolynomialCandidate {
+    /// The degree of the polynomial.
+    pub degree: usize,
+    /// The polynomial is only considered if there are more points than this.
+    pub min_len: usize,
+    /// Multiplied with the R², to partially mitigate
+    /// [overfitting](https://en.wikipedia.org/wiki/Overfitting).
+    pub disadvantage: f64,
+}
+
+/// Controls which models [`best_fit_with_options`] considers and how they're rated.
+///
+/// The [`Default`] is what [`best_fit`] uses. Change the fields or use the builder methods, e.g.
+/// `BestFitOptions::default().exponential(false).max_degree(2)`.
+#[must_use]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestFitOptions {
+    /// Consider power fits (`y = k * x^e`).
+    pub power: bool,
+    /// Consider exponential fits (`y = k * b^x`).
+    pub exponential: bool,
+    /// Consider logarithmic fits (`y = a + b ln(x)`).
+    pub logarithmic: bool,
+    /// The polynomials of degree 2 and up to consider. Only used if a [`PolynomialEstimator`] is
+    /// given to [`best_fit_with_options`].
+    pub polynomials: Vec<PolynomialCandidate>,
+    /// Power and exponential fits are only considered if no data is less than this, and
+    /// logarithmic fits only if no predictor is less than this.
+    ///
+    /// This is due to the sub-optimal behaviour of logarithm with values close to and under 0.
+    pub transform_min: f64,
+    /// Added to the R² of the line, as that's probably what you want.
+    pub linear_bump: f64,
+    /// Multiplied with the R² of power fits for each of the exponent and its reciprocal being
+    /// close to a "nice" number.
+    pub power_bump: f64,
+    /// Multiplied with the R² of power and exponential fits once if R² > 0.8 and again if
+    /// R² > 0.92, which seldom happens with exponential regression.
+    pub exponential_bump: f64,
+    /// The least increase of R² to use the [nonlinear least squares](derived::power_nls)
+    /// refinement of power and exponential fits.
+    pub refinement_min_improvement: f64,
+}
+impl BestFitOptions {
+    /// Sets [`Self::power`].
+    pub fn power(mut self, consider: bool) -> Self {
+        self.power = consider;
+        self
+    }
+    /// Sets [`Self::exponential`].
+    pub fn exponential(mut self, consider: bool) -> Self {
+        self.exponential = consider;
+        self
+    }
+    /// Sets [`Self::logarithmic`].
+    pub fn logarithmic(mut self, consider: bool) -> Self {
+        self.logarithmic = consider;
+        self
+    }
+    /// Removes the [polynomials](Self::polynomials) with a degree larger than `degree`.
+    /// Use 1 to only consider lines.
+    pub fn max_degree(mut self, degree: usize) -> Self {
+        self.polynomials
+            .retain(|candidate| candidate.degree <= degree);
+        self
+    }
+    /// Sets [`Self::transform_min`].
+    pub fn transform_min(mut self, min: f64) -> Self {
+        self.transform_min = min;
+        self
+    }
+}
+impl Default for BestFitOptions {
+    fn default() -> Self {
+        // These values are chosen from heuristics in my brain
+        Self {
+            power: true,
+            exponential: true,
+            logarithmic: true,
+            polynomials: vec![
+                PolynomialCandidate {
+                    degree: 2,
+                    min_len: 15,
+                    disadvantage: 0.94,
+                },
+                PolynomialCandidate {
+                    degree: 3,
+                    min_len: 50,
+                    disadvantage: 0.9,
+                },
+            ],
+            transform_min: 1.0,
+            linear_bump: 0.0,
+            power_bump: 1.5,
+            exponential_bump: 1.3,
+            refinement_min_improvement: 0.005,
+        }
+    }
 }
 
 /// Finds the model best fit to the input data.
 /// This is done using heuristics and testing of methods.
 ///
+/// Uses the default [`BestFitOptions`]. See [`best_fit_with_options`] to change them.
+///
 /// # Panics
 ///
 /// Panics if the model has less than two parameters or if the two slices have different lengths.
@@ -635,6 +1401,9 @@ pub mod models {
 ///   distance_from_integer < 0.15 && -2.5 <= exponent <= 3.5`
 /// - Power is also heavily favoured if the same as above occurs but with the reciprocal of the
 ///   exponent. Then, the range 0.5 < exponent.recip() <= 3.5 is considered.
+/// - Logarithmic (`a + b ln(x)`) only if no predictor is < 1, for the same reason.
+/// - Power and exponential fits are [refined](derived::power_nls) to minimize the error of the
+///   untransformed values, if that increases R² by more than 0.005.
 /// - Exponential favoured if R² > 0.8, which seldom happens with exponential regression.
 /// - Bump the rating of linear, as that's probably what you want.
 /// - 2'nd degree polynomial is only considered if `n > 15`, where `n` is `predictors.len()`.
@@ -644,41 +1413,84 @@ pub fn best_fit(
     outcomes: &[f64],
     linear_estimator: &impl LinearEstimator,
 ) -> DynModel {
-    // These values are chosen from heuristics in my brain
-    /// Additive
-    const LINEAR_BUMP: f64 = 0.0;
-    /// Multiplicative
-    const POWER_BUMP: f64 = 1.5;
-    /// Multiplicative
-    const EXPONENTIAL_BUMP: f64 = 1.3;
-    /// Used to partially mitigate [overfitting](https://en.wikipedia.org/wiki/Overfitting).
-    ///
-    /// Multiplicative
-    // `TODO`: remove when we use generic polynomial provider
-    #[allow(unused)]
-    const SECOND_DEGREE_DISADVANTAGE: f64 = 0.94;
-    /// Used to partially mitigate [overfitting](https://en.wikipedia.org/wiki/Overfitting).
-    ///
-    /// Multiplicative
-    // `TODO`: remove when we use generic polynomial provider
-    #[allow(unused)]
-    const THIRD_DEGREE_DISADVANTAGE: f64 = 0.9;
-
-    let mut best: Option<(DynModel, f64)> = None;
-    macro_rules! update_best {
+    #[cfg(feature = "ols")]
+    let polynomial_estimator: Option<&dyn PolynomialEstimator> = Some(&OlsEstimator);
+    #[cfg(not(feature = "ols"))]
+    let polynomial_estimator = None;
+    best_fit_with_options(
+        predictors,
+        outcomes,
+        linear_estimator,
+        polynomial_estimator,
+        &BestFitOptions::default(),
+    )
+}
+/// Same as [`best_fit`], but with the considered models and the constants of the heuristics
+/// given by `options`.
+///
+/// The [polynomials](BestFitOptions::polynomials) are fitted using `polynomial_estimator`, e.g.
+/// [`OlsEstimator`] (which [`best_fit`] uses) or a robust estimator. If it's [`None`], no
+/// polynomials are considered.
+///
+/// # Panics
+///
+/// See [`best_fit`].
+pub fn best_fit_with_options(
+    predictors: &[f64],
+    outcomes: &[f64],
+    linear_estimator: &impl LinearEstimator,
+    polynomial_estimator: Option<&dyn PolynomialEstimator>,
+    options: &BestFitOptions,
+) -> DynModel {
+    best_fit_ranked(
+        predictors,
+        outcomes,
+        linear_estimator,
+        polynomial_estimator,
+        options,
+    )
+    .swap_remove(0)
+    .model
+}
+
+/// A model evaluated by [`best_fit_ranked`].
+pub struct RankedModel {
+    /// The fitted model.
+    pub model: DynModel,
+    /// The R² of [`Self::model`].
+    pub determination: f64,
+    /// The R² weighted by the heuristics of [`best_fit`]. The models are ranked by this.
+    pub score: f64,
+}
+/// Evaluates all the models considered by [`best_fit_with_options`], and returns them ranked by
+/// their [score](RankedModel::score), best first. The first item is what
+/// [`best_fit_with_options`] returns.
+///
+/// Use this to show the runner-up fits and how close the decision was.
+///
+/// # Panics
+///
+/// See [`best_fit`].
+pub fn best_fit_ranked(
+    predictors: &[f64],
+    outcomes: &[f64],
+    linear_estimator: &impl LinearEstimator,
+    polynomial_estimator: Option<&dyn PolynomialEstimator>,
+    options: &BestFitOptions,
+) -> Vec<RankedModel> {
+    let mut ranked = Vec::new();
+    macro_rules! evaluate {
         ($new: expr, $e: ident, $modificator: expr, $err: expr) => {
             let $e = $err;
             let weighted = $modificator;
-            if let Some((_, error)) = &best {
-                if weighted > *error {
-                    best = Some((DynModel::new($new), weighted))
-                }
-            } else {
-                best = Some((DynModel::new($new), weighted))
-            }
+            ranked.push(RankedModel {
+                model: DynModel::new($new),
+                determination: $e,
+                score: weighted,
+            });
         };
         ($new: expr, $e: ident, $modificator: expr) => {
-            update_best!(
+            evaluate!(
                 $new,
                 $e,
                 $modificator,
@@ -686,14 +1498,22 @@ pub fn best_fit(
             )
         };
         ($new: expr) => {
-            update_best!($new, e, e)
+            evaluate!($new, e, e)
         };
     }
 
+    let refine_if_better = |model: &dyn Predictive, refined: &dyn Predictive| -> bool {
+        let improvement = refined.determination_slice(predictors, outcomes)
+            - model.determination_slice(predictors, outcomes);
+        improvement > options.refinement_min_improvement
+    };
+
     let predictor_min = derived::min(predictors).unwrap();
     let outcomes_min = derived::min(outcomes).unwrap();
+    let transformable_predictors = predictor_min >= options.transform_min;
+    let transformable = transformable_predictors && outcomes_min >= options.transform_min;
 
-    if predictor_min >= 1.0 && outcomes_min >= 1.0 {
+    if transformable && options.power {
         let mut mod_predictors = predictors.to_vec();
         let mut mod_outcomes = outcomes.to_vec();
         let power = derived::power_given_min(
@@ -703,29 +1523,35 @@ pub fn best_fit(
             outcomes_min,
             linear_estimator,
         );
+        let refined = derived::refine_power(predictors, outcomes, &power);
+        let power = if refine_if_better(&power, &refined) {
+            refined
+        } else {
+            power
+        };
 
         let distance_from_integer = -(0.5 - power.e % 1.0).abs() + 0.5;
         let mut power_bump = 1.0;
         if distance_from_integer < 0.15 && power.e <= 3.5 && power.e >= -2.5 {
-            power_bump *= POWER_BUMP;
+            power_bump *= options.power_bump;
         }
         let distance_from_fraction = -(0.5 - power.e.recip() % 1.0).abs() + 0.5;
         if distance_from_fraction < 0.1 && power.e.recip() <= 3.5 && power.e.recip() > 0.5 {
-            power_bump *= POWER_BUMP;
+            power_bump *= options.power_bump;
         }
         let certainty = power.determination_slice(predictors, outcomes);
         if certainty > 0.8 {
-            power_bump *= EXPONENTIAL_BUMP;
+            power_bump *= options.exponential_bump;
         }
         if certainty > 0.92 {
-            power_bump *= EXPONENTIAL_BUMP;
+            power_bump *= options.exponential_bump;
         }
 
-        update_best!(power, e, e * power_bump, certainty);
-
-        mod_predictors[..].copy_from_slice(predictors);
-        mod_outcomes[..].copy_from_slice(outcomes);
-
+        evaluate!(power, e, e * power_bump, certainty);
+    }
+    if transformable && options.exponential {
+        let mut mod_predictors = predictors.to_vec();
+        let mut mod_outcomes = outcomes.to_vec();
         let exponential = derived::exponential_given_min(
             &mut mod_predictors,
             &mut mod_outcomes,
@@ -733,47 +1559,58 @@ pub fn best_fit(
             outcomes_min,
             linear_estimator,
         );
+        let refined = derived::refine_exponential(predictors, outcomes, &exponential);
+        let exponential = if refine_if_better(&exponential, &refined) {
+            refined
+        } else {
+            exponential
+        };
         let certainty = exponential.determination_slice(predictors, outcomes);
 
         let mut exponential_bump = if certainty > 0.8 {
-            EXPONENTIAL_BUMP
+            options.exponential_bump
         } else {
             1.0
         };
         if certainty > 0.92 {
-            exponential_bump *= EXPONENTIAL_BUMP;
+            exponential_bump *= options.exponential_bump;
         }
 
-        update_best!(exponential, e, e * exponential_bump, certainty);
+        evaluate!(exponential, e, e * exponential_bump, certainty);
     }
-    // `TODO`: use generic polynomial provider.
-    #[cfg(feature = "ols")]
-    if predictors.len() > 15 {
-        let degree_2 = ols::polynomial(
-            predictors.iter().copied(),
-            outcomes.iter().copied(),
-            predictors.len(),
-            2,
+    if transformable_predictors && options.logarithmic {
+        let mut mod_predictors = predictors.to_vec();
+        let logarithmic = derived::logarithmic_given_min(
+            &mut mod_predictors,
+            outcomes,
+            predictor_min,
+            linear_estimator,
         );
-
-        update_best!(degree_2, e, e * SECOND_DEGREE_DISADVANTAGE);
+        evaluate!(logarithmic);
     }
-    #[cfg(feature = "ols")]
-    if predictors.len() > 50 {
-        let degree_3 = ols::polynomial(
-            predictors.iter().copied(),
-            outcomes.iter().copied(),
-            predictors.len(),
-            3,
-        );
+    if let Some(estimator) = polynomial_estimator {
+        for candidate in &options.polynomials {
+            if predictors.len() > candidate.min_len && predictors.len() > candidate.degree {
+                let polynomial = estimator.model_polynomial(predictors, outcomes, candidate.degree);
 
-        update_best!(degree_3, e, e * THIRD_DEGREE_DISADVANTAGE);
+                evaluate!(polynomial, e, e * candidate.disadvantage);
+            }
+        }
     }
 
     let linear = linear_estimator.model_linear(predictors, outcomes);
-    update_best!(linear, e, e + LINEAR_BUMP);
-    // UNWRAP: We just set it, at least there's a linear.
-    best.unwrap().0
+    evaluate!(linear, e, e + options.linear_bump);
+
+    // NaN last. Stable, so the first evaluated model wins ties.
+    let score = |model: &RankedModel| {
+        if model.score.is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            model.score
+        }
+    };
+    ranked.sort_by(|a, b| crate::F64OrdHash::f64_cmp(score(b), score(a)));
+    ranked
 }
 /// Convenience function for [`best_fit`] using [`OlsEstimator`].
 #[cfg(feature = "ols")]
@@ -781,18 +1618,213 @@ pub fn best_fit_ols(predictors: &[f64], outcomes: &[f64]) -> DynModel {
     best_fit(predictors, outcomes, &OlsEstimator)
 }
 
-/// Estimators derived from others, usual [`LinearEstimator`].
+/// Finds the model best fit to the input data using
+/// [cross-validation](https://en.wikipedia.org/wiki/Cross-validation_(statistics)).
 ///
-/// These do not (for now) implement [`PowerEstimator`] nor [`ExponentialEstimator`]
-/// because of the requirement of mutable slices instead of immutable ones.
+/// Each candidate model is fitted `folds` times, each time leaving out every `folds`th point,
+/// and scored by the mean squared error of the predictions of the left out points. The candidate
+/// with the lowest error is then fitted to all the data. Unlike the R² used by [`best_fit`], this
+/// penalizes overfitting without any hand-tuned constants, which matters for small and noisy
+/// datasets.
 ///
-/// See the docs on the items for more info about how they're created.
-pub mod derived {
-    use super::*;
-    pub(super) fn min(slice: &[f64]) -> Option<f64> {
-        slice
-            .iter()
-            .copied()
+/// If `folds >= predictors.len()`, this is leave-one-out cross-validation.
+///
+/// The candidates are the same as in [`best_fit`]: linear, power and exponential (if no data is <
+/// 1), logarithmic (if no predictor is < 1), and, with the `ols` feature, polynomials of degree 2
+/// and 3. Power and exponential fits use [nonlinear least squares](derived::power_nls).
+/// Candidates with at least as many coefficients as the points of a fold's training set are
+/// skipped.
+///
+/// # Panics
+///
+/// Panics if `folds < 2`, if the two slices have different lengths, or if there are too few points
+/// to fit a line without one of the folds (`n - ⌈n / folds⌉ < 2`).
+pub fn best_fit_cv(
+    predictors: &[f64],
+    outcomes: &[f64],
+    linear_estimator: &impl LinearEstimator,
+    folds: usize,
+) -> DynModel {
+    assert_eq!(
+        predictors.len(),
+        outcomes.len(),
+        "predictors and outcomes must have the same number of items"
+    );
+    assert!(folds >= 2, "cross-validation requires at least 2 folds");
+    let len = predictors.len();
+    let folds = folds.min(len);
+    // the smallest training set, without the largest fold
+    let training_len = len - (len + folds - 1) / folds;
+    assert!(
+        training_len >= 2,
+        "too few points to fit a line without one of the folds"
+    );
+
+    type Fit<'a> = Box<dyn Fn(&[f64], &[f64]) -> DynModel + 'a>;
+    let mut candidates: Vec<Fit> =
+        vec![Box::new(|x, y| linear_estimator.model_linear(x, y).boxed())];
+    let predictor_min = derived::min(predictors).unwrap();
+    let outcomes_min = derived::min(outcomes).unwrap();
+    if training_len > 2 && predictor_min >= 1.0 && outcomes_min >= 1.0 {
+        candidates.push(Box::new(|x, y| {
+            derived::power_nls(x, y, linear_estimator).boxed()
+        }));
+        candidates.push(Box::new(|x, y| {
+            derived::exponential_nls(x, y, linear_estimator).boxed()
+        }));
+    }
+    if training_len > 2 && predictor_min >= 1.0 {
+        candidates.push(Box::new(|x, y| {
+            derived::logarithmic(&mut x.to_vec(), y, linear_estimator).boxed()
+        }));
+    }
+    #[cfg(feature = "ols")]
+    for degree in (2..=3).filter(|degree| training_len > degree + 1) {
+        candidates.push(Box::new(move |x, y| {
+            ols::polynomial(x.iter().copied(), y.iter().copied(), x.len(), degree).boxed()
+        }));
+    }
+
+    let errors: Vec<f64> = candidates
+        .iter()
+        .map(|fit| cross_validated_error(predictors, outcomes, folds, fit))
+        .collect();
+    let best = errors
+        .iter()
+        .enumerate()
+        .min_by(|a, b| crate::F64OrdHash::f64_cmp(*a.1, *b.1))
+        // UNWRAP: there's always the linear candidate
+        .unwrap()
+        .0;
+    candidates[best](predictors, outcomes)
+}
+/// Convenience function for [`best_fit_cv`] using [`OlsEstimator`].
+#[cfg(feature = "ols")]
+pub fn best_fit_cv_ols(predictors: &[f64], outcomes: &[f64], folds: usize) -> DynModel {
+    best_fit_cv(predictors, outcomes, &OlsEstimator, folds)
+}
+/// The mean squared error of predicting each `folds`th point with a model `fit` to the others.
+/// NaN errors are infinite.
+fn cross_validated_error(
+    predictors: &[f64],
+    outcomes: &[f64],
+    folds: usize,
+    fit: &dyn Fn(&[f64], &[f64]) -> DynModel,
+) -> f64 {
+    let len = predictors.len();
+    let mut training_predictors = Vec::with_capacity(len);
+    let mut training_outcomes = Vec::with_capacity(len);
+    let mut squared_error = 0.;
+    for fold in 0..folds {
+        training_predictors.clear();
+        training_outcomes.clear();
+        for (index, (x, y)) in predictors.iter().zip(outcomes).enumerate() {
+            if index % folds != fold {
+                training_predictors.push(*x);
+                training_outcomes.push(*y);
+            }
+        }
+        let model = fit(&training_predictors, &training_outcomes);
+        squared_error += predictors
+            .iter()
+            .zip(outcomes)
+            .skip(fold)
+            .step_by(folds)
+            .map(|(x, y)| (y - model.predict_outcome(*x)).powi(2))
+            .sum::<f64>();
+    }
+    let error = squared_error / len as f64;
+    if error.is_nan() {
+        f64::INFINITY
+    } else {
+        error
+    }
+}
+
+/// How [`select_degree`] compares the degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DegreeCriterion {
+    /// The [Bayesian information criterion](https://en.wikipedia.org/wiki/Bayesian_information_criterion),
+    /// `n ln(RSS / n) + k ln(n)`, where `k` is the count of coefficients. It's fast, as each
+    /// degree is only fitted once.
+    Bic,
+    /// The mean squared error of [k-fold cross-validation](best_fit_cv) with this many folds.
+    CrossValidation {
+        /// A value larger than the count of points is leave-one-out.
+        folds: usize,
+    },
+}
+/// Chooses the degree of a polynomial fit to the data, from 1 to `max_degree`, using `criterion`.
+///
+/// The R² of a polynomial grows with its degree, so it can't be used to choose one. Both criteria
+/// instead penalize the added coefficients. Ties are resolved to the lowest degree.
+///
+/// Degrees with at least as many coefficients as there are points (or points in a fold's training
+/// set) are skipped. Returns [`None`] if that's all of them, i.e. if there are fewer than 3 points,
+/// or fewer than 2 without one of the folds.
+///
+/// # Panics
+///
+/// Panics if `max_degree` is 0, if the two slices have different lengths, or if the
+/// cross-validation has fewer than 2 folds.
+pub fn select_degree(
+    predictors: &[f64],
+    outcomes: &[f64],
+    estimator: &impl PolynomialEstimator,
+    max_degree: usize,
+    criterion: DegreeCriterion,
+) -> Option<usize> {
+    assert_eq!(
+        predictors.len(),
+        outcomes.len(),
+        "predictors and outcomes must have the same number of items"
+    );
+    assert!(max_degree >= 1, "the max degree must be at least 1");
+    let len = predictors.len();
+    let score = |degree: usize| -> Option<f64> {
+        let fit = |x: &[f64], y: &[f64]| estimator.model_polynomial(x, y, degree).boxed();
+        match criterion {
+            DegreeCriterion::Bic => {
+                if degree + 1 >= len {
+                    return None;
+                }
+                let model = fit(predictors, outcomes);
+                let rss: f64 = predictors
+                    .iter()
+                    .zip(outcomes)
+                    .map(|(x, y)| (y - model.predict_outcome(*x)).powi(2))
+                    .sum();
+                let n = len as f64;
+                let bic = n * (rss / n).ln() + (degree + 1) as f64 * n.ln();
+                Some(if bic.is_nan() { f64::INFINITY } else { bic })
+            }
+            DegreeCriterion::CrossValidation { folds } => {
+                assert!(folds >= 2, "cross-validation requires at least 2 folds");
+                let folds = folds.min(len);
+                let training_len = len - (len + folds - 1) / folds;
+                (training_len > degree + 1 || (degree == 1 && training_len >= 2))
+                    .then(|| cross_validated_error(predictors, outcomes, folds, &fit))
+            }
+        }
+    };
+    (1..=max_degree)
+        .filter_map(|degree| Some((degree, score(degree)?)))
+        .min_by(|a, b| crate::F64OrdHash::f64_cmp(a.1, b.1))
+        .map(|(degree, _)| degree)
+}
+
+/// Estimators derived from others, usual [`LinearEstimator`].
+///
+/// These do not (for now) implement [`PowerEstimator`] nor [`ExponentialEstimator`]
+/// because of the requirement of mutable slices instead of immutable ones.
+///
+/// See the docs on the items for more info about how they're created.
+pub mod derived {
+    use super::*;
+    pub(super) fn min(slice: &[f64]) -> Option<f64> {
+        slice
+            .iter()
+            .copied()
             .map(crate::F64OrdHash)
             .min()
             .map(|f| f.0)
@@ -964,122 +1996,913 @@ pub mod derived {
             outcome_additive: outcome_additive.unwrap_or(0.),
         }
     }
-}
 
-/// This module enables the use of [`rug::Float`] inside of [`nalgebra`].
-///
-/// Many functions are not implemented. PRs are welcome.
-#[cfg(feature = "arbitrary-precision")]
-pub mod arbitrary_linear_algebra {
-    use std::cell::RefCell;
-    use std::fmt::{self, Display};
-    use std::ops::{
-        Add, AddAssign, Deref, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
-    };
+    /// Convenience-method for [`logarithmic`] using [`OlsEstimator`].
+    #[cfg(feature = "ols")]
+    pub fn logarithmic_ols(predictors: &mut [f64], outcomes: &[f64]) -> LogarithmicCoefficients {
+        logarithmic(predictors, outcomes, &OlsEstimator)
+    }
+    /// Fits a curve with the equation `y = a + b * ln(x)` (optionally with an additive to the `x`
+    /// if any predictor is < 1).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors` and `outcomes` have different lengths.
+    /// The length must be greater than 2.
+    ///
+    /// # Derivation
+    ///
+    /// y = a + b * ln(x) is a line in ln(x).
+    ///
+    /// Transform: x => ln(x)
+    pub fn logarithmic<E: LinearEstimator>(
+        predictors: &mut [f64],
+        outcomes: &[f64],
+        estimator: &E,
+    ) -> LogarithmicCoefficients {
+        assert!(predictors.len() > 2);
+        let predictor_min = min(predictors).unwrap();
+        logarithmic_given_min(predictors, outcomes, predictor_min, estimator)
+    }
+    /// Same as [`logarithmic`], but takes the min value of the predictors.
+    ///
+    /// # Panics
+    ///
+    /// See [`logarithmic`].
+    pub fn logarithmic_given_min<E: LinearEstimator>(
+        predictors: &mut [f64],
+        outcomes: &[f64],
+        predictor_min: f64,
+        estimator: &E,
+    ) -> LogarithmicCoefficients {
+        assert_eq!(predictors.len(), outcomes.len());
+        assert!(predictors.len() > 2);
 
-    use nalgebra::{ComplexField, RealField};
-    use rug::Assign;
+        // If less than 1, exception. Read more about this in the `power` function docs.
+        let predictor_additive = if predictor_min < 1.0 {
+            1.0 - predictor_min
+        } else {
+            0.0
+        };
+        predictors
+            .iter_mut()
+            .for_each(|pred| *pred = (*pred + predictor_additive).ln());
 
-    thread_local! {
-        /// The default precision.
-        ///
-        /// This is thread-local.
-        pub static DEFAULT_PRECISION: RefCell<u32> = const { RefCell::new(256) };
+        let coefficients = estimator.model_linear(predictors, outcomes);
+        LogarithmicCoefficients {
+            a: coefficients.m,
+            b: coefficients.k,
+            predictor_additive,
+        }
     }
-    /// Set the default precision **for this thread**.
-    pub fn set_default_precision(new: u32) {
-        DEFAULT_PRECISION.with(|v| *v.borrow_mut() = new);
+
+    /// Fits `y = a * x^b` like [`power`], then refines the coefficients to minimize the squared
+    /// error of the untransformed values with [`refine_power`].
+    ///
+    /// The logarithmic transform of [`power`] gives small values more weight, which biases the
+    /// fit. This removes that bias, at the cost of a few extra passes over the data.
+    ///
+    /// # Panics
+    ///
+    /// See [`power`].
+    pub fn power_nls<E: LinearEstimator>(
+        predictors: &[f64],
+        outcomes: &[f64],
+        estimator: &E,
+    ) -> PowerCoefficients {
+        let start = power(&mut predictors.to_vec(), &mut outcomes.to_vec(), estimator);
+        refine_power(predictors, outcomes, &start)
     }
-    /// Get the default precision.
-    /// Can be set using [`set_default_precision`].
-    pub fn default_precision() -> u32 {
-        DEFAULT_PRECISION.with(|v| *v.borrow())
+    /// Fits `y = a * b^x` like [`exponential`], then refines the coefficients to minimize the
+    /// squared error of the untransformed values with [`refine_exponential`].
+    ///
+    /// See [`power_nls`] for why.
+    ///
+    /// # Panics
+    ///
+    /// See [`exponential`].
+    pub fn exponential_nls<E: LinearEstimator>(
+        predictors: &[f64],
+        outcomes: &[f64],
+        estimator: &E,
+    ) -> ExponentialCoefficients {
+        let start = exponential(&mut predictors.to_vec(), &mut outcomes.to_vec(), estimator);
+        refine_exponential(predictors, outcomes, &start)
     }
-    /// A wrapper around [`rug::Float`] to implement traits for.
-    #[derive(Debug, Clone, PartialEq, PartialOrd)]
-    pub struct FloatWrapper(pub rug::Float);
-    impl From<rug::Float> for FloatWrapper {
-        fn from(f: rug::Float) -> Self {
-            Self(f)
-        }
+    /// Minimizes the squared error of `start` on the points using
+    /// [Levenberg-Marquardt](https://en.wikipedia.org/wiki/Levenberg%E2%80%93Marquardt_algorithm).
+    ///
+    /// [`PowerCoefficients::k`] and [`PowerCoefficients::e`] are optimized, the additives are
+    /// kept. `start` is returned if it can't be improved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors` and `outcomes` have different lengths.
+    pub fn refine_power(
+        predictors: &[f64],
+        outcomes: &[f64],
+        start: &PowerCoefficients,
+    ) -> PowerCoefficients {
+        let [k, e] = levenberg_marquardt([start.k, start.e], predictors, outcomes, |[k, e], x| {
+            let base = x + start.predictor_additive;
+            let power = base.powf(e);
+            (
+                k * power - start.outcome_additive,
+                [power, k * power * base.ln()],
+            )
+        });
+        PowerCoefficients { k, e, ..*start }
     }
-
-    impl simba::scalar::SupersetOf<f64> for FloatWrapper {
-        fn is_in_subset(&self) -> bool {
-            self.0.prec() <= 53
+    /// Same as [`refine_power`], for [`ExponentialCoefficients`]. [`ExponentialCoefficients::k`]
+    /// and [`ExponentialCoefficients::b`] are optimized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors` and `outcomes` have different lengths.
+    pub fn refine_exponential(
+        predictors: &[f64],
+        outcomes: &[f64],
+        start: &ExponentialCoefficients,
+    ) -> ExponentialCoefficients {
+        // optimize the logarithm of the base, which keeps the base positive
+        let [k, rate] = levenberg_marquardt(
+            [start.k, start.b.ln()],
+            predictors,
+            outcomes,
+            |[k, rate], x| {
+                let x = x + start.predictor_additive;
+                let exp = (rate * x).exp();
+                (k * exp - start.outcome_additive, [exp, k * x * exp])
+            },
+        );
+        ExponentialCoefficients {
+            k,
+            b: rate.exp(),
+            ..*start
         }
-        fn to_subset(&self) -> Option<f64> {
-            if simba::scalar::SupersetOf::<f64>::is_in_subset(self) {
-                Some(self.0.to_f64())
-            } else {
-                None
+    }
+    /// Minimizes `Σ(y - f(x))²` over the two parameters of `f`, starting at `start`.
+    ///
+    /// `model` returns `f(x)` and its derivatives with respect to the parameters.
+    fn levenberg_marquardt(
+        start: [f64; 2],
+        predictors: &[f64],
+        outcomes: &[f64],
+        model: impl Fn([f64; 2], f64) -> (f64, [f64; 2]),
+    ) -> [f64; 2] {
+        assert_eq!(
+            predictors.len(),
+            outcomes.len(),
+            "predictors and outcomes must have the same number of items"
+        );
+        let error = |parameters: [f64; 2]| -> f64 {
+            predictors
+                .iter()
+                .zip(outcomes)
+                .map(|(x, y)| (y - model(parameters, *x).0).powi(2))
+                .sum()
+        };
+        let mut parameters = start;
+        let mut current = error(parameters);
+        if !current.is_finite() {
+            return start;
+        }
+        let mut damping = 1e-3;
+        for _ in 0..100 {
+            // the normal equations of the linearized model, `JᵀJ δ = Jᵀr`
+            let mut jtj = [[0.; 2]; 2];
+            let mut jtr = [0.; 2];
+            for (x, y) in predictors.iter().zip(outcomes) {
+                let (predicted, derivatives) = model(parameters, *x);
+                let residual = y - predicted;
+                for i in 0..2 {
+                    jtr[i] += derivatives[i] * residual;
+                    for j in 0..2 {
+                        jtj[i][j] += derivatives[i] * derivatives[j];
+                    }
+                }
+            }
+            let improved = loop {
+                // scaling the damping by the diagonal makes it independent of the units
+                let a = jtj[0][0] * (1. + damping);
+                let d = jtj[1][1] * (1. + damping);
+                let determinant = a * d - jtj[0][1] * jtj[1][0];
+                let step = [
+                    (d * jtr[0] - jtj[0][1] * jtr[1]) / determinant,
+                    (a * jtr[1] - jtj[1][0] * jtr[0]) / determinant,
+                ];
+                let candidate = [parameters[0] + step[0], parameters[1] + step[1]];
+                let candidate_error = error(candidate);
+                if candidate_error.is_finite() && candidate_error < current {
+                    damping = (damping / 10.).max(1e-12);
+                    let improvement = (current - candidate_error) / current;
+                    parameters = candidate;
+                    current = candidate_error;
+                    break improvement > 1e-12;
+                }
+                damping *= 10.;
+                if damping > 1e12 {
+                    break false;
+                }
+            };
+            if !improved {
+                break;
             }
         }
-        fn to_subset_unchecked(&self) -> f64 {
-            self.0.to_f64()
+        parameters
+    }
+
+    /// Which axes are logarithmic (base 10) when fitting a line with [`fit_axes`].
+    ///
+    /// Unlike [`power`] and [`exponential`], no offsets are added to values below 1; the
+    /// transform is exactly what's drawn on a plot with these axes.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Axes {
+        /// Both axes are logarithmic. A line corresponds to `y = k * x^e`.
+        LogLog,
+        /// Only the predictor axis is logarithmic. A line corresponds to `y = k * lg(x) + m`.
+        SemiLogX,
+        /// Only the outcome axis is logarithmic. A line corresponds to `y = k * b^x`.
+        SemiLogY,
+    }
+    impl Axes {
+        /// If the predictors are transformed.
+        pub fn log_x(self) -> bool {
+            matches!(self, Self::LogLog | Self::SemiLogX)
         }
-        fn from_subset(element: &f64) -> Self {
-            rug::Float::with_val(default_precision(), element).into()
+        /// If the outcomes are transformed.
+        pub fn log_y(self) -> bool {
+            matches!(self, Self::LogLog | Self::SemiLogY)
         }
     }
-    impl simba::scalar::SupersetOf<f32> for FloatWrapper {
-        fn is_in_subset(&self) -> bool {
-            self.0.prec() <= 24
-        }
-        fn to_subset(&self) -> Option<f32> {
-            if simba::scalar::SupersetOf::<f32>::is_in_subset(self) {
-                Some(self.0.to_f32())
-            } else {
-                None
+    /// Returned from [`Axes::from_str`](std::str::FromStr::from_str).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ParseAxesError;
+    impl Display for ParseAxesError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("expected one of `loglog`, `semilogx`, or `semilogy`")
+        }
+    }
+    impl std::error::Error for ParseAxesError {}
+    impl std::str::FromStr for Axes {
+        type Err = ParseAxesError;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.trim().to_ascii_lowercase().as_str() {
+                "loglog" => Ok(Self::LogLog),
+                "semilogx" => Ok(Self::SemiLogX),
+                "semilogy" => Ok(Self::SemiLogY),
+                _ => Err(ParseAxesError),
             }
         }
-        fn to_subset_unchecked(&self) -> f32 {
-            self.0.to_f32()
-        }
-        fn from_subset(element: &f32) -> Self {
-            rug::Float::with_val(default_precision(), element).into()
-        }
     }
-    impl simba::scalar::SubsetOf<Self> for FloatWrapper {
-        fn to_superset(&self) -> Self {
-            self.clone()
-        }
 
-        fn from_superset_unchecked(element: &Self) -> Self {
-            element.clone()
+    /// Returned from [`fit_axes`] when a value on a logarithmic axis isn't positive.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct NonPositiveError {
+        /// The index of the data point.
+        pub index: usize,
+        /// The value which isn't positive.
+        pub value: f64,
+    }
+    impl Display for NonPositiveError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "data point {} has the value {}, which has no logarithm",
+                self.index + 1,
+                self.value
+            )
         }
+    }
+    impl std::error::Error for NonPositiveError {}
 
-        fn is_in_subset(_element: &Self) -> bool {
-            true
+    /// A line fitted to data on logarithmic [`Axes`]. Returned from [`fit_axes`].
+    ///
+    /// [`Predictive`] and [`Display`] use the original (untransformed) values.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct AxesFit {
+        /// The axes the line was fitted on.
+        pub axes: Axes,
+        /// The line, in the transformed space.
+        pub line: LinearCoefficients,
+        /// The R² of [`Self::line`] in the transformed space.
+        pub determination: f64,
+    }
+    impl AxesFit {
+        /// The equivalent power function, if fitted on [`Axes::LogLog`].
+        pub fn power(&self) -> Option<PowerCoefficients> {
+            (self.axes == Axes::LogLog).then(|| PowerCoefficients {
+                k: 10f64.powf(self.line.m),
+                e: self.line.k,
+                predictor_additive: 0.,
+                outcome_additive: 0.,
+            })
         }
-    }
-    impl num_traits::cast::FromPrimitive for FloatWrapper {
-        fn from_i64(n: i64) -> Option<Self> {
-            Some(rug::Float::with_val(default_precision(), n).into())
+        /// The equivalent exponential function, if fitted on [`Axes::SemiLogY`].
+        pub fn exponential(&self) -> Option<ExponentialCoefficients> {
+            (self.axes == Axes::SemiLogY).then(|| ExponentialCoefficients {
+                k: 10f64.powf(self.line.m),
+                b: 10f64.powf(self.line.k),
+                predictor_additive: 0.,
+                outcome_additive: 0.,
+            })
         }
-        fn from_u64(n: u64) -> Option<Self> {
-            Some(rug::Float::with_val(default_precision(), n).into())
+    }
+    impl Predictive for AxesFit {
+        fn predict_outcome(&self, predictor: f64) -> f64 {
+            let x = if self.axes.log_x() {
+                predictor.log10()
+            } else {
+                predictor
+            };
+            let y = self.line.predict_outcome(x);
+            if self.axes.log_y() {
+                10f64.powf(y)
+            } else {
+                y
+            }
         }
     }
-    impl Display for FloatWrapper {
+    impl Display for AxesFit {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            self.0.fmt(f)
+            let p = f.precision().unwrap_or(5);
+            match self.axes {
+                Axes::LogLog => write!(f, "{:.1$}", self.power().unwrap(), p),
+                Axes::SemiLogY => write!(f, "{:.1$}", self.exponential().unwrap(), p),
+                Axes::SemiLogX => {
+                    write!(f, "{:.2$} * lg(x) + {:.2$}", self.line.k, self.line.m, p)
+                }
+            }
         }
     }
-    impl simba::simd::SimdValue for FloatWrapper {
-        type Element = FloatWrapper;
-        type SimdBool = bool;
-
-        const LANES: usize = 1;
 
-        #[inline(always)]
-        fn splat(val: Self::Element) -> Self {
-            val
-        }
-
-        #[inline(always)]
-        fn extract(&self, _: usize) -> Self::Element {
-            self.clone()
+    /// Fits a line to the data after taking the base 10 logarithm of the axes given by `axes`.
+    ///
+    /// This makes the transform done by [`power`] and [`exponential`] explicit: the returned
+    /// [`AxesFit`] contains both the line in the transformed space, its R² in that space, and
+    /// the equivalent equation in the original space.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first value on a logarithmic axis which isn't positive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors` and `outcomes` have different lengths.
+    pub fn fit_axes<E: LinearEstimator>(
+        predictors: &[f64],
+        outcomes: &[f64],
+        axes: Axes,
+        estimator: &E,
+    ) -> Result<AxesFit, NonPositiveError> {
+        assert_eq!(
+            predictors.len(),
+            outcomes.len(),
+            "predictors and outcomes must have the same number of items"
+        );
+        let transform = |values: &[f64], log: bool| {
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, &value)| {
+                    if !log {
+                        Ok(value)
+                    } else if value > 0. {
+                        Ok(value.log10())
+                    } else {
+                        Err(NonPositiveError { index, value })
+                    }
+                })
+                .collect::<Result<Vec<f64>, _>>()
+        };
+        let x = transform(predictors, axes.log_x())?;
+        let y = transform(outcomes, axes.log_y())?;
+        let line = estimator.model_linear(&x, &y);
+        let determination = line.determination_slice(&x, &y);
+        Ok(AxesFit {
+            axes,
+            line,
+            determination,
+        })
+    }
+
+    #[cfg(all(test, feature = "ols"))]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn axes() {
+            let x = [1., 2., 4., 8., 16.];
+            let y: Vec<f64> = x.iter().map(|x: &f64| 3. * x.powf(1.5)).collect();
+            let fit = fit_axes(&x, &y, Axes::LogLog, &OlsEstimator).unwrap();
+            let power = fit.power().unwrap();
+            assert!((power.k - 3.).abs() < 1e-9);
+            assert!((power.e - 1.5).abs() < 1e-9);
+            assert!((fit.determination - 1.).abs() < 1e-9);
+            assert!((fit.predict_outcome(32.) - 3. * 32f64.powf(1.5)).abs() < 1e-6);
+            assert!(fit.exponential().is_none());
+
+            let y: Vec<f64> = x.iter().map(|x| 2. * 1.1f64.powf(*x)).collect();
+            let fit = fit_axes(&x, &y, "semilogy".parse().unwrap(), &OlsEstimator).unwrap();
+            let exponential = fit.exponential().unwrap();
+            assert!((exponential.b - 1.1).abs() < 1e-9);
+
+            let err = fit_axes(&[1., 0., 2.], &[1., 2., 3.], Axes::SemiLogX, &OlsEstimator);
+            assert_eq!(err.unwrap_err().index, 1);
+        }
+        #[test]
+        fn logarithmic_fit() {
+            let x: Vec<f64> = (0..40).map(|v| v as f64 / 4.).collect();
+            // the smallest predictor is 0, so an offset of 1 is added
+            let y: Vec<f64> = x.iter().map(|x| 2. + 3. * (x + 1.).ln()).collect();
+            let fit = logarithmic(&mut x.clone(), &y, &OlsEstimator);
+            assert_eq!(fit.predictor_additive, 1.);
+            assert!((fit.a - 2.).abs() < 1e-9 && (fit.b - 3.).abs() < 1e-9);
+            assert_eq!(format!("{fit:.1}"), "2.0 + 3.0 * ln(x + 1.0)");
+
+            let x: Vec<f64> = (1..=40).map(f64::from).collect();
+            let y: Vec<f64> = x.iter().map(|x| 10. - 4. * x.ln()).collect();
+            let best = best_fit(&x, &y, &OlsEstimator);
+            assert_eq!(format!("{best:.1}"), "10.0 + -4.0 * ln(x)");
+            let best = best_fit_cv(&x, &y, &OlsEstimator, 5);
+            assert_eq!(format!("{best:.1}"), "10.0 + -4.0 * ln(x)");
+        }
+        #[test]
+        fn best_fit_options() {
+            let x: Vec<f64> = (1..=30).map(f64::from).collect();
+            let y: Vec<f64> = x.iter().map(|x| 3. * 1.2f64.powf(*x)).collect();
+            let best = best_fit(&x, &y, &OlsEstimator);
+            assert_eq!(format!("{best:.1}"), "3.0 * 1.2^x");
+
+            let options = BestFitOptions::default().exponential(false).max_degree(2);
+            assert_eq!(options.polynomials.len(), 1);
+            let best = best_fit_with_options(&x, &y, &OlsEstimator, Some(&OlsEstimator), &options);
+            assert_ne!(format!("{best:.1}"), "3.0 * 1.2^x");
+
+            let options = BestFitOptions::default()
+                .power(false)
+                .exponential(false)
+                .logarithmic(false)
+                .max_degree(1);
+            let best = best_fit_with_options(&x, &y, &OlsEstimator, Some(&OlsEstimator), &options);
+            let line = OlsEstimator.model_linear(&x, &y);
+            assert_eq!(format!("{best}"), format!("{line}"));
+
+            let ranked = best_fit_ranked(
+                &x,
+                &y,
+                &OlsEstimator,
+                Some(&OlsEstimator),
+                &BestFitOptions::default(),
+            );
+            // power, exponential, logarithmic, 2'nd degree, and linear
+            assert_eq!(ranked.len(), 5);
+            assert_eq!(format!("{:.1}", ranked[0].model), "3.0 * 1.2^x");
+            assert!((ranked[0].determination - 1.).abs() < 1e-9);
+            assert!(ranked.windows(2).all(|w| w[0].score >= w[1].score));
+            // without a polynomial estimator
+            let ranked = best_fit_ranked(&x, &y, &OlsEstimator, None, &BestFitOptions::default());
+            assert_eq!(ranked.len(), 4);
+
+            // a robust polynomial estimator
+            let x: Vec<f64> = (0..20).map(f64::from).collect();
+            let mut y: Vec<f64> = x.iter().map(|x| x * x - 3. * x + 5.).collect();
+            y[4] += 200.;
+            y[13] -= 150.;
+            let options = BestFitOptions::default()
+                .power(false)
+                .exponential(false)
+                .logarithmic(false);
+            let best = best_fit_with_options(
+                &x,
+                &y,
+                &OlsEstimator,
+                Some(&theil_sen::PolynomialTheilSen),
+                &options,
+            );
+            assert!((best.predict_outcome(10.) - 75.).abs() < 1e-6, "{best}");
+        }
+        #[test]
+        fn cross_validation() {
+            // noisy line, which higher degree polynomials overfit
+            let x: Vec<f64> = (0..12).map(f64::from).collect();
+            let noise = [
+                0.8, -1.1, 0.3, 1.2, -0.9, -0.2, 1.0, -1.3, 0.4, 0.9, -0.6, -0.5,
+            ];
+            let y: Vec<f64> = x.iter().zip(noise).map(|(x, e)| 2. * x - 3. + e).collect();
+            let best = best_fit_cv(&x, &y, &OlsEstimator, x.len());
+            let line = OlsEstimator.model_linear(&x, &y);
+            assert_eq!(format!("{best}"), format!("{line}"));
+
+            let y: Vec<f64> = x.iter().map(|x| 0.5 * x * x - x + 4.).collect();
+            let best = best_fit_cv(&x, &y, &OlsEstimator, 4);
+            assert!((best.predict_outcome(20.) - 184.).abs() < 1e-6, "{best}");
+        }
+        #[test]
+        fn degree_selection() {
+            let x: Vec<f64> = (0..20).map(f64::from).collect();
+            let noise = |i: usize| [0.8, -1.1, 0.3, 1.2, -0.9, -0.2, 1.0, -1.3][i % 8];
+            let y: Vec<f64> = x
+                .iter()
+                .enumerate()
+                .map(|(i, x)| 0.05 * x.powi(3) - x * x + 2. * x + 1. + noise(i))
+                .collect();
+            for criterion in [
+                DegreeCriterion::Bic,
+                DegreeCriterion::CrossValidation { folds: 5 },
+            ] {
+                let degree = select_degree(&x, &y, &OlsEstimator, 8, criterion);
+                assert_eq!(degree, Some(3), "{criterion:?}");
+            }
+            let y: Vec<f64> = x.iter().enumerate().map(|(i, x)| x + noise(i)).collect();
+            let bic = DegreeCriterion::Bic;
+            assert_eq!(select_degree(&x, &y, &OlsEstimator, 6, bic), Some(1));
+            // too few points for the higher degrees, or any
+            assert_eq!(
+                select_degree(&x[..3], &y[..3], &OlsEstimator, 6, bic),
+                Some(1)
+            );
+            assert_eq!(select_degree(&x[..2], &y[..2], &OlsEstimator, 6, bic), None);
+        }
+        #[test]
+        fn nonlinear_least_squares() {
+            let squared_error = |model: &dyn Predictive, x: &[f64], y: &[f64]| -> f64 {
+                x.iter()
+                    .zip(y)
+                    .map(|(x, y)| (y - model.predict_outcome(*x)).powi(2))
+                    .sum()
+            };
+            let x: Vec<f64> = (1..=30).map(f64::from).collect();
+            // additive noise, which the logarithmic transform over-weights for small values
+            let noise = |i: usize| if i % 2 == 0 { 15. } else { -15. };
+            let y: Vec<f64> = x
+                .iter()
+                .enumerate()
+                .map(|(i, x)| 5. * x.powf(1.7) + noise(i) + 20.)
+                .collect();
+            let transformed = power(&mut x.clone(), &mut y.clone(), &OlsEstimator);
+            let refined = power_nls(&x, &y, &OlsEstimator);
+            assert!(squared_error(&refined, &x, &y) < squared_error(&transformed, &x, &y));
+            assert!(
+                (refined.e - 1.7).abs() < (transformed.e - 1.7).abs(),
+                "{refined}"
+            );
+
+            let y: Vec<f64> = x
+                .iter()
+                .enumerate()
+                .map(|(i, x)| 3. * 1.2f64.powf(*x) + noise(i) + 20.)
+                .collect();
+            let transformed = exponential(&mut x.clone(), &mut y.clone(), &OlsEstimator);
+            let refined = exponential_nls(&x, &y, &OlsEstimator);
+            assert!(squared_error(&refined, &x, &y) < squared_error(&transformed, &x, &y));
+            assert!((refined.b - 1.2).abs() < 0.03, "{refined}");
+        }
+    }
+}
+
+/// Comparison of lines fitted to several groups of the data, e.g. the series of an A/B benchmark
+/// sweep.
+pub mod grouped {
+    use crate::distributions::FisherF;
+    use crate::tests::TestResult;
+
+    /// Returned from [`compare_slopes`].
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct SlopeComparison {
+        /// The slope of parallel lines (one intercept per group) fitted to all groups.
+        pub common_slope: f64,
+        /// The F-test of whether the slopes differ.
+        ///
+        /// The [statistic](TestResult::statistic) is `F`, with `groups - 1` and
+        /// `n - 2 * groups` degrees of freedom.
+        pub test: TestResult,
+    }
+
+    /// Tests if the slopes of lines fitted to each of `groups` differ, i.e. if there's an
+    /// interaction between the group and the predictor.
+    ///
+    /// Each group is a pair of predictors and outcomes. The lines are fitted using least
+    /// squares. The residuals of separate lines are compared to those of parallel lines, which
+    /// only have separate intercepts. This is the same test as in an analysis of covariance.
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than 2 groups, if the predictors and outcomes of a group have
+    /// different lengths, or if there aren't more data points than twice the count of groups.
+    pub fn compare_slopes(groups: &[(&[f64], &[f64])]) -> SlopeComparison {
+        assert!(groups.len() >= 2, "at least 2 groups are required");
+        let mut n = 0;
+        let mut separate_residuals = 0.;
+        let (mut sxx_total, mut sxy_total, mut syy_total) = (0., 0., 0.);
+        for (predictors, outcomes) in groups {
+            assert_eq!(
+                predictors.len(),
+                outcomes.len(),
+                "predictors and outcomes must have the same number of items"
+            );
+            let len = predictors.len() as f64;
+            let x_mean = predictors.iter().sum::<f64>() / len;
+            let y_mean = outcomes.iter().sum::<f64>() / len;
+            let (mut sxx, mut sxy, mut syy) = (0., 0., 0.);
+            for (x, y) in predictors.iter().zip(outcomes.iter()) {
+                let (dx, dy) = (x - x_mean, y - y_mean);
+                sxx += dx * dx;
+                sxy += dx * dy;
+                syy += dy * dy;
+            }
+            separate_residuals += if sxx > 0. { syy - sxy * sxy / sxx } else { syy };
+            sxx_total += sxx;
+            sxy_total += sxy;
+            syy_total += syy;
+            n += predictors.len();
+        }
+        let k = groups.len();
+        assert!(
+            n > 2 * k,
+            "more data points than twice the count of groups are required"
+        );
+        let common_residuals = syy_total - sxy_total * sxy_total / sxx_total;
+        let numerator_df = (k - 1) as f64;
+        let denominator_df = (n - 2 * k) as f64;
+        let f = ((common_residuals - separate_residuals).max(0.) / numerator_df)
+            / (separate_residuals / denominator_df);
+        let p_value = if f.is_nan() {
+            1.
+        } else {
+            FisherF::new(numerator_df, denominator_df).upper_tail(f)
+        };
+        SlopeComparison {
+            common_slope: sxy_total / sxx_total,
+            test: TestResult {
+                statistic: f,
+                p_value,
+            },
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn slopes() {
+            let x = [1., 2., 3., 4., 5., 6.];
+            let a = [2.1, 3.9, 6.2, 7.8, 10.1, 12.0];
+            let b = [1.0, 1.6, 1.9, 2.6, 3.0, 3.4];
+            let comparison = compare_slopes(&[(&x, &a), (&x, &b)]);
+            assert!(comparison.test.is_significant(0.001));
+
+            // parallel lines
+            let c: Vec<f64> = a.iter().map(|y| y + 5.).collect();
+            let comparison = compare_slopes(&[(&x, &a), (&x, &c)]);
+            assert!(comparison.test.statistic.abs() < 1e-9);
+            assert!(comparison.test.p_value > 0.99);
+        }
+    }
+}
+
+/// Data for the standard plots used to check if a model fits the data, and if the assumptions of
+/// the regression hold.
+///
+/// See [`diagnostic_plots_data`].
+pub mod diagnostics {
+    use super::*;
+
+    /// The points of the four standard regression-diagnostic plots.
+    ///
+    /// Every list contains one point `(x, y)` per data point.
+    /// The standardized residuals are the residuals divided by their estimated standard
+    /// deviation, `s * √(1 - h)`, where `h` is the leverage.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct DiagnosticPlots {
+        /// The residuals (y) against the fitted values (x).
+        /// A pattern means the model doesn't capture the shape of the data.
+        pub residuals_vs_fitted: Vec<(f64, f64)>,
+        /// The sorted standardized residuals (y) against the quantiles of the standard normal
+        /// distribution (x). Points away from the line `y = x` mean the residuals aren't
+        /// normally distributed.
+        pub normal_qq: Vec<(f64, f64)>,
+        /// `√|standardized residual|` (y) against the fitted values (x).
+        /// A trend means the variance of the residuals isn't constant.
+        pub scale_location: Vec<(f64, f64)>,
+        /// The standardized residuals (y) against the leverage (x).
+        /// Points with both large leverage and residuals have a large influence on the model.
+        pub residuals_vs_leverage: Vec<(f64, f64)>,
+    }
+
+    /// Computes the [`DiagnosticPlots`] of `model` for the data.
+    ///
+    /// The leverage of each point is `1/n + (x - x̄)² / Σ(x - x̄)²`, which is exact for lines
+    /// and an approximation for other models. The standard deviation of the residuals is
+    /// estimated with 2 degrees of freedom used by the model.
+    ///
+    /// O(n log n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors` and `outcomes` have different lengths.
+    pub fn diagnostic_plots_data(
+        model: &impl Predictive,
+        predictors: &[f64],
+        outcomes: &[f64],
+    ) -> DiagnosticPlots {
+        assert_eq!(
+            predictors.len(),
+            outcomes.len(),
+            "predictors and outcomes must have the same number of items"
+        );
+        let n = predictors.len();
+        let fitted: Vec<f64> = predictors
+            .iter()
+            .map(|x| model.predict_outcome(*x))
+            .collect();
+        let residuals: Vec<f64> = outcomes.iter().zip(&fitted).map(|(y, f)| y - f).collect();
+
+        let x_mean = predictors.iter().sum::<f64>() / n as f64;
+        let sxx: f64 = predictors.iter().map(|x| (x - x_mean).powi(2)).sum();
+        let leverage: Vec<f64> = predictors
+            .iter()
+            .map(|x| {
+                let spread = if sxx > 0. {
+                    (x - x_mean).powi(2) / sxx
+                } else {
+                    0.
+                };
+                1. / n as f64 + spread
+            })
+            .collect();
+        let rss: f64 = residuals.iter().map(|r| r * r).sum();
+        let s = (rss / n.saturating_sub(2).max(1) as f64).sqrt();
+        let standardized: Vec<f64> = residuals
+            .iter()
+            .zip(&leverage)
+            .map(|(r, h)| r / (s * (1. - h).max(0.).sqrt()))
+            .collect();
+
+        let mut sorted = standardized.clone();
+        sorted.sort_by(|a, b| crate::F64OrdHash::f64_cmp(*a, *b));
+        let normal_qq = sorted
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let p = (i as f64 + 0.5) / n as f64;
+                (crate::distributions::Normal::STANDARD.quantile(p), r)
+            })
+            .collect();
+
+        DiagnosticPlots {
+            residuals_vs_fitted: fitted.iter().copied().zip(residuals).collect(),
+            normal_qq,
+            scale_location: fitted
+                .iter()
+                .zip(&standardized)
+                .map(|(f, r)| (*f, r.abs().sqrt()))
+                .collect(),
+            residuals_vs_leverage: leverage.into_iter().zip(standardized).collect(),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn diagnostics() {
+            let model = LinearCoefficients { k: 2., m: 1. };
+            let x = [1., 2., 3., 4., 5.];
+            let y = [3.5, 4.5, 7.5, 8.5, 11.];
+            let plots = diagnostic_plots_data(&model, &x, &y);
+            assert_eq!(plots.residuals_vs_fitted[0], (3., 0.5));
+            assert_eq!(plots.residuals_vs_fitted[1], (5., -0.5));
+            // the middle point has the least leverage
+            assert!((plots.residuals_vs_leverage[2].0 - 0.2).abs() < 1e-12);
+            assert!((plots.residuals_vs_leverage[0].0 - 0.6).abs() < 1e-12);
+            // the normal quantiles are symmetric, and the residuals sorted
+            assert_eq!(plots.normal_qq[2].0, 0.);
+            assert!((plots.normal_qq[0].0 + plots.normal_qq[4].0).abs() < 1e-9);
+            assert!(plots.normal_qq.windows(2).all(|w| w[0].1 <= w[1].1));
+            assert!(plots.scale_location.iter().all(|(_, v)| *v >= 0.));
+        }
+    }
+}
+
+/// This module enables the use of [`rug::Float`] inside of [`nalgebra`].
+///
+/// Many functions are not implemented. PRs are welcome.
+#[cfg(feature = "arbitrary-precision")]
+pub mod arbitrary_linear_algebra {
+    use std::cell::RefCell;
+    use std::fmt::{self, Display};
+    use std::ops::{
+        Add, AddAssign, Deref, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+    };
+
+    use nalgebra::{ComplexField, RealField};
+    use rug::Assign;
+
+    thread_local! {
+        /// The default precision.
+        ///
+        /// This is thread-local.
+        pub static DEFAULT_PRECISION: RefCell<u32> = const { RefCell::new(256) };
+    }
+    /// Set the default precision **for this thread**.
+    pub fn set_default_precision(new: u32) {
+        DEFAULT_PRECISION.with(|v| *v.borrow_mut() = new);
+    }
+    /// Get the default precision.
+    /// Can be set using [`set_default_precision`].
+    pub fn default_precision() -> u32 {
+        DEFAULT_PRECISION.with(|v| *v.borrow())
+    }
+    /// A wrapper around [`rug::Float`] to implement traits for.
+    #[derive(Debug, Clone, PartialEq, PartialOrd)]
+    pub struct FloatWrapper(pub rug::Float);
+    impl From<rug::Float> for FloatWrapper {
+        fn from(f: rug::Float) -> Self {
+            Self(f)
+        }
+    }
+
+    impl simba::scalar::SupersetOf<f64> for FloatWrapper {
+        fn is_in_subset(&self) -> bool {
+            self.0.prec() <= 53
+        }
+        fn to_subset(&self) -> Option<f64> {
+            if simba::scalar::SupersetOf::<f64>::is_in_subset(self) {
+                Some(self.0.to_f64())
+            } else {
+                None
+            }
+        }
+        fn to_subset_unchecked(&self) -> f64 {
+            self.0.to_f64()
+        }
+        fn from_subset(element: &f64) -> Self {
+            rug::Float::with_val(default_precision(), element).into()
+        }
+    }
+    impl simba::scalar::SupersetOf<f32> for FloatWrapper {
+        fn is_in_subset(&self) -> bool {
+            self.0.prec() <= 24
+        }
+        fn to_subset(&self) -> Option<f32> {
+            if simba::scalar::SupersetOf::<f32>::is_in_subset(self) {
+                Some(self.0.to_f32())
+            } else {
+                None
+            }
+        }
+        fn to_subset_unchecked(&self) -> f32 {
+            self.0.to_f32()
+        }
+        fn from_subset(element: &f32) -> Self {
+            rug::Float::with_val(default_precision(), element).into()
+        }
+    }
+    impl simba::scalar::SubsetOf<Self> for FloatWrapper {
+        fn to_superset(&self) -> Self {
+            self.clone()
+        }
+
+        fn from_superset_unchecked(element: &Self) -> Self {
+            element.clone()
+        }
+
+        fn is_in_subset(_element: &Self) -> bool {
+            true
+        }
+    }
+    impl num_traits::cast::FromPrimitive for FloatWrapper {
+        fn from_i64(n: i64) -> Option<Self> {
+            Some(rug::Float::with_val(default_precision(), n).into())
+        }
+        fn from_u64(n: u64) -> Option<Self> {
+            Some(rug::Float::with_val(default_precision(), n).into())
+        }
+    }
+    impl Display for FloatWrapper {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+    impl simba::simd::SimdValue for FloatWrapper {
+        type Element = FloatWrapper;
+        type SimdBool = bool;
+
+        const LANES: usize = 1;
+
+        #[inline(always)]
+        fn splat(val: Self::Element) -> Self {
+            val
+        }
+
+        #[inline(always)]
+        fn extract(&self, _: usize) -> Self::Element {
+            self.clone()
         }
 
         #[inline(always)]
@@ -1554,6 +3377,8 @@ pub mod ols {
 
     use nalgebra::DMatrix;
 
+    use crate::distributions::StudentT;
+
     use super::*;
 
     #[must_use]
@@ -1635,6 +3460,56 @@ pub mod ols {
             )
         }
     }
+    impl LinearEstimator<f32> for OlsEstimator {
+        fn model_linear(&self, predictors: &[f32], outcomes: &[f32]) -> LinearCoefficients<f32> {
+            linear_generic(predictors, outcomes)
+        }
+    }
+    #[cfg(feature = "arbitrary-precision")]
+    impl LinearEstimator<arbitrary_linear_algebra::FloatWrapper> for OlsEstimator {
+        fn model_linear(
+            &self,
+            predictors: &[arbitrary_linear_algebra::FloatWrapper],
+            outcomes: &[arbitrary_linear_algebra::FloatWrapper],
+        ) -> LinearCoefficients<arbitrary_linear_algebra::FloatWrapper> {
+            linear_generic(predictors, outcomes)
+        }
+    }
+
+    /// Least squares line of any [`Scalar`], computed in that type.
+    ///
+    /// `k = Σ(x - x̄)(y - ȳ) / Σ(x - x̄)²` and `m = ȳ - k x̄`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors` and `outcomes` have different lengths.
+    pub fn linear_generic<F: Scalar>(predictors: &[F], outcomes: &[F]) -> LinearCoefficients<F> {
+        assert_eq!(
+            predictors.len(),
+            outcomes.len(),
+            "predictors and outcomes must have the same number of items"
+        );
+        let len = F::from_usize(predictors.len()).expect("the length fits in the scalar");
+        let mean = |values: &[F]| {
+            values
+                .iter()
+                .cloned()
+                .fold(F::zero(), |sum, value| sum + value)
+                / len.clone()
+        };
+        let x_mean = mean(predictors);
+        let y_mean = mean(outcomes);
+        let mut sxx = F::zero();
+        let mut sxy = F::zero();
+        for (x, y) in predictors.iter().zip(outcomes) {
+            let dx = x.clone() - x_mean.clone();
+            sxy = sxy + dx.clone() * (y.clone() - y_mean.clone());
+            sxx = sxx + dx.clone() * dx;
+        }
+        let k = sxy / sxx;
+        let m = y_mean - k.clone() * x_mean;
+        LinearCoefficients { k, m }
+    }
 
     /// # Panics
     ///
@@ -1694,9 +3569,6 @@ pub mod ols {
         ) -> PolynomialCoefficients {
             RUNTIME.with(move |runtime| {
                 let mut runtime = runtime.borrow_mut();
-                // cheap clone call, it's an iterator
-                let predictor_original = predictors.clone();
-                let mut predictor_iter = predictors;
 
                 runtime.resize(len, degree);
 
@@ -1710,10 +3582,33 @@ pub mod ols {
                     ..
                 } = &mut *runtime;
 
+                #[cfg(feature = "parallel")]
                 {
-                    let (rows, columns) = design.shape();
-                    for column in 0..columns {
-                        for row in 0..rows {
+                    let predictors: Vec<f64> = predictors.collect();
+                    let rows = design.nrows();
+                    // the matrix is stored column by column
+                    for (column, values) in design.as_mut_slice().chunks_mut(rows).enumerate() {
+                        crate::parallel::for_each_chunk_mut(values, |offset, chunk| {
+                            let predictors = &predictors[offset..offset + chunk.len()];
+                            for (v, predictor) in chunk.iter_mut().zip(predictors) {
+                                *v = match column {
+                                    0 => 1.0,
+                                    1 => *predictor,
+                                    _ => predictor.powi(column as _),
+                                };
+                            }
+                        });
+                    }
+                }
+                #[cfg(not(feature = "parallel"))]
+                {
+                    // cheap clone call, it's an iterator
+                    let predictor_original = predictors.clone();
+                    let mut predictor_iter = predictors;
+
+                    let (rows, columns) = design.shape();
+                    for column in 0..columns {
+                        for row in 0..rows {
                             let v = unsafe { design.get_unchecked_mut((row, column)) };
                             *v = if column == 0 {
                                 1.0
@@ -1813,479 +3708,691 @@ pub mod ols {
         #[cfg(not(feature = "arbitrary-precision"))]
         polynomial_simple_preallocated(predictors, outcomes, len, degree)
     }
-}
-
-/// [Theil-Sen estimator](https://en.wikipedia.org/wiki/Theil%E2%80%93Sen_estimator), a robust
-/// linear (also implemented as polynomial) estimator.
-/// Up to ~27% of values can be *outliers* - erroneous data far from the otherwise good data -
-/// without large effects on the result.
-///
-/// [`LinearTheilSen`] implements [`LinearEstimator`].
-pub mod theil_sen {
-    use super::*;
-    use crate::{percentile, F64OrdHash};
-    use std::fmt::Debug;
 
-    /// A buffer returned by [`PermutationIter`] to avoid allocations.
-    pub struct PermutationIterBuffer<T> {
-        buf: Vec<(T, T)>,
+    /// Least squares with some coefficients of the polynomial fixed, e.g. the constant term at 0
+    /// to force the curve through the origin (`f(0) = 0`).
+    ///
+    /// The fixed terms are subtracted from the outcomes, then the remaining coefficients are
+    /// fitted using least squares.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ConstrainedOlsEstimator {
+        /// The index (the exponent of `x`) and value of each fixed coefficient.
+        pub fixed: Vec<(usize, f64)>,
     }
-    impl<T> Deref for PermutationIterBuffer<T> {
-        type Target = [(T, T)];
-        fn deref(&self) -> &Self::Target {
-            &self.buf
+    impl ConstrainedOlsEstimator {
+        /// Fixes the coefficients given by `fixed`, as `(exponent, value)`.
+        pub fn new(fixed: impl Into<Vec<(usize, f64)>>) -> Self {
+            Self {
+                fixed: fixed.into(),
+            }
+        }
+        /// Fixes the constant term at 0.
+        pub fn through_origin() -> Self {
+            Self::new([(0, 0.)])
         }
     }
-    /// An iterator over the permutations.
-    #[derive(Debug)]
-    pub struct PermutationIter<'a, T> {
-        s1: &'a [T],
-        s2: &'a [T],
-        iters: Vec<usize>,
-        values: Option<Vec<(T, T)>>,
-        values_backup: Vec<(T, T)>,
-        pairs: usize,
+    impl LinearEstimator for ConstrainedOlsEstimator {
+        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+            let coefficients = self.model_polynomial(predictors, outcomes, 1);
+            LinearCoefficients {
+                k: coefficients[1],
+                m: coefficients[0],
+            }
+        }
     }
-    impl<'a, T: Copy + Debug> PermutationIter<'a, T> {
-        fn new(s1: &'a [T], s2: &'a [T], pairs: usize) -> Self {
-            assert!(
-                pairs > 1,
-                "each coordinate pair must be associated with at least one."
-            );
-            assert_eq!(s1.len(), s2.len());
-            assert!(pairs <= s1.len());
-            let iters = Vec::with_capacity(pairs);
-            let values_backup = Vec::with_capacity(pairs);
-            let mut me = Self {
-                s1,
-                s2,
-                iters,
-                values: None,
-                values_backup,
-                pairs,
-            };
-            for i in 0..pairs {
-                // `+ (not last) as usize` since the last iterator doesn't first read vec.
-                me.iters.push(i + usize::from(i + 1 < pairs) - 1);
+    impl PolynomialEstimator for ConstrainedOlsEstimator {
+        fn model_polynomial(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+        ) -> PolynomialCoefficients {
+            polynomial_constrained(predictors, outcomes, degree, &self.fixed)
+        }
+    }
+
+    /// Fits a polynomial of `degree` using least squares, with the coefficients given by `fixed`
+    /// (as `(exponent, value)`) set to their values.
+    ///
+    /// Fixed coefficients with exponents larger than `degree` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors` and `outcomes` have different lengths, or if there are fewer points
+    /// than free coefficients.
+    pub fn polynomial_constrained(
+        predictors: &[f64],
+        outcomes: &[f64],
+        degree: usize,
+        fixed: &[(usize, f64)],
+    ) -> PolynomialCoefficients {
+        assert_eq!(
+            predictors.len(),
+            outcomes.len(),
+            "predictors and outcomes must have the same number of items"
+        );
+        let mut coefficients = vec![None; degree + 1];
+        for (exponent, value) in fixed {
+            if let Some(coefficient) = coefficients.get_mut(*exponent) {
+                *coefficient = Some(*value);
             }
-            #[allow(clippy::needless_range_loop)] // clarity
-            for i in 0..pairs - 1 {
-                me.values_backup.push((me.s1[i], me.s2[i]));
+        }
+        let free: Vec<usize> = (0..=degree)
+            .filter(|exponent| coefficients[*exponent].is_none())
+            .collect();
+        assert!(
+            free.len() <= predictors.len(),
+            "there must be at least as many points as free coefficients"
+        );
+        let fixed_term = |x: f64| -> f64 {
+            coefficients
+                .iter()
+                .enumerate()
+                .filter_map(|(exponent, c)| c.map(|c| c * x.powi(exponent as i32)))
+                .sum()
+        };
+
+        if !free.is_empty() {
+            let design = DMatrix::from_fn(predictors.len(), free.len(), |row, column| {
+                predictors[row].powi(free[column] as i32)
+            });
+            let remaining = DMatrix::from_iterator(
+                outcomes.len(),
+                1,
+                predictors
+                    .iter()
+                    .zip(outcomes)
+                    .map(|(x, y)| y - fixed_term(*x)),
+            );
+            let t = design.transpose();
+            let gram = &t * &design;
+            let inverse = gram
+                .clone()
+                .try_inverse()
+                .unwrap_or_else(|| gram.pseudo_inverse(1e-8).unwrap());
+            let result = inverse * t * remaining;
+            for (exponent, value) in free.iter().zip(result.iter()) {
+                coefficients[*exponent] = Some(*value);
             }
-            me.values_backup.push(me.values_backup[0]);
-            me.values = Some(me.values_backup.clone());
-            me
         }
-        /// Please hand the buffer back after each iteration. This greatly reduces allocations.
-        #[inline(always)]
-        pub fn give_buffer(&mut self, buf: PermutationIterBuffer<T>) {
-            debug_assert!(self.values.is_none());
-            self.values = Some(buf.buf)
+        PolynomialCoefficients {
+            coefficients: coefficients
+                .into_iter()
+                .map(|c| c.expect("all coefficients are fixed or fitted"))
+                .collect(),
         }
-        /// Collects the permutations in `pairs` number of [`Vec`]s, with `returned[i]` containing
-        /// the i-th pair.
-        pub fn collect_by_index(mut self) -> Vec<Vec<(T, T)>> {
-            let mut vecs = Vec::with_capacity(self.pairs);
-            for _ in 0..self.pairs {
-                vecs.push(Vec::new());
-            }
-            while let Some(buf) = self.next() {
-                for (pos, pair) in buf.iter().enumerate() {
-                    vecs[pos].push(*pair)
-                }
+    }
 
-                self.give_buffer(buf);
+    /// A least squares fit together with the uncertainty of its coefficients.
+    ///
+    /// The errors are assumed to be independent and normally distributed with a constant variance.
+    /// The coefficients are in the order of [`PolynomialCoefficients`], the constant term first.
+    ///
+    /// Created by [`linear_with_uncertainty`] and [`polynomial_with_uncertainty`].
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct WithUncertainty<C> {
+        /// The fitted model.
+        pub model: C,
+        /// The covariance matrix of the coefficients, `s²(XᵀX)⁻¹`.
+        pub covariance: Vec<Vec<f64>>,
+        /// The estimated variance of the errors, `s² = Σ residual² / (n - p)`,
+        /// where `p` is the number of coefficients.
+        pub residual_variance: f64,
+        /// The degrees of freedom of the residuals, `n - p`.
+        pub degrees_of_freedom: usize,
+        coefficients: Vec<f64>,
+    }
+    impl<C> WithUncertainty<C> {
+        /// The standard error of each coefficient.
+        pub fn standard_errors(&self) -> Vec<f64> {
+            (0..self.covariance.len())
+                .map(|i| self.covariance[i][i].sqrt())
+                .collect()
+        }
+        /// The standard deviation of the errors, `s`.
+        pub fn residual_standard_error(&self) -> f64 {
+            self.residual_variance.sqrt()
+        }
+        /// Returns `(NaN, NaN)` if there are no degrees of freedom left.
+        fn interval(&self, center: f64, variance: f64, confidence: f64) -> (f64, f64) {
+            assert!(
+                confidence > 0. && confidence < 1.,
+                "confidence must be in the range (0, 1)"
+            );
+            if self.degrees_of_freedom == 0 {
+                return (f64::NAN, f64::NAN);
             }
-            vecs
+            let t =
+                StudentT::new(self.degrees_of_freedom as f64).two_sided_critical_value(confidence);
+            let half_width = t * variance.sqrt();
+            (center - half_width, center + half_width)
+        }
+        /// The variance of the fitted curve at `predictor`, `x₀ᵀCx₀`.
+        fn curve_variance(&self, predictor: f64) -> f64 {
+            let powers: Vec<f64> = (0..self.covariance.len())
+                .map(|exponent| predictor.powi(exponent as i32))
+                .collect();
+            self.covariance
+                .iter()
+                .zip(&powers)
+                .map(|(row, a)| row.iter().zip(&powers).map(|(c, b)| a * c * b).sum::<f64>())
+                .sum()
+        }
+        /// The confidence interval `(lower, upper)` of the coefficient with `index`
+        /// (the exponent of `x`).
+        ///
+        /// # Panics
+        ///
+        /// Panics if `confidence` isn't in the range `(0, 1)` or if `index` is out of bounds.
+        pub fn coefficient_interval(&self, index: usize, confidence: f64) -> (f64, f64) {
+            self.interval(
+                self.coefficients[index],
+                self.covariance[index][index],
+                confidence,
+            )
         }
-        /// Collects `LEN` pairs from this iterator in a [`Vec`].
+        /// The confidence interval `(lower, upper)` of the mean outcome at `predictor`,
+        /// i.e. where the true curve lies. Evaluate this over a range of predictors to get the
+        /// confidence band.
         ///
         /// # Panics
         ///
-        /// `LEN` must be the same length as `pairs` supplied to [`permutations_generic`].
-        pub fn collect_len<const LEN: usize>(mut self) -> Vec<[(T, T); LEN]> {
-            let mut vec = Vec::new();
-            while let Some(buf) = self.next() {
-                let array = <[(T, T); LEN]>::try_from(&*buf).expect(
-                    "tried to collect with set len, but permutations returned different len",
-                );
-                vec.push(array);
-                self.give_buffer(buf);
-            }
-            vec
+        /// Panics if `confidence` isn't in the range `(0, 1)`.
+        pub fn confidence_band(&self, predictor: f64, confidence: f64) -> (f64, f64)
+        where
+            C: Predictive,
+        {
+            self.interval(
+                self.model.predict_outcome(predictor),
+                self.curve_variance(predictor),
+                confidence,
+            )
         }
-    }
-    impl<T: Copy + Debug> Iterator for PermutationIter<'_, T> {
-        type Item = PermutationIterBuffer<T>;
-        #[inline]
-        fn next(&mut self) -> Option<Self::Item> {
-            for (num, iter) in self.iters.iter_mut().enumerate().rev() {
-                *iter += 1;
-
-                if let Some(value) = self.s1.get(*iter) {
-                    // SAFETY: they are the same length, so getting from one guarantees we can get
-                    // the same index from the other one.
-                    let next = (*value, *unsafe { self.s2.get_unchecked(*iter) });
-
-                    let values = &mut self.values_backup;
-                    if let Some(v) = self.values.as_mut() {
-                        // SAFETY: The length of `self.values`, `self.values_backup`, and
-                        // `self.iters` are equal. Therefore, we can get the index of `self.values`
-                        // returned by the enumeration of `self.iters`
-                        *unsafe { v.get_unchecked_mut(num) } = next;
-                    }
-                    // SAFETY: See above.
-                    *unsafe { values.get_unchecked_mut(num) } = next;
-                    if num + 1 == self.pairs {
-                        let values = match self.values.take() {
-                            Some(x) => x,
-                            None => self.values_backup.clone(),
-                        };
-                        return Some(PermutationIterBuffer { buf: values });
-                    } else {
-                        // optimization - if items left is less than what is required to fill the "tower"
-                        // of succeeding indices, we return
-                        if self.s1.len() - *iter <= self.pairs - 1 - num {
-                            continue;
-                        }
-
-                        // Not pushing unsafe/inline as hard here, as this isn't as hot of a path.
-
-                        #[allow(clippy::needless_range_loop)] // clarity
-                        for i in num + 1..self.pairs {
-                            // start is 1+ the previous?
-                            let new = self.iters[i - 1] + usize::from(i + 1 < self.pairs);
-                            self.iters[i] = new;
-                            // fix values for lower iterators than the top one
-                            if i + 1 < self.pairs {
-                                if new >= self.s1.len() {
-                                    continue;
-                                }
-                                values[i] = (self.s1[new], self.s2[new]);
-                                if let Some(v) = self.values.as_mut() {
-                                    v[i] = (self.s1[new], self.s2[new]);
-                                }
-                            }
-                        }
-                        return self.next();
-                    }
-                }
-            }
-            None
+        /// The prediction interval `(lower, upper)` of a new observation at `predictor`.
+        /// This includes both the uncertainty of the curve and the noise.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `confidence` isn't in the range `(0, 1)`.
+        pub fn predict_interval(&self, predictor: f64, confidence: f64) -> (f64, f64)
+        where
+            C: Predictive,
+        {
+            self.interval(
+                self.model.predict_outcome(predictor),
+                self.residual_variance + self.curve_variance(predictor),
+                confidence,
+            )
         }
     }
-    /// The returned iterator is a bit funky.
-    /// It returns a buffer, which at all costs should be reused.
-    /// This could either be done using a while loop
-    /// (e.g. `while let Some(buf) = iter.next() { iter.give_buffer(buf) }`)
-    /// or any of the [built-in methods](PermutationIter).
-    /// If you know the length at compile time, use [`PermutationIter::collect_len`].
-    pub fn permutations_generic<'a, T: Copy + Debug>(
-        s1: &'a [T],
-        s2: &'a [T],
-        pairs: usize,
-    ) -> PermutationIter<'a, T> {
-        PermutationIter::new(s1, s2, pairs)
-    }
-    /// Lower-bound estimate (up to `pairs > 20`), within 100x (which is quite good for factorials).
-    ///
-    /// The original equation is `elements!/(pairs! (elements - pairs)!)`
-    #[inline]
-    pub fn estimate_permutation_count(elements: usize, pairs: usize) -> f64 {
-        let e = elements as f64;
-        let p = pairs as f64;
-        e.powf(p) / (p.powf(p - 0.8))
+    impl<C: Predictive> Predictive for WithUncertainty<C> {
+        fn predict_outcome(&self, predictor: f64) -> f64 {
+            self.model.predict_outcome(predictor)
+        }
     }
-    /// An exact count of permutations.
-    /// Returns [`None`] if the arithmetic can't fit.
-    #[inline]
-    pub fn permutation_count(elements: usize, pairs: usize) -> Option<usize> {
-        fn factorial(num: u128) -> Option<u128> {
-            match num {
-                0 | 1 => Some(1),
-                _ => factorial(num - 1)?.checked_mul(num),
-            }
+    impl<C: Display> Display for WithUncertainty<C> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.model.fmt(f)
         }
-
-        Some(
-            (factorial(elements as _)?
-                / (factorial(pairs as _)?.checked_mul(factorial((elements - pairs) as _)?))?)
-                as usize,
-        )
     }
 
-    /// Unique permutations of two elements - an iterator of all the pairs of associated values in the slices.
+    /// Fits a line using least squares and estimates the uncertainty of `k` and `m`.
     ///
-    /// This function will behave unexpectedly if `s1` and `s2` have different lengths.
+    /// In [`WithUncertainty::covariance`], index 0 is `m` and index 1 is `k`.
     ///
-    /// Returns an iterator which yields `O(n²)` items.
-    // `TODO`: Make these return indices.
-    pub fn permutations<'a, T: Copy>(
-        s1: &'a [T],
-        s2: &'a [T],
-    ) -> impl Iterator<Item = ((T, T), (T, T))> + 'a {
-        s1.iter()
-            .zip(s2.iter())
-            .enumerate()
-            .flat_map(|(pos, (t11, t21))| {
-                // +1 because we don't want our selves.
-                let left = &s1[pos + 1..];
-                let left_other = &s2[pos + 1..];
-                left.iter()
-                    .zip(left_other.iter())
-                    .map(|(t12, t22)| ((*t11, *t21), (*t12, *t22)))
+    /// # Panics
+    ///
+    /// Panics if `predictors` and `outcomes` have different lengths or if there are fewer than 2
+    /// points.
+    pub fn linear_with_uncertainty(
+        predictors: &[f64],
+        outcomes: &[f64],
+    ) -> WithUncertainty<LinearCoefficients> {
+        let fit = polynomial_with_uncertainty(predictors, outcomes, 1);
+        WithUncertainty {
+            model: LinearCoefficients {
+                k: fit.coefficients[1],
+                m: fit.coefficients[0],
+            },
+            covariance: fit.covariance,
+            residual_variance: fit.residual_variance,
+            degrees_of_freedom: fit.degrees_of_freedom,
+            coefficients: fit.coefficients,
+        }
+    }
+    /// Fits a polynomial of `degree` using least squares and estimates the uncertainty of the
+    /// coefficients.
+    ///
+    /// If there are exactly `degree + 1` points, the curve fits them perfectly and nothing is
+    /// known about the noise: the variances are NaN and the intervals `(NaN, NaN)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors` and `outcomes` have different lengths or if `degree + 1 > len`.
+    pub fn polynomial_with_uncertainty(
+        predictors: &[f64],
+        outcomes: &[f64],
+        degree: usize,
+    ) -> WithUncertainty<PolynomialCoefficients> {
+        assert_eq!(
+            predictors.len(),
+            outcomes.len(),
+            "predictors and outcomes must have the same number of items"
+        );
+        assert!(
+            degree < predictors.len(),
+            "there must be at least as many points as coefficients"
+        );
+        let columns = degree + 1;
+        let design = DMatrix::from_fn(predictors.len(), columns, |row, column| {
+            predictors[row].powi(column as i32)
+        });
+        let y = DMatrix::from_column_slice(outcomes.len(), 1, outcomes);
+        let t = design.transpose();
+        let gram = &t * &design;
+        let inverse = gram
+            .clone()
+            .try_inverse()
+            .unwrap_or_else(|| gram.pseudo_inverse(1e-8).unwrap());
+        let result = &inverse * &t * &y;
+        let residuals = &y - &design * &result;
+
+        let degrees_of_freedom = predictors.len() - columns;
+        let residual_variance = if degrees_of_freedom == 0 {
+            f64::NAN
+        } else {
+            residuals.norm_squared() / degrees_of_freedom as f64
+        };
+        let covariance = (0..columns)
+            .map(|row| {
+                (0..columns)
+                    .map(|column| residual_variance * inverse[(row, column)])
+                    .collect()
             })
-    }
-
-    /// Linear estimation using the Theil-Sen estimatior. This is robust against outliers.
-    /// `O(n²)`
-    pub struct LinearTheilSen;
-    impl LinearEstimator for LinearTheilSen {
-        #[inline]
-        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
-            slow_linear(predictors, outcomes)
-        }
-    }
-    /// Polynomial estimation using the Theil-Sen estimatior. Very slow and should probably not be
-    /// used.
-    /// `O(n^degree)`
-    pub struct PolynomialTheilSen;
-    impl PolynomialEstimator for PolynomialTheilSen {
-        #[inline]
-        fn model_polynomial(
-            &self,
-            predictors: &[f64],
-            outcomes: &[f64],
-            degree: usize,
-        ) -> PolynomialCoefficients {
-            slow_polynomial(predictors, outcomes, degree)
+            .collect();
+        let coefficients: Vec<f64> = result.iter().copied().collect();
+        WithUncertainty {
+            model: PolynomialCoefficients {
+                coefficients: coefficients.clone(),
+            },
+            covariance,
+            residual_variance,
+            degrees_of_freedom,
+            coefficients,
         }
     }
 
-    /// Naive Theil-Sen implementation, which checks each line.
+    /// Weighted least squares line, which minimizes `Σ wᵢ (yᵢ - (k xᵢ + m))²`.
     ///
-    /// Time & space: O(n²)
+    /// See [`weighted_polynomial`].
     ///
     /// # Panics
     ///
-    /// Panics if `predictors.len() != outcomes.len()`.
-    pub fn slow_linear(predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
-        assert_eq!(predictors.len(), outcomes.len());
-        // I've isolated the `Vec`s into blocks so we only have one at a time.
-        // This reduces memory usage.
-        let median_slope = {
-            let slopes = permutations(predictors, outcomes).map(|((x1, y1), (x2, y2))| {
-                // Δy/Δx
-                (y1 - y2) / (x1 - x2)
-            });
-            let mut slopes: Vec<_> = slopes.map(F64OrdHash).collect();
-
-            percentile::median(&mut slopes).resolve()
-        };
-
-        //// Old intersect code. Gets the median of all x components, and the median of all y
-        //// components. We then use that as a point to extrapolate the intersection.
-        //
-        // let predictor_median = {
-        // let mut predictors = predictors.to_vec();
-        // let predictors = F64OrdHash::from_mut_f64_slice(&mut predictors);
-        // percentile::median(predictors).resolve()
-        // };
-        // let outcome_median = {
-        // let mut outcomes = outcomes.to_vec();
-        // let outcomes = F64OrdHash::from_mut_f64_slice(&mut outcomes);
-        // percentile::median(outcomes).resolve()
-        // };
-        // y=slope * x + intersect
-        // y - slope * x = intersect
-        // let intersect = outcome_median - median_slope * predictor_median;
-
-        // New intersect. This works by getting the median point by it's y value. Then, we
-        // extrapolate from that.
-        //
-        // This produces much better results, but isn't what's commonly used.
-        //
-        // See https://stats.stackexchange.com/a/96166
-        // for reference.
-        let median = {
-            let mut values: Vec<_> = predictors.iter().zip(outcomes.iter()).collect();
-            match percentile::percentile_default_pivot_by(
-                &mut values,
-                crate::Fraction::HALF,
-                &mut |a, b| F64OrdHash::f64_cmp(*a.1, *b.1),
-            ) {
-                percentile::MeanValue::Single(v) => (*v.0, *v.1),
-                percentile::MeanValue::Mean(v1, v2) => ((v1.0 + v2.0) / 2.0, (v1.1 + v2.1) / 2.0),
-            }
-        };
-        let intersect = median.1 - median.0 * median_slope;
-
+    /// Panics if `predictors`, `outcomes`, and `weights` have different lengths.
+    pub fn weighted_linear(
+        predictors: &[f64],
+        outcomes: &[f64],
+        weights: &[f64],
+    ) -> LinearCoefficients {
+        let coefficients = weighted_polynomial(predictors, outcomes, weights, 1);
         LinearCoefficients {
-            k: median_slope,
-            m: intersect,
+            k: coefficients[1],
+            m: coefficients[0],
         }
     }
-
-    /// Naive Theil-Sen implementation, which checks each polynomial.
+    /// Weighted least squares polynomial of `degree`, which minimizes `Σ wᵢ (yᵢ - f(xᵢ))²`.
     ///
-    /// Time & space: O(n^m) where m is `degree + 1`.
+    /// A point with the weight `n` affects the fit as much as `n` copies of it, so this also fits
+    /// counted points. Use it when the points have different precision, with the weights
+    /// `1 / σᵢ²`.
+    ///
+    /// Solves the normal equations `XᵀWX β = XᵀWy`.
     ///
     /// # Panics
     ///
-    /// Panics if `predictors.len() != outcomes.len()`.
-    pub fn slow_polynomial(
+    /// Panics if `predictors`, `outcomes`, and `weights` have different lengths.
+    pub fn weighted_polynomial(
         predictors: &[f64],
         outcomes: &[f64],
+        weights: &[f64],
         degree: usize,
     ) -> PolynomialCoefficients {
-        assert_eq!(predictors.len(), outcomes.len());
-
-        // if degree == 0, get median.
-        if degree == 0 {
-            let mut outcomes = outcomes.to_vec();
-            let constant = crate::percentile::percentile_default_pivot_by(
-                &mut outcomes,
-                crate::Fraction::HALF,
-                &mut |a, b| crate::F64OrdHash::f64_cmp(*a, *b),
-            )
-            .resolve();
-            return PolynomialCoefficients {
-                coefficients: vec![constant],
-            };
+        assert_eq!(
+            predictors.len(),
+            outcomes.len(),
+            "predictors and outcomes must have the same number of items"
+        );
+        assert_eq!(
+            predictors.len(),
+            weights.len(),
+            "predictors and weights must have the same number of items"
+        );
+        let columns = degree + 1;
+        let mut gram = DMatrix::<f64>::zeros(columns, columns);
+        let mut moments = DMatrix::<f64>::zeros(columns, 1);
+        let mut powers = vec![0.; columns];
+        for ((x, y), w) in predictors.iter().zip(outcomes).zip(weights) {
+            let mut power = 1.;
+            for p in &mut powers {
+                *p = power;
+                power *= x;
+            }
+            for (row, p_row) in powers.iter().enumerate() {
+                moments[row] += w * p_row * y;
+                for (column, p_column) in powers.iter().enumerate() {
+                    gram[(row, column)] += w * p_row * p_column;
+                }
+            }
         }
-
-        // init
-        let mut iter = permutations_generic(predictors, outcomes, degree + 1);
-        let mut coefficients = Vec::with_capacity(degree + 1);
-        let permutations_count = permutation_count(predictors.len(), degree + 1)
-            .unwrap_or_else(|| estimate_permutation_count(predictors.len(), degree + 1) as usize);
-        for _ in 0..degree + 1 {
-            // now that's a lot of allocations.
-            coefficients.push(Vec::with_capacity(permutations_count))
+        let inverse = gram
+            .clone()
+            .try_inverse()
+            .unwrap_or_else(|| gram.pseudo_inverse(1e-8).unwrap());
+        let result = inverse * moments;
+        PolynomialCoefficients {
+            coefficients: result.iter().copied().collect(),
         }
+    }
 
-        // Hard-code some of these to increase performance. Else, we'd have to do linear algebra to
-        // get the equation of a straight line from two points.
-        match degree {
-            0 => unreachable!("we handled this above"),
-            1 => {
-                while let Some(buf) = iter.next() {
-                    debug_assert_eq!(buf.len(), 2);
-
-                    // SAFETY: I know the buf is going to be exactly 2 in length, as I wrote the
-                    // code.
-                    let p1 = unsafe { buf.get_unchecked(0) };
-                    let x1 = p1.0;
-                    let y1 = p1.1;
-                    let p2 = unsafe { buf.get_unchecked(1) };
-                    let x2 = p2.0;
-                    let y2 = p2.1;
-
-                    let slope = (y1 - y2) / (x1 - x2);
-                    // y=slope * x + intersect
-                    // intersect = y - slope * x
-                    // we could've chosen p2, it doesn't matter.
-                    let intersect = y1 - x1 * slope;
-
-                    // SAFETY: we pushed these vecs to `coefficients` above.
-                    unsafe {
-                        coefficients.get_unchecked_mut(1).push(slope);
-                        coefficients.get_unchecked_mut(0).push(intersect);
-                    }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-                    iter.give_buffer(buf);
-                }
-            }
-            // 10x performance increase with this hand-crafted technique.
-            2 => {
-                while let Some(buf) = iter.next() {
-                    debug_assert_eq!(buf.len(), 3);
+        #[test]
+        fn weighted() {
+            let x = [1., 2., 3., 4.];
+            let y = [2.1, 3.9, 6.2, 7.8];
+            // a weight of 2 is the same as the point twice
+            let line = weighted_linear(&x, &y, &[1., 2., 1., 1.]);
+            let repeated =
+                OlsEstimator.model_linear(&[1., 2., 2., 3., 4.], &[2.1, 3.9, 3.9, 6.2, 7.8]);
+            assert!((line.k - repeated.k).abs() < 1e-9 && (line.m - repeated.m).abs() < 1e-9);
+            // a weight of 0 ignores the point
+            let fit =
+                weighted_polynomial(&[1., 2., 3., 10.], &[1., 4., 9., 0.], &[1., 1., 1., 0.], 2);
+            assert!((fit[2] - 1.).abs() < 1e-9 && fit[0].abs() < 1e-9, "{fit}");
+        }
+        #[test]
+        fn constrained() {
+            let x = [1., 2., 3., 4., 5.];
+            let y = [2.1, 3.9, 6.2, 7.8, 10.1];
+            let line = ConstrainedOlsEstimator::through_origin().model_linear(&x, &y);
+            assert_eq!(line.m, 0.);
+            // Σxy / Σx²
+            assert!((line.k - 110.2 / 55.).abs() < 1e-12, "{line}");
+
+            // y = 3 + 0x + 2x², with the linear term fixed at 0
+            let y: Vec<f64> = x.iter().map(|x| 3. + 2. * x * x).collect();
+            let fit = polynomial_constrained(&x, &y, 2, &[(1, 0.)]);
+            assert_eq!(fit[1], 0.);
+            assert!((fit[0] - 3.).abs() < 1e-9 && (fit[2] - 2.).abs() < 1e-9);
+            // all fixed
+            let fit = polynomial_constrained(&x, &y, 1, &[(0, 1.), (1, 2.)]);
+            assert_eq!(*fit, [1., 2.]);
+        }
+        #[test]
+        fn generic() {
+            let x = [1., 2., 3., 4., 5.];
+            let y = [2.1, 3.9, 6.2, 7.8, 10.1];
+            let line = OlsEstimator.model_linear(&x, &y);
+            let x32 = x.map(|x| x as f32);
+            let y32 = y.map(|y| y as f32);
+            let line32 = OlsEstimator.model_linear(&x32, &y32);
+            assert!((line32.k - 1.99).abs() < 1e-5 && (line32.m - 0.05).abs() < 1e-5);
+            assert!((line.k - line32.k as f64).abs() < 1e-5);
+        }
+        #[test]
+        fn uncertainty() {
+            let x = [1., 2., 3., 4., 5.];
+            let y = [2.1, 3.9, 6.2, 7.8, 10.1];
+            let fit = linear_with_uncertainty(&x, &y);
+            assert!((fit.model.k - 1.99).abs() < 1e-12 && (fit.model.m - 0.05).abs() < 1e-12);
+            assert_eq!(fit.degrees_of_freedom, 3);
+            // Σ residual² = 0.107
+            let s2 = 0.107 / 3.;
+            assert!((fit.residual_variance - s2).abs() < 1e-12);
+            let errors = fit.standard_errors();
+            // s² / Sxx and s² (1/n + x̄² / Sxx)
+            assert!((errors[1] - (s2 / 10.).sqrt()).abs() < 1e-12);
+            assert!((errors[0] - (s2 * 1.1).sqrt()).abs() < 1e-12);
+
+            // t(0.975, 3)
+            let t = 3.182446305284263;
+            let (low, high) = fit.coefficient_interval(1, 0.95);
+            assert!((low - (1.99 - t * errors[1])).abs() < 1e-6, "{low}");
+            assert!((high - (1.99 + t * errors[1])).abs() < 1e-6, "{high}");
+            // (6 - x̄)² / Sxx = 0.9
+            let (low, high) = fit.confidence_band(6., 0.95);
+            assert!(
+                (low - (11.99 - t * (s2 * 1.1).sqrt())).abs() < 1e-6,
+                "{low}"
+            );
+            assert!(
+                (high - (11.99 + t * (s2 * 1.1).sqrt())).abs() < 1e-6,
+                "{high}"
+            );
+            let (low, high) = fit.predict_interval(6., 0.95);
+            assert!(
+                (low - (11.99 - t * (s2 * 2.1).sqrt())).abs() < 1e-6,
+                "{low}"
+            );
+            assert!(
+                (high - (11.99 + t * (s2 * 2.1).sqrt())).abs() < 1e-6,
+                "{high}"
+            );
 
-                    // SAFETY: I know the buf is going to be exactly 2 in length, as I wrote the
-                    // code.
-                    let p1 = unsafe { buf.get_unchecked(0) };
-                    let x1 = p1.0;
-                    let y1 = p1.1;
-                    let p2 = unsafe { buf.get_unchecked(1) };
-                    let x2 = p2.0;
-                    let y2 = p2.1;
-                    let p3 = unsafe { buf.get_unchecked(2) };
-                    let x3 = p3.0;
-                    let y3 = p3.1;
+            // perfect fit, nothing known about the noise
+            let fit = polynomial_with_uncertainty(&x[..3], &y[..3], 2);
+            assert!(fit.predict_interval(1., 0.9).0.is_nan());
+        }
+    }
+}
 
-                    // Derived from the systems of equation this makes.
-                    // See https://math.stackexchange.com/a/680695
+/// [Ridge regression](https://en.wikipedia.org/wiki/Ridge_regression) (Tikhonov
+/// regularization): least squares with a penalty of `lambda` times the squared coefficients.
+///
+/// This stabilizes fits where the columns of the design matrix are (almost) collinear, such as
+/// high-degree polynomials (`x⁹` and `x¹⁰` are nearly parallel on most ranges), at the cost of
+/// shrinking the coefficients towards 0. The intercept isn't penalized, and the other columns
+/// are standardized before fitting, so `lambda` means the same regardless of the scale of the
+/// predictors.
+///
+/// Use [`RidgeEstimator::select_lambda`] to choose `lambda` by leave-one-out cross-validation.
+#[cfg(feature = "ols")]
+pub mod ridge {
+    use nalgebra::{DMatrix, DVector};
 
-                    let a = (x1 * (y3 - y2) + x2 * (y1 - y3) + x3 * (y2 - y1))
-                        / ((x1 - x2) * (x1 - x3) * (x2 - x3));
-                    let b = (y2 - y1) / (x2 - x1) - a * (x1 + x2);
-                    let c = y1 - a * x1 * x1 - b * x1;
+    use super::*;
 
-                    // SAFETY: we pushed these vecs to `coefficients` above.
-                    unsafe {
-                        coefficients.get_unchecked_mut(2).push(a);
-                        coefficients.get_unchecked_mut(1).push(b);
-                        coefficients.get_unchecked_mut(0).push(c);
-                    }
+    /// The candidates of `lambda` used when you have no better guess, from `1e-6` to `1e3`.
+    pub const DEFAULT_LAMBDAS: [f64; 10] = [1e-6, 1e-5, 1e-4, 1e-3, 1e-2, 1e-1, 1., 1e1, 1e2, 1e3];
 
-                    iter.give_buffer(buf);
-                }
+    /// The standardized design matrix of a polynomial, with the means and scales to undo the
+    /// standardization.
+    struct Standardized {
+        design: DMatrix<f64>,
+        outcomes: DVector<f64>,
+        predictor_means: Vec<f64>,
+        predictor_scales: Vec<f64>,
+        outcome_mean: f64,
+    }
+    impl Standardized {
+        fn new(predictors: &[f64], outcomes: &[f64], degree: usize) -> Self {
+            assert_eq!(
+                predictors.len(),
+                outcomes.len(),
+                "predictors and outcomes must have the same number of items"
+            );
+            let len = predictors.len();
+            let mut design = DMatrix::from_fn(len, degree, |row, column| {
+                predictors[row].powi(column as i32 + 1)
+            });
+            let mut predictor_means = Vec::with_capacity(degree);
+            let mut predictor_scales = Vec::with_capacity(degree);
+            for mut column in design.column_iter_mut() {
+                let mean = column.mean();
+                let scale = column
+                    .iter()
+                    .map(|v| (v - mean).powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                // constant columns are all 0 after centering, the scale doesn't matter
+                let scale = if scale > 0. { scale } else { 1. };
+                column.apply(|v| *v = (*v - mean) / scale);
+                predictor_means.push(mean);
+                predictor_scales.push(scale);
             }
-            #[cfg(not(feature = "ols"))]
-            _ => {
-                panic!("unsupported degree for polynomial Theil-Sen. Supports 1,2 without the OLS cargo feature.");
+            let outcome_mean = crate::mean(outcomes);
+            let outcomes = DVector::from_iterator(len, outcomes.iter().map(|y| y - outcome_mean));
+            Self {
+                design,
+                outcomes,
+                predictor_means,
+                predictor_scales,
+                outcome_mean,
             }
-            #[cfg(feature = "ols")]
-            _ => {
-                while let Some(buf) = iter.next() {
-                    #[inline(always)]
-                    fn tuple_first(t: &(f64, f64)) -> f64 {
-                        t.0
-                    }
-                    #[inline(always)]
-                    fn tuple_second(t: &(f64, f64)) -> f64 {
-                        t.1
-                    }
-
-                    debug_assert_eq!(buf.len(), degree + 1);
-
-                    let predictors = buf.iter().map(tuple_first);
-                    let outcomes = buf.iter().map(tuple_second);
-
-                    let polynomial = ols::polynomial(predictors, outcomes, degree + 1, degree);
-                    for (pos, coefficient) in polynomial.iter().enumerate() {
-                        // SAFETY: we pushed these vecs to `coefficients` above.
-                        // pos is less than the size of `coefficients`.
-                        unsafe { coefficients.get_unchecked_mut(pos).push(*coefficient) };
-                    }
-
-                    iter.give_buffer(buf);
-                }
+        }
+        /// `(XᵀX + λI)⁻¹`, the inverse of the regularized Gram matrix.
+        fn regularized_inverse(&self, lambda: f64) -> DMatrix<f64> {
+            let t = self.design.transpose();
+            let mut gram = &t * &self.design;
+            for i in 0..gram.nrows() {
+                gram[(i, i)] += lambda;
+            }
+            gram.clone()
+                .try_inverse()
+                .unwrap_or_else(|| gram.pseudo_inverse(1e-12).unwrap())
+        }
+        /// The coefficients of the standardized columns.
+        fn solve(&self, inverse: &DMatrix<f64>) -> DVector<f64> {
+            inverse * (self.design.transpose() * &self.outcomes)
+        }
+        /// Transforms the coefficients back to the original scale.
+        fn coefficients(&self, standardized: &DVector<f64>) -> PolynomialCoefficients {
+            let mut coefficients = Vec::with_capacity(standardized.len() + 1);
+            let mut intercept = self.outcome_mean;
+            coefficients.push(0.);
+            for ((coefficient, mean), scale) in standardized
+                .iter()
+                .zip(&self.predictor_means)
+                .zip(&self.predictor_scales)
+            {
+                let coefficient = coefficient / scale;
+                intercept -= coefficient * mean;
+                coefficients.push(coefficient);
             }
+            coefficients[0] = intercept;
+            PolynomialCoefficients { coefficients }
         }
+    }
 
-        #[inline(always)]
-        fn f64_cmp(a: &f64, b: &f64) -> std::cmp::Ordering {
-            crate::F64OrdHash::f64_cmp(*a, *b)
+    /// Ridge regression with the penalty [`Self::lambda`].
+    ///
+    /// With `lambda = 0`, this is the same as [`OlsEstimator`] (apart from rounding).
+    ///
+    /// `O(n*degree²)`
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct RidgeEstimator {
+        /// The strength of the penalty. Larger values shrink the coefficients more.
+        pub lambda: f64,
+    }
+    impl RidgeEstimator {
+        /// # Panics
+        ///
+        /// Panics if `lambda` is negative or `NaN`.
+        pub fn new(lambda: f64) -> Self {
+            assert!(lambda >= 0., "lambda must be non-negative");
+            Self { lambda }
         }
+        /// The mean squared leave-one-out error of fitting a polynomial of `degree` with this
+        /// `lambda`.
+        ///
+        /// Each point is predicted by the model fitted on all the other points. This doesn't
+        /// refit `n` times; the leave-one-out residual is `eᵢ / (1 - hᵢᵢ)`, where `hᵢᵢ` is the
+        /// leverage of the point (the diagonal of the hat matrix).
+        ///
+        /// # Panics
+        ///
+        /// Panics if `predictors` and `outcomes` have different lengths.
+        pub fn leave_one_out_error(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+        ) -> f64 {
+            let standardized = Standardized::new(predictors, outcomes, degree);
+            leave_one_out_error(&standardized, self.lambda)
+        }
+        /// Chooses the `lambda` of `candidates` with the smallest
+        /// [leave-one-out error](Self::leave_one_out_error) when fitting a polynomial of
+        /// `degree`. [`DEFAULT_LAMBDAS`] are reasonable candidates.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `candidates` is empty, any candidate is negative, or `predictors` and
+        /// `outcomes` have different lengths.
+        pub fn select_lambda(
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+            candidates: &[f64],
+        ) -> Self {
+            assert!(!candidates.is_empty(), "no candidates of lambda");
+            let standardized = Standardized::new(predictors, outcomes, degree);
+            let lambda = candidates
+                .iter()
+                .map(|lambda| {
+                    let estimator = Self::new(*lambda);
+                    (
+                        *lambda,
+                        leave_one_out_error(&standardized, estimator.lambda),
+                    )
+                })
+                .min_by(|(_, a), (_, b)| crate::F64OrdHash::f64_cmp(*a, *b))
+                .expect("candidates isn't empty")
+                .0;
+            Self::new(lambda)
+        }
+    }
+    fn leave_one_out_error(standardized: &Standardized, lambda: f64) -> f64 {
+        let len = standardized.outcomes.len();
+        if len == 0 {
+            return f64::NAN;
+        }
+        let inverse = standardized.regularized_inverse(lambda);
+        let coefficients = standardized.solve(&inverse);
+        let fitted = &standardized.design * &coefficients;
+        let sum: f64 = (0..len)
+            .map(|i| {
+                let row = standardized.design.row(i);
+                // the intercept adds 1/n to the leverage
+                let leverage = (row * &inverse).dot(&row) + 1. / len as f64;
+                let residual = standardized.outcomes[i] - fitted[i];
+                (residual / (1. - leverage)).powi(2)
+            })
+            .sum();
+        sum / len as f64
+    }
 
-        let mut result = Vec::with_capacity(degree + 1);
-        for mut coefficients in coefficients {
-            // `TODO`: Choose coefficients for a single point (the median of the coefficient with the
-            // highest exponential) instead of then median of the single values.
-
-            // 5x boost in performance here when using `O(n)` median instead of sorting. (when
-            // using args `-t -d5` with a detaset of 40 values).
-            let median = crate::percentile::percentile_default_pivot_by(
-                &mut coefficients,
-                crate::Fraction::HALF,
-                &mut f64_cmp,
-            )
-            .resolve();
-            result.push(median);
+    impl LinearEstimator for RidgeEstimator {
+        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+            let coefficients = self.model_polynomial(predictors, outcomes, 1);
+            LinearCoefficients {
+                k: coefficients[1],
+                m: coefficients[0],
+            }
         }
-        PolynomialCoefficients {
-            coefficients: result,
+    }
+    impl PolynomialEstimator for RidgeEstimator {
+        fn model_polynomial(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+        ) -> PolynomialCoefficients {
+            let standardized = Standardized::new(predictors, outcomes, degree);
+            let inverse = standardized.regularized_inverse(self.lambda);
+            standardized.coefficients(&standardized.solve(&inverse))
         }
     }
 
@@ -2294,107 +4401,2444 @@ pub mod theil_sen {
         use super::*;
 
         #[test]
-        fn permutations_eq_1() {
-            let s1 = [1., 2., 3., 4., 5.];
-            let s2 = [2., 4., 6., 8., 10.];
+        fn ridge() {
+            let x: Vec<f64> = (0..20).map(|v| v as f64 / 2.).collect();
+            let y: Vec<f64> = x.iter().map(|x| 3. * x - 2. + (x * 7.).sin()).collect();
 
-            let permutations1 = permutations(&s1, &s2)
-                .map(|(x, y)| [x, y])
-                .collect::<Vec<_>>();
-            let permutations2 = permutations_generic(&s1, &s2, 2).collect_len();
+            let ols = OlsEstimator.model_linear(&x, &y);
+            let ridge = RidgeEstimator::new(0.).model_linear(&x, &y);
+            assert!((ols.k - ridge.k).abs() < 1e-9 && (ols.m - ridge.m).abs() < 1e-9);
 
-            assert_eq!(permutations1, permutations2);
-        }
-        #[test]
-        #[cfg(feature = "rand")]
-        fn permutations_eq_2() {
-            use rand::Rng;
+            // the penalty shrinks the slope, but not the intercept, towards the mean
+            let shrunk = RidgeEstimator::new(10.).model_linear(&x, &y);
+            assert!(shrunk.k < ols.k && shrunk.k > 0.);
+            let x_mean = crate::mean(&x);
+            let y_mean = crate::mean(&y);
+            assert!((shrunk.predict_outcome(x_mean) - y_mean).abs() < 1e-9);
 
-            let mut s1 = [0.0; 20];
-            let mut s2 = [0.0; 20];
+            // a degree 8 fit of noisy linear data is overfitted without a penalty
+            let selected = RidgeEstimator::select_lambda(&x, &y, 8, &DEFAULT_LAMBDAS);
+            let selected_error = selected.leave_one_out_error(&x, &y, 8);
+            let unpenalized_error = RidgeEstimator::new(0.).leave_one_out_error(&x, &y, 8);
+            assert!(selected.lambda > 0.);
+            assert!(selected_error <= unpenalized_error, "{selected_error}");
+        }
+    }
+}
 
-            let mut rng = rand::rng();
-            rng.fill(&mut s1);
-            rng.fill(&mut s2);
+/// [Lasso regression](https://en.wikipedia.org/wiki/Lasso_(statistics)): least squares with a
+/// penalty of `lambda` times the sum of the absolute coefficients.
+///
+/// Unlike [ridge regression](ridge), the penalty sets the coefficients of unimportant features to
+/// exactly 0, which selects a sparse subset of e.g. the terms of a polynomial or the columns of a
+/// multi-dimensional dataset.
+///
+/// The minimized objective is `1/(2n) Σ(yᵢ - ŷᵢ)² + λ Σ|βⱼ|`, with the features standardized to
+/// mean 0 and variance 1 and the intercept unpenalized. It's solved with
+/// [coordinate descent](https://en.wikipedia.org/wiki/Coordinate_descent): each coefficient is in
+/// turn set to its optimum given the others, which is the
+/// [soft-thresholded](LassoEstimator::soft_threshold) correlation of its feature with the
+/// residuals.
+pub mod lasso {
+    use super::*;
 
-            let permutations1 = permutations(&s1, &s2)
-                .map(|(x, y)| [x, y])
-                .collect::<Vec<_>>();
-            let permutations2 = permutations_generic(&s1, &s2, 2).collect_len();
+    /// Coefficients of a linear model of several features, fitted by [`LassoEstimator`].
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct LassoFit {
+        /// The additive term.
+        pub intercept: f64,
+        /// The coefficient of each feature, in the order of the features. Many are exactly 0.
+        pub coefficients: Vec<f64>,
+        /// The count of passes over all coefficients.
+        pub iterations: usize,
+    }
+    impl LassoFit {
+        /// The predicted outcome of the `features` of a point.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the count of `features` isn't the count of [`Self::coefficients`].
+        pub fn predict(&self, features: &[f64]) -> f64 {
+            assert_eq!(
+                features.len(),
+                self.coefficients.len(),
+                "one feature per coefficient is required"
+            );
+            self.intercept
+                + features
+                    .iter()
+                    .zip(&self.coefficients)
+                    .map(|(x, c)| x * c)
+                    .sum::<f64>()
+        }
+        /// The indices of the features with a non-zero coefficient.
+        pub fn selected(&self) -> Vec<usize> {
+            self.coefficients
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c != 0.)
+                .map(|(i, _)| i)
+                .collect()
+        }
+    }
 
-            assert_eq!(permutations1, permutations2);
+    /// Lasso regression with the penalty [`Self::lambda`].
+    ///
+    /// `O(n*features*iterations)`
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct LassoEstimator {
+        /// The strength of the penalty, relative to the standardized features. Larger values set
+        /// more coefficients to 0.
+        pub lambda: f64,
+        /// The maximum count of passes over all coefficients.
+        pub max_iterations: usize,
+        /// Stop when no standardized coefficient changes more than this in a pass.
+        pub tolerance: f64,
+    }
+    impl LassoEstimator {
+        /// At most 1000 passes, with a tolerance of `1e-9`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `lambda` is negative or `NaN`.
+        pub fn new(lambda: f64) -> Self {
+            assert!(lambda >= 0., "lambda must be non-negative");
+            Self {
+                lambda,
+                max_iterations: 1000,
+                tolerance: 1e-9,
+            }
         }
-        #[test]
-        fn permutations_len_3() {
-            let s1 = [1., 2., 3., 4., 5.];
-            let s2 = [2., 4., 6., 8., 10.];
+        /// `sign(value) * max(|value| - threshold, 0)`, the minimizer of
+        /// `(β - value)²/2 + threshold|β|`.
+        pub fn soft_threshold(value: f64, threshold: f64) -> f64 {
+            if value > threshold {
+                value - threshold
+            } else if value < -threshold {
+                value + threshold
+            } else {
+                0.
+            }
+        }
+        /// Fits `outcomes` to the `features`, each a slice with one value per outcome.
+        ///
+        /// Features without variance get a coefficient of 0.
+        ///
+        /// # Panics
+        ///
+        /// Panics if any of the `features` doesn't have the same length as `outcomes`.
+        pub fn model_features(&self, features: &[&[f64]], outcomes: &[f64]) -> LassoFit {
+            let len = outcomes.len();
+            for feature in features {
+                assert_eq!(
+                    feature.len(),
+                    len,
+                    "features and outcomes must have the same number of items"
+                );
+            }
+            if len == 0 {
+                return LassoFit {
+                    intercept: f64::NAN,
+                    coefficients: vec![0.; features.len()],
+                    iterations: 0,
+                };
+            }
+            let n = len as f64;
+            let outcome_mean = crate::mean(outcomes);
+            // standardized features, and the mean and scale to undo it
+            let mut columns = Vec::with_capacity(features.len());
+            let mut transforms = Vec::with_capacity(features.len());
+            for feature in features {
+                let mean = crate::mean(feature);
+                let scale = (feature.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+                if scale > 0. {
+                    columns.push(Some(
+                        feature
+                            .iter()
+                            .map(|v| (v - mean) / scale)
+                            .collect::<Vec<_>>(),
+                    ));
+                } else {
+                    columns.push(None);
+                }
+                transforms.push((mean, scale));
+            }
 
-            let permutations = permutations_generic(&s1, &s2, 3).collect_len::<3>();
+            let mut residuals: Vec<f64> = outcomes.iter().map(|y| y - outcome_mean).collect();
+            let mut coefficients = vec![0.; features.len()];
+            let mut iterations = 0;
+            while iterations < self.max_iterations {
+                iterations += 1;
+                let mut max_change: f64 = 0.;
+                for (column, coefficient) in columns.iter().zip(&mut coefficients) {
+                    let Some(column) = column else { continue };
+                    // the correlation with the residuals without this feature's contribution
+                    let correlation = column
+                        .iter()
+                        .zip(&residuals)
+                        .map(|(x, r)| x * r)
+                        .sum::<f64>()
+                        / n
+                        + *coefficient;
+                    let new = Self::soft_threshold(correlation, self.lambda);
+                    let change = new - *coefficient;
+                    if change != 0. {
+                        for (r, x) in residuals.iter_mut().zip(column) {
+                            *r -= change * x;
+                        }
+                        *coefficient = new;
+                    }
+                    max_change = max_change.max(change.abs());
+                }
+                if max_change <= self.tolerance {
+                    break;
+                }
+            }
 
-            let expected: &[[(f64, f64); 3]] = &[
-                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0)],
-                [(1.0, 2.0), (2.0, 4.0), (4.0, 8.0)],
-                [(1.0, 2.0), (2.0, 4.0), (5.0, 10.0)],
-                [(1.0, 2.0), (3.0, 6.0), (4.0, 8.0)],
-                [(1.0, 2.0), (3.0, 6.0), (5.0, 10.0)],
-                [(1.0, 2.0), (4.0, 8.0), (5.0, 10.0)],
-                [(2.0, 4.0), (3.0, 6.0), (4.0, 8.0)],
-                [(2.0, 4.0), (3.0, 6.0), (5.0, 10.0)],
-                [(2.0, 4.0), (4.0, 8.0), (5.0, 10.0)],
-                [(3.0, 6.0), (4.0, 8.0), (5.0, 10.0)],
-            ];
+            let mut intercept = outcome_mean;
+            for (coefficient, (mean, scale)) in coefficients.iter_mut().zip(transforms) {
+                if *coefficient != 0. {
+                    *coefficient /= scale;
+                    intercept -= *coefficient * mean;
+                }
+            }
+            LassoFit {
+                intercept,
+                coefficients,
+                iterations,
+            }
+        }
+    }
+    impl LinearEstimator for LassoEstimator {
+        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+            let fit = self.model_features(&[predictors], outcomes);
+            LinearCoefficients {
+                k: fit.coefficients[0],
+                m: fit.intercept,
+            }
+        }
+    }
+    impl PolynomialEstimator for LassoEstimator {
+        fn model_polynomial(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+        ) -> PolynomialCoefficients {
+            let powers: Vec<Vec<f64>> = (1..=degree)
+                .map(|exponent| predictors.iter().map(|x| x.powi(exponent as i32)).collect())
+                .collect();
+            let features: Vec<&[f64]> = powers.iter().map(Vec::as_slice).collect();
+            let fit = self.model_features(&features, outcomes);
+            let mut coefficients = fit.coefficients;
+            coefficients.insert(0, fit.intercept);
+            PolynomialCoefficients { coefficients }
+        }
+    }
 
-            assert_eq!(expected.len(), permutation_count(5, 3).unwrap());
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-            assert_eq!(permutations, expected,);
-        }
         #[test]
-        fn permutations_len_4_1() {
-            let s1 = [1., 2., 3., 4., 5.];
-            let s2 = [2., 4., 6., 8., 10.];
+        fn lasso() {
+            assert_eq!(LassoEstimator::soft_threshold(3., 1.), 2.);
+            assert_eq!(LassoEstimator::soft_threshold(-0.5, 1.), 0.);
+
+            let x: Vec<f64> = (0..30).map(|v| v as f64 / 3.).collect();
+            let y: Vec<f64> = x.iter().map(|x| 2. * x + 1.).collect();
+            let line = LassoEstimator::new(0.).model_linear(&x, &y);
+            assert!((line.k - 2.).abs() < 1e-6 && (line.m - 1.).abs() < 1e-6);
+
+            // only the first and third features affect the outcome
+            let a: Vec<f64> = (0..40).map(|v| (v as f64 * 0.7).sin()).collect();
+            let b: Vec<f64> = (0..40).map(|v| (v as f64 * 1.3).cos()).collect();
+            let c: Vec<f64> = (0..40).map(|v| (v % 7) as f64).collect();
+            let noise: Vec<f64> = (0..40).map(|v| ((v * 13) % 5) as f64 * 0.01).collect();
+            let y: Vec<f64> = (0..40)
+                .map(|i| 3. * a[i] - 0.5 * c[i] + 4. + noise[i])
+                .collect();
+            let fit = LassoEstimator::new(0.05).model_features(&[&a, &b, &c], &y);
+            assert_eq!(fit.selected(), [0, 2]);
+            assert!((fit.coefficients[0] - 3.).abs() < 0.2, "{fit:?}");
+            assert!((fit.predict(&[a[5], b[5], c[5]]) - y[5]).abs() < 0.3);
+        }
+    }
+}
 
-            let permutations = permutations_generic(&s1, &s2, 4).collect_len();
+/// [LOESS](https://en.wikipedia.org/wiki/Local_regression) (locally estimated scatterplot
+/// smoothing), a smoother which doesn't assume the shape of the data.
+///
+/// Every prediction fits a polynomial of low degree (usually 1) to the nearest
+/// [`Loess::span`] fraction of the points, weighted by the
+/// [tricube](https://en.wikipedia.org/wiki/Kernel_(statistics)#Kernel_functions_in_common_use)
+/// of their distance, and evaluates it at the predictor. Use this when you need a smooth curve
+/// through the data, rather than the equation of a [`best_fit`]
+/// model.
+///
+/// The points are kept in the [`LoessCurve`], and each prediction is `O(log n + span*n)`.
+pub mod loess {
+    use super::*;
 
-            let expected: &[[(f64, f64); 4]] = &[
-                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (4.0, 8.0)],
-                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (5.0, 10.0)],
-                [(1.0, 2.0), (2.0, 4.0), (4.0, 8.0), (5.0, 10.0)],
-                [(1.0, 2.0), (3.0, 6.0), (4.0, 8.0), (5.0, 10.0)],
-                [(2.0, 4.0), (3.0, 6.0), (4.0, 8.0), (5.0, 10.0)],
-            ];
+    /// The parameters of a LOESS fit. Use [`Self::fit`] to get the [`LoessCurve`].
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Loess {
+        /// The fraction of the points used in each local fit, in `(0, 1]`. Larger values give a
+        /// smoother curve.
+        pub span: f64,
+        /// The degree of the local polynomials, 0 (a weighted moving average), 1, or 2.
+        pub degree: usize,
+    }
+    impl Loess {
+        /// # Panics
+        ///
+        /// Panics if `span` isn't in `(0, 1]` or `degree > 2`.
+        pub fn new(span: f64, degree: usize) -> Self {
+            assert!(span > 0. && span <= 1., "span must be in the range (0, 1]");
+            assert!(degree <= 2, "the degree must be 0, 1, or 2");
+            Self { span, degree }
+        }
+        /// Prepares the curve through the points (`predictors`, `outcomes`).
+        ///
+        /// O(n log n)
+        ///
+        /// # Panics
+        ///
+        /// Panics if `predictors` and `outcomes` have different lengths, or are empty.
+        pub fn fit(&self, predictors: &[f64], outcomes: &[f64]) -> LoessCurve {
+            assert_eq!(
+                predictors.len(),
+                outcomes.len(),
+                "predictors and outcomes must have the same number of items"
+            );
+            assert!(!predictors.is_empty(), "at least one point is required");
+            let mut points: Vec<(f64, f64)> = predictors
+                .iter()
+                .copied()
+                .zip(outcomes.iter().copied())
+                .collect();
+            points.sort_unstable_by(|a, b| crate::F64OrdHash::f64_cmp(a.0, b.0));
+            let neighbours = ((self.span * points.len() as f64).ceil() as usize)
+                .max(self.degree + 1)
+                .min(points.len());
+            LoessCurve {
+                points,
+                neighbours,
+                loess: *self,
+            }
+        }
+    }
+    impl Default for Loess {
+        /// A span of 0.75 and degree 2, the defaults of R's `loess`.
+        fn default() -> Self {
+            Self::new(0.75, 2)
+        }
+    }
 
-            assert_eq!(expected.len(), permutation_count(5, 4).unwrap());
+    /// A smooth curve through the points, see [`Loess`].
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct LoessCurve {
+        /// Sorted by the predictor.
+        points: Vec<(f64, f64)>,
+        /// The count of points in each local fit.
+        neighbours: usize,
+        loess: Loess,
+    }
+    impl LoessCurve {
+        /// The parameters used for the fit.
+        pub fn parameters(&self) -> Loess {
+            self.loess
+        }
+        /// The points of the curve at each of the predictors, in ascending order of the
+        /// predictor. Useful for plotting.
+        ///
+        /// O(n * span*n)
+        pub fn smoothed(&self) -> Vec<(f64, f64)> {
+            self.points
+                .iter()
+                .map(|(x, _)| (*x, self.predict_outcome(*x)))
+                .collect()
+        }
+        /// The `neighbours` points closest to `x`.
+        fn nearest(&self, x: f64) -> &[(f64, f64)] {
+            let len = self.points.len();
+            let mut start = self.points.partition_point(|(v, _)| *v < x);
+            let mut end = start;
+            while end - start < self.neighbours {
+                let take_left = if start == 0 {
+                    false
+                } else if end == len {
+                    true
+                } else {
+                    x - self.points[start - 1].0 <= self.points[end].0 - x
+                };
+                if take_left {
+                    start -= 1;
+                } else {
+                    end += 1;
+                }
+            }
+            &self.points[start..end]
+        }
+    }
+    impl Predictive for LoessCurve {
+        fn predict_outcome(&self, predictor: f64) -> f64 {
+            let nearest = self.nearest(predictor);
+            let max_distance = nearest
+                .iter()
+                .map(|(x, _)| (x - predictor).abs())
+                .fold(0., f64::max);
+            let weights: Vec<f64> = nearest
+                .iter()
+                .map(|(x, _)| {
+                    if max_distance == 0. {
+                        return 1.;
+                    }
+                    let d = (x - predictor).abs() / max_distance;
+                    (1. - d * d * d).powi(3)
+                })
+                .collect();
+            // lower the degree if the points don't determine the polynomial (e.g. too few
+            // points with a non-zero weight)
+            for degree in (0..=self.loess.degree).rev() {
+                if let Some(value) = local_fit(nearest, &weights, predictor, degree) {
+                    return value;
+                }
+            }
+            f64::NAN
+        }
+    }
+    impl Display for LoessCurve {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "LOESS (span {}, degree {})",
+                self.loess.span, self.loess.degree
+            )
+        }
+    }
 
-            assert_eq!(permutations, expected,);
+    /// The value at `at` of the weighted least-squares polynomial of `degree`.
+    ///
+    /// The predictors are centered on `at`, so the value is the constant coefficient.
+    /// Returns [`None`] if the normal equations are singular.
+    fn local_fit(points: &[(f64, f64)], weights: &[f64], at: f64, degree: usize) -> Option<f64> {
+        let size = degree + 1;
+        // the normal equations, `[XᵀWX | XᵀWy]`
+        let mut matrix = [[0.; 4]; 3];
+        for ((x, y), w) in points.iter().zip(weights) {
+            let u = x - at;
+            let powers = [1., u, u * u, u * u * u, u * u * u * u];
+            for (row, values) in matrix.iter_mut().enumerate().take(size) {
+                for (column, value) in values.iter_mut().enumerate().take(size) {
+                    *value += w * powers[row + column];
+                }
+                values[3] += w * powers[row] * y;
+            }
         }
-        #[test]
-        fn permutations_len_4_2() {
-            let s1 = [1., 2., 3., 4., 5., 6.];
-            let s2 = [2., 4., 6., 8., 10., 12.];
+        // Gauss-Jordan elimination with partial pivoting
+        let scale = matrix[0][0];
+        for column in 0..size {
+            let pivot = (column..size)
+                .max_by(|a, b| {
+                    crate::F64OrdHash::f64_cmp(matrix[*a][column].abs(), matrix[*b][column].abs())
+                })
+                .expect("the range isn't empty");
+            let magnitude = matrix[pivot][column].abs();
+            if magnitude.is_nan() || magnitude <= scale * 1e-12 {
+                return None;
+            }
+            matrix.swap(column, pivot);
+            for row in 0..size {
+                if row != column {
+                    let factor = matrix[row][column] / matrix[column][column];
+                    let pivot_row = matrix[column];
+                    for (value, pivot_value) in matrix[row].iter_mut().zip(pivot_row) {
+                        *value -= factor * pivot_value;
+                    }
+                }
+            }
+        }
+        Some(matrix[0][3] / matrix[0][0])
+    }
 
-            let permutations = permutations_generic(&s1, &s2, 4).collect_len();
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn smooths() {
+            let x: Vec<f64> = (0..100).map(|v| v as f64 / 10.).collect();
+            // a sine wave with alternating noise
+            let y: Vec<f64> = x
+                .iter()
+                .enumerate()
+                .map(|(i, x)| x.sin() + if i % 2 == 0 { 0.2 } else { -0.2 })
+                .collect();
+            let curve = Loess::new(0.2, 2).fit(&x, &y);
+            for x in [1., 2.5, 4.7, 8.] {
+                let predicted = curve.predict_outcome(x);
+                assert!((predicted - f64::sin(x)).abs() < 0.05, "{x}: {predicted}");
+            }
+            assert_eq!(curve.smoothed().len(), 100);
+
+            // local linear fits of a line reproduce it exactly
+            let line: Vec<f64> = x.iter().map(|x| 3. * x - 1.).collect();
+            let curve = Loess::new(0.3, 1).fit(&x, &line);
+            assert!((curve.predict_outcome(5.55) - 15.65).abs() < 1e-9);
+            assert!((curve.predict_outcome(0.) + 1.).abs() < 1e-9);
+        }
+    }
+}
+
+/// Segmented (piecewise linear) regression, which fits separate lines to consecutive ranges of
+/// the predictors and finds the breakpoints between them.
+///
+/// The breakpoints are chosen to minimize the total squared error of the lines, which is exact
+/// (not a local search): every split of the sorted points is considered using dynamic
+/// programming. This finds e.g. the load where the throughput of a system stops increasing.
+pub mod segmented {
+    use super::*;
+    #[cfg(feature = "serde")]
+    use serde::{Deserialize, Serialize};
+
+    /// Lines on consecutive ranges of the predictor. Returned from [`segmented`](fn@segmented).
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct SegmentedCoefficients {
+        /// The predictors where the segments change, in ascending order. Each is halfway between
+        /// the last point of a segment and the first of the next.
+        pub breakpoints: Vec<f64>,
+        /// The line of each segment. There's one more segment than breakpoints.
+        pub segments: Vec<LinearCoefficients>,
+    }
+    impl SegmentedCoefficients {
+        /// The index of the segment `predictor` belongs to.
+        pub fn segment_of(&self, predictor: f64) -> usize {
+            self.breakpoints
+                .partition_point(|breakpoint| *breakpoint <= predictor)
+        }
+    }
+    impl Predictive for SegmentedCoefficients {
+        fn predict_outcome(&self, predictor: f64) -> f64 {
+            self.segments[self.segment_of(predictor)].predict_outcome(predictor)
+        }
+    }
+    impl Display for SegmentedCoefficients {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let p = f.precision().unwrap_or(5);
+            for (i, segment) in self.segments.iter().enumerate() {
+                if i > 0 {
+                    f.write_str("; ")?;
+                }
+                match (
+                    i.checked_sub(1).map(|i| self.breakpoints[i]),
+                    self.breakpoints.get(i),
+                ) {
+                    (None, Some(end)) => write!(f, "x < {end:.p$}: ")?,
+                    (Some(start), Some(end)) => write!(f, "{start:.p$} ≤ x < {end:.p$}: ")?,
+                    (Some(start), None) => write!(f, "x ≥ {start:.p$}: ")?,
+                    (None, None) => {}
+                }
+                write!(f, "{segment:.p$}")?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Sums of the points before each index, giving the least-squares line of any range in O(1).
+    struct PrefixSums {
+        sums: Vec<[f64; 5]>,
+    }
+    impl PrefixSums {
+        /// `points` should be centered, to reduce cancellation.
+        fn new(points: &[(f64, f64)]) -> Self {
+            let mut sums = Vec::with_capacity(points.len() + 1);
+            let mut sum = [0.; 5];
+            sums.push(sum);
+            for (x, y) in points {
+                sum[0] += x;
+                sum[1] += y;
+                sum[2] += x * x;
+                sum[3] += x * y;
+                sum[4] += y * y;
+                sums.push(sum);
+            }
+            Self { sums }
+        }
+        /// The line and squared error of the points `start..end`.
+        fn fit(&self, start: usize, end: usize) -> (LinearCoefficients, f64) {
+            let n = (end - start) as f64;
+            let [x, y, xx, xy, yy] = {
+                let (a, b) = (self.sums[start], self.sums[end]);
+                [
+                    b[0] - a[0],
+                    b[1] - a[1],
+                    b[2] - a[2],
+                    b[3] - a[3],
+                    b[4] - a[4],
+                ]
+            };
+            let sxx = xx - x * x / n;
+            let sxy = xy - x * y / n;
+            let syy = yy - y * y / n;
+            let k = if sxx > 0. { sxy / sxx } else { 0. };
+            let error = (syy - k * sxy).max(0.);
+            (
+                LinearCoefficients {
+                    k,
+                    m: (y - k * x) / n,
+                },
+                error,
+            )
+        }
+    }
+
+    /// Fits `segments` lines to the points, choosing the breakpoints which minimize the total
+    /// squared error. Each segment has at least `min_points` points (at least 2).
+    ///
+    /// Breakpoints are only placed between different predictors. If there are too few distinct
+    /// predictors, fewer segments are returned.
+    ///
+    /// Time: O(segments * n²), space: O(segments * n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors` and `outcomes` have different lengths, are empty, or `segments` is 0.
+    pub fn segmented(
+        predictors: &[f64],
+        outcomes: &[f64],
+        segments: usize,
+        min_points: usize,
+    ) -> SegmentedCoefficients {
+        assert_eq!(
+            predictors.len(),
+            outcomes.len(),
+            "predictors and outcomes must have the same number of items"
+        );
+        assert!(!predictors.is_empty(), "at least one point is required");
+        assert!(segments > 0, "at least one segment is required");
+        let min_points = min_points.max(2);
+
+        let x_mean = crate::mean(predictors);
+        let y_mean = crate::mean(outcomes);
+        let mut points: Vec<(f64, f64)> = predictors
+            .iter()
+            .zip(outcomes)
+            .map(|(x, y)| (x - x_mean, y - y_mean))
+            .collect();
+        points.sort_unstable_by(|a, b| crate::F64OrdHash::f64_cmp(a.0, b.0));
+        let len = points.len();
+        let sums = PrefixSums::new(&points);
+        // a segment can start at `i` if it isn't between equal predictors
+        let can_split = |i: usize| i == 0 || i == len || points[i - 1].0 < points[i].0;
+
+        // `errors[s][i]`: the least error of `s + 1` segments of the first `i` points, and
+        // `starts[s][i]` the start of the last of those segments.
+        let mut errors = vec![vec![f64::INFINITY; len + 1]; segments];
+        let mut starts = vec![vec![0; len + 1]; segments];
+        for (end, error) in errors[0].iter_mut().enumerate().skip(min_points.min(len)) {
+            if can_split(end) {
+                *error = sums.fit(0, end).1;
+            }
+        }
+        for segment in 1..segments {
+            for end in (segment + 1) * min_points..=len {
+                if !can_split(end) {
+                    continue;
+                }
+                for start in segment * min_points..=end - min_points {
+                    let previous = errors[segment - 1][start];
+                    if !can_split(start) || previous == f64::INFINITY {
+                        continue;
+                    }
+                    let error = previous + sums.fit(start, end).1;
+                    if error < errors[segment][end] {
+                        errors[segment][end] = error;
+                        starts[segment][end] = start;
+                    }
+                }
+            }
+        }
+
+        // the most segments (up to `segments`) which fit the points
+        let used = (0..segments)
+            .rev()
+            .find(|segment| errors[*segment][len] < f64::INFINITY)
+            .unwrap_or(0);
+        let mut bounds = vec![len];
+        let mut end = len;
+        for segment in (1..=used).rev() {
+            end = starts[segment][end];
+            bounds.push(end);
+        }
+        bounds.push(0);
+        bounds.reverse();
+
+        let uncenter = |line: LinearCoefficients| LinearCoefficients {
+            k: line.k,
+            m: line.m + y_mean - line.k * x_mean,
+        };
+        let segments = bounds
+            .windows(2)
+            .map(|range| uncenter(sums.fit(range[0], range[1]).0))
+            .collect();
+        let breakpoints = bounds[1..bounds.len() - 1]
+            .iter()
+            .map(|i| (points[i - 1].0 + points[*i].0) / 2. + x_mean)
+            .collect();
+        SegmentedCoefficients {
+            breakpoints,
+            segments,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn breakpoint() {
+            // the throughput increases until a load of 20, then flattens
+            let x: Vec<f64> = (1..=40).map(f64::from).collect();
+            let y: Vec<f64> = x
+                .iter()
+                .map(|x| {
+                    if *x <= 20. {
+                        5. * x + 1.
+                    } else {
+                        110. + 0.2 * (x - 20.)
+                    }
+                })
+                .collect();
+            let fit = segmented(&x, &y, 2, 3);
+            assert_eq!(fit.breakpoints, [20.5]);
+            assert!((fit.segments[0].k - 5.).abs() < 1e-9);
+            assert!((fit.segments[1].k - 0.2).abs() < 1e-9);
+            assert!((fit.predict_outcome(30.) - 112.).abs() < 1e-9);
+            assert_eq!(
+                format!("{fit:.1}"),
+                "x < 20.5: 5.0x + 1.0; x ≥ 20.5: 0.2x + 106.0"
+            );
+
+            let three = segmented(&x, &y, 3, 3);
+            assert_eq!(three.segments.len(), 3);
+            // too few points for 3 segments
+            assert_eq!(segmented(&x[..5], &y[..5], 3, 2).segments.len(), 2);
+        }
+    }
+}
+
+/// [Theil-Sen estimator](https://en.wikipedia.org/wiki/Theil%E2%80%93Sen_estimator), a robust
+/// linear (also implemented as polynomial) estimator.
+/// Up to ~27% of values can be *outliers* - erroneous data far from the otherwise good data -
+/// without large effects on the result.
+///
+/// [`LinearTheilSen`] implements [`LinearEstimator`].
+pub mod theil_sen {
+    use super::*;
+    use crate::{percentile, F64OrdHash};
+    use std::fmt::Debug;
+
+    /// A buffer returned by [`PermutationIter`] to avoid allocations.
+    pub struct PermutationIterBuffer<T> {
+        buf: Vec<(T, T)>,
+    }
+    impl<T> Deref for PermutationIterBuffer<T> {
+        type Target = [(T, T)];
+        fn deref(&self) -> &Self::Target {
+            &self.buf
+        }
+    }
+    /// An iterator over the permutations.
+    #[derive(Debug)]
+    pub struct PermutationIter<'a, T> {
+        s1: &'a [T],
+        s2: &'a [T],
+        iters: Vec<usize>,
+        values: Option<Vec<(T, T)>>,
+        values_backup: Vec<(T, T)>,
+        pairs: usize,
+    }
+    impl<'a, T: Copy + Debug> PermutationIter<'a, T> {
+        fn new(s1: &'a [T], s2: &'a [T], pairs: usize) -> Self {
+            assert!(
+                pairs > 1,
+                "each coordinate pair must be associated with at least one."
+            );
+            assert_eq!(s1.len(), s2.len());
+            assert!(pairs <= s1.len());
+            let iters = Vec::with_capacity(pairs);
+            let values_backup = Vec::with_capacity(pairs);
+            let mut me = Self {
+                s1,
+                s2,
+                iters,
+                values: None,
+                values_backup,
+                pairs,
+            };
+            for i in 0..pairs {
+                // `+ (not last) as usize` since the last iterator doesn't first read vec.
+                me.iters.push(i + usize::from(i + 1 < pairs) - 1);
+            }
+            #[allow(clippy::needless_range_loop)] // clarity
+            for i in 0..pairs - 1 {
+                me.values_backup.push((me.s1[i], me.s2[i]));
+            }
+            me.values_backup.push(me.values_backup[0]);
+            me.values = Some(me.values_backup.clone());
+            me
+        }
+        /// Please hand the buffer back after each iteration. This greatly reduces allocations.
+        #[inline(always)]
+        pub fn give_buffer(&mut self, buf: PermutationIterBuffer<T>) {
+            debug_assert!(self.values.is_none());
+            self.values = Some(buf.buf)
+        }
+        /// Collects the permutations in `pairs` number of [`Vec`]s, with `returned[i]` containing
+        /// the i-th pair.
+        pub fn collect_by_index(mut self) -> Vec<Vec<(T, T)>> {
+            let mut vecs = Vec::with_capacity(self.pairs);
+            for _ in 0..self.pairs {
+                vecs.push(Vec::new());
+            }
+            while let Some(buf) = self.next() {
+                for (pos, pair) in buf.iter().enumerate() {
+                    vecs[pos].push(*pair)
+                }
+
+                self.give_buffer(buf);
+            }
+            vecs
+        }
+        /// Collects `LEN` pairs from this iterator in a [`Vec`].
+        ///
+        /// # Panics
+        ///
+        /// `LEN` must be the same length as `pairs` supplied to [`permutations_generic`].
+        pub fn collect_len<const LEN: usize>(mut self) -> Vec<[(T, T); LEN]> {
+            let mut vec = Vec::new();
+            while let Some(buf) = self.next() {
+                let array = <[(T, T); LEN]>::try_from(&*buf).expect(
+                    "tried to collect with set len, but permutations returned different len",
+                );
+                vec.push(array);
+                self.give_buffer(buf);
+            }
+            vec
+        }
+    }
+    impl<T: Copy + Debug> Iterator for PermutationIter<'_, T> {
+        type Item = PermutationIterBuffer<T>;
+        #[inline]
+        fn next(&mut self) -> Option<Self::Item> {
+            for (num, iter) in self.iters.iter_mut().enumerate().rev() {
+                *iter += 1;
+
+                if let Some(value) = self.s1.get(*iter) {
+                    // SAFETY: they are the same length, so getting from one guarantees we can get
+                    // the same index from the other one.
+                    let next = (*value, *unsafe { self.s2.get_unchecked(*iter) });
+
+                    let values = &mut self.values_backup;
+                    if let Some(v) = self.values.as_mut() {
+                        // SAFETY: The length of `self.values`, `self.values_backup`, and
+                        // `self.iters` are equal. Therefore, we can get the index of `self.values`
+                        // returned by the enumeration of `self.iters`
+                        *unsafe { v.get_unchecked_mut(num) } = next;
+                    }
+                    // SAFETY: See above.
+                    *unsafe { values.get_unchecked_mut(num) } = next;
+                    if num + 1 == self.pairs {
+                        let values = match self.values.take() {
+                            Some(x) => x,
+                            None => self.values_backup.clone(),
+                        };
+                        return Some(PermutationIterBuffer { buf: values });
+                    } else {
+                        // optimization - if items left is less than what is required to fill the "tower"
+                        // of succeeding indices, we return
+                        if self.s1.len() - *iter <= self.pairs - 1 - num {
+                            continue;
+                        }
+
+                        // Not pushing unsafe/inline as hard here, as this isn't as hot of a path.
+
+                        #[allow(clippy::needless_range_loop)] // clarity
+                        for i in num + 1..self.pairs {
+                            // start is 1+ the previous?
+                            let new = self.iters[i - 1] + usize::from(i + 1 < self.pairs);
+                            self.iters[i] = new;
+                            // fix values for lower iterators than the top one
+                            if i + 1 < self.pairs {
+                                if new >= self.s1.len() {
+                                    continue;
+                                }
+                                values[i] = (self.s1[new], self.s2[new]);
+                                if let Some(v) = self.values.as_mut() {
+                                    v[i] = (self.s1[new], self.s2[new]);
+                                }
+                            }
+                        }
+                        return self.next();
+                    }
+                }
+            }
+            None
+        }
+    }
+    /// The returned iterator is a bit funky.
+    /// It returns a buffer, which at all costs should be reused.
+    /// This could either be done using a while loop
+    /// (e.g. `while let Some(buf) = iter.next() { iter.give_buffer(buf) }`)
+    /// or any of the [built-in methods](PermutationIter).
+    /// If you know the length at compile time, use [`PermutationIter::collect_len`].
+    pub fn permutations_generic<'a, T: Copy + Debug>(
+        s1: &'a [T],
+        s2: &'a [T],
+        pairs: usize,
+    ) -> PermutationIter<'a, T> {
+        PermutationIter::new(s1, s2, pairs)
+    }
+    /// Lower-bound estimate (up to `pairs > 20`), within 100x (which is quite good for factorials).
+    ///
+    /// The original equation is `elements!/(pairs! (elements - pairs)!)`
+    #[inline]
+    pub fn estimate_permutation_count(elements: usize, pairs: usize) -> f64 {
+        let e = elements as f64;
+        let p = pairs as f64;
+        e.powf(p) / (p.powf(p - 0.8))
+    }
+    /// An exact count of permutations.
+    /// Returns [`None`] if the arithmetic can't fit.
+    #[inline]
+    pub fn permutation_count(elements: usize, pairs: usize) -> Option<usize> {
+        fn factorial(num: u128) -> Option<u128> {
+            match num {
+                0 | 1 => Some(1),
+                _ => factorial(num - 1)?.checked_mul(num),
+            }
+        }
+
+        Some(
+            (factorial(elements as _)?
+                / (factorial(pairs as _)?.checked_mul(factorial((elements - pairs) as _)?))?)
+                as usize,
+        )
+    }
+
+    /// Unique permutations of two elements - an iterator of all the pairs of associated values in the slices.
+    ///
+    /// This function will behave unexpectedly if `s1` and `s2` have different lengths.
+    ///
+    /// Returns an iterator which yields `O(n²)` items.
+    // `TODO`: Make these return indices.
+    pub fn permutations<'a, T: Copy>(
+        s1: &'a [T],
+        s2: &'a [T],
+    ) -> impl Iterator<Item = ((T, T), (T, T))> + 'a {
+        s1.iter()
+            .zip(s2.iter())
+            .enumerate()
+            .flat_map(|(pos, (t11, t21))| {
+                // +1 because we don't want our selves.
+                let left = &s1[pos + 1..];
+                let left_other = &s2[pos + 1..];
+                left.iter()
+                    .zip(left_other.iter())
+                    .map(|(t12, t22)| ((*t11, *t21), (*t12, *t22)))
+            })
+    }
+
+    /// Linear estimation using the Theil-Sen estimatior. This is robust against outliers.
+    /// `O(n²)`
+    pub struct LinearTheilSen;
+    impl LinearEstimator for LinearTheilSen {
+        #[inline]
+        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+            slow_linear(predictors, outcomes)
+        }
+    }
+    impl LinearEstimator<f32> for LinearTheilSen {
+        #[inline]
+        fn model_linear(&self, predictors: &[f32], outcomes: &[f32]) -> LinearCoefficients<f32> {
+            slow_linear_generic(predictors, outcomes)
+        }
+    }
+    #[cfg(feature = "arbitrary-precision")]
+    impl LinearEstimator<arbitrary_linear_algebra::FloatWrapper> for LinearTheilSen {
+        #[inline]
+        fn model_linear(
+            &self,
+            predictors: &[arbitrary_linear_algebra::FloatWrapper],
+            outcomes: &[arbitrary_linear_algebra::FloatWrapper],
+        ) -> LinearCoefficients<arbitrary_linear_algebra::FloatWrapper> {
+            slow_linear_generic(predictors, outcomes)
+        }
+    }
+    /// Polynomial estimation using the Theil-Sen estimatior. Very slow and should probably not be
+    /// used.
+    /// `O(n^degree)`
+    pub struct PolynomialTheilSen;
+    impl PolynomialEstimator for PolynomialTheilSen {
+        #[inline]
+        fn model_polynomial(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+        ) -> PolynomialCoefficients {
+            slow_polynomial(predictors, outcomes, degree)
+        }
+    }
+
+    /// Naive Theil-Sen implementation, which checks each line.
+    ///
+    /// Time & space: O(n²)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors.len() != outcomes.len()`.
+    pub fn slow_linear(predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+        assert_eq!(predictors.len(), outcomes.len());
+        // I've isolated the `Vec`s into blocks so we only have one at a time.
+        // This reduces memory usage.
+        #[cfg(feature = "parallel")]
+        let median_slope = parallel_median_slope(predictors, outcomes);
+        #[cfg(not(feature = "parallel"))]
+        let median_slope = {
+            let slopes = permutations(predictors, outcomes).map(|((x1, y1), (x2, y2))| {
+                // Δy/Δx
+                (y1 - y2) / (x1 - x2)
+            });
+            let mut slopes: Vec<_> = slopes.map(F64OrdHash).collect();
+
+            percentile::median(&mut slopes).resolve()
+        };
+
+        //// Old intersect code. Gets the median of all x components, and the median of all y
+        //// components. We then use that as a point to extrapolate the intersection.
+        //
+        // let predictor_median = {
+        // let mut predictors = predictors.to_vec();
+        // let predictors = F64OrdHash::from_mut_f64_slice(&mut predictors);
+        // percentile::median(predictors).resolve()
+        // };
+        // let outcome_median = {
+        // let mut outcomes = outcomes.to_vec();
+        // let outcomes = F64OrdHash::from_mut_f64_slice(&mut outcomes);
+        // percentile::median(outcomes).resolve()
+        // };
+        // y=slope * x + intersect
+        // y - slope * x = intersect
+        // let intersect = outcome_median - median_slope * predictor_median;
+
+        // New intersect. This works by getting the median point by it's y value. Then, we
+        // extrapolate from that.
+        //
+        // This produces much better results, but isn't what's commonly used.
+        //
+        // See https://stats.stackexchange.com/a/96166
+        // for reference.
+        let median = median_point(predictors, outcomes);
+        let intersect = median.1 - median.0 * median_slope;
+
+        LinearCoefficients {
+            k: median_slope,
+            m: intersect,
+        }
+    }
+
+    /// [`slow_linear`] of any [`Scalar`], computed in that type.
+    ///
+    /// Pairs of points with the same predictor are skipped, as their slope isn't defined.
+    ///
+    /// Time & space: O(n²)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors.len() != outcomes.len()` or if all the predictors are equal.
+    pub fn slow_linear_generic<F: Scalar>(
+        predictors: &[F],
+        outcomes: &[F],
+    ) -> LinearCoefficients<F> {
+        assert_eq!(
+            predictors.len(),
+            outcomes.len(),
+            "predictors and outcomes must have the same number of items"
+        );
+        fn median<T: Scalar>(values: &mut [T]) -> T {
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let middle = values.len() / 2;
+            if values.len() % 2 == 0 {
+                let two = T::one() + T::one();
+                (values[middle - 1].clone() + values[middle].clone()) / two
+            } else {
+                values[middle].clone()
+            }
+        }
+        let mut slopes = Vec::new();
+        for (i, (x1, y1)) in predictors.iter().zip(outcomes).enumerate() {
+            for (x2, y2) in predictors[i + 1..].iter().zip(&outcomes[i + 1..]) {
+                if x1 != x2 {
+                    slopes.push((y1.clone() - y2.clone()) / (x1.clone() - x2.clone()));
+                }
+            }
+        }
+        assert!(!slopes.is_empty(), "the predictors must not all be equal");
+        let k = median(&mut slopes);
+
+        // the median point by the outcome, like in `median_point`
+        let mut points: Vec<(F, F)> = predictors
+            .iter()
+            .cloned()
+            .zip(outcomes.iter().cloned())
+            .collect();
+        points.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        let middle = points.len() / 2;
+        let (x, y) = if points.len() % 2 == 0 {
+            let two = F::one() + F::one();
+            (
+                (points[middle - 1].0.clone() + points[middle].0.clone()) / two.clone(),
+                (points[middle - 1].1.clone() + points[middle].1.clone()) / two,
+            )
+        } else {
+            points[middle].clone()
+        };
+        let m = y - x * k.clone();
+        LinearCoefficients { k, m }
+    }
+
+    /// The point with the median outcome (the mean of the two middle points if the count is even).
+    fn median_point(predictors: &[f64], outcomes: &[f64]) -> (f64, f64) {
+        let mut values: Vec<_> = predictors.iter().zip(outcomes.iter()).collect();
+        match percentile::percentile_default_pivot_by(
+            &mut values,
+            crate::Fraction::HALF,
+            &mut |a, b| F64OrdHash::f64_cmp(*a.1, *b.1),
+        ) {
+            percentile::MeanValue::Single(v) => (*v.0, *v.1),
+            percentile::MeanValue::Mean(v1, v2) => ((v1.0 + v2.0) / 2.0, (v1.1 + v2.1) / 2.0),
+        }
+    }
+    /// Approximate Theil-Sen, using the median slope of `samples` random pairs of points instead
+    /// of all `O(n²)`. The intercept is calculated like in [`slow_linear`].
+    ///
+    /// The median of the sampled slopes has a standard error of about `1.25 σ / √samples`, where
+    /// `σ` is the spread of the slopes, so ~10 000 samples usually suffice regardless of the count
+    /// of points. Use a seeded `rng` for reproducible results.
+    ///
+    /// Time: O(n + samples), space: O(n + samples)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors.len() != outcomes.len()`, there are less than 2 points, or
+    /// `samples` is 0.
+    #[cfg(feature = "rand")]
+    pub fn sampled_linear(
+        predictors: &[f64],
+        outcomes: &[f64],
+        samples: usize,
+        rng: &mut impl rand::Rng,
+    ) -> LinearCoefficients {
+        assert_eq!(predictors.len(), outcomes.len());
+        let len = predictors.len();
+        assert!(len >= 2, "at least 2 points are required");
+        assert!(samples > 0, "at least 1 sample is required");
+        let mut slopes: Vec<_> = (0..samples)
+            .map(|_| {
+                let i = rng.random_range(0..len);
+                // a different point than `i`
+                let j = (i + rng.random_range(1..len)) % len;
+                let (x1, y1) = (predictors[i], outcomes[i]);
+                let (x2, y2) = (predictors[j], outcomes[j]);
+                F64OrdHash((y1 - y2) / (x1 - x2))
+            })
+            .collect();
+        let median_slope = percentile::median(&mut slopes).resolve();
+
+        let median = median_point(predictors, outcomes);
+        LinearCoefficients {
+            k: median_slope,
+            m: median.1 - median.0 * median_slope,
+        }
+    }
+
+    /// The median of the slopes of all [`permutations`], computed on several threads.
+    ///
+    /// Point `i` is paired with the later points, so the rows `i` and `n - 1 - i` are given to
+    /// the same thread, which evens out the work.
+    #[cfg(feature = "parallel")]
+    fn parallel_median_slope(predictors: &[f64], outcomes: &[f64]) -> f64 {
+        let len = predictors.len();
+        let rows: Vec<usize> = (0..(len + 1) / 2).collect();
+        // each pair of rows is `len` slopes
+        let min_chunk = (1 << 16) / len.max(1);
+        let chunks = crate::parallel::map_reduce_min_chunk(
+            &rows,
+            min_chunk,
+            |rows| {
+                let mut slopes = Vec::with_capacity(rows.len() * len);
+                for row in rows {
+                    let mut add_row = |i: usize| {
+                        let (x1, y1) = (predictors[i], outcomes[i]);
+                        slopes.extend(
+                            predictors[i + 1..]
+                                .iter()
+                                .zip(&outcomes[i + 1..])
+                                // Δy/Δx
+                                .map(|(x2, y2)| (y1 - y2) / (x1 - x2)),
+                        );
+                    };
+                    add_row(*row);
+                    if len - 1 - row != *row {
+                        add_row(len - 1 - row);
+                    }
+                }
+                vec![slopes]
+            },
+            |mut a, b| {
+                a.extend(b);
+                a
+            },
+        );
+        crate::parallel::median_of_chunks(&chunks)
+    }
+
+    /// [Siegel's repeated medians](https://en.wikipedia.org/wiki/Repeated_median_regression)
+    /// estimator. This is robust against up to 50% outliers, compared to ~27% for
+    /// [`LinearTheilSen`].
+    /// `O(n²)`
+    pub struct LinearSiegel;
+    impl LinearEstimator for LinearSiegel {
+        #[inline]
+        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+            siegel(predictors, outcomes)
+        }
+    }
+
+    /// Linear estimation using [`sampled_linear`]. `O(n + samples)`
+    ///
+    /// Uses the random number generator of the thread, or a generator seeded with
+    /// [`Self::seed`] for the same result every time.
+    #[cfg(feature = "rand")]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SampledTheilSen {
+        /// The count of random pairs of points.
+        pub samples: usize,
+        /// The seed of the random number generator.
+        pub seed: Option<u64>,
+    }
+    #[cfg(feature = "rand")]
+    impl Default for SampledTheilSen {
+        /// 10 000 samples, not seeded.
+        fn default() -> Self {
+            Self {
+                samples: 10_000,
+                seed: None,
+            }
+        }
+    }
+    #[cfg(feature = "rand")]
+    impl LinearEstimator for SampledTheilSen {
+        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+            match self.seed {
+                Some(seed) => {
+                    use rand::SeedableRng;
+                    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+                    sampled_linear(predictors, outcomes, self.samples, &mut rng)
+                }
+                None => sampled_linear(predictors, outcomes, self.samples, &mut rand::rng()),
+            }
+        }
+    }
+
+    /// Repeated-median regression: for each point, the median slope of the lines through it and
+    /// the other points. The slope is the median of those, and the intercept the median of
+    /// `y - slope * x`.
+    ///
+    /// Lines between points with the same predictor are ignored.
+    ///
+    /// Time & space: O(n²)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors.len() != outcomes.len()`.
+    pub fn siegel(predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+        assert_eq!(predictors.len(), outcomes.len());
+        let len = predictors.len();
+        let mut point_slopes: Vec<Vec<F64OrdHash>> = vec![Vec::with_capacity(len); len];
+        let indices: Vec<usize> = (0..len).collect();
+        // pairs of indices, to know which points each slope belongs to
+        for ((i, _), (j, _)) in permutations(&indices, &indices) {
+            let (x1, y1) = (predictors[i], outcomes[i]);
+            let (x2, y2) = (predictors[j], outcomes[j]);
+            if x1 == x2 {
+                continue;
+            }
+            let slope = (y1 - y2) / (x1 - x2);
+            point_slopes[i].push(F64OrdHash(slope));
+            point_slopes[j].push(F64OrdHash(slope));
+        }
+        let mut medians: Vec<F64OrdHash> = point_slopes
+            .into_iter()
+            .filter(|slopes| !slopes.is_empty())
+            .map(|mut slopes| F64OrdHash(percentile::median(&mut slopes).resolve()))
+            .collect();
+        if medians.is_empty() {
+            return LinearCoefficients {
+                k: f64::NAN,
+                m: f64::NAN,
+            };
+        }
+        let slope = percentile::median(&mut medians).resolve();
+
+        let mut intercepts: Vec<F64OrdHash> = predictors
+            .iter()
+            .zip(outcomes)
+            .map(|(x, y)| F64OrdHash(y - slope * x))
+            .collect();
+        let intercept = percentile::median(&mut intercepts).resolve();
+        LinearCoefficients {
+            k: slope,
+            m: intercept,
+        }
+    }
+
+    /// Naive Theil-Sen implementation, which checks each polynomial.
+    ///
+    /// Time & space: O(n^m) where m is `degree + 1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `predictors.len() != outcomes.len()`.
+    pub fn slow_polynomial(
+        predictors: &[f64],
+        outcomes: &[f64],
+        degree: usize,
+    ) -> PolynomialCoefficients {
+        assert_eq!(predictors.len(), outcomes.len());
+
+        // if degree == 0, get median.
+        if degree == 0 {
+            let mut outcomes = outcomes.to_vec();
+            let constant = crate::percentile::percentile_default_pivot_by(
+                &mut outcomes,
+                crate::Fraction::HALF,
+                &mut |a, b| crate::F64OrdHash::f64_cmp(*a, *b),
+            )
+            .resolve();
+            return PolynomialCoefficients {
+                coefficients: vec![constant],
+            };
+        }
+
+        // init
+        let mut iter = permutations_generic(predictors, outcomes, degree + 1);
+        let mut coefficients = Vec::with_capacity(degree + 1);
+        let permutations_count = permutation_count(predictors.len(), degree + 1)
+            .unwrap_or_else(|| estimate_permutation_count(predictors.len(), degree + 1) as usize);
+        for _ in 0..degree + 1 {
+            // now that's a lot of allocations.
+            coefficients.push(Vec::with_capacity(permutations_count))
+        }
+
+        // Hard-code some of these to increase performance. Else, we'd have to do linear algebra to
+        // get the equation of a straight line from two points.
+        match degree {
+            0 => unreachable!("we handled this above"),
+            1 => {
+                while let Some(buf) = iter.next() {
+                    debug_assert_eq!(buf.len(), 2);
+
+                    // SAFETY: I know the buf is going to be exactly 2 in length, as I wrote the
+                    // code.
+                    let p1 = unsafe { buf.get_unchecked(0) };
+                    let x1 = p1.0;
+                    let y1 = p1.1;
+                    let p2 = unsafe { buf.get_unchecked(1) };
+                    let x2 = p2.0;
+                    let y2 = p2.1;
+
+                    let slope = (y1 - y2) / (x1 - x2);
+                    // y=slope * x + intersect
+                    // intersect = y - slope * x
+                    // we could've chosen p2, it doesn't matter.
+                    let intersect = y1 - x1 * slope;
+
+                    // SAFETY: we pushed these vecs to `coefficients` above.
+                    unsafe {
+                        coefficients.get_unchecked_mut(1).push(slope);
+                        coefficients.get_unchecked_mut(0).push(intersect);
+                    }
+
+                    iter.give_buffer(buf);
+                }
+            }
+            // 10x performance increase with this hand-crafted technique.
+            2 => {
+                while let Some(buf) = iter.next() {
+                    debug_assert_eq!(buf.len(), 3);
+
+                    // SAFETY: I know the buf is going to be exactly 2 in length, as I wrote the
+                    // code.
+                    let p1 = unsafe { buf.get_unchecked(0) };
+                    let x1 = p1.0;
+                    let y1 = p1.1;
+                    let p2 = unsafe { buf.get_unchecked(1) };
+                    let x2 = p2.0;
+                    let y2 = p2.1;
+                    let p3 = unsafe { buf.get_unchecked(2) };
+                    let x3 = p3.0;
+                    let y3 = p3.1;
+
+                    // Derived from the systems of equation this makes.
+                    // See https://math.stackexchange.com/a/680695
+
+                    let a = (x1 * (y3 - y2) + x2 * (y1 - y3) + x3 * (y2 - y1))
+                        / ((x1 - x2) * (x1 - x3) * (x2 - x3));
+                    let b = (y2 - y1) / (x2 - x1) - a * (x1 + x2);
+                    let c = y1 - a * x1 * x1 - b * x1;
+
+                    // SAFETY: we pushed these vecs to `coefficients` above.
+                    unsafe {
+                        coefficients.get_unchecked_mut(2).push(a);
+                        coefficients.get_unchecked_mut(1).push(b);
+                        coefficients.get_unchecked_mut(0).push(c);
+                    }
+
+                    iter.give_buffer(buf);
+                }
+            }
+            #[cfg(not(feature = "ols"))]
+            _ => {
+                panic!("unsupported degree for polynomial Theil-Sen. Supports 1,2 without the OLS cargo feature.");
+            }
+            #[cfg(feature = "ols")]
+            _ => {
+                while let Some(buf) = iter.next() {
+                    #[inline(always)]
+                    fn tuple_first(t: &(f64, f64)) -> f64 {
+                        t.0
+                    }
+                    #[inline(always)]
+                    fn tuple_second(t: &(f64, f64)) -> f64 {
+                        t.1
+                    }
+
+                    debug_assert_eq!(buf.len(), degree + 1);
+
+                    let predictors = buf.iter().map(tuple_first);
+                    let outcomes = buf.iter().map(tuple_second);
+
+                    let polynomial = ols::polynomial(predictors, outcomes, degree + 1, degree);
+                    for (pos, coefficient) in polynomial.iter().enumerate() {
+                        // SAFETY: we pushed these vecs to `coefficients` above.
+                        // pos is less than the size of `coefficients`.
+                        unsafe { coefficients.get_unchecked_mut(pos).push(*coefficient) };
+                    }
+
+                    iter.give_buffer(buf);
+                }
+            }
+        }
+
+        #[inline(always)]
+        fn f64_cmp(a: &f64, b: &f64) -> std::cmp::Ordering {
+            crate::F64OrdHash::f64_cmp(*a, *b)
+        }
+
+        let mut result = Vec::with_capacity(degree + 1);
+        for mut coefficients in coefficients {
+            // `TODO`: Choose coefficients for a single point (the median of the coefficient with the
+            // highest exponential) instead of then median of the single values.
+
+            // 5x boost in performance here when using `O(n)` median instead of sorting. (when
+            // using args `-t -d5` with a detaset of 40 values).
+            let median = crate::percentile::percentile_default_pivot_by(
+                &mut coefficients,
+                crate::Fraction::HALF,
+                &mut f64_cmp,
+            )
+            .resolve();
+            result.push(median);
+        }
+        PolynomialCoefficients {
+            coefficients: result,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn generic() {
+            let x: Vec<f64> = (0..31).map(|v| v as f64 / 3.).collect();
+            let y: Vec<f64> = x.iter().map(|x| 1.5 * x - 2. + (x * 5.).sin()).collect();
+            let expected = LinearTheilSen.model_linear(&x, &y);
+            let x32: Vec<f32> = x.iter().map(|x| *x as f32).collect();
+            let y32: Vec<f32> = y.iter().map(|y| *y as f32).collect();
+            let line: LinearCoefficients<f32> = LinearTheilSen.model_linear(&x32, &y32);
+            assert!((line.k as f64 - expected.k).abs() < 1e-5, "{line}");
+            assert!((line.m as f64 - expected.m).abs() < 1e-5, "{line}");
+        }
+        #[test]
+        #[cfg(feature = "parallel")]
+        fn parallel_slope() {
+            // ~2 million slopes, enough to be split between threads
+            let x: Vec<f64> = (0..2000).map(|v| ((v * 37) % 2003) as f64).collect();
+            let y: Vec<f64> = x.iter().map(|x| 0.3 * x + ((x * 13.) % 7.) - 3.).collect();
+            let mut slopes: Vec<_> = permutations(&x, &y)
+                .map(|((x1, y1), (x2, y2))| F64OrdHash((y1 - y2) / (x1 - x2)))
+                .collect();
+            let expected = percentile::median(&mut slopes).resolve();
+            assert_eq!(parallel_median_slope(&x, &y), expected);
+        }
+        #[test]
+        #[cfg(feature = "rand")]
+        fn sampled() {
+            let x: Vec<f64> = (0..1000).map(|v| v as f64 / 50.).collect();
+            let y: Vec<f64> = x
+                .iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    if i % 10 == 0 {
+                        -x * 20.
+                    } else {
+                        4. * x + 1. + (x * 3.).sin()
+                    }
+                })
+                .collect();
+            let estimator = SampledTheilSen {
+                samples: 5000,
+                seed: Some(1),
+            };
+            let fit = estimator.model_linear(&x, &y);
+            assert_eq!(fit, estimator.model_linear(&x, &y));
+            let exact = slow_linear(&x, &y);
+            assert!((fit.k - exact.k).abs() < 0.02, "{fit} {exact}");
+            assert!((fit.m - exact.m).abs() < 0.3, "{fit} {exact}");
+        }
+        #[test]
+        fn siegel_outliers() {
+            let x: Vec<f64> = (0..40).map(|v| v as f64).collect();
+            // 45% of the points are outliers, too many for Theil-Sen
+            let y: Vec<f64> = x
+                .iter()
+                .map(|x| {
+                    if (*x as usize) % 20 < 9 {
+                        100. - 5. * x
+                    } else {
+                        1.5 * x + 2.
+                    }
+                })
+                .collect();
+            let fit = siegel(&x, &y);
+            assert!((fit.k - 1.5).abs() < 1e-9, "{fit}");
+            assert!((fit.m - 2.).abs() < 1e-9, "{fit}");
+            let theil_sen = slow_linear(&x, &y);
+            assert!((theil_sen.k - 1.5).abs() > 0.1, "{theil_sen}");
+        }
+        #[test]
+        fn permutations_eq_1() {
+            let s1 = [1., 2., 3., 4., 5.];
+            let s2 = [2., 4., 6., 8., 10.];
+
+            let permutations1 = permutations(&s1, &s2)
+                .map(|(x, y)| [x, y])
+                .collect::<Vec<_>>();
+            let permutations2 = permutations_generic(&s1, &s2, 2).collect_len();
+
+            assert_eq!(permutations1, permutations2);
+        }
+        #[test]
+        #[cfg(feature = "rand")]
+        fn permutations_eq_2() {
+            use rand::Rng;
+
+            let mut s1 = [0.0; 20];
+            let mut s2 = [0.0; 20];
+
+            let mut rng = rand::rng();
+            rng.fill(&mut s1);
+            rng.fill(&mut s2);
+
+            let permutations1 = permutations(&s1, &s2)
+                .map(|(x, y)| [x, y])
+                .collect::<Vec<_>>();
+            let permutations2 = permutations_generic(&s1, &s2, 2).collect_len();
+
+            assert_eq!(permutations1, permutations2);
+        }
+        #[test]
+        fn permutations_len_3() {
+            let s1 = [1., 2., 3., 4., 5.];
+            let s2 = [2., 4., 6., 8., 10.];
+
+            let permutations = permutations_generic(&s1, &s2, 3).collect_len::<3>();
+
+            let expected: &[[(f64, f64); 3]] = &[
+                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0)],
+                [(1.0, 2.0), (2.0, 4.0), (4.0, 8.0)],
+                [(1.0, 2.0), (2.0, 4.0), (5.0, 10.0)],
+                [(1.0, 2.0), (3.0, 6.0), (4.0, 8.0)],
+                [(1.0, 2.0), (3.0, 6.0), (5.0, 10.0)],
+                [(1.0, 2.0), (4.0, 8.0), (5.0, 10.0)],
+                [(2.0, 4.0), (3.0, 6.0), (4.0, 8.0)],
+                [(2.0, 4.0), (3.0, 6.0), (5.0, 10.0)],
+                [(2.0, 4.0), (4.0, 8.0), (5.0, 10.0)],
+                [(3.0, 6.0), (4.0, 8.0), (5.0, 10.0)],
+            ];
+
+            assert_eq!(expected.len(), permutation_count(5, 3).unwrap());
+
+            assert_eq!(permutations, expected,);
+        }
+        #[test]
+        fn permutations_len_4_1() {
+            let s1 = [1., 2., 3., 4., 5.];
+            let s2 = [2., 4., 6., 8., 10.];
+
+            let permutations = permutations_generic(&s1, &s2, 4).collect_len();
+
+            let expected: &[[(f64, f64); 4]] = &[
+                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (4.0, 8.0)],
+                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (5.0, 10.0)],
+                [(1.0, 2.0), (2.0, 4.0), (4.0, 8.0), (5.0, 10.0)],
+                [(1.0, 2.0), (3.0, 6.0), (4.0, 8.0), (5.0, 10.0)],
+                [(2.0, 4.0), (3.0, 6.0), (4.0, 8.0), (5.0, 10.0)],
+            ];
+
+            assert_eq!(expected.len(), permutation_count(5, 4).unwrap());
+
+            assert_eq!(permutations, expected,);
+        }
+        #[test]
+        fn permutations_len_4_2() {
+            let s1 = [1., 2., 3., 4., 5., 6.];
+            let s2 = [2., 4., 6., 8., 10., 12.];
+
+            let permutations = permutations_generic(&s1, &s2, 4).collect_len();
+
+            let expected: &[[(f64, f64); 4]] = &[
+                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (4.0, 8.0)],
+                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (5.0, 10.0)],
+                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (6.0, 12.0)],
+                [(1.0, 2.0), (2.0, 4.0), (4.0, 8.0), (5.0, 10.0)],
+                [(1.0, 2.0), (2.0, 4.0), (4.0, 8.0), (6.0, 12.0)],
+                [(1.0, 2.0), (2.0, 4.0), (5.0, 10.0), (6.0, 12.0)],
+                [(1.0, 2.0), (3.0, 6.0), (4.0, 8.0), (5.0, 10.0)],
+                [(1.0, 2.0), (3.0, 6.0), (4.0, 8.0), (6.0, 12.0)],
+                [(1.0, 2.0), (3.0, 6.0), (5.0, 10.0), (6.0, 12.0)],
+                [(1.0, 2.0), (4.0, 8.0), (5.0, 10.0), (6.0, 12.0)],
+                [(2.0, 4.0), (3.0, 6.0), (4.0, 8.0), (5.0, 10.0)],
+                [(2.0, 4.0), (3.0, 6.0), (4.0, 8.0), (6.0, 12.0)],
+                [(2.0, 4.0), (3.0, 6.0), (5.0, 10.0), (6.0, 12.0)],
+                [(2.0, 4.0), (4.0, 8.0), (5.0, 10.0), (6.0, 12.0)],
+                [(3.0, 6.0), (4.0, 8.0), (5.0, 10.0), (6.0, 12.0)],
+            ];
+
+            assert_eq!(expected.len(), permutation_count(6, 4).unwrap());
+
+            assert_eq!(permutations, expected,);
+        }
+    }
+}
+
+/// Bayesian linear regression, using the conjugate
+/// [Normal-inverse-gamma](https://en.wikipedia.org/wiki/Normal-inverse-gamma_distribution) prior.
+///
+/// Instead of a single line, this gives a distribution over all lines given the data
+/// ([`LinearPosterior`](bayesian::LinearPosterior)), which lets us reason about the uncertainty in
+/// the slope, intercept, and predictions. With the
+/// [default prior](bayesian::BayesianLinear::default), the posterior mean is (almost)
+/// the same line as [`ols`] returns.
+///
+/// The model is `y = kx + m + ε` where `ε ~ N(0, σ²)`. The prior is
+/// `(m, k) | σ² ~ N(μ₀, σ²Λ₀⁻¹)` and `σ² ~ InvGamma(a₀, b₀)`, with `Λ₀` diagonal.
+/// Since the prior is conjugate, the posterior has the same form and is calculated in `O(n)`.
+/// See [Bayesian linear regression](https://en.wikipedia.org/wiki/Bayesian_linear_regression)
+/// for the derivation.
+pub mod bayesian {
+    use super::*;
+    use crate::distributions::StudentT;
+
+    /// The prior of a Bayesian linear regression. Use [`Self::posterior`] to update it with data.
+    ///
+    /// The precisions are relative to the noise variance `σ²`: a precision of `λ` means the
+    /// coefficient is believed to be within `μ ± σ/√λ`.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct BayesianLinear {
+        /// The prior mean of the coefficients.
+        pub prior_mean: LinearCoefficients,
+        /// The prior precision of the y intersect (`m`).
+        pub intercept_precision: f64,
+        /// The prior precision of the slope (`k`).
+        pub slope_precision: f64,
+        /// The shape (`a₀`) of the inverse gamma prior of the noise variance.
+        pub noise_shape: f64,
+        /// The scale (`b₀`) of the inverse gamma prior of the noise variance.
+        pub noise_scale: f64,
+    }
+    impl Default for BayesianLinear {
+        /// A vague prior, centered on `y = 0`, which lets the data dominate the posterior.
+        fn default() -> Self {
+            Self {
+                prior_mean: LinearCoefficients { k: 0., m: 0. },
+                intercept_precision: 1e-6,
+                slope_precision: 1e-6,
+                noise_shape: 1e-3,
+                noise_scale: 1e-3,
+            }
+        }
+    }
+    impl BayesianLinear {
+        /// Updates the prior with the data, returning the posterior.
+        ///
+        /// # Panics
+        ///
+        /// The two slices must have the same length.
+        pub fn posterior(&self, predictors: &[f64], outcomes: &[f64]) -> LinearPosterior {
+            assert_eq!(
+                predictors.len(),
+                outcomes.len(),
+                "predictors and outcomes must have the same number of items"
+            );
+            let n = predictors.len() as f64;
+            let (mut x_sum, mut x2_sum, mut y_sum, mut xy_sum) = (0., 0., 0., 0.);
+            for (x, y) in predictors.iter().copied().zip(outcomes.iter().copied()) {
+                x_sum += x;
+                x2_sum += x * x;
+                y_sum += y;
+                xy_sum += x * y;
+            }
+            // Λₙ = XᵀX + Λ₀. Index 0 is the intercept, 1 is the slope.
+            let precision = [
+                [n + self.intercept_precision, x_sum],
+                [x_sum, x2_sum + self.slope_precision],
+            ];
+            let det = precision[0][0] * precision[1][1] - precision[0][1] * precision[1][0];
+            let covariance = [
+                [precision[1][1] / det, -precision[0][1] / det],
+                [-precision[1][0] / det, precision[0][0] / det],
+            ];
+            // μₙ = Λₙ⁻¹(Λ₀μ₀ + Xᵀy)
+            let rhs = [
+                self.intercept_precision * self.prior_mean.m + y_sum,
+                self.slope_precision * self.prior_mean.k + xy_sum,
+            ];
+            let m = covariance[0][0] * rhs[0] + covariance[0][1] * rhs[1];
+            let k = covariance[1][0] * rhs[0] + covariance[1][1] * rhs[1];
+            let mean = LinearCoefficients { k, m };
+
+            // bₙ = b₀ + ½(yᵀy + μ₀ᵀΛ₀μ₀ - μₙᵀΛₙμₙ), which is rewritten to the sum of squared
+            // residuals plus the prior's penalty to avoid catastrophic cancellation.
+            let residuals: f64 = predictors
+                .iter()
+                .copied()
+                .zip(outcomes.iter().copied())
+                .map(|(x, y)| {
+                    let r = y - mean.predict_outcome(x);
+                    r * r
+                })
+                .sum();
+            let dm = m - self.prior_mean.m;
+            let dk = k - self.prior_mean.k;
+            let penalty = self.intercept_precision * dm * dm + self.slope_precision * dk * dk;
+
+            LinearPosterior {
+                mean,
+                covariance,
+                noise_shape: self.noise_shape + n / 2.,
+                noise_scale: self.noise_scale + (residuals + penalty) / 2.,
+            }
+        }
+    }
+    impl LinearEstimator for BayesianLinear {
+        /// Returns the [posterior mean](LinearPosterior::mean).
+        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+            self.posterior(predictors, outcomes).mean
+        }
+    }
+
+    /// The posterior distribution of a [`BayesianLinear`] regression.
+    ///
+    /// The intervals are equal-tailed: with a credibility of `0.95`, there's a 2.5% posterior
+    /// probability the value is below the interval, and 2.5% it's above.
+    ///
+    /// [`Predictive`] and [`Display`] use the posterior mean.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct LinearPosterior {
+        /// The posterior mean of the coefficients.
+        pub mean: LinearCoefficients,
+        /// `Λₙ⁻¹`, the covariance of `(m, k)` divided by `σ²`.
+        pub covariance: [[f64; 2]; 2],
+        /// The shape (`aₙ`) of the inverse gamma posterior of the noise variance.
+        pub noise_shape: f64,
+        /// The scale (`bₙ`) of the inverse gamma posterior of the noise variance.
+        pub noise_scale: f64,
+    }
+    impl LinearPosterior {
+        /// The degrees of freedom of the (Student's t) marginal posteriors.
+        pub fn degrees_of_freedom(&self) -> f64 {
+            2. * self.noise_shape
+        }
+        /// The posterior mean of the noise variance `σ²`.
+        /// Is infinite if there's too little data (`aₙ <= 1`).
+        pub fn noise_variance(&self) -> f64 {
+            if self.noise_shape <= 1. {
+                f64::INFINITY
+            } else {
+                self.noise_scale / (self.noise_shape - 1.)
+            }
+        }
+        fn interval(&self, center: f64, variance: f64, credibility: f64) -> (f64, f64) {
+            let t = StudentT::new(self.degrees_of_freedom()).two_sided_critical_value(credibility);
+            let half_width = t * (self.noise_scale / self.noise_shape * variance).sqrt();
+            (center - half_width, center + half_width)
+        }
+        /// The credible interval `(lower, upper)` of the slope (`k`).
+        ///
+        /// # Panics
+        ///
+        /// Panics if `credibility` isn't in the range `(0, 1)`.
+        pub fn slope_interval(&self, credibility: f64) -> (f64, f64) {
+            self.interval(self.mean.k, self.covariance[1][1], credibility)
+        }
+        /// The credible interval `(lower, upper)` of the y intersect (`m`).
+        ///
+        /// # Panics
+        ///
+        /// Panics if `credibility` isn't in the range `(0, 1)`.
+        pub fn intercept_interval(&self, credibility: f64) -> (f64, f64) {
+            self.interval(self.mean.m, self.covariance[0][0], credibility)
+        }
+        /// The posterior predictive interval `(lower, upper)` of a new observation at `predictor`.
+        /// This includes both the uncertainty of the line and the noise.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `credibility` isn't in the range `(0, 1)`.
+        pub fn predictive_interval(&self, predictor: f64, credibility: f64) -> (f64, f64) {
+            let c = &self.covariance;
+            let x = predictor;
+            let line_variance = c[0][0] + 2. * x * c[0][1] + x * x * c[1][1];
+            self.interval(
+                self.mean.predict_outcome(x),
+                1. + line_variance,
+                credibility,
+            )
+        }
+    }
+    impl Predictive for LinearPosterior {
+        fn predict_outcome(&self, predictor: f64) -> f64 {
+            self.mean.predict_outcome(predictor)
+        }
+    }
+    impl Display for LinearPosterior {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Display::fmt(&self.mean, f)
+        }
+    }
 
-            let expected: &[[(f64, f64); 4]] = &[
-                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (4.0, 8.0)],
-                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (5.0, 10.0)],
-                [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0), (6.0, 12.0)],
-                [(1.0, 2.0), (2.0, 4.0), (4.0, 8.0), (5.0, 10.0)],
-                [(1.0, 2.0), (2.0, 4.0), (4.0, 8.0), (6.0, 12.0)],
-                [(1.0, 2.0), (2.0, 4.0), (5.0, 10.0), (6.0, 12.0)],
-                [(1.0, 2.0), (3.0, 6.0), (4.0, 8.0), (5.0, 10.0)],
-                [(1.0, 2.0), (3.0, 6.0), (4.0, 8.0), (6.0, 12.0)],
-                [(1.0, 2.0), (3.0, 6.0), (5.0, 10.0), (6.0, 12.0)],
-                [(1.0, 2.0), (4.0, 8.0), (5.0, 10.0), (6.0, 12.0)],
-                [(2.0, 4.0), (3.0, 6.0), (4.0, 8.0), (5.0, 10.0)],
-                [(2.0, 4.0), (3.0, 6.0), (4.0, 8.0), (6.0, 12.0)],
-                [(2.0, 4.0), (3.0, 6.0), (5.0, 10.0), (6.0, 12.0)],
-                [(2.0, 4.0), (4.0, 8.0), (5.0, 10.0), (6.0, 12.0)],
-                [(3.0, 6.0), (4.0, 8.0), (5.0, 10.0), (6.0, 12.0)],
-            ];
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-            assert_eq!(expected.len(), permutation_count(6, 4).unwrap());
+        #[test]
+        fn matches_ols() {
+            let x = [1., 2., 3., 4., 5., 6.];
+            let y = [2.1, 3.9, 6.2, 7.8, 10.1, 12.];
+            let posterior = BayesianLinear::default().posterior(&x, &y);
+            // ordinary least squares: k = 1.99143, m = 0.04667
+            assert!((posterior.mean.k - 1.99143).abs() < 1e-4);
+            assert!((posterior.mean.m - 0.04667).abs() < 1e-3);
+            let (low, high) = posterior.slope_interval(0.95);
+            assert!(low < 1.99 && 1.99 < high && high - low < 0.5);
+            let (low, high) = posterior.predictive_interval(3.5, 0.95);
+            assert!(low < 7. && 7. < high);
+            // the noise makes new observations more uncertain than the line itself
+            let line = posterior.mean.predict_outcome(3.5);
+            assert!(high - line > 0.3);
+        }
+        #[test]
+        fn strong_prior() {
+            let prior = BayesianLinear {
+                prior_mean: LinearCoefficients { k: 1., m: 0. },
+                intercept_precision: 1e9,
+                slope_precision: 1e9,
+                ..Default::default()
+            };
+            let posterior = prior.posterior(&[1., 2., 3.], &[5., 7., 9.]);
+            assert!((posterior.mean.k - 1.).abs() < 1e-6);
+            assert!(posterior.mean.m.abs() < 1e-6);
+        }
+    }
+}
 
-            assert_eq!(permutations, expected,);
+/// Trimmed regression, which excludes the points furthest from the fit.
+///
+/// [`Trimmed`](trimmed::Trimmed) wraps any estimator. It fits all the points, then repeatedly refits using only
+/// the points with the smallest residuals. This approximates
+/// [least trimmed squares](https://en.wikipedia.org/wiki/Least_trimmed_squares) when wrapping
+/// [`OlsEstimator`].
+///
+/// It's more robust than the wrapped estimator, and the only parameter is the fraction of points
+/// you expect to be outliers. It can however be misled if the outliers pull the first fit
+/// far enough that good points get the largest residuals.
+pub mod trimmed {
+    use super::*;
+
+    /// Wraps an `estimator`, excluding the [`Self::trim`] fraction of points with the largest
+    /// residuals before the final fit.
+    ///
+    /// Implements [`LinearEstimator`] and [`PolynomialEstimator`] if the wrapped estimator does.
+    /// Use [`Self::fit_linear`] or [`Self::fit_polynomial`] to also get the excluded points.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Trimmed<E> {
+        /// The estimator used for each fit.
+        pub estimator: E,
+        /// The fraction of points to exclude, in `[0, 1)`.
+        pub trim: f64,
+        /// The maximum count of refits. Stops earlier if the excluded points don't change.
+        pub iterations: usize,
+    }
+    impl<E> Trimmed<E> {
+        /// Excludes `trim` of the points, with at most 10 refits.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `trim` isn't in `[0, 1)`.
+        pub fn new(estimator: E, trim: f64) -> Self {
+            assert!(
+                (0.0..1.0).contains(&trim),
+                "trim must be in the range [0, 1)"
+            );
+            Self {
+                estimator,
+                trim,
+                iterations: 10,
+            }
+        }
+        fn fit<C: Predictive>(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            mut model: impl FnMut(&[f64], &[f64]) -> C,
+        ) -> TrimmedFit<C> {
+            assert_eq!(
+                predictors.len(),
+                outcomes.len(),
+                "predictors and outcomes must have the same number of items"
+            );
+            let len = predictors.len();
+            let keep = len - (len as f64 * self.trim).floor() as usize;
+            let mut fit = model(predictors, outcomes);
+            let mut order: Vec<usize> = (0..len).collect();
+            let mut excluded: Vec<usize> = Vec::new();
+            let mut x = Vec::with_capacity(keep);
+            let mut y = Vec::with_capacity(keep);
+            for _ in 0..self.iterations {
+                let residuals: Vec<f64> = predictors
+                    .iter()
+                    .zip(outcomes)
+                    .map(|(x, y)| (y - fit.predict_outcome(*x)).abs())
+                    .collect();
+                order.sort_by(|a, b| crate::F64OrdHash::f64_cmp(residuals[*a], residuals[*b]));
+                let mut new_excluded = order[keep..].to_vec();
+                new_excluded.sort_unstable();
+                if new_excluded == excluded {
+                    break;
+                }
+                excluded = new_excluded;
+
+                x.clear();
+                y.clear();
+                for idx in &order[..keep] {
+                    x.push(predictors[*idx]);
+                    y.push(outcomes[*idx]);
+                }
+                fit = model(&x, &y);
+            }
+            TrimmedFit {
+                model: fit,
+                excluded,
+            }
+        }
+    }
+    impl<E: LinearEstimator> Trimmed<E> {
+        /// Fit a line, and return which points were excluded.
+        ///
+        /// # Panics
+        ///
+        /// The two slices must have the same length.
+        pub fn fit_linear(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+        ) -> TrimmedFit<LinearCoefficients> {
+            self.fit(predictors, outcomes, |x, y| {
+                self.estimator.model_linear(x, y)
+            })
+        }
+    }
+    impl<E: PolynomialEstimator> Trimmed<E> {
+        /// Fit a polynomial of `degree`, and return which points were excluded.
+        ///
+        /// # Panics
+        ///
+        /// The two slices must have the same length, and there must be more than `degree` points
+        /// left after trimming.
+        pub fn fit_polynomial(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+        ) -> TrimmedFit<PolynomialCoefficients> {
+            self.fit(predictors, outcomes, |x, y| {
+                self.estimator.model_polynomial(x, y, degree)
+            })
+        }
+    }
+    impl<E: LinearEstimator> LinearEstimator for Trimmed<E> {
+        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+            self.fit_linear(predictors, outcomes).model
+        }
+    }
+    impl<E: PolynomialEstimator> PolynomialEstimator for Trimmed<E> {
+        fn model_polynomial(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+        ) -> PolynomialCoefficients {
+            self.fit_polynomial(predictors, outcomes, degree).model
+        }
+    }
+
+    /// The result of a [`Trimmed`] fit.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct TrimmedFit<C> {
+        /// The model fitted to the points which weren't excluded.
+        pub model: C,
+        /// The indices of the excluded points, in ascending order.
+        pub excluded: Vec<usize>,
+    }
+
+    #[cfg(all(test, feature = "ols"))]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn excludes_outliers() {
+            let x: Vec<f64> = (0..20).map(|v| v as f64).collect();
+            let mut y: Vec<f64> = x.iter().map(|x| 3. * x + 1.).collect();
+            y[4] = 100.;
+            y[15] = -50.;
+            let trimmed = Trimmed::new(OlsEstimator, 0.1);
+            let fit = trimmed.fit_linear(&x, &y);
+            assert_eq!(fit.excluded, [4, 15]);
+            assert!((fit.model.k - 3.).abs() < 1e-9);
+            assert!((fit.model.m - 1.).abs() < 1e-9);
+            // without trimming, the outliers pull the line
+            let ols = OlsEstimator.model_linear(&x, &y);
+            assert!((ols.k - 3.).abs() > 0.5);
+        }
+    }
+}
+/// [RANSAC](https://en.wikipedia.org/wiki/Random_sample_consensus) (random sample consensus),
+/// which finds the model most points agree with.
+///
+/// [`Ransac`](ransac::Ransac) wraps any estimator. It repeatedly fits the minimal count of randomly chosen
+/// points (2 for a line), and counts the points within [the threshold](ransac::Ransac::threshold) of that fit
+/// (the inliers). The model is then refitted to the largest set of inliers found.
+///
+/// Unlike [`theil_sen`] (~27%) and [`trimmed`], this tolerates a majority of outliers, as long as
+/// the good points agree within the threshold. The result is random; use
+/// [`Ransac::fit_linear_with_rng`](ransac::Ransac::fit_linear_with_rng) with a seeded random number generator for reproducible fits.
+#[cfg(feature = "rand")]
+pub mod ransac {
+    use rand::Rng;
+
+    use super::*;
+
+    /// Wraps an `estimator`, fitting it to the largest consensus set of a random search.
+    ///
+    /// Implements [`LinearEstimator`] and [`PolynomialEstimator`] if the wrapped estimator does.
+    /// Use [`Self::fit_linear`] or [`Self::fit_polynomial`] to also get the inliers.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Ransac<E> {
+        /// The estimator used for each fit.
+        pub estimator: E,
+        /// The largest absolute residual of a point counted as an inlier.
+        pub threshold: f64,
+        /// The count of random subsets to fit.
+        pub iterations: usize,
+    }
+    impl<E> Ransac<E> {
+        /// Counts points within `threshold` of a fit as inliers, trying 100 random subsets.
+        ///
+        /// With 2 points per subset, 100 iterations find a subset of only inliers with a
+        /// probability of 99.8% when 25% of the points are inliers.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `threshold` is negative or `NaN`.
+        pub fn new(estimator: E, threshold: f64) -> Self {
+            assert!(threshold >= 0., "threshold must be non-negative");
+            Self {
+                estimator,
+                threshold,
+                iterations: 100,
+            }
+        }
+        fn fit<C: Predictive>(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            sample_size: usize,
+            rng: &mut impl Rng,
+            mut model: impl FnMut(&[f64], &[f64]) -> C,
+        ) -> RansacFit<C> {
+            assert_eq!(
+                predictors.len(),
+                outcomes.len(),
+                "predictors and outcomes must have the same number of items"
+            );
+            let len = predictors.len();
+            let inliers_of = |fit: &C| -> (Vec<usize>, f64) {
+                let mut error = 0.;
+                let inliers = (0..len)
+                    .filter(|i| {
+                        let residual = (outcomes[*i] - fit.predict_outcome(predictors[*i])).abs();
+                        let inlier = residual <= self.threshold;
+                        if inlier {
+                            error += residual * residual;
+                        }
+                        inlier
+                    })
+                    .collect();
+                (inliers, error)
+            };
+
+            let mut best: Option<(Vec<usize>, f64)> = None;
+            let mut x = Vec::with_capacity(sample_size);
+            let mut y = Vec::with_capacity(sample_size);
+            if len > sample_size {
+                for _ in 0..self.iterations {
+                    x.clear();
+                    y.clear();
+                    for i in rand::seq::index::sample(rng, len, sample_size) {
+                        x.push(predictors[i]);
+                        y.push(outcomes[i]);
+                    }
+                    let candidate = inliers_of(&model(&x, &y));
+                    let better = best.as_ref().map_or(true, |(inliers, error)| {
+                        candidate.0.len() > inliers.len()
+                            || (candidate.0.len() == inliers.len() && candidate.1 < *error)
+                    });
+                    if better {
+                        best = Some(candidate);
+                    }
+                }
+            }
+
+            match best {
+                Some((inliers, _)) if inliers.len() >= sample_size => {
+                    x.clear();
+                    y.clear();
+                    for i in &inliers {
+                        x.push(predictors[*i]);
+                        y.push(outcomes[*i]);
+                    }
+                    let fit = model(&x, &y);
+                    // the refit can move points across the threshold
+                    let (refit_inliers, _) = inliers_of(&fit);
+                    RansacFit {
+                        model: fit,
+                        inliers: refit_inliers,
+                    }
+                }
+                // no consensus (or too few points to choose from), use all points
+                _ => {
+                    let fit = model(predictors, outcomes);
+                    let (inliers, _) = inliers_of(&fit);
+                    RansacFit {
+                        model: fit,
+                        inliers,
+                    }
+                }
+            }
+        }
+    }
+    impl<E: LinearEstimator> Ransac<E> {
+        /// Fit a line, and return which points are inliers.
+        ///
+        /// # Panics
+        ///
+        /// The two slices must have the same length.
+        pub fn fit_linear(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+        ) -> RansacFit<LinearCoefficients> {
+            self.fit_linear_with_rng(predictors, outcomes, &mut rand::rng())
+        }
+        /// Same as [`Self::fit_linear`], but choosing the subsets with `rng`.
+        ///
+        /// # Panics
+        ///
+        /// The two slices must have the same length.
+        pub fn fit_linear_with_rng(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            rng: &mut impl Rng,
+        ) -> RansacFit<LinearCoefficients> {
+            self.fit(predictors, outcomes, 2, rng, |x, y| {
+                self.estimator.model_linear(x, y)
+            })
+        }
+    }
+    impl<E: PolynomialEstimator> Ransac<E> {
+        /// Fit a polynomial of `degree`, using subsets of `degree + 1` points, and return which
+        /// points are inliers.
+        ///
+        /// # Panics
+        ///
+        /// The two slices must have the same length, and there must be more than `degree`
+        /// points.
+        pub fn fit_polynomial(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+        ) -> RansacFit<PolynomialCoefficients> {
+            self.fit_polynomial_with_rng(predictors, outcomes, degree, &mut rand::rng())
+        }
+        /// Same as [`Self::fit_polynomial`], but choosing the subsets with `rng`.
+        ///
+        /// # Panics
+        ///
+        /// See [`Self::fit_polynomial`].
+        pub fn fit_polynomial_with_rng(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+            rng: &mut impl Rng,
+        ) -> RansacFit<PolynomialCoefficients> {
+            self.fit(predictors, outcomes, degree + 1, rng, |x, y| {
+                self.estimator.model_polynomial(x, y, degree)
+            })
+        }
+    }
+    impl<E: LinearEstimator> LinearEstimator for Ransac<E> {
+        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+            self.fit_linear(predictors, outcomes).model
+        }
+    }
+    impl<E: PolynomialEstimator> PolynomialEstimator for Ransac<E> {
+        fn model_polynomial(
+            &self,
+            predictors: &[f64],
+            outcomes: &[f64],
+            degree: usize,
+        ) -> PolynomialCoefficients {
+            self.fit_polynomial(predictors, outcomes, degree).model
+        }
+    }
+
+    /// The result of a [`Ransac`] fit.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RansacFit<C> {
+        /// The model fitted to the consensus set.
+        pub model: C,
+        /// The indices of the points within the threshold of [`Self::model`], in ascending
+        /// order.
+        pub inliers: Vec<usize>,
+    }
+
+    #[cfg(all(test, feature = "ols"))]
+    mod tests {
+        use super::*;
+        use rand::SeedableRng;
+
+        #[test]
+        fn majority_outliers() {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+            let x: Vec<f64> = (0..100).map(|v| v as f64).collect();
+            // 60% of the points are far from the line
+            let y: Vec<f64> = x
+                .iter()
+                .map(|x| {
+                    if *x as usize % 5 < 3 {
+                        rng.random_range(-500.0..500.0)
+                    } else {
+                        2. * x - 3. + (x * 1.7).sin() * 0.1
+                    }
+                })
+                .collect();
+            let ransac = Ransac::new(OlsEstimator, 0.5);
+            let fit = ransac.fit_linear_with_rng(&x, &y, &mut rng);
+            assert!((fit.model.k - 2.).abs() < 0.01, "{}", fit.model);
+            assert!((fit.model.m + 3.).abs() < 0.1, "{}", fit.model);
+            assert!(fit.inliers.len() >= 40 && fit.inliers.len() < 45);
+            assert!(fit.inliers.iter().all(|i| i % 5 >= 3));
+        }
+    }
+}
+/// [Huber](https://en.wikipedia.org/wiki/Huber_loss) regression, an
+/// [M-estimator](https://en.wikipedia.org/wiki/M-estimator) of a line.
+///
+/// The loss is quadratic (like OLS) for residuals within [`HuberEstimator::delta`] robust
+/// standard deviations, and linear beyond that, so outliers have a bounded influence on the fit.
+/// It's solved with [iteratively reweighted least squares](https://en.wikipedia.org/wiki/Iteratively_reweighted_least_squares):
+/// each iteration is a weighted least-squares fit, where points with large residuals get the
+/// weight `delta / |residual|`.
+///
+/// This lies between [OLS](ols) (efficient, but fragile) and [Theil-Sen](theil_sen) (robust, but
+/// `O(n²)`): each iteration is `O(n)`, and it handles outliers in the outcomes well. Like OLS, it's
+/// however sensitive to outliers in the predictors (high-leverage points).
+pub mod huber {
+    use super::*;
+
+    /// Huber regression of lines. See the [module-level documentation](self).
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct HuberEstimator {
+        /// The residual (in robust standard deviations, estimated with the
+        /// [MAD](crate::robust::mad) of the residuals) where the loss becomes linear. Smaller
+        /// values are more robust, and larger closer to OLS.
+        pub delta: f64,
+        /// Stop when neither coefficient changes more than this (relative to its magnitude, when
+        /// larger than 1) in an iteration.
+        pub tolerance: f64,
+        /// The maximum count of reweighted fits.
+        pub max_iterations: usize,
+    }
+    impl HuberEstimator {
+        /// Uses the given `delta`, with a tolerance of `1e-10` and at most 100 iterations.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `delta` isn't positive.
+        pub fn new(delta: f64) -> Self {
+            assert!(delta > 0., "delta must be positive");
+            Self {
+                delta,
+                tolerance: 1e-10,
+                max_iterations: 100,
+            }
+        }
+    }
+    impl Default for HuberEstimator {
+        /// `delta = 1.345`, which is 95% as efficient as OLS for normally distributed errors.
+        fn default() -> Self {
+            Self::new(1.345)
+        }
+    }
+
+    /// Weighted least squares of a line.
+    fn weighted_linear(
+        predictors: &[f64],
+        outcomes: &[f64],
+        weights: &[f64],
+    ) -> LinearCoefficients {
+        let weight_sum: f64 = weights.iter().sum();
+        let mut x_mean = 0.;
+        let mut y_mean = 0.;
+        for ((x, y), w) in predictors.iter().zip(outcomes).zip(weights) {
+            x_mean += w * x;
+            y_mean += w * y;
+        }
+        x_mean /= weight_sum;
+        y_mean /= weight_sum;
+        let mut sxx = 0.;
+        let mut sxy = 0.;
+        for ((x, y), w) in predictors.iter().zip(outcomes).zip(weights) {
+            sxx += w * (x - x_mean) * (x - x_mean);
+            sxy += w * (x - x_mean) * (y - y_mean);
+        }
+        let k = sxy / sxx;
+        LinearCoefficients {
+            k,
+            m: y_mean - k * x_mean,
+        }
+    }
+
+    impl LinearEstimator for HuberEstimator {
+        fn model_linear(&self, predictors: &[f64], outcomes: &[f64]) -> LinearCoefficients {
+            assert_eq!(
+                predictors.len(),
+                outcomes.len(),
+                "predictors and outcomes must have the same number of items"
+            );
+            let mut weights = vec![1.; predictors.len()];
+            let mut fit = weighted_linear(predictors, outcomes, &weights);
+            let mut residuals = Vec::with_capacity(predictors.len());
+            for _ in 0..self.max_iterations {
+                residuals.clear();
+                residuals.extend(
+                    predictors
+                        .iter()
+                        .zip(outcomes)
+                        .map(|(x, y)| y - fit.predict_outcome(*x)),
+                );
+                let scale = crate::robust::mad(&residuals);
+                // more than half of the points are on the line
+                if scale.is_nan() || scale <= 0. {
+                    break;
+                }
+                let threshold = self.delta * scale;
+                for (weight, residual) in weights.iter_mut().zip(&residuals) {
+                    let residual = residual.abs();
+                    *weight = if residual <= threshold {
+                        1.
+                    } else {
+                        threshold / residual
+                    };
+                }
+                let new = weighted_linear(predictors, outcomes, &weights);
+                let converged =
+                    |old: f64, new: f64| (new - old).abs() <= self.tolerance * old.abs().max(1.);
+                let done = converged(fit.k, new.k) && converged(fit.m, new.m);
+                fit = new;
+                if done {
+                    break;
+                }
+            }
+            fit
+        }
+    }
+
+    #[cfg(all(test, feature = "ols"))]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn contaminated() {
+            let x: Vec<f64> = (0..50).map(|v| v as f64).collect();
+            let mut y: Vec<f64> = x
+                .iter()
+                .map(|x| 0.5 * x + 10. + (x * 2.3).sin() * 0.3)
+                .collect();
+            // 10% of the outcomes are gross errors
+            for i in [3, 11, 22, 30, 47] {
+                y[i] += 80.;
+            }
+            let ols = OlsEstimator.model_linear(&x, &y);
+            let huber = HuberEstimator::default().model_linear(&x, &y);
+            assert!((ols.m - 10.).abs() > 3., "{ols}");
+            assert!((huber.k - 0.5).abs() < 0.01, "{huber}");
+            assert!((huber.m - 10.).abs() < 0.3, "{huber}");
+
+            // without outliers, it's close to OLS
+            let clean: Vec<f64> = x.iter().map(|x| 0.5 * x + 10. + (x * 2.3).sin()).collect();
+            let ols = OlsEstimator.model_linear(&x, &clean);
+            let huber = HuberEstimator::default().model_linear(&x, &clean);
+            assert!((ols.k - huber.k).abs() < 0.01 && (ols.m - huber.m).abs() < 0.2);
         }
     }
 }
@@ -0,0 +1,200 @@
+//! Robust estimators of scale and correlation, which aren't affected much by outliers.
+//!
+//! The [standard deviation](crate::standard_deviation) and [Pearson's r](crate::correlation::pearson_r)
+//! can be made arbitrarily large or small by a single outlier. The estimators here tolerate up to
+//! half of the values being outliers (a breakdown point of 50%), similar to how the
+//! [Theil-Sen estimator](crate::regression::theil_sen) handles outliers in regression.
+//!
+//! - [`mad`], [`sn`], and [`qn`] estimate the scale. They're multiplied by a constant so they
+//!   estimate the standard deviation of normally distributed values.
+//! - [`covariance`] and [`correlation`] combine one of the scale estimators using the identity of
+//!   [Gnanadesikan & Kettenring (1972)](https://doi.org/10.2307/2528963). This handles outliers
+//!   in both variables.
+//! - [`rank_correlation`] estimates Pearson's r from [Kendall's τ](crate::correlation::kendall_tau).
+use crate::percentile::{self, KthSmallest};
+use crate::{F64OrdHash, Fraction};
+
+fn select(values: &mut [f64], target: impl percentile::OrderedListIndex) -> f64 {
+    percentile::percentile_default_pivot_by(values, target, &mut |a, b| F64OrdHash::f64_cmp(*a, *b))
+        .resolve()
+}
+
+/// The median of `values`. `values` is reordered.
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+fn median(values: &mut [f64]) -> f64 {
+    select(values, Fraction::HALF)
+}
+
+/// The [median absolute deviation](https://en.wikipedia.org/wiki/Median_absolute_deviation),
+/// i.e. the median of the distance from each value to the median.
+///
+/// It's multiplied by `1.4826`, so it estimates the standard deviation of normally distributed
+/// values. It's simple, but assumes the values are symmetric around the median.
+///
+/// O(n)
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+pub fn mad(values: &[f64]) -> f64 {
+    let mut values = values.to_vec();
+    let m = median(&mut values);
+    for v in &mut values {
+        *v = (*v - m).abs();
+    }
+    1.4826 * median(&mut values)
+}
+/// The `Sn` estimator of [Rousseeuw & Croux (1993)](https://doi.org/10.1080/01621459.1993.10476408):
+/// the median over all values of the median distance to the other values.
+///
+/// Unlike [`mad`], this doesn't assume the values are symmetric.
+/// It's multiplied by `1.1926`, so it estimates the standard deviation of normally distributed
+/// values.
+///
+/// O(n²) time, O(n) memory.
+///
+/// # Panics
+///
+/// Panics if `values` is empty.
+pub fn sn(values: &[f64]) -> f64 {
+    let n = values.len();
+    let mut distances = vec![0.; n];
+    let mut medians: Vec<f64> = values
+        .iter()
+        .map(|a| {
+            for (distance, b) in distances.iter_mut().zip(values) {
+                *distance = (a - b).abs();
+            }
+            // the high median
+            select(&mut distances, KthSmallest::new(n / 2))
+        })
+        .collect();
+    // the low median
+    1.1926 * select(&mut medians, KthSmallest::new((n + 1) / 2 - 1))
+}
+/// The `Qn` estimator of [Rousseeuw & Croux (1993)](https://doi.org/10.1080/01621459.1993.10476408):
+/// roughly the first quartile of the distances between all pairs of values.
+///
+/// It's more efficient than [`sn`] and [`mad`] for normally distributed values, while being as
+/// robust. It's multiplied by `2.2219`, so it estimates the standard deviation of normally
+/// distributed values.
+///
+/// O(n²) time and memory.
+///
+/// # Panics
+///
+/// Panics if `values` has less than 2 items.
+pub fn qn(values: &[f64]) -> f64 {
+    let n = values.len();
+    assert!(n >= 2, "Qn requires at least 2 values");
+    let mut distances = Vec::with_capacity(n * (n - 1) / 2);
+    for (idx, a) in values.iter().enumerate() {
+        for b in &values[idx + 1..] {
+            distances.push((a - b).abs());
+        }
+    }
+    let h = n / 2 + 1;
+    let k = h * (h - 1) / 2;
+    2.2219 * select(&mut distances, KthSmallest::new(k - 1))
+}
+
+/// The standardized sum and difference of `x` and `y`, and the scales of `x` and `y`.
+fn sum_difference(
+    x: &[f64],
+    y: &[f64],
+    scale: &impl Fn(&[f64]) -> f64,
+) -> (Vec<f64>, Vec<f64>, f64, f64) {
+    assert_eq!(
+        x.len(),
+        y.len(),
+        "x and y must have the same number of items"
+    );
+    let x_scale = scale(x);
+    let y_scale = scale(y);
+    let sum = x
+        .iter()
+        .zip(y)
+        .map(|(x, y)| x / x_scale + y / y_scale)
+        .collect();
+    let difference = x
+        .iter()
+        .zip(y)
+        .map(|(x, y)| x / x_scale - y / y_scale)
+        .collect();
+    (sum, difference, x_scale, y_scale)
+}
+/// A robust covariance of `x` and `y`, using `scale` (e.g. [`qn`]) to estimate the spread of
+/// their standardized sum and difference.
+///
+/// Returns `NaN` if the scale of either of the variables is 0.
+///
+/// # Panics
+///
+/// `x` and `y` must have the same length, and `scale` can panic if they're too short.
+pub fn covariance(x: &[f64], y: &[f64], scale: impl Fn(&[f64]) -> f64) -> f64 {
+    let (sum, difference, x_scale, y_scale) = sum_difference(x, y, &scale);
+    let sum_scale = scale(&sum);
+    let difference_scale = scale(&difference);
+    x_scale * y_scale * (sum_scale * sum_scale - difference_scale * difference_scale) / 4.
+}
+/// A robust correlation of `x` and `y`, using `scale` (e.g. [`qn`]). It's in the range `[-1, 1]`
+/// and estimates [Pearson's r](crate::correlation::pearson_r) of the values without outliers.
+///
+/// Returns `NaN` if the scale of either of the variables is 0.
+///
+/// # Panics
+///
+/// `x` and `y` must have the same length, and `scale` can panic if they're too short.
+pub fn correlation(x: &[f64], y: &[f64], scale: impl Fn(&[f64]) -> f64) -> f64 {
+    let (sum, difference, _, _) = sum_difference(x, y, &scale);
+    let sum_variance = scale(&sum).powi(2);
+    let difference_variance = scale(&difference).powi(2);
+    ((sum_variance - difference_variance) / (sum_variance + difference_variance)).clamp(-1., 1.)
+}
+/// A rank-based estimate of [Pearson's r](crate::correlation::pearson_r), `sin(π/2 τ)`, where `τ`
+/// is [Kendall's τ](crate::correlation::kendall_tau).
+///
+/// This is exact for normally distributed values, and as it only depends on the order of the
+/// values, outliers have little effect.
+///
+/// O(n log n)
+///
+/// # Panics
+///
+/// `x` and `y` must have the same length.
+pub fn rank_correlation(x: &[f64], y: &[f64]) -> f64 {
+    (std::f64::consts::FRAC_PI_2 * crate::correlation::kendall_tau(x, y)).sin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale() {
+        let values = [1., 2., 3., 4., 5.];
+        assert!((mad(&values) - 1.4826).abs() < 1e-12);
+        assert!((sn(&values) - 1.1926).abs() < 1e-12);
+        assert!((qn(&values) - 2.2219).abs() < 1e-12);
+        // an outlier doesn't change them much
+        let values = [1., 2., 3., 4., 1000.];
+        assert!((mad(&values) - 1.4826).abs() < 1e-12);
+        assert!(sn(&values) < 3.);
+        assert!(qn(&values) < 5.);
+    }
+    #[test]
+    fn outliers() {
+        let x: Vec<f64> = (0..20).map(|v| v as f64).collect();
+        let mut y: Vec<f64> = x.iter().map(|x| 2. * x + (x * 7.).sin()).collect();
+        y[19] = -100.;
+        y[18] = -80.;
+        assert!(crate::correlation::pearson_r(&x, &y) < 0.);
+        assert!(correlation(&x, &y, qn) > 0.9);
+        assert!(correlation(&x, &y, sn) > 0.9);
+        assert!(rank_correlation(&x, &y) > 0.6);
+        assert!(covariance(&x, &y, qn) > 0.);
+    }
+}
@@ -0,0 +1,114 @@
+//! Random values drawn from observed data, e.g. for simulations and synthetic data.
+//!
+//! - [`NormalSampler`] draws from the normal distribution with the mean and standard deviation
+//!   of the data (a [`StandardDeviationOutput`]).
+//! - [`EmpiricalSampler`] draws the observed values themselves (with replacement), so the
+//!   synthetic data has the same distribution, including e.g. long tails.
+//!
+//! All samplers take the random number generator as an argument, so you can use a seeded one to
+//! get reproducible values.
+//!
+//! Enabled by the `sample` feature.
+use rand::distr::Open01;
+use rand::Rng;
+
+use crate::distributions::Normal;
+use crate::{ClusterList, StandardDeviationOutput};
+
+/// Draws random values.
+pub trait Sampler {
+    /// Draws a value.
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64;
+    /// Draws `n` values.
+    fn sample_n<R: Rng + ?Sized>(&self, n: usize, rng: &mut R) -> Vec<f64> {
+        (0..n).map(|_| self.sample(rng)).collect()
+    }
+}
+
+/// Draws values from a normal distribution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalSampler {
+    pub mean: f64,
+    /// If this is 0, all values are [`Self::mean`].
+    pub standard_deviation: f64,
+}
+impl NormalSampler {
+    /// The normal distribution with the mean and standard deviation of `fit`, e.g. the output of
+    /// [`standard_deviation`](crate::standard_deviation).
+    pub fn new(fit: &StandardDeviationOutput<f64>) -> Self {
+        Self {
+            mean: fit.mean,
+            standard_deviation: fit.standard_deviation,
+        }
+    }
+}
+impl Sampler for NormalSampler {
+    /// Uses inverse transform sampling with [`Normal::quantile`].
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        let p: f64 = rng.sample(Open01);
+        self.mean + self.standard_deviation * Normal::STANDARD.quantile(p)
+    }
+}
+
+/// Draws the values of a [`ClusterList`], each with the probability of its count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmpiricalSampler {
+    values: Vec<f64>,
+    /// The count of values before and including each value.
+    ends: Vec<usize>,
+}
+impl EmpiricalSampler {
+    /// O(m), where m is the number of [`Cluster`](crate::Cluster)s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    pub fn new(values: &ClusterList) -> Self {
+        assert!(!values.is_empty(), "can't sample from no values");
+        let mut end = 0;
+        let (values, ends) = values
+            .list
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(v, count)| {
+                end += count;
+                (*v, end)
+            })
+            .unzip();
+        Self { values, ends }
+    }
+}
+impl Sampler for EmpiricalSampler {
+    /// O(log m)
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        let len = *self.ends.last().expect("there's at least one value");
+        let index = rng.random_range(0..len);
+        self.values[self.ends.partition_point(|end| *end <= index)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn samplers() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let fit = StandardDeviationOutput {
+            standard_deviation: 2.,
+            mean: 10.,
+        };
+        let values = NormalSampler::new(&fit).sample_n(10_000, &mut rng);
+        let output = crate::standard_deviation(&values);
+        assert!((output.mean - 10.).abs() < 0.1);
+        assert!((output.standard_deviation - 2.).abs() < 0.1);
+
+        let clusters = [(1., 1), (5., 0), (2., 3)];
+        let sampler = EmpiricalSampler::new(&ClusterList::new(&clusters));
+        let values = sampler.sample_n(4000, &mut rng);
+        let twos = values.iter().filter(|v| **v == 2.).count();
+        assert!(values.iter().all(|v| *v == 1. || *v == 2.));
+        assert!((2800..3200).contains(&twos), "{twos}");
+    }
+}
@@ -0,0 +1,109 @@
+//! Loops split into chunks, which the compiler vectorizes. Enabled by the `simd` feature.
+//!
+//! Each loop keeps [`LANES`] independent accumulators, which removes the dependency of each
+//! addition on the previous. This changes the order of the additions, so results can differ
+//! from the scalar loops in the last bits.
+use crate::Cluster;
+
+/// The count of accumulators. 8 fills a 512-bit register of `f64`s.
+const LANES: usize = 8;
+
+/// Σ `f(item)` for the items of `list`.
+#[inline]
+fn sum_slice<T>(list: &[T], f: impl Fn(&T) -> f64) -> f64 {
+    let mut acc = [0.; LANES];
+    let chunks = list.chunks_exact(LANES);
+    let remainder: f64 = chunks.remainder().iter().map(&f).sum();
+    for chunk in chunks {
+        for (acc, item) in acc.iter_mut().zip(chunk) {
+            *acc += f(item);
+        }
+    }
+    acc.iter().sum::<f64>() + remainder
+}
+
+/// Σ `v * count` of the clusters.
+pub(crate) fn cluster_sum(list: &[Cluster]) -> f64 {
+    sum_slice(list, |(v, count)| v * *count as f64)
+}
+/// Σ `(v - base)² * count` of the clusters.
+pub(crate) fn cluster_sum_squared_diff(list: &[Cluster], base: f64) -> f64 {
+    sum_slice(list, |(v, count)| {
+        let diff = v - base;
+        diff * diff * *count as f64
+    })
+}
+
+/// Σ `(a - b)²` of the pairs of `a` and `b`.
+///
+/// The items are first collected in a buffer of [`LANES`] items, so the accumulation is
+/// vectorized even if the iterators themselves aren't.
+#[cfg(feature = "regression")]
+pub(crate) fn sum_squared_diff(
+    mut a: impl Iterator<Item = f64>,
+    mut b: impl Iterator<Item = f64>,
+) -> f64 {
+    let mut acc = [0.; LANES];
+    loop {
+        let mut diffs = [0.; LANES];
+        let mut len = 0;
+        for diff in &mut diffs {
+            let (Some(a), Some(b)) = (a.next(), b.next()) else {
+                break;
+            };
+            *diff = a - b;
+            len += 1;
+        }
+        for (acc, diff) in acc.iter_mut().zip(diffs) {
+            *acc += diff * diff;
+        }
+        if len < LANES {
+            break;
+        }
+    }
+    acc.iter().sum()
+}
+/// Σ `values`, buffered like [`sum_squared_diff`].
+#[cfg(feature = "regression")]
+pub(crate) fn sum(mut values: impl Iterator<Item = f64>) -> f64 {
+    let mut acc = [0.; LANES];
+    loop {
+        let mut buffer = [0.; LANES];
+        let mut len = 0;
+        for (slot, value) in buffer.iter_mut().zip(&mut values) {
+            *slot = value;
+            len += 1;
+        }
+        for (acc, value) in acc.iter_mut().zip(buffer) {
+            *acc += value;
+        }
+        if len < LANES {
+            break;
+        }
+    }
+    acc.iter().sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunked() {
+        let list: Vec<Cluster> = (0..21).map(|v| (v as f64, v % 3 + 1)).collect();
+        let scalar: f64 = list.iter().map(|(v, c)| v * *c as f64).sum();
+        assert_eq!(cluster_sum(&list), scalar);
+        let scalar: f64 = list
+            .iter()
+            .map(|(v, c)| (v - 2.5).powi(2) * *c as f64)
+            .sum();
+        assert_eq!(cluster_sum_squared_diff(&list, 2.5), scalar);
+        #[cfg(feature = "regression")]
+        {
+            let values = (0..19).map(f64::from);
+            assert_eq!(sum(values.clone()), 171.);
+            let shifted = values.clone().map(|v| v + 2.);
+            assert_eq!(sum_squared_diff(values, shifted), 19. * 4.);
+        }
+    }
+}
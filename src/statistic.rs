@@ -0,0 +1,290 @@
+//! Named statistics, which other crates can add to.
+//!
+//! A [`Statistic`] computes a single value (e.g. the mean) of a [`ClusterList`]. A [`Registry`]
+//! maps names to statistics. The binary looks up the names given to `--stats` in
+//! [`Registry::global`], so statistics registered there (e.g. domain-specific scores) can be
+//! selected by name, and are included in the JSON output.
+use std::any::Any;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::online::{Accumulator, RunningStats};
+use crate::percentile::cluster;
+use crate::{Cluster, ClusterList, Fraction, OwnedClusterList};
+
+/// A statistic of a list of values, e.g. the mean.
+pub trait Statistic: Send + Sync {
+    /// The name, used to select the statistic and as its key in the output.
+    fn name(&self) -> &str;
+    /// Computes the statistic of `values`. Returns `NaN` if it's undefined, e.g. when there are
+    /// no values.
+    fn compute(&self, values: &ClusterList) -> f64;
+    /// Computes the statistic of a slice of values.
+    ///
+    /// By default, this turns each value into a [`Cluster`] and calls [`Self::compute`].
+    fn compute_slice(&self, values: &[f64]) -> f64 {
+        let clusters: Vec<Cluster> = values.iter().map(|v| (*v, 1)).collect();
+        self.compute(&ClusterList::new(&clusters))
+    }
+    /// A state which is updated one value at a time and can be merged with other states, if
+    /// the statistic supports it. This enables computing the statistic of data which doesn't
+    /// fit in memory, or is split between threads or machines.
+    ///
+    /// The default returns [`None`].
+    fn state(&self) -> Option<Box<dyn StatisticState>> {
+        None
+    }
+}
+impl fmt::Debug for dyn Statistic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Statistic")
+            .field("name", &self.name())
+            .finish()
+    }
+}
+
+/// The mergeable state of a [`Statistic`]. Returned from [`Statistic::state`].
+pub trait StatisticState: Send {
+    /// Adds `value`.
+    fn push(&mut self, value: f64);
+    /// Merges `other` into `self`.
+    ///
+    /// # Panics
+    ///
+    /// May panic if `other` was created by another statistic.
+    fn merge(&mut self, other: &dyn StatisticState);
+    /// The value of the statistic of all the values added.
+    fn value(&self) -> f64;
+    /// Used to downcast `other` in [`Self::merge`].
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// A [`StatisticState`] using an [`Accumulator`], and a function getting the value of the
+/// statistic from it.
+pub struct AccumulatorState<A> {
+    /// The accumulated values.
+    pub accumulator: A,
+    value: fn(&A) -> f64,
+}
+impl<A> AccumulatorState<A> {
+    pub fn new(accumulator: A, value: fn(&A) -> f64) -> Self {
+        Self { accumulator, value }
+    }
+}
+impl<A: Accumulator<Item = f64> + Send + 'static> StatisticState for AccumulatorState<A> {
+    fn push(&mut self, value: f64) {
+        self.accumulator.push(value);
+    }
+    fn merge(&mut self, other: &dyn StatisticState) {
+        let other = other
+            .as_any()
+            .downcast_ref::<Self>()
+            .expect("merged states must be of the same statistic");
+        self.accumulator.merge(&other.accumulator);
+    }
+    fn value(&self) -> f64 {
+        (self.value)(&self.accumulator)
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A statistic computed from a [`RunningStats`].
+struct Running {
+    name: &'static str,
+    value: fn(&RunningStats) -> f64,
+}
+impl Statistic for Running {
+    fn name(&self) -> &str {
+        self.name
+    }
+    fn compute(&self, values: &ClusterList) -> f64 {
+        let mut stats = RunningStats::new();
+        for (v, count) in values.list {
+            stats.merge(&RunningStats::from_parts(*count, *v, 0., *v, *v));
+        }
+        (self.value)(&stats)
+    }
+    fn state(&self) -> Option<Box<dyn StatisticState>> {
+        Some(Box::new(AccumulatorState::new(
+            RunningStats::new(),
+            self.value,
+        )))
+    }
+}
+
+/// The percentile at [`Self::target`], named e.g. `p95`. The median is named `median`.
+#[derive(Debug, Clone)]
+pub struct Percentile {
+    name: String,
+    /// The fraction of the values below the percentile.
+    pub target: Fraction,
+}
+impl Percentile {
+    /// Named `p` followed by the percent, e.g. `p95`.
+    pub fn new(target: Fraction) -> Self {
+        let percent = target.numerator as f64 * 100. / target.denominator as f64;
+        Self::with_name(format!("p{percent}"), target)
+    }
+    pub fn with_name(name: impl Into<String>, target: Fraction) -> Self {
+        Self {
+            name: name.into(),
+            target,
+        }
+    }
+}
+impl Statistic for Percentile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn compute(&self, values: &ClusterList) -> f64 {
+        if values.is_empty() {
+            return f64::NAN;
+        }
+        let mut values = OwnedClusterList::new(values.list.to_vec());
+        cluster::percentile_default_pivot(&mut values, self.target).resolve()
+    }
+}
+
+/// Statistics by name.
+///
+/// [`Self::default`] contains the built-in statistics: `count`, `sum`, `mean`, `std_dev`,
+/// `variance`, `min`, `max`, `median`, `p25`, `p75`, `p90`, `p95`, and `p99`.
+#[derive(Debug, Clone)]
+pub struct Registry {
+    statistics: Vec<Arc<dyn Statistic>>,
+}
+impl Registry {
+    /// Creates a registry without any statistics.
+    pub fn new() -> Self {
+        Self {
+            statistics: Vec::new(),
+        }
+    }
+    /// The registry used by the binary. Initialized with the [default](Self::default)
+    /// statistics.
+    pub fn global() -> &'static RwLock<Registry> {
+        static GLOBAL: OnceLock<RwLock<Registry>> = OnceLock::new();
+        GLOBAL.get_or_init(|| RwLock::new(Registry::default()))
+    }
+    /// Adds `statistic`, replacing any with the same name.
+    pub fn register(&mut self, statistic: impl Statistic + 'static) {
+        let statistic: Arc<dyn Statistic> = Arc::new(statistic);
+        match self
+            .statistics
+            .iter_mut()
+            .find(|s| s.name() == statistic.name())
+        {
+            Some(existing) => *existing = statistic,
+            None => self.statistics.push(statistic),
+        }
+    }
+    /// Gets the statistic named `name`.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Statistic>> {
+        self.statistics.iter().find(|s| s.name() == name).cloned()
+    }
+    /// The names of all statistics, in the order they were registered.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.statistics.iter().map(|s| s.name())
+    }
+}
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        // `NaN` instead of the placeholders of `RunningStats` when empty
+        fn defined(stats: &RunningStats, value: f64) -> f64 {
+            if stats.is_empty() {
+                f64::NAN
+            } else {
+                value
+            }
+        }
+        let running: [Running; 7] = [
+            Running {
+                name: "count",
+                value: |s| s.len() as f64,
+            },
+            Running {
+                name: "sum",
+                value: RunningStats::sum,
+            },
+            Running {
+                name: "mean",
+                value: RunningStats::mean,
+            },
+            Running {
+                name: "std_dev",
+                value: |s| defined(s, s.standard_deviation().standard_deviation),
+            },
+            Running {
+                name: "variance",
+                value: |s| defined(s, s.variance()),
+            },
+            Running {
+                name: "min",
+                value: |s| defined(s, s.min()),
+            },
+            Running {
+                name: "max",
+                value: |s| defined(s, s.max()),
+            },
+        ];
+        for statistic in running {
+            registry.register(statistic);
+        }
+        registry.register(Percentile::with_name("median", Fraction::HALF));
+        for percent in [25, 75, 90, 95, 99] {
+            registry.register(Percentile::new(Fraction::new(percent, 100)));
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin() {
+        let registry = Registry::default();
+        let values = [(1., 2), (4., 1), (10., 1)];
+        let values = ClusterList::new(&values);
+        assert_eq!(registry.get("count").unwrap().compute(&values), 4.);
+        assert_eq!(registry.get("mean").unwrap().compute(&values), 4.);
+        assert_eq!(registry.get("median").unwrap().compute(&values), 2.5);
+        assert_eq!(registry.get("max").unwrap().compute_slice(&[3., 7.]), 7.);
+        assert!(registry.get("mean").unwrap().compute_slice(&[]).is_nan());
+        assert!(registry.names().any(|name| name == "p95"));
+        assert!(registry.get("p95").unwrap().state().is_none());
+
+        let std_dev = registry.get("std_dev").unwrap();
+        let mut a = std_dev.state().unwrap();
+        let mut b = std_dev.state().unwrap();
+        [1., 2., 3.].iter().for_each(|v| a.push(*v));
+        [4., 5.].iter().for_each(|v| b.push(*v));
+        a.merge(&*b);
+        let expected = std_dev.compute_slice(&[1., 2., 3., 4., 5.]);
+        assert!((a.value() - expected).abs() < 1e-12);
+    }
+    #[test]
+    fn custom() {
+        struct Range;
+        impl Statistic for Range {
+            fn name(&self) -> &str {
+                "range"
+            }
+            fn compute(&self, values: &ClusterList) -> f64 {
+                let min = values.list.iter().map(|c| c.0).fold(f64::NAN, f64::min);
+                let max = values.list.iter().map(|c| c.0).fold(f64::NAN, f64::max);
+                max - min
+            }
+        }
+        let mut registry = Registry::new();
+        registry.register(Range);
+        registry.register(Range);
+        assert_eq!(registry.names().count(), 1);
+        assert_eq!(registry.get("range").unwrap().compute_slice(&[3., -1.]), 4.);
+        assert!(registry.get("mean").is_none());
+    }
+}
@@ -0,0 +1,233 @@
+//! Summaries of data sets, and comparisons between them.
+//!
+//! Use [`Summary::diff`] to compare two runs of e.g. a benchmark. The [`SummaryDiff`] contains
+//! the change of every statistic, and if the change of the mean is
+//! [statistically significant](SummaryDiff::mean_significance).
+//!
+//! With the `serde` feature, all the types here can be serialized, e.g. to JSON.
+use crate::online::RunningStats;
+use crate::tests::TTest;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The quantiles calculated by [`Summary::new`].
+pub const DEFAULT_QUANTILES: [f64; 5] = [0.25, 0.75, 0.9, 0.95, 0.99];
+
+/// The common statistics of a data set.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub standard_deviation: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Only available when the summary is created from all the values, see [`Self::new`].
+    pub median: Option<f64>,
+    /// `(quantile, value)`, sorted by the quantile.
+    /// Only available when the summary is created from all the values, see [`Self::new`].
+    pub quantiles: Vec<(f64, f64)>,
+}
+impl Summary {
+    /// Summarizes `values`, including the [`DEFAULT_QUANTILES`].
+    ///
+    /// O(n)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    pub fn new(values: &[f64]) -> Self {
+        Self::with_quantiles(values, &DEFAULT_QUANTILES)
+    }
+    /// Summarizes `values`, including the `quantiles` (in `[0, 1]`).
+    /// Quantiles are rounded to 4 decimals.
+    ///
+    /// O(nq), where q is the count of quantiles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty or any quantile is outside `[0, 1]`.
+    pub fn with_quantiles(values: &[f64], quantiles: &[f64]) -> Self {
+        let mut stats = RunningStats::new();
+        stats.extend(values.iter().copied());
+        let mut values = values.to_vec();
+        let mut quantile = |q: f64| {
+            assert!((0.0..=1.0).contains(&q), "quantiles must be in [0, 1]");
+            let fraction = crate::Fraction::new((q * 10_000.).round() as u64, 10_000);
+            crate::percentile::percentile_default_pivot_by(&mut values, fraction, &mut |a, b| {
+                crate::F64OrdHash::f64_cmp(*a, *b)
+            })
+            .resolve()
+        };
+        let median = quantile(0.5);
+        let mut quantiles: Vec<(f64, f64)> = quantiles.iter().map(|q| (*q, quantile(*q))).collect();
+        quantiles.sort_by(|a, b| crate::F64OrdHash::f64_cmp(a.0, b.0));
+        Self {
+            median: Some(median),
+            quantiles,
+            ..Self::from(&stats)
+        }
+    }
+    /// The value of `quantile`, if it was calculated. `0.5` returns the median.
+    pub fn quantile(&self, quantile: f64) -> Option<f64> {
+        if quantile == 0.5 {
+            return self.median;
+        }
+        self.quantiles
+            .iter()
+            .find(|(q, _)| (q - quantile).abs() < 1e-9)
+            .map(|(_, v)| *v)
+    }
+    /// The confidence interval `(lower, upper)` of the mean, see
+    /// [`crate::distributions::mean_interval`]. [`None`] if there are fewer than 2 values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `confidence` isn't in the range `(0, 1)`.
+    pub fn mean_interval(&self, confidence: f64) -> Option<(f64, f64)> {
+        crate::distributions::mean_interval(
+            self.mean,
+            self.standard_deviation,
+            self.count,
+            confidence,
+        )
+    }
+    /// Compares `self` (e.g. the baseline) to `other` (e.g. the new run).
+    ///
+    /// The deltas are `other - self`.
+    pub fn diff(&self, other: &Self) -> SummaryDiff {
+        let median = match (self.median, other.median) {
+            (Some(a), Some(b)) => Some(Delta::new(a, b)),
+            _ => None,
+        };
+        let quantiles = self
+            .quantiles
+            .iter()
+            .filter_map(|(q, before)| {
+                other
+                    .quantile(*q)
+                    .map(|after| (*q, Delta::new(*before, after)))
+            })
+            .collect();
+        SummaryDiff {
+            count: Delta::new(self.count as f64, other.count as f64),
+            mean: Delta::new(self.mean, other.mean),
+            standard_deviation: Delta::new(self.standard_deviation, other.standard_deviation),
+            min: Delta::new(self.min, other.min),
+            max: Delta::new(self.max, other.max),
+            median,
+            quantiles,
+            mean_significance: crate::tests::welch_t_test_summaries(other, self),
+        }
+    }
+}
+impl From<&RunningStats> for Summary {
+    /// The median isn't known from a [`RunningStats`], so it's [`None`].
+    fn from(stats: &RunningStats) -> Self {
+        let std_dev = stats.standard_deviation();
+        Self {
+            count: stats.len(),
+            mean: std_dev.mean,
+            standard_deviation: std_dev.standard_deviation,
+            min: stats.min(),
+            max: stats.max(),
+            median: None,
+            quantiles: Vec::new(),
+        }
+    }
+}
+
+/// The change of a statistic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Delta {
+    pub before: f64,
+    pub after: f64,
+    /// `after - before`.
+    pub absolute: f64,
+    /// `(after - before) / |before|`, e.g. `0.1` for an increase of 10%.
+    /// Infinite or `NaN` if `before` is 0.
+    pub relative: f64,
+}
+impl Delta {
+    pub fn new(before: f64, after: f64) -> Self {
+        let absolute = after - before;
+        Self {
+            before,
+            after,
+            absolute,
+            relative: absolute / before.abs(),
+        }
+    }
+}
+
+/// The result of [Welch's t-test](https://en.wikipedia.org/wiki/Welch%27s_t-test) of the
+/// difference between two means. See [`crate::tests::welch_t_test_summaries`].
+pub type Significance = TTest;
+
+/// Returned from [`Summary::diff`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SummaryDiff {
+    pub count: Delta,
+    pub mean: Delta,
+    pub standard_deviation: Delta,
+    pub min: Delta,
+    pub max: Delta,
+    /// [`None`] if either [`Summary`] lacks the median.
+    pub median: Option<Delta>,
+    /// `(quantile, change)` of the quantiles in both [`Summary`]s.
+    pub quantiles: Vec<(f64, Delta)>,
+    /// If the change of the mean is statistically significant.
+    /// [`None`] if either summary has less than 2 values, or both have no variance.
+    pub mean_significance: Option<Significance>,
+}
+impl SummaryDiff {
+    /// The change of `quantile`, if it's in both [`Summary`]s. `0.5` returns the median.
+    pub fn quantile(&self, quantile: f64) -> Option<Delta> {
+        if quantile == 0.5 {
+            return self.median;
+        }
+        self.quantiles
+            .iter()
+            .find(|(q, _)| (q - quantile).abs() < 1e-9)
+            .map(|(_, delta)| *delta)
+    }
+    /// If the change of the mean is significant at the level `alpha` (e.g. `0.05`).
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.mean_significance
+            .is_some_and(|significance| significance.p_value < alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff() {
+        let before = Summary::new(&[10., 11., 9., 10., 10.5, 9.5]);
+        let after = Summary::new(&[12., 13., 11., 12., 12.5, 11.5]);
+        let diff = before.diff(&after);
+        assert!((diff.mean.absolute - 2.).abs() < 1e-12);
+        assert!((diff.mean.relative - 0.2).abs() < 1e-12);
+        assert_eq!(diff.median.unwrap().absolute, 2.);
+        assert_eq!(diff.standard_deviation.absolute, 0.);
+        assert_eq!(diff.quantile(0.99).unwrap().absolute, 2.);
+        assert_eq!(after.quantile(0.25), Some(11.5));
+        // t = 2 / sqrt(2 * 0.5 / 6) ≈ 4.899 with 10 degrees of freedom
+        let significance = diff.mean_significance.unwrap();
+        assert!((significance.degrees_of_freedom - 10.).abs() < 1e-9);
+        assert!((significance.p_value - 0.000624).abs() < 1e-5);
+        assert!(diff.is_significant(0.01));
+    }
+    #[test]
+    fn not_significant() {
+        let before = Summary::new(&[1., 5., 3., 7.]);
+        let after = Summary::new(&[2., 4., 6., 5.]);
+        assert!(!before.diff(&after).is_significant(0.05));
+        let single = Summary::new(&[1.]);
+        assert_eq!(single.diff(&before).mean_significance, None);
+    }
+}
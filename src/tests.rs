@@ -0,0 +1,613 @@
+//! Hypothesis tests, which tell how likely a difference is to be caused by chance.
+//!
+//! All tests return a [`TestResult`], except the t-tests, which return a [`TTest`] with the
+//! degrees of freedom. A small [p-value](TestResult::p_value) (commonly below `0.05`) means the
+//! difference is unlikely to be caused by chance alone.
+//!
+//! # Means
+//!
+//! The [t-tests](https://en.wikipedia.org/wiki/Student%27s_t-test) assume the means are
+//! approximately normally distributed, which holds for most data when there are more than a few
+//! dozen values.
+//!
+//! - [`one_sample_t_test`] tests if the mean is a given value.
+//! - [`welch_t_test`] tests if two means are equal. Prefer this to [`student_t_test`], which
+//!   also assumes the variances are equal.
+//! - [`anova_one_way`] tests if the means of any number of groups are equal.
+//!
+//! - [`anderson_darling`] tests if values are normally distributed, which the t-tests (and
+//!   summarizing data by its mean and standard deviation) assume.
+//! - [`mann_whitney_u`] tests if the values of one series tend to be larger than those of
+//!   another, without assuming a distribution. Use this instead of the t-tests for data which
+//!   isn't normally distributed.
+//!
+//! # Paired data
+//!
+//! When each value of one series corresponds to a value of the other (e.g. the same benchmark run
+//! before and after a change, on the same machine), analyze the differences of the pairs using
+//! [`paired`]. This removes the variation between the pairs, which makes the tests far more
+//! sensitive than comparing two independent [`Summary`]s.
+//!
+//! - [`sign_test`] only considers if each difference is positive or negative.
+//! - [`wilcoxon_signed_rank`] also considers the magnitude of the differences, through their
+//!   ranks. It assumes the differences are distributed symmetrically.
+use crate::distributions::{special, FisherF, StudentT};
+use crate::online::RunningStats;
+use crate::summary::Summary;
+use crate::ClusterList;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The result of a hypothesis test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TestResult {
+    /// The test statistic. What this is depends on the test.
+    pub statistic: f64,
+    /// The two-sided p-value: the probability of a result at least this extreme if there's no
+    /// difference.
+    pub p_value: f64,
+}
+impl TestResult {
+    /// If the [p-value](Self::p_value) is below `alpha` (e.g. `0.05`).
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// The result of a t-test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TTest {
+    /// The t statistic.
+    pub t: f64,
+    /// The degrees of freedom of the t-distribution. Not an integer for [`welch_t_test`].
+    pub degrees_of_freedom: f64,
+    /// The two-sided p-value: the probability of a difference at least this large if the means
+    /// are equal.
+    pub p_value: f64,
+}
+impl TTest {
+    /// `t` divided by `standard_error`, with the p-value from the t-distribution.
+    fn new(difference: f64, standard_error: f64, degrees_of_freedom: f64) -> Option<Self> {
+        if standard_error <= 0. || !standard_error.is_finite() {
+            return None;
+        }
+        let t = difference / standard_error;
+        let p_value = 2. * StudentT::new(degrees_of_freedom).cdf(-t.abs());
+        Some(Self {
+            t,
+            degrees_of_freedom,
+            p_value,
+        })
+    }
+    /// If the [p-value](Self::p_value) is below `alpha` (e.g. `0.05`).
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+impl From<TTest> for TestResult {
+    fn from(test: TTest) -> Self {
+        Self {
+            statistic: test.t,
+            p_value: test.p_value,
+        }
+    }
+}
+fn summarize(values: &[f64]) -> Summary {
+    let mut stats = RunningStats::new();
+    stats.extend(values.iter().copied());
+    Summary::from(&stats)
+}
+
+/// The one-sample t-test of whether the mean of `values` is `mean`.
+///
+/// The t statistic is positive if the mean of `values` is greater.
+/// Returns [`None`] if there are less than 2 values, or they have no variance.
+///
+/// O(n)
+pub fn one_sample_t_test(values: &[f64], mean: f64) -> Option<TTest> {
+    let summary = summarize(values);
+    if summary.count < 2 {
+        return None;
+    }
+    let n = summary.count as f64;
+    TTest::new(
+        summary.mean - mean,
+        summary.standard_deviation / n.sqrt(),
+        n - 1.,
+    )
+}
+/// Student's t-test of whether the means of `a` and `b` are equal, assuming they have the same
+/// variance. Use [`welch_t_test`] if that might not be the case.
+///
+/// The t statistic is positive if the mean of `a` is greater.
+/// Returns [`None`] if either has less than 2 values, or both have no variance.
+///
+/// O(n)
+pub fn student_t_test(a: &[f64], b: &[f64]) -> Option<TTest> {
+    student_t_test_summaries(&summarize(a), &summarize(b))
+}
+/// Same as [`student_t_test`], but of the values summarized by `a` and `b`.
+pub fn student_t_test_summaries(a: &Summary, b: &Summary) -> Option<TTest> {
+    if a.count < 2 || b.count < 2 {
+        return None;
+    }
+    let (a_n, b_n) = (a.count as f64, b.count as f64);
+    let degrees_of_freedom = a_n + b_n - 2.;
+    let pooled_variance = ((a_n - 1.) * a.standard_deviation.powi(2)
+        + (b_n - 1.) * b.standard_deviation.powi(2))
+        / degrees_of_freedom;
+    let standard_error = (pooled_variance * (1. / a_n + 1. / b_n)).sqrt();
+    TTest::new(a.mean - b.mean, standard_error, degrees_of_freedom)
+}
+/// [Welch's t-test](https://en.wikipedia.org/wiki/Welch%27s_t-test) of whether the means of `a`
+/// and `b` are equal. The degrees of freedom are from the Welch–Satterthwaite equation.
+///
+/// The t statistic is positive if the mean of `a` is greater.
+/// Returns [`None`] if either has less than 2 values, or both have no variance.
+///
+/// O(n)
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> Option<TTest> {
+    welch_t_test_summaries(&summarize(a), &summarize(b))
+}
+/// Same as [`welch_t_test`], but of the values summarized by `a` and `b`.
+/// Used by [`Summary::diff`].
+pub fn welch_t_test_summaries(a: &Summary, b: &Summary) -> Option<TTest> {
+    if a.count < 2 || b.count < 2 {
+        return None;
+    }
+    let a_error = a.standard_deviation.powi(2) / a.count as f64;
+    let b_error = b.standard_deviation.powi(2) / b.count as f64;
+    let error = a_error + b_error;
+    let degrees_of_freedom = error * error
+        / (a_error * a_error / (a.count - 1) as f64 + b_error * b_error / (b.count - 1) as f64);
+    TTest::new(a.mean - b.mean, error.sqrt(), degrees_of_freedom)
+}
+
+/// Returned from [`anova_one_way`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Anova {
+    /// The F statistic, the ratio of the mean squares between and within the groups.
+    pub f: f64,
+    /// The sum of squares of the group means from the grand mean, weighted by the group sizes.
+    pub between_sum_of_squares: f64,
+    /// The sum of squares of the values from their group's mean.
+    pub within_sum_of_squares: f64,
+    /// The count of groups minus 1.
+    pub between_degrees_of_freedom: f64,
+    /// The count of values minus the count of groups.
+    pub within_degrees_of_freedom: f64,
+    /// The probability of an F statistic at least this large if the means are equal.
+    pub p_value: f64,
+}
+impl Anova {
+    /// If the [p-value](Self::p_value) is below `alpha` (e.g. `0.05`).
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+impl From<Anova> for TestResult {
+    fn from(anova: Anova) -> Self {
+        Self {
+            statistic: anova.f,
+            p_value: anova.p_value,
+        }
+    }
+}
+/// [One-way ANOVA](https://en.wikipedia.org/wiki/One-way_analysis_of_variance) of whether the
+/// means of all `groups` are equal, e.g. the latencies of several deployments.
+///
+/// Assumes the values of each group are approximately normally distributed with the same
+/// variance. Empty groups are ignored.
+/// Returns [`None`] if there are less than 2 groups, no more values than groups, or no variance
+/// within the groups.
+///
+/// O(m), where m is the number of [`Cluster`](crate::Cluster)s.
+pub fn anova_one_way(groups: &[ClusterList]) -> Option<Anova> {
+    let groups: Vec<&ClusterList> = groups.iter().filter(|g| !g.is_empty()).collect();
+    let len: usize = groups.iter().map(|g| g.len()).sum();
+    if groups.len() < 2 || len <= groups.len() {
+        return None;
+    }
+    let grand_mean = groups.iter().map(|g| g.sum()).sum::<f64>() / len as f64;
+    let (mut between, mut within) = (0., 0.);
+    for group in &groups {
+        let mean = crate::mean_cluster(group);
+        between += group.len() as f64 * (mean - grand_mean) * (mean - grand_mean);
+        within += group.sum_squared_diff(mean);
+    }
+    if within <= 0. {
+        return None;
+    }
+    let between_degrees_of_freedom = (groups.len() - 1) as f64;
+    let within_degrees_of_freedom = (len - groups.len()) as f64;
+    let f = (between / between_degrees_of_freedom) / (within / within_degrees_of_freedom);
+    Some(Anova {
+        f,
+        between_sum_of_squares: between,
+        within_sum_of_squares: within,
+        between_degrees_of_freedom,
+        within_degrees_of_freedom,
+        p_value: FisherF::new(between_degrees_of_freedom, within_degrees_of_freedom).upper_tail(f),
+    })
+}
+
+/// Σ(t³ - t) over the groups of t tied values.
+fn tie_correction(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| crate::F64OrdHash::f64_cmp(*a, *b));
+    let mut correction = 0.;
+    let mut start = 0;
+    while start < values.len() {
+        let end = start
+            + values[start..]
+                .iter()
+                .take_while(|v| **v == values[start])
+                .count();
+        let t = (end - start) as f64;
+        correction += t * t * t - t;
+        start = end;
+    }
+    correction
+}
+
+/// The [sign test](https://en.wikipedia.org/wiki/Sign_test) of whether the median of
+/// `differences` is 0.
+///
+/// The [statistic](TestResult::statistic) is the count of positive differences.
+/// Differences of 0 are ignored. The p-value is exact, from the binomial distribution.
+///
+/// O(n)
+pub fn sign_test(differences: &[f64]) -> TestResult {
+    let positive = differences.iter().filter(|d| **d > 0.).count();
+    let negative = differences.iter().filter(|d| **d < 0.).count();
+    let n = positive + negative;
+    if n == 0 {
+        return TestResult {
+            statistic: 0.,
+            p_value: 1.,
+        };
+    }
+    // P(X <= k) for X ~ Binomial(n, ½)
+    let k = positive.min(negative);
+    let ln_n_factorial = special::ln_gamma(n as f64 + 1.);
+    let tail: f64 = (0..=k)
+        .map(|i| {
+            let ln_choose = ln_n_factorial
+                - special::ln_gamma(i as f64 + 1.)
+                - special::ln_gamma((n - i) as f64 + 1.);
+            (ln_choose - n as f64 * std::f64::consts::LN_2).exp()
+        })
+        .sum();
+    TestResult {
+        statistic: positive as f64,
+        p_value: (2. * tail).min(1.),
+    }
+}
+
+/// The [Wilcoxon signed-rank test](https://en.wikipedia.org/wiki/Wilcoxon_signed-rank_test) of
+/// whether the `differences` are distributed symmetrically around 0.
+///
+/// The [statistic](TestResult::statistic) is `W+`, the sum of the ranks of the absolute
+/// differences which are positive. Differences of 0 are ignored, and ties get the average rank.
+///
+/// The p-value is exact when there are at most 50 differences and no ties. Else, it uses the
+/// normal approximation with corrections for ties and continuity.
+///
+/// O(n log n), or O(n³) for the exact p-value.
+pub fn wilcoxon_signed_rank(differences: &[f64]) -> TestResult {
+    let differences: Vec<f64> = differences.iter().copied().filter(|d| *d != 0.).collect();
+    let n = differences.len();
+    if n == 0 {
+        return TestResult {
+            statistic: 0.,
+            p_value: 1.,
+        };
+    }
+    let absolute: Vec<f64> = differences.iter().map(|d| d.abs()).collect();
+    let ranks = crate::correlation::ranks(&absolute);
+    let w: f64 = differences
+        .iter()
+        .zip(&ranks)
+        .filter(|(d, _)| **d > 0.)
+        .map(|(_, rank)| rank)
+        .sum();
+    let tie_correction = tie_correction(absolute);
+
+    let p_value = if n <= 50 && tie_correction == 0. {
+        // counts[s] is the count of subsets of the ranks 1..=n with the sum s
+        let max = n * (n + 1) / 2;
+        let mut counts = vec![0.; max + 1];
+        counts[0] = 1.;
+        for rank in 1..=n {
+            for s in (rank..=max).rev() {
+                counts[s] += counts[s - rank];
+            }
+        }
+        let total = 2f64.powi(n as i32);
+        let w = w as usize;
+        let lower: f64 = counts[..=w].iter().sum();
+        let upper: f64 = counts[w..].iter().sum();
+        (2. * lower.min(upper) / total).min(1.)
+    } else {
+        let n = n as f64;
+        let mean = n * (n + 1.) / 4.;
+        let variance = n * (n + 1.) * (2. * n + 1.) / 24. - tie_correction / 48.;
+        let z = ((w - mean).abs() - 0.5).max(0.) / variance.sqrt();
+        special::erfc(z / std::f64::consts::SQRT_2)
+    };
+    TestResult {
+        statistic: w,
+        p_value,
+    }
+}
+
+/// The [Mann–Whitney U test](https://en.wikipedia.org/wiki/Mann%E2%80%93Whitney_U_test)
+/// (also called the Wilcoxon rank-sum test) of whether values of `a` tend to be larger or
+/// smaller than those of `b`.
+///
+/// This is the nonparametric alternative to [`welch_t_test`], for data which isn't normally
+/// distributed (e.g. latencies with long tails). It only considers the ranks of the values, so
+/// it's robust to outliers.
+///
+/// The [statistic](TestResult::statistic) is U of `a`: the count of pairs where the value of `a`
+/// is larger, with ties counting as ½. When there are no ties and at most 50 values in total,
+/// the p-value is exact. Else, the normal approximation (with a continuity and tie correction)
+/// is used.
+///
+/// O(n log n), or O(n³) for the exact p-value
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> TestResult {
+    let (n_a, n_b) = (a.len(), b.len());
+    if n_a == 0 || n_b == 0 {
+        return TestResult {
+            statistic: 0.,
+            p_value: 1.,
+        };
+    }
+    let n = n_a + n_b;
+    let combined: Vec<f64> = a.iter().chain(b).copied().collect();
+    let ranks = crate::correlation::ranks(&combined);
+    let rank_sum: f64 = ranks[..n_a].iter().sum();
+    let u = rank_sum - (n_a * (n_a + 1)) as f64 / 2.;
+    let tie_correction = tie_correction(combined);
+
+    let p_value = if n <= 50 && tie_correction == 0. {
+        // counts[k][s] is the count of subsets of k of the ranks 1..=n with the sum s
+        let max = n * (n + 1) / 2;
+        let mut counts = vec![vec![0.; max + 1]; n_a + 1];
+        counts[0][0] = 1.;
+        for rank in 1..=n {
+            for k in (1..=n_a.min(rank)).rev() {
+                let (smaller, current) = counts.split_at_mut(k);
+                for s in rank..=max {
+                    current[0][s] += smaller[k - 1][s - rank];
+                }
+            }
+        }
+        let counts = &counts[n_a];
+        let total: f64 = counts.iter().sum();
+        let rank_sum = rank_sum as usize;
+        let lower: f64 = counts[..=rank_sum].iter().sum();
+        let upper: f64 = counts[rank_sum..].iter().sum();
+        (2. * lower.min(upper) / total).min(1.)
+    } else {
+        let (n_a, n_b, n) = (n_a as f64, n_b as f64, n as f64);
+        let mean = n_a * n_b / 2.;
+        let variance = n_a * n_b / 12. * ((n + 1.) - tie_correction / (n * (n - 1.)));
+        if variance <= 0. {
+            1.
+        } else {
+            let z = ((u - mean).abs() - 0.5).max(0.) / variance.sqrt();
+            special::erfc(z / std::f64::consts::SQRT_2)
+        }
+    };
+    TestResult {
+        statistic: u,
+        p_value,
+    }
+}
+
+/// The [Anderson–Darling test](https://en.wikipedia.org/wiki/Anderson%E2%80%93Darling_test)
+/// of whether `values` are normally distributed, with the mean and standard deviation estimated
+/// from them.
+///
+/// A small p-value means the values are unlikely to be normally distributed, so e.g. the mean
+/// and standard deviation might not describe them well. The test is sensitive to the tails of
+/// the distribution.
+///
+/// Returns [`None`] if there are less than 8 values, or they have no variance.
+///
+/// O(n log n)
+pub fn anderson_darling(values: &[f64]) -> Option<TestResult> {
+    let clusters: Vec<crate::Cluster> = values.iter().map(|v| (*v, 1)).collect();
+    anderson_darling_cluster(&ClusterList::new(&clusters))
+}
+/// Same as [`anderson_darling`], but for clustered `values`.
+///
+/// The [statistic](TestResult::statistic) is A², adjusted for the estimated parameters
+/// (`A²(1 + 0.75/n + 2.25/n²)`). The p-value is from the approximation of D'Agostino and
+/// Stephens (1986).
+///
+/// O(m log m), where m is the number of [`Cluster`](crate::Cluster)s.
+pub fn anderson_darling_cluster(values: &ClusterList) -> Option<TestResult> {
+    let n = values.len();
+    if n < 8 {
+        return None;
+    }
+    let std_dev = crate::standard_deviation_cluster(values);
+    if std_dev.standard_deviation <= 0. || !std_dev.standard_deviation.is_finite() {
+        return None;
+    }
+    let mut sorted = values.list.to_vec();
+    sorted.sort_by(|a, b| crate::F64OrdHash::f64_cmp(a.0, b.0));
+
+    // A² = -n - 1/n Σ (2i - 1) (ln Φ(zᵢ) + ln(1 - Φ(zₙ₊₁₋ᵢ))), which for each value zⱼ is
+    // -n - 1/n Σ ((2j - 1) ln Φ(zⱼ) + (2n + 1 - 2j) ln(1 - Φ(zⱼ)))
+    let n_f = n as f64;
+    let mut sum = 0.;
+    let mut start = 0_usize;
+    for (v, count) in &sorted {
+        let z = (v - std_dev.mean) / std_dev.standard_deviation;
+        let ln_cdf = (special::erfc(-z / std::f64::consts::SQRT_2) / 2.).ln();
+        let ln_survival = (special::erfc(z / std::f64::consts::SQRT_2) / 2.).ln();
+        let (s, e) = (start as f64, (start + count) as f64);
+        // Σ (2j - 1) and Σ j for j in start+1..=start+count
+        let odd_sum = e * e - s * s;
+        let index_sum = (e * (e + 1.) - s * (s + 1.)) / 2.;
+        sum += odd_sum * ln_cdf + ((2. * n_f + 1.) * *count as f64 - 2. * index_sum) * ln_survival;
+        start += count;
+    }
+    let a2 = -n_f - sum / n_f;
+    let a2 = a2 * (1. + 0.75 / n_f + 2.25 / (n_f * n_f));
+    let p_value = if a2 >= 0.6 {
+        (1.2937 - 5.709 * a2 + 0.0186 * a2 * a2).exp()
+    } else if a2 >= 0.34 {
+        (0.9177 - 4.279 * a2 - 1.38 * a2 * a2).exp()
+    } else if a2 >= 0.2 {
+        1. - (-8.318 + 42.796 * a2 - 59.938 * a2 * a2).exp()
+    } else {
+        1. - (-13.436 + 101.14 * a2 - 223.73 * a2 * a2).exp()
+    };
+    Some(TestResult {
+        statistic: a2,
+        p_value: p_value.clamp(0., 1.),
+    })
+}
+
+/// Returned from [`paired`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PairedComparison {
+    /// The summary (including the quantiles) of the differences `after - before`.
+    pub differences: Summary,
+    /// The [`sign_test`] of the differences.
+    pub sign_test: TestResult,
+    /// The [`wilcoxon_signed_rank`] test of the differences.
+    pub wilcoxon: TestResult,
+}
+/// Analyzes the differences `after - before` of each pair.
+///
+/// # Panics
+///
+/// Panics if `before` and `after` have different lengths or are empty.
+pub fn paired(before: &[f64], after: &[f64]) -> PairedComparison {
+    assert_eq!(
+        before.len(),
+        after.len(),
+        "paired series must have the same number of items"
+    );
+    let differences: Vec<f64> = before.iter().zip(after).map(|(a, b)| b - a).collect();
+    PairedComparison {
+        differences: Summary::new(&differences),
+        sign_test: sign_test(&differences),
+        wilcoxon: wilcoxon_signed_rank(&differences),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign() {
+        let differences = [1., 2., 3., -1., 4., 5., 0., 6., 7., -2., 8.];
+        let result = sign_test(&differences);
+        assert_eq!(result.statistic, 8.);
+        // 2 * P(X <= 2), X ~ Binomial(10, ½)
+        assert!((result.p_value - 0.109_375).abs() < 1e-12);
+    }
+    #[test]
+    fn wilcoxon() {
+        let differences = [1.5, -0.5, 2.0, 3.1, 0.8, -1.2, 2.5, 4.0, 1.1, 0.3];
+        let result = wilcoxon_signed_rank(&differences);
+        assert_eq!(result.statistic, 48.);
+        assert!((result.p_value - 0.037_109_375).abs() < 1e-12);
+        // with ties, the normal approximation is used
+        let differences = [1., -1., 2., 2., 3., -3., 4., 5., 0., 2.];
+        let result = wilcoxon_signed_rank(&differences);
+        assert_eq!(result.statistic, 37.);
+        assert!((result.p_value - 0.095_439_715_567_427_86).abs() < 1e-9);
+    }
+    #[test]
+    fn t_tests() {
+        let a = [5.1, 4.9, 5.6, 5.8, 6.0, 5.5, 5.3];
+        let b = [4.1, 4.6, 4.4, 4.9, 4.0, 4.8];
+        let student = student_t_test(&a, &b).unwrap();
+        assert_eq!(student.degrees_of_freedom, 11.);
+        assert!((student.t - 4.711_700).abs() < 1e-5);
+        assert!((student.p_value - 0.000_638).abs() < 1e-6);
+        let welch = welch_t_test(&a, &b).unwrap();
+        assert!((welch.t - 4.732_409).abs() < 1e-5);
+        assert!((welch.degrees_of_freedom - 10.853_617).abs() < 1e-5);
+        assert!(welch.is_significant(0.01));
+
+        let one_sample = one_sample_t_test(&[1., 2., 3., 4., 5.], 2.).unwrap();
+        // (3 - 2) / (√2.5 / √5)
+        assert!((one_sample.t - 2f64.sqrt()).abs() < 1e-12);
+        assert_eq!(one_sample.degrees_of_freedom, 4.);
+        assert!((one_sample.p_value - 0.230_200).abs() < 1e-6);
+        assert_eq!(one_sample_t_test(&[1., 1., 1.], 2.), None);
+    }
+    #[test]
+    fn anova() {
+        let a = [(1., 1), (2., 1), (3., 1)];
+        let b = [(4., 1), (5., 1), (6., 1)];
+        let c = [(7., 1), (8., 1), (9., 1)];
+        let groups = [
+            ClusterList::new(&a),
+            ClusterList::new(&b),
+            ClusterList::new(&c),
+        ];
+        let result = anova_one_way(&groups).unwrap();
+        assert_eq!(result.between_sum_of_squares, 54.);
+        assert_eq!(result.within_sum_of_squares, 6.);
+        assert_eq!(result.f, 27.);
+        // for 2 and 6 degrees of freedom, P(F >= f) = (1 + f / 3)⁻³
+        assert!((result.p_value - 0.001).abs() < 1e-12);
+
+        let clustered = [(7., 1), (8., 3)];
+        let clustered = anova_one_way(&[ClusterList::new(&a), ClusterList::new(&clustered)]);
+        assert_eq!(clustered.unwrap().within_degrees_of_freedom, 5.);
+        assert_eq!(anova_one_way(&groups[..1]), None);
+    }
+    #[test]
+    fn mann_whitney() {
+        let result = mann_whitney_u(&[1.1, 2.2, 3.3, 4.4], &[5.5, 6.6, 7.7]);
+        assert_eq!(result.statistic, 0.);
+        // 2 of the C(7, 3) = 35 orderings are as extreme
+        assert!((result.p_value - 2. / 35.).abs() < 1e-12);
+        let result = mann_whitney_u(&[3., 1., 4., 6.], &[2., 5., 7.]);
+        assert_eq!(result.statistic, 4.);
+        assert!((result.p_value - 22. / 35.).abs() < 1e-12);
+        // with ties, the normal approximation is used
+        let result = mann_whitney_u(&[1., 2., 2., 3., 4., 5.], &[3., 4., 5., 5., 6., 7., 8.]);
+        assert_eq!(result.statistic, 5.);
+        assert!((result.p_value - 0.025_359_042_166_350_53).abs() < 1e-9);
+    }
+    #[test]
+    fn normality() {
+        let values = [
+            148., 154., 158., 160., 161., 162., 166., 170., 182., 195., 236.,
+        ];
+        let result = anderson_darling(&values).unwrap();
+        assert!((result.statistic - 1.028_930).abs() < 1e-6);
+        assert!((result.p_value - 0.010_454).abs() < 1e-6);
+        let clusters = [(1., 3), (2., 4), (3., 3)];
+        let clustered = anderson_darling_cluster(&ClusterList::new(&clusters)).unwrap();
+        let expanded = anderson_darling(&[1., 1., 1., 2., 2., 2., 2., 3., 3., 3.]).unwrap();
+        assert!((clustered.statistic - expanded.statistic).abs() < 1e-12);
+        assert_eq!(anderson_darling(&[1., 2., 3.]), None);
+    }
+    #[test]
+    fn paired_differences() {
+        let before = [10., 12., 11., 13., 12.];
+        let after = [11., 13., 12.5, 13.5, 13.];
+        let comparison = paired(&before, &after);
+        assert_eq!(comparison.differences.median, Some(1.));
+        assert_eq!(comparison.sign_test.statistic, 5.);
+        assert!((comparison.sign_test.p_value - 0.0625).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,333 @@
+//! Human-readable rendering of values, using SI (`12.4k`, `3.1M`) or binary (`1.5Gi`) prefixes
+//! and a fixed count of significant digits, in plain, fixed, or scientific [`Notation`].
+//!
+//! Use [`ValueFormat::format`] to render a value, and [`parse_value`] to read values with
+//! prefixes or units of time.
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+const SI_LARGE: [&str; 6] = ["k", "M", "G", "T", "P", "E"];
+const SI_SMALL: [&str; 6] = ["m", "µ", "n", "p", "f", "a"];
+const BINARY: [&str; 6] = ["Ki", "Mi", "Gi", "Ti", "Pi", "Ei"];
+
+/// Which prefixes to scale values with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Prefixes {
+    /// No prefix, e.g. `12400`.
+    #[default]
+    None,
+    /// Powers of 1000, e.g. `12.4k` and `3.1µ`.
+    Si,
+    /// Powers of 1024, e.g. `1.5Gi`. Useful for counts of bytes.
+    /// Values less than 1024 aren't scaled.
+    Binary,
+}
+impl Prefixes {
+    /// Splits `value` into the scaled value and the prefix.
+    ///
+    /// Values which aren't finite, and 0, are returned unchanged with no prefix.
+    pub fn scale(self, value: f64) -> (f64, &'static str) {
+        if !value.is_finite() || value == 0. {
+            return (value, "");
+        }
+        let (base, large, small): (f64, _, &[&str]) = match self {
+            Self::None => return (value, ""),
+            Self::Si => (1000., &SI_LARGE, &SI_SMALL),
+            Self::Binary => (1024., &BINARY, &[]),
+        };
+        let mut scaled = value;
+        if value.abs() >= base {
+            let mut prefix = "";
+            for p in large {
+                if scaled.abs() < base {
+                    break;
+                }
+                scaled /= base;
+                prefix = p;
+            }
+            (scaled, prefix)
+        } else if value.abs() < 1. && !small.is_empty() {
+            let mut prefix = "";
+            for p in small {
+                if scaled.abs() >= 1. {
+                    break;
+                }
+                scaled *= base;
+                prefix = p;
+            }
+            (scaled, prefix)
+        } else {
+            (value, "")
+        }
+    }
+}
+/// Returned from [`Prefixes::from_str`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsePrefixesError;
+impl Display for ParsePrefixesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected one of `plain`, `si`, or `binary`")
+    }
+}
+impl std::error::Error for ParsePrefixesError {}
+impl FromStr for Prefixes {
+    type Err = ParsePrefixesError;
+    /// Parses `plain` (or `none`), `si`, or `binary` (or `iec`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "plain" | "none" => Ok(Self::None),
+            "si" => Ok(Self::Si),
+            "binary" | "iec" => Ok(Self::Binary),
+            _ => Err(ParsePrefixesError),
+        }
+    }
+}
+
+/// How to write the digits of a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Notation {
+    /// The shortest representation, unless a count of decimals is given, e.g. `1234.5`.
+    #[default]
+    Plain,
+    /// A fixed count of decimals, 6 unless another count is given, e.g. `1234.500000`.
+    Fixed,
+    /// Scientific notation, e.g. `1.2345e3`. Prefixes aren't used.
+    Scientific,
+}
+/// Returned from [`Notation::from_str`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseNotationError;
+impl Display for ParseNotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected one of `plain`, `fixed`, or `scientific`")
+    }
+}
+impl std::error::Error for ParseNotationError {}
+impl FromStr for Notation {
+    type Err = ParseNotationError;
+    /// Parses `plain`, `fixed`, or `scientific` (or `sci`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "plain" => Ok(Self::Plain),
+            "fixed" => Ok(Self::Fixed),
+            "scientific" | "sci" => Ok(Self::Scientific),
+            _ => Err(ParseNotationError),
+        }
+    }
+}
+
+/// Units of time, and their length in seconds.
+const DURATIONS: [(&str, f64); 8] = [
+    ("ns", 1e-9),
+    ("µs", 1e-6),
+    ("us", 1e-6),
+    ("ms", 1e-3),
+    ("s", 1.),
+    ("min", 60.),
+    ("h", 3600.),
+    ("d", 86400.),
+];
+
+/// Returned from [`parse_value`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseValueError;
+impl Display for ParseValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected a number, optionally followed by a prefix or unit of time")
+    }
+}
+impl std::error::Error for ParseValueError {}
+
+/// Parses a number, optionally followed by an SI (`1.5k`, `300µ`) or binary (`4Gi`) prefix, or a
+/// unit of time (`250ms`, `1.2s`, `5min`, `2h`).
+///
+/// Times are converted to seconds, so values with different units can be mixed. `m` is milli;
+/// use `min` for minutes. `u` is accepted for µ.
+pub fn parse_value(s: &str) -> Result<f64, ParseValueError> {
+    let s = s.trim();
+    if let Ok(value) = s.parse() {
+        return Ok(value);
+    }
+    let si = SI_LARGE
+        .iter()
+        .enumerate()
+        .map(|(idx, prefix)| (*prefix, 1000f64.powi(idx as i32 + 1)))
+        .chain(
+            SI_SMALL
+                .iter()
+                .enumerate()
+                .map(|(idx, prefix)| (*prefix, 1000f64.powi(-(idx as i32) - 1))),
+        )
+        .chain([("u", 1e-6)]);
+    let binary = BINARY
+        .iter()
+        .enumerate()
+        .map(|(idx, prefix)| (*prefix, 1024f64.powi(idx as i32 + 1)));
+    // longer suffixes first, so e.g. `ms` isn't read as `s`
+    let mut suffixes: Vec<(&str, f64)> = DURATIONS.into_iter().chain(binary).chain(si).collect();
+    suffixes.sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.chars().count()));
+    suffixes
+        .into_iter()
+        .find_map(|(suffix, factor)| {
+            let number = s.strip_suffix(suffix)?.trim_end();
+            // don't read e.g. `inf` as `in` and femto
+            if !number.ends_with(|c: char| c.is_ascii_digit() || c == '.') {
+                return None;
+            }
+            number.parse::<f64>().ok().map(|v| v * factor)
+        })
+        .ok_or(ParseValueError)
+}
+
+/// Rounds `value` to `digits` significant digits.
+pub fn round_significant(value: f64, digits: usize) -> f64 {
+    if value == 0. || !value.is_finite() || digits == 0 {
+        return value;
+    }
+    let exponent = digits as i32 - 1 - value.abs().log10().floor() as i32;
+    let factor = 10f64.powi(exponent);
+    let rounded = (value * factor).round() / factor;
+    // very large factors overflow
+    if rounded.is_finite() {
+        rounded
+    } else {
+        value
+    }
+}
+
+/// How to render a value.
+///
+/// When both [`Self::decimals`] and [`Self::significant_digits`] are [`None`], the shortest
+/// representation of the (scaled) value is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ValueFormat {
+    pub prefixes: Prefixes,
+    /// Round to this count of significant digits, e.g. `12.4k` for 3 significant digits.
+    /// Takes precedence over [`Self::decimals`].
+    pub significant_digits: Option<usize>,
+    /// The count of decimals of the scaled value.
+    pub decimals: Option<usize>,
+    pub notation: Notation,
+}
+impl ValueFormat {
+    pub fn new(prefixes: Prefixes) -> Self {
+        Self {
+            prefixes,
+            ..Default::default()
+        }
+    }
+    /// Renders `value`.
+    pub fn format(&self, value: f64) -> String {
+        if self.notation == Notation::Scientific {
+            let decimals = self
+                .significant_digits
+                .map(|digits| digits.saturating_sub(1))
+                .or(self.decimals);
+            return match decimals {
+                Some(decimals) => format!("{value:.decimals$e}"),
+                None => format!("{value:e}"),
+            };
+        }
+        // round before scaling, so e.g. 999 960 with 3 significant digits becomes `1.00M`, not
+        // `1000k`
+        let rounded = self
+            .significant_digits
+            .map_or(value, |digits| round_significant(value, digits));
+        let (scaled, prefix) = self.prefixes.scale(rounded);
+        if let Some(digits) = self.significant_digits.filter(|_| scaled.is_finite()) {
+            let exponent = if scaled == 0. {
+                0
+            } else {
+                scaled.abs().log10().floor() as i64
+            };
+            let decimals = (digits as i64 - 1 - exponent).max(0) as usize;
+            format!("{scaled:.decimals$}{prefix}")
+        } else if let Some(decimals) = self.decimals {
+            format!("{scaled:.decimals$}{prefix}")
+        } else if self.notation == Notation::Fixed {
+            format!("{scaled:.6}{prefix}")
+        } else {
+            format!("{scaled}{prefix}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes() {
+        let si = ValueFormat {
+            prefixes: Prefixes::Si,
+            significant_digits: Some(3),
+            decimals: None,
+            notation: Notation::Plain,
+        };
+        assert_eq!(si.format(12_400.), "12.4k");
+        assert_eq!(si.format(3_141_592.), "3.14M");
+        assert_eq!(si.format(999_960.), "1.00M");
+        assert_eq!(si.format(-0.000_25), "-250µ");
+        assert_eq!(si.format(42.), "42.0");
+        assert_eq!(si.format(0.), "0.00");
+
+        let binary = ValueFormat {
+            prefixes: Prefixes::Binary,
+            decimals: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(binary.format(1536.), "1.5Ki");
+        assert_eq!(binary.format(3. * 1024. * 1024. * 1024.), "3.0Gi");
+        assert_eq!(binary.format(0.5), "0.5");
+
+        let plain = ValueFormat::new(Prefixes::None);
+        assert_eq!(plain.format(1234.5), "1234.5");
+        assert_eq!("SI".parse(), Ok(Prefixes::Si));
+        assert!("metric".parse::<Prefixes>().is_err());
+    }
+    #[test]
+    fn significant() {
+        assert_eq!(round_significant(123_456., 2), 120_000.);
+        assert_eq!(round_significant(0.012_345, 3), 0.0123);
+        let format = ValueFormat {
+            significant_digits: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(format.format(1_234_567.), "1235000");
+        assert_eq!(format.format(0.000_123_456), "0.0001235");
+    }
+    #[test]
+    fn notation() {
+        let scientific = ValueFormat {
+            notation: Notation::Scientific,
+            decimals: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(scientific.format(1234.5), "1.23e3");
+        assert_eq!(scientific.format(-0.000_25), "-2.50e-4");
+        let fixed = ValueFormat {
+            notation: Notation::Fixed,
+            ..Default::default()
+        };
+        assert_eq!(fixed.format(1234.5), "1234.500000");
+        assert_eq!("sci".parse(), Ok(Notation::Scientific));
+        assert!("engineering".parse::<Notation>().is_err());
+    }
+    #[test]
+    fn suffixes() {
+        let parse = |s| parse_value(s).unwrap();
+        assert_eq!(parse("4e-3"), 0.004);
+        assert_eq!(parse("1.5k"), 1500.);
+        assert_eq!(parse("2M"), 2_000_000.);
+        assert!((parse("300µ") - 0.0003).abs() < 1e-15);
+        assert_eq!(parse("3us"), parse("3µs"));
+        assert_eq!(parse("1.5Ki"), 1536.);
+        assert_eq!(parse("250ms"), 0.25);
+        assert_eq!(parse("1.2s"), 1.2);
+        assert_eq!(parse("2min"), 120.);
+        assert_eq!(parse("5m"), 0.005);
+        assert!(parse_value("ms").is_err());
+        assert!(parse_value("12 apples").is_err());
+        assert!(parse_value("1.5kk").is_err());
+    }
+}